@@ -1,15 +1,33 @@
 //! CLI command definitions and execution logic.
 //!
 //! Defines the [`Cli`] struct and [`Commands`] enum for the `gba` binary,
-//! then dispatches to the appropriate engine workflow (init, plan, run).
+//! then dispatches to the appropriate engine workflow (init, plan, run) or
+//! configuration, spec and prompt inspection command.
 
-use std::path::PathBuf;
+use std::io::Write as _;
+use std::net::SocketAddr;
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
 
 use anyhow::{Context, Result};
-use clap::{Parser, Subcommand};
+use clap::{Parser, Subcommand, ValueEnum};
 use tracing::info;
 
-use gba_core::{Engine, EngineConfig, PlanEvent, RunEvent};
+use gba_core::{
+    Diagnostic, Engine, EngineConfig, EventRecord, PermissionDecision, PermissionMode,
+    PermissionRequest, PlanEvent, PlanSession, QueueEvent, QueueRun, QueueStatus, ResolvedConfig,
+    RunEvent, RunStream, SessionRecord, StepStatus, feature_spec_schema, project_config_schema,
+    validate_feature_spec, validate_project_config, validate_prompts,
+};
+
+use crate::inspect;
+use crate::prompts;
+use crate::queue;
+use crate::report;
+use crate::serve;
+use crate::status;
+use crate::tui;
+use crate::tui::plan::Outcome;
 
 /// CLI entry point for GBA -- Claude Agent powered repo automation.
 #[derive(Debug, Parser)]
@@ -29,16 +47,37 @@ pub enum Commands {
         #[arg(short, long, default_value = ".")]
         repo: PathBuf,
     },
-    /// Start interactive planning session
+    /// Plan a feature interactively or from a written brief
     Plan {
         /// Feature slug
         slug: String,
+        /// Plan from a written brief without asking questions (`-` reads stdin)
+        #[arg(long, value_name = "BRIEF")]
+        from: Option<PathBuf>,
+        /// Resume the feature's last plan session
+        #[arg(long, conflicts_with = "from")]
+        resume: bool,
+        /// Amend the existing plan, keeping completed phases' results
+        #[arg(long, conflicts_with_all = ["from", "resume"])]
+        amend: bool,
         /// Path to the target repository
         #[arg(short, long, default_value = ".")]
         repo: PathBuf,
         /// Model to use
         #[arg(short, long)]
         model: Option<String>,
+        /// Permission mode for agent tool use (auto, manual, none)
+        #[arg(long, value_parser = parse_permission_mode)]
+        permission_mode: Option<PermissionMode>,
+        /// Output format
+        ///
+        /// `json` prints one event per line (NDJSON) as
+        /// `{"version", "type", "data"}`, reads replies from stdin line by
+        /// line (end of input finishes the session) and exits with 0 when
+        /// the plan completed, 1 on error and 4 when the session ended
+        /// early.
+        #[arg(long, value_enum, default_value_t = OutputFormat::Text)]
+        output: OutputFormat,
     },
     /// Execute feature plan phase by phase
     Run {
@@ -50,6 +89,288 @@ pub enum Commands {
         /// Model to use
         #[arg(short, long)]
         model: Option<String>,
+        /// Permission mode for agent tool use (auto, manual, none)
+        #[arg(long, value_parser = parse_permission_mode)]
+        permission_mode: Option<PermissionMode>,
+        /// Output format
+        ///
+        /// `json` prints one event per line (NDJSON) as
+        /// `{"version", "type", "data"}` and exits with 0 when finished,
+        /// 1 on error, 3 when verification failed, 4 when the event
+        /// stream ended early and 5 when the run finished but PR creation
+        /// failed. In manual permission mode, answer each
+        /// `permissionRequested` event with a JSON line on stdin such as
+        /// `{"decision": "allowOnce"}`.
+        #[arg(long, value_enum, default_value_t = OutputFormat::Text)]
+        output: OutputFormat,
+    },
+    /// Show the progress of planned features
+    Status {
+        /// Feature slug (all features when omitted)
+        slug: Option<String>,
+        /// Path to the target repository
+        #[arg(short, long, default_value = ".")]
+        repo: PathBuf,
+        /// Output format
+        #[arg(long, value_enum, default_value_t = StatusFormat::Table)]
+        format: StatusFormat,
+    },
+    /// Generate a report of a feature's plan and run
+    Report {
+        /// Feature slug
+        slug: String,
+        /// Path to the target repository
+        #[arg(short, long, default_value = ".")]
+        repo: PathBuf,
+        /// Output format
+        #[arg(long, value_enum, default_value_t = ReportFormat::Md)]
+        format: ReportFormat,
+    },
+    /// Show the archived agent sessions of a feature's runs
+    ///
+    /// Renders each session's prompts, agent text, tool calls with their
+    /// commands, outputs and file diffs, and a summary of the tools used and
+    /// time spent. On a terminal, sessions open in a viewer where tool calls
+    /// expand and collapse; otherwise they are printed with tool calls
+    /// collapsed to one line unless `--expand` is given. Filters combine.
+    Inspect {
+        /// Feature slug
+        slug: String,
+        /// Only sessions working on phase N (1-based)
+        #[arg(long, value_name = "N", value_parser = clap::value_parser!(u16).range(1..))]
+        phase: Option<u16>,
+        /// Only sessions of this pipeline stage, e.g. `review`
+        #[arg(long)]
+        stage: Option<String>,
+        /// Only this session, e.g. `review-2`
+        #[arg(long, value_name = "NAME")]
+        session: Option<String>,
+        /// List the matching sessions instead of showing them
+        #[arg(long)]
+        list: bool,
+        /// Print prompts and tool calls expanded
+        #[arg(long, conflicts_with = "list")]
+        expand: bool,
+        /// Path to the target repository
+        #[arg(short, long, default_value = ".")]
+        repo: PathBuf,
+    },
+    /// Queue planned features and run them one after another or in parallel
+    Queue {
+        /// Queue subcommand.
+        #[command(subcommand)]
+        command: QueueCommands,
+    },
+    /// Serve a local HTTP API for dashboards and editor integrations
+    ///
+    /// Exposes feature status, runs and plan sessions; run and plan events
+    /// stream as Server-Sent Events. Requests must be addressed to the
+    /// listening address or localhost, and browsers may only call it from
+    /// `--cors-origin` origins. Without `--token` anyone who can reach the
+    /// address can use it, so only bind it to addresses that untrusted users
+    /// cannot reach.
+    Serve {
+        /// Address to listen on
+        #[arg(long, default_value = "127.0.0.1:4380")]
+        addr: SocketAddr,
+        /// Require `Authorization: Bearer <TOKEN>` on every request
+        #[arg(long)]
+        token: Option<String>,
+        /// Browser origin allowed to call the API, e.g. http://localhost:3000
+        /// (repeatable)
+        #[arg(long = "cors-origin", value_name = "ORIGIN")]
+        cors_origins: Vec<String>,
+        /// Path to the target repository
+        #[arg(short, long, default_value = ".")]
+        repo: PathBuf,
+        /// Model to use
+        #[arg(short, long)]
+        model: Option<String>,
+        /// Permission mode for agent tool use (auto, manual, none)
+        #[arg(long, value_parser = parse_permission_mode)]
+        permission_mode: Option<PermissionMode>,
+    },
+    /// Inspect GBA configuration
+    Config {
+        /// Configuration subcommand.
+        #[command(subcommand)]
+        command: ConfigCommands,
+    },
+    /// Inspect feature specs
+    Spec {
+        /// Spec subcommand.
+        #[command(subcommand)]
+        command: SpecCommands,
+    },
+    /// List, show and render prompt templates
+    Prompts {
+        /// Prompts subcommand.
+        #[command(subcommand)]
+        command: PromptsCommands,
+    },
+}
+
+/// Output format of `gba plan` and `gba run`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum OutputFormat {
+    /// Human-readable output, or the TUI on a terminal
+    Text,
+    /// One JSON event per line
+    Json,
+}
+
+/// Output format of `gba status`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum StatusFormat {
+    /// Human-readable table
+    Table,
+    /// JSON
+    Json,
+}
+
+/// Output format of `gba report`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum ReportFormat {
+    /// Markdown
+    Md,
+    /// Self-contained HTML page
+    Html,
+    /// JSON
+    Json,
+}
+
+/// Subcommands of `gba queue`.
+#[derive(Debug, Subcommand)]
+pub enum QueueCommands {
+    /// Add planned features to the end of the queue
+    Add {
+        /// Feature slugs
+        #[arg(required = true)]
+        slugs: Vec<String>,
+        /// Path to the target repository
+        #[arg(short, long, default_value = ".")]
+        repo: PathBuf,
+    },
+    /// Run the queued features, each in its own worktree
+    ///
+    /// A failed feature is recorded and the queue continues with the next
+    /// one. Queued runs cannot ask for permission, so tool calls that need
+    /// approval in manual permission mode are denied. Only one queue run
+    /// can be in progress at a time. Exits with 1 if any feature failed.
+    Run {
+        /// Maximum number of features running at once
+        #[arg(short, long, default_value_t = 1, value_parser = clap::value_parser!(u16).range(1..))]
+        jobs: u16,
+        /// Path to the target repository
+        #[arg(short, long, default_value = ".")]
+        repo: PathBuf,
+        /// Model to use
+        #[arg(short, long)]
+        model: Option<String>,
+        /// Permission mode for agent tool use (auto, manual, none)
+        #[arg(long, value_parser = parse_permission_mode)]
+        permission_mode: Option<PermissionMode>,
+    },
+    /// Show the queued, running and finished features
+    Status {
+        /// Path to the target repository
+        #[arg(short, long, default_value = ".")]
+        repo: PathBuf,
+        /// Output format
+        #[arg(long, value_enum, default_value_t = StatusFormat::Table)]
+        format: StatusFormat,
+    },
+}
+
+/// Subcommands of `gba config`.
+#[derive(Debug, Subcommand)]
+pub enum ConfigCommands {
+    /// Show the effective configuration after merging all layers
+    Show {
+        /// Path to the target repository
+        #[arg(short, long, default_value = ".")]
+        repo: PathBuf,
+        /// Print each value with the layer it came from
+        #[arg(long)]
+        resolved: bool,
+    },
+    /// Validate every config layer and the merged configuration
+    Validate {
+        /// Path to the target repository
+        #[arg(short, long, default_value = ".")]
+        repo: PathBuf,
+    },
+    /// Print the JSON Schema for `.gba/config.yaml`
+    Schema,
+}
+
+/// Subcommands of `gba spec`.
+#[derive(Debug, Subcommand)]
+pub enum SpecCommands {
+    /// Validate a feature's `phases.yaml`
+    Validate {
+        /// Feature slug
+        slug: String,
+        /// Path to the target repository
+        #[arg(short, long, default_value = ".")]
+        repo: PathBuf,
+    },
+    /// Print the JSON Schema for `phases.yaml`
+    Schema,
+}
+
+/// Subcommands of `gba prompts`.
+#[derive(Debug, Subcommand)]
+pub enum PromptsCommands {
+    /// List the effective templates and whether each is built in or loaded
+    /// from a file
+    List {
+        /// Path to the target repository
+        #[arg(short, long, default_value = ".")]
+        repo: PathBuf,
+    },
+    /// Print a template's effective source
+    Show {
+        /// Template name, e.g. `code/task`
+        name: String,
+        /// Path to the target repository
+        #[arg(short, long, default_value = ".")]
+        repo: PathBuf,
+    },
+    /// Render a template with a JSON context or a feature's run context
+    ///
+    /// With `--slug`, the context is the one `gba run` would pass to the
+    /// template's pipeline stage for the feature in its current state: the
+    /// next phase to run (or `--phase N`) for the phases stage, the
+    /// worktree's current diff for review and custom agent stages. Fix
+    /// templates need a run's failures, which only a JSON context provides.
+    Render {
+        /// Template name, e.g. `code/task`
+        name: String,
+        /// JSON file with the template context (`-` reads stdin)
+        #[arg(long, value_name = "FILE", required_unless_present = "slug")]
+        context: Option<PathBuf>,
+        /// Build the context `gba run` would use for this feature
+        #[arg(long, conflicts_with = "context")]
+        slug: Option<String>,
+        /// Pipeline stage whose context to build (defaults to the stage
+        /// using the template)
+        #[arg(long, requires = "slug")]
+        stage: Option<String>,
+        /// Phase N (1-based) of the phases stage (defaults to the next
+        /// phase to run)
+        #[arg(long, value_name = "N", requires = "slug", value_parser = clap::value_parser!(u16).range(1..))]
+        phase: Option<u16>,
+        /// Path to the target repository
+        #[arg(short, long, default_value = ".")]
+        repo: PathBuf,
+    },
+    /// Check templates against their declared variables and the contexts
+    /// gba renders them with, and render them with sample contexts
+    Check {
+        /// Path to the target repository
+        #[arg(short, long, default_value = ".")]
+        repo: PathBuf,
     },
 }
 
@@ -57,12 +378,38 @@ impl Cli {
     /// Extract the repo path and optional slug for logging setup.
     ///
     /// Returns `(repo_path, Some(slug))` for `plan` and `run` commands,
-    /// and `(repo_path, None)` for `init`.
+    /// and `(repo_path, None)` for `init`, `status`, `report`, `inspect`,
+    /// `queue`, `serve`, `config`, `spec` and `prompts`.
     pub fn log_context(&self) -> (PathBuf, Option<String>) {
         match &self.command {
             Commands::Init { repo } => (repo.clone(), None),
             Commands::Plan { slug, repo, .. } => (repo.clone(), Some(slug.clone())),
             Commands::Run { slug, repo, .. } => (repo.clone(), Some(slug.clone())),
+            Commands::Status { repo, .. } => (repo.clone(), None),
+            Commands::Report { repo, .. } => (repo.clone(), None),
+            Commands::Inspect { repo, .. } => (repo.clone(), None),
+            Commands::Queue { command } => match command {
+                QueueCommands::Add { repo, .. }
+                | QueueCommands::Run { repo, .. }
+                | QueueCommands::Status { repo, .. } => (repo.clone(), None),
+            },
+            Commands::Serve { repo, .. } => (repo.clone(), None),
+            Commands::Config { command } => match command {
+                ConfigCommands::Show { repo, .. } | ConfigCommands::Validate { repo } => {
+                    (repo.clone(), None)
+                }
+                ConfigCommands::Schema => (PathBuf::from("."), None),
+            },
+            Commands::Spec { command } => match command {
+                SpecCommands::Validate { repo, .. } => (repo.clone(), None),
+                SpecCommands::Schema => (PathBuf::from("."), None),
+            },
+            Commands::Prompts { command } => match command {
+                PromptsCommands::List { repo }
+                | PromptsCommands::Show { repo, .. }
+                | PromptsCommands::Render { repo, .. }
+                | PromptsCommands::Check { repo } => (repo.clone(), None),
+            },
         }
     }
 
@@ -75,7 +422,7 @@ impl Cli {
     ///
     /// Returns an error if engine creation fails or the selected workflow
     /// encounters an unrecoverable error.
-    pub async fn run(self) -> Result<()> {
+    pub async fn run(self) -> Result<ExitCode> {
         match self.command {
             Commands::Init { repo } => {
                 let config = EngineConfig::builder().repo_path(repo).build();
@@ -85,60 +432,62 @@ impl Cli {
                 engine.init().await.context("init failed")?;
                 info!("Repository initialized for GBA.");
                 println!("Repository initialized for GBA.");
-                Ok(())
+                Ok(ExitCode::SUCCESS)
             }
-            Commands::Plan { slug, repo, model } => {
-                let config = build_engine_config(repo, model);
+            Commands::Plan {
+                slug,
+                from,
+                resume,
+                amend,
+                repo,
+                model,
+                permission_mode,
+                output,
+            } => {
+                let brief = match from {
+                    Some(path) => Some(read_brief(&path).await?),
+                    None => None,
+                };
+                let config = build_engine_config(repo, model, permission_mode);
                 let engine = Engine::new(config)
                     .await
                     .context("failed to create engine")?;
-                let mut session = engine
-                    .plan(&slug)
-                    .await
-                    .context("failed to start plan session")?;
+                let mut session = match &brief {
+                    Some(brief) => engine.plan_from_brief(&slug, brief).await,
+                    None if resume => engine.resume_plan(&slug).await,
+                    None if amend => engine.amend_plan(&slug).await,
+                    None => engine.plan(&slug).await,
+                }
+                .context("failed to start plan session")?;
 
-                while let Some(event) = session.next().await {
-                    match event {
-                        PlanEvent::Message(text) => {
-                            println!("{text}");
-                        }
-                        PlanEvent::WaitingForInput => {
-                            let input = tokio::task::spawn_blocking(|| {
-                                use std::io::Write;
-                                print!("> ");
-                                std::io::stdout().flush().ok();
-                                let mut line = String::new();
-                                std::io::stdin().read_line(&mut line).map(|_| line)
-                            })
-                            .await?
-                            .context("failed to read input")?;
-
-                            let trimmed = input.trim();
-                            if !trimmed.is_empty() {
-                                session
-                                    .respond(trimmed)
-                                    .await
-                                    .context("failed to send input")?;
-                            }
-                        }
-                        PlanEvent::SpecGenerated { path, .. } => {
-                            println!("[x] Generated: {}", path.display());
-                        }
-                        PlanEvent::Completed => {
-                            println!("\nPlan complete. Run `gba run {slug}` to execute.");
-                            break;
+                if output == OutputFormat::Json {
+                    return plan_json(&mut session).await.map(ExitCode::from);
+                }
+                if brief.is_none() && tui::is_interactive() {
+                    let feature_dir = engine.gba_dir().join("features").join(&slug);
+                    match tui::plan::run(&mut session, &feature_dir).await? {
+                        Outcome::Completed => {
+                            println!("Plan complete. Run `gba run {slug}` to execute.");
                         }
-                        PlanEvent::Error(e) => {
-                            eprintln!("[!] Error: {e}");
-                            break;
+                        Outcome::Failed(e) => eprintln!("[!] Error: {e}"),
+                        Outcome::Quit => {
+                            println!("Plan not finished. Continue with `gba plan {slug} --resume`.")
                         }
                     }
+                    Ok(ExitCode::SUCCESS)
+                } else {
+                    plan_plain(&mut session, &slug).await?;
+                    Ok(ExitCode::SUCCESS)
                 }
-
-                Ok(())
             }
-            Commands::Run { slug, repo, model } => {
-                let config = build_engine_config(repo, model);
+            Commands::Run {
+                slug,
+                repo,
+                model,
+                permission_mode,
+                output,
+            } => {
+                let config = build_engine_config(repo, model, permission_mode);
                 let engine = Engine::new(config)
                     .await
                     .context("failed to create engine")?;
@@ -147,14 +496,583 @@ impl Cli {
                     .await
                     .context("failed to start run stream")?;
 
-                while let Some(event) = stream.next().await {
-                    display_run_event(&event);
+                if output == OutputFormat::Json {
+                    return run_json(&mut stream).await.map(ExitCode::from);
+                }
+                if !tui::is_interactive() {
+                    run_plain(&mut stream).await?;
+                    return Ok(ExitCode::SUCCESS);
+                }
+                match tui::run::run(&mut stream).await? {
+                    tui::run::Outcome::Finished { pr } => {
+                        println!("Done!");
+                        if let Some(url) = pr {
+                            println!("PR: {url}");
+                        }
+                    }
+                    tui::run::Outcome::Failed(e) => eprintln!("[!] Error: {e}"),
+                    tui::run::Outcome::Stopped => println!(
+                        "Run stopped. Run `gba run {slug}` again to continue from the last completed phase."
+                    ),
+                }
+                Ok(ExitCode::SUCCESS)
+            }
+            Commands::Status { slug, repo, format } => {
+                let config = EngineConfig::builder().repo_path(repo).build();
+                let engine = Engine::new(config)
+                    .await
+                    .context("failed to create engine")?;
+                let now = status::now();
+                match slug {
+                    Some(slug) => {
+                        let feature = engine
+                            .feature_status(&slug)
+                            .await
+                            .with_context(|| format!("failed to read status of {slug:?}"))?;
+                        match format {
+                            StatusFormat::Table => print!("{}", status::detail(&feature, now)),
+                            StatusFormat::Json => print_json(&feature)?,
+                        }
+                    }
+                    None => {
+                        let features = engine
+                            .list_features()
+                            .await
+                            .context("failed to list features")?;
+                        match format {
+                            StatusFormat::Table if features.is_empty() => {
+                                println!("No planned features. Start one with `gba plan <slug>`.");
+                            }
+                            StatusFormat::Table => print!("{}", status::table(&features, now)),
+                            StatusFormat::Json => print_json(&features)?,
+                        }
+                    }
+                }
+                Ok(ExitCode::SUCCESS)
+            }
+            Commands::Report { slug, repo, format } => {
+                let config = EngineConfig::builder().repo_path(repo).build();
+                let engine = Engine::new(config)
+                    .await
+                    .context("failed to create engine")?;
+                let report = engine
+                    .report(&slug)
+                    .await
+                    .with_context(|| format!("failed to build report for {slug:?}"))?;
+                match format {
+                    ReportFormat::Md => print!("{}", report::markdown(&report)),
+                    ReportFormat::Html => print!("{}", report::html(&report)),
+                    ReportFormat::Json => print_json(&report)?,
+                }
+                Ok(ExitCode::SUCCESS)
+            }
+            Commands::Inspect {
+                slug,
+                phase,
+                stage,
+                session,
+                list,
+                expand,
+                repo,
+            } => {
+                let config = EngineConfig::builder().repo_path(repo).build();
+                let engine = Engine::new(config)
+                    .await
+                    .context("failed to create engine")?;
+                let records = engine
+                    .transcripts(&slug)
+                    .with_context(|| format!("failed to read sessions of {slug:?}"))?;
+                let selected = inspect::select(
+                    &records,
+                    phase.map(usize::from),
+                    stage.as_deref(),
+                    session.as_deref(),
+                );
+                if records.is_empty() {
+                    println!(
+                        "No archived agent sessions. Sessions are recorded by `gba run {slug}`."
+                    );
+                } else if selected.is_empty() {
+                    println!(
+                        "No archived session matches. List them with `gba inspect {slug} --list`."
+                    );
+                } else if list {
+                    print!("{}", inspect::list(&selected, status::now()));
+                } else {
+                    inspect_sessions(&engine, &slug, &selected, expand).await?;
+                }
+                Ok(ExitCode::SUCCESS)
+            }
+            Commands::Queue { command } => match command {
+                QueueCommands::Add { slugs, repo } => {
+                    let config = EngineConfig::builder().repo_path(repo).build();
+                    let engine = Engine::new(config)
+                        .await
+                        .context("failed to create engine")?;
+                    let added = engine
+                        .queue_add(&slugs)
+                        .context("failed to queue features")?;
+                    for slug in &added {
+                        println!("Queued: {slug}");
+                    }
+                    if added.len() < slugs.len() {
+                        println!("Skipped features that are already queued or running.");
+                    }
+                    Ok(ExitCode::SUCCESS)
+                }
+                QueueCommands::Run {
+                    jobs,
+                    repo,
+                    model,
+                    permission_mode,
+                } => {
+                    let config = build_engine_config(repo, model, permission_mode);
+                    let engine = Engine::new(config)
+                        .await
+                        .context("failed to create engine")?;
+                    let mut run = engine
+                        .run_queue(usize::from(jobs))
+                        .context("failed to start queue")?;
+                    run_queue_plain(&mut run).await
+                }
+                QueueCommands::Status { repo, format } => {
+                    let config = EngineConfig::builder().repo_path(repo).build();
+                    let engine = Engine::new(config)
+                        .await
+                        .context("failed to create engine")?;
+                    let entries = engine.queue().context("failed to read queue")?.entries;
+                    match format {
+                        StatusFormat::Table if entries.is_empty() => {
+                            println!(
+                                "The queue is empty. Add features with `gba queue add <slug>`."
+                            );
+                        }
+                        StatusFormat::Table => print!("{}", queue::table(&entries, status::now())),
+                        StatusFormat::Json => print_json(&entries)?,
+                    }
+                    Ok(ExitCode::SUCCESS)
+                }
+            },
+            Commands::Serve {
+                addr,
+                token,
+                cors_origins,
+                repo,
+                model,
+                permission_mode,
+            } => {
+                let config = build_engine_config(repo, model, permission_mode);
+                let engine = Engine::new(config)
+                    .await
+                    .context("failed to create engine")?;
+                let listener = tokio::net::TcpListener::bind(addr)
+                    .await
+                    .with_context(|| format!("failed to listen on {addr}"))?;
+                let addr = listener.local_addr().context("failed to read address")?;
+                println!("Listening on http://{addr}");
+                let access = serve::Access {
+                    token,
+                    cors_origins,
+                };
+                serve::serve(engine, listener, access).await?;
+                Ok(ExitCode::SUCCESS)
+            }
+            Commands::Config { command } => match command {
+                ConfigCommands::Show { repo, resolved } => {
+                    let config = EngineConfig::builder().repo_path(repo).build();
+                    let resolved_config =
+                        ResolvedConfig::load(&config).context("failed to resolve config")?;
+                    if resolved {
+                        display_resolved_config(&resolved_config);
+                    } else {
+                        let yaml = serde_yaml::to_string(resolved_config.config())
+                            .context("failed to serialize config")?;
+                        print!("{yaml}");
+                    }
+                    Ok(ExitCode::SUCCESS)
+                }
+                ConfigCommands::Validate { repo } => {
+                    let config = EngineConfig::builder().repo_path(repo).build();
+                    let diagnostics =
+                        validate_project_config(&config).context("failed to validate config")?;
+                    report_diagnostics(&diagnostics, "configuration")?;
+                    Ok(ExitCode::SUCCESS)
+                }
+                ConfigCommands::Schema => {
+                    print_schema(&project_config_schema())?;
+                    Ok(ExitCode::SUCCESS)
+                }
+            },
+            Commands::Spec { command } => match command {
+                SpecCommands::Validate { slug, repo } => {
+                    let config = EngineConfig::builder().repo_path(repo).build();
+                    let diagnostics = validate_feature_spec(&config, &slug)
+                        .with_context(|| format!("failed to validate spec {slug:?}"))?;
+                    report_diagnostics(&diagnostics, &format!("spec {slug:?}"))?;
+                    Ok(ExitCode::SUCCESS)
+                }
+                SpecCommands::Schema => {
+                    print_schema(&feature_spec_schema())?;
+                    Ok(ExitCode::SUCCESS)
+                }
+            },
+            Commands::Prompts { command } => match command {
+                PromptsCommands::List { repo } => {
+                    let config = EngineConfig::builder().repo_path(repo).build();
+                    let engine = Engine::new(config)
+                        .await
+                        .context("failed to create engine")?;
+                    let repo_path = engine.config().repo_path();
+                    print!("{}", prompts::table(&engine.prompts(), repo_path));
+                    Ok(ExitCode::SUCCESS)
+                }
+                PromptsCommands::Show { name, repo } => {
+                    let config = EngineConfig::builder().repo_path(repo).build();
+                    let engine = Engine::new(config)
+                        .await
+                        .context("failed to create engine")?;
+                    let template = engine
+                        .prompt(&name)
+                        .with_context(|| format!("failed to load template {name:?}"))?;
+                    print_text(&template.source);
+                    Ok(ExitCode::SUCCESS)
+                }
+                PromptsCommands::Render {
+                    name,
+                    context,
+                    slug,
+                    stage,
+                    phase,
+                    repo,
+                } => {
+                    let config = EngineConfig::builder().repo_path(repo).build();
+                    let engine = Engine::new(config)
+                        .await
+                        .context("failed to create engine")?;
+                    let ctx = match (context, slug) {
+                        (_, Some(slug)) => engine
+                            .prompt_context(&slug, &name, stage.as_deref(), phase.map(usize::from))
+                            .await
+                            .with_context(|| format!("failed to build context for {slug:?}"))?,
+                        (Some(path), None) => read_context(&path).await?,
+                        (None, None) => anyhow::bail!("pass --context or --slug"),
+                    };
+                    let rendered = engine
+                        .render_prompt(&name, &ctx)
+                        .with_context(|| format!("failed to render template {name:?}"))?;
+                    print_text(&rendered);
+                    Ok(ExitCode::SUCCESS)
+                }
+                PromptsCommands::Check { repo } => {
+                    let config = EngineConfig::builder().repo_path(repo).build();
+                    let diagnostics =
+                        validate_prompts(&config).context("failed to check templates")?;
+                    report_diagnostics(&diagnostics, "prompt templates")?;
+                    Ok(ExitCode::SUCCESS)
+                }
+            },
+        }
+    }
+}
+
+/// Exit status of `gba plan` and `gba run` with `--output json`.
+///
+/// Usage errors exit with 2 (from clap) and failures before the first event
+/// (e.g. an invalid config) with 1, like every other command.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum JsonExit {
+    /// The plan completed, or the run finished and verification passed.
+    Success = 0,
+    /// The session or run stopped with an error.
+    Failed = 1,
+    /// The run finished, but verification failed.
+    VerificationFailed = 3,
+    /// The event stream ended without a terminal event.
+    Interrupted = 4,
+    /// The run finished, but a stage whose failure does not stop the run
+    /// (PR creation) failed.
+    StageFailed = 5,
+}
+
+impl From<JsonExit> for ExitCode {
+    fn from(exit: JsonExit) -> Self {
+        Self::from(exit as u8)
+    }
+}
+
+/// Tracks which terminal state a run's events lead to.
+#[derive(Debug)]
+struct RunExit {
+    /// Whether the last verification passed.
+    verification_passed: bool,
+    /// Whether a stage finished with a failed status.
+    stage_failed: bool,
+    /// Exit status if the stream ended now.
+    exit: JsonExit,
+}
+
+impl RunExit {
+    fn new() -> Self {
+        Self {
+            verification_passed: true,
+            stage_failed: false,
+            exit: JsonExit::Interrupted,
+        }
+    }
+
+    /// Update the state with the next event. Errors after which the run
+    /// goes on (a failed PR creation) do not count as terminal, but the
+    /// failed stage is remembered for the final status.
+    fn observe(&mut self, event: &RunEvent) {
+        self.exit = match event {
+            RunEvent::VerificationCompleted { passed, .. } => {
+                self.verification_passed = *passed;
+                JsonExit::Interrupted
+            }
+            RunEvent::StageFinished {
+                status: StepStatus::Failed,
+                ..
+            } => {
+                self.stage_failed = true;
+                JsonExit::Interrupted
+            }
+            RunEvent::Finished if !self.verification_passed => JsonExit::VerificationFailed,
+            RunEvent::Finished if self.stage_failed => JsonExit::StageFailed,
+            RunEvent::Finished => JsonExit::Success,
+            RunEvent::Error(_) => JsonExit::Failed,
+            _ => JsonExit::Interrupted,
+        };
+    }
+}
+
+/// Drive a plan session with NDJSON output. Replies are read from stdin line
+/// by line; end of input finishes the session.
+async fn plan_json(session: &mut PlanSession) -> Result<JsonExit> {
+    let mut exit = JsonExit::Interrupted;
+    while let Some(event) = session.next().await {
+        print_event(&event)?;
+        match event {
+            PlanEvent::WaitingForInput => loop {
+                let input = read_line("").await?;
+                if input.is_empty() {
+                    // End of input
+                    session.finish();
+                    break;
                 }
+                if !input.trim().is_empty() {
+                    session
+                        .respond(input.trim())
+                        .await
+                        .context("failed to send input")?;
+                    break;
+                }
+            },
+            PlanEvent::Completed => exit = JsonExit::Success,
+            PlanEvent::Error(_) => exit = JsonExit::Failed,
+            _ => {}
+        }
+    }
 
-                Ok(())
+    Ok(exit)
+}
+
+/// Print run progress as NDJSON. Permission requests are answered with a
+/// [`PermissionDecision`] JSON line from stdin; end of input denies them.
+async fn run_json(stream: &mut RunStream) -> Result<JsonExit> {
+    let mut state = RunExit::new();
+    while let Some(event) = stream.next().await {
+        print_event(&event)?;
+        state.observe(&event);
+        if let RunEvent::PermissionRequested(request) = &event {
+            let answer = read_line("").await?;
+            let decision = if answer.is_empty() {
+                PermissionDecision::Deny {
+                    reason: "no answer from the user".to_owned(),
+                }
+            } else {
+                serde_json::from_str(&answer).unwrap_or_else(|e| PermissionDecision::Deny {
+                    reason: format!("invalid permission answer: {e}"),
+                })
+            };
+            stream
+                .respond_permission(request.id, decision)
+                .await
+                .context("failed to send permission decision")?;
+        }
+    }
+
+    Ok(state.exit)
+}
+
+/// Print an event as one line of versioned JSON.
+fn print_event(event: &impl serde::Serialize) -> Result<()> {
+    let mut stdout = std::io::stdout().lock();
+    serde_json::to_writer(&mut stdout, &EventRecord::new(event))
+        .context("failed to write event")?;
+    writeln!(stdout).context("failed to write event")?;
+    stdout.flush().context("failed to write event")
+}
+
+/// Show archived agent sessions in the viewer on a terminal, or print
+/// them, expanded if `expand` is set.
+async fn inspect_sessions(
+    engine: &Engine,
+    slug: &str,
+    sessions: &[&SessionRecord],
+    expand: bool,
+) -> Result<()> {
+    let mut entries = Vec::new();
+    for record in sessions {
+        let items = engine
+            .transcript(slug, record)
+            .with_context(|| format!("failed to read session {}", record.file))?;
+        entries.extend(inspect::entries(record, &items));
+    }
+    if !expand && tui::is_interactive() {
+        tui::inspect::run(&format!("gba inspect {slug}"), entries).await
+    } else {
+        print!("{}", inspect::plain(&entries, expand));
+        Ok(())
+    }
+}
+
+/// Print queue progress line by line, then a summary of the features that
+/// ran. Fails the process if any feature or the queue itself failed.
+async fn run_queue_plain(run: &mut QueueRun<'_>) -> Result<ExitCode> {
+    let mut failed = false;
+    while let Some(event) = run.next().await {
+        if let Some(line) = queue::describe(&event) {
+            println!("{line}");
+        }
+        match event {
+            QueueEvent::Error(_) => failed = true,
+            QueueEvent::Finished { entries } if entries.is_empty() => {
+                println!("No queued features.");
             }
+            QueueEvent::Finished { entries } => {
+                failed |= entries.iter().any(|e| e.status == QueueStatus::Failed);
+                println!("\n{}", queue::table(&entries, status::now()).trim_end());
+            }
+            _ => {}
         }
     }
+
+    Ok(if failed {
+        ExitCode::FAILURE
+    } else {
+        ExitCode::SUCCESS
+    })
+}
+
+/// Drive a plan session with line-based output, for briefs and
+/// non-terminal stdin/stdout. End of input finishes the session.
+async fn plan_plain(session: &mut PlanSession, slug: &str) -> Result<()> {
+    while let Some(event) = session.next().await {
+        match event {
+            PlanEvent::Message(text) => {
+                println!("{text}");
+            }
+            PlanEvent::WaitingForInput => {
+                let input = read_line("> ").await?;
+                if input.is_empty() {
+                    // End of input
+                    session.finish();
+                    continue;
+                }
+
+                let trimmed = input.trim();
+                if !trimmed.is_empty() {
+                    session
+                        .respond(trimmed)
+                        .await
+                        .context("failed to send input")?;
+                }
+            }
+            PlanEvent::SpecGenerated { path, .. } => {
+                println!("[x] Generated: {}", path.display());
+            }
+            PlanEvent::SpecValidated {
+                attempt,
+                diagnostics,
+            } => {
+                if diagnostics.is_empty() {
+                    println!("[x] Specs valid");
+                } else {
+                    println!(
+                        "[!] Specs invalid (round {attempt}, {} problem(s)):",
+                        diagnostics.len()
+                    );
+                    for diagnostic in &diagnostics {
+                        println!("    {diagnostic}");
+                    }
+                }
+            }
+            PlanEvent::Completed => {
+                println!("\nPlan complete. Run `gba run {slug}` to execute.");
+                break;
+            }
+            PlanEvent::Error(e) => {
+                eprintln!("[!] Error: {e}");
+                break;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Print validation diagnostics, failing when there are any.
+fn report_diagnostics(diagnostics: &[Diagnostic], subject: &str) -> Result<()> {
+    if diagnostics.is_empty() {
+        println!("[x] {subject} is valid");
+        return Ok(());
+    }
+    for diagnostic in diagnostics {
+        eprintln!("[!] {diagnostic}");
+    }
+    anyhow::bail!("{subject} has {} problem(s)", diagnostics.len())
+}
+
+/// Print a JSON Schema to stdout.
+fn print_schema(schema: &serde_json::Value) -> Result<()> {
+    let json = serde_json::to_string_pretty(schema).context("failed to serialize schema")?;
+    println!("{json}");
+    Ok(())
+}
+
+/// Print a value as pretty JSON to stdout.
+fn print_json(value: &impl serde::Serialize) -> Result<()> {
+    let json = serde_json::to_string_pretty(value).context("failed to serialize JSON")?;
+    println!("{json}");
+    Ok(())
+}
+
+/// Display every resolved config value with the layer it came from.
+fn display_resolved_config(resolved: &ResolvedConfig) {
+    let entries = resolved.entries();
+    let width = entries.iter().map(|e| e.key.len()).max().unwrap_or(0);
+    for entry in entries {
+        println!(
+            "{:<width$} = {}  # {}",
+            entry.key, entry.value, entry.origin
+        );
+    }
+}
+
+/// Print run progress line by line, for non-terminal stdin/stdout.
+async fn run_plain(stream: &mut RunStream) -> Result<()> {
+    while let Some(event) = stream.next().await {
+        display_run_event(&event);
+        if let RunEvent::PermissionRequested(request) = &event {
+            let decision = ask_permission().await?;
+            stream
+                .respond_permission(request.id, decision)
+                .await
+                .context("failed to send permission decision")?;
+        }
+    }
+
+    Ok(())
 }
 
 /// Display a single run event to stdout.
@@ -165,17 +1083,40 @@ impl Cli {
 /// - `[!]` for warnings/failures
 fn display_run_event(event: &RunEvent) {
     match event {
-        RunEvent::Started {
-            feature,
-            total_phases,
+        RunEvent::Started { feature, phases } => {
+            println!("Running feature: {feature} ({} phases)", phases.len());
+        }
+        RunEvent::StageStarted { name, kind } => {
+            println!("[~] Stage {name} ({kind})...");
+        }
+        RunEvent::StageFinished {
+            name,
+            status,
+            summary,
+            ..
         } => {
-            println!("Running feature: {feature} ({total_phases} phases)");
+            let indicator = if *status == StepStatus::Failed {
+                "!"
+            } else {
+                "x"
+            };
+            println!("[{indicator}] Stage {name}: {summary}");
         }
         RunEvent::PhaseStarted { index, name } => {
             println!("[~] Phase {}: {name}", index + 1);
         }
-        RunEvent::CodingOutput(text) => {
-            print!("{text}");
+        RunEvent::AgentOutput { agent, text, .. } => {
+            println!("[{agent}] {}", text.trim_end());
+        }
+        RunEvent::UsageReported(usage) => {
+            let cost = usage
+                .cost_usd
+                .map(|c| format!(", ${c:.2}"))
+                .unwrap_or_default();
+            println!(
+                "    {} ({}): {} turns, {} in / {} out tokens{cost}",
+                usage.agent, usage.phase, usage.turns, usage.input_tokens, usage.output_tokens
+            );
         }
         RunEvent::HookResult { hook, passed } => {
             let indicator = if *passed { "x" } else { "!" };
@@ -184,28 +1125,193 @@ fn display_run_event(event: &RunEvent) {
         RunEvent::PhaseCommitted { index, commit_hash } => {
             println!("[x] Phase {} committed: {commit_hash}", index + 1);
         }
-        RunEvent::ReviewStarted => println!("[~] Code review..."),
         RunEvent::ReviewCompleted { issues } => {
             println!("[x] Code review completed ({} issues)", issues.len());
         }
-        RunEvent::VerificationStarted => println!("[~] Verification..."),
         RunEvent::VerificationCompleted { passed, details } => {
             let indicator = if *passed { "x" } else { "!" };
             println!("[{indicator}] Verification: {details}");
         }
+        RunEvent::PermissionRequested(request) => display_permission_request(request),
+        RunEvent::ToolDenied(denial) => {
+            println!(
+                "[!] Denied {} for {} ({}): {}",
+                denial.tool, denial.agent, denial.phase, denial.reason
+            );
+        }
         RunEvent::PrCreated { url } => println!("[x] PR created: {url}"),
         RunEvent::Finished => println!("\nDone!"),
         RunEvent::Error(e) => eprintln!("[!] Error: {e}"),
     }
 }
 
+/// Maximum characters of tool input shown in a permission prompt.
+const MAX_TOOL_INPUT_CHARS: usize = 500;
+
+/// Show which tool an agent wants to use and with what input.
+fn display_permission_request(request: &PermissionRequest) {
+    let input = match request.input.get("command").and_then(|c| c.as_str()) {
+        Some(command) => command.to_owned(),
+        None => request.input.to_string(),
+    };
+    let shown: String = input.chars().take(MAX_TOOL_INPUT_CHARS).collect();
+    let ellipsis = if shown.len() < input.len() { "..." } else { "" };
+    println!(
+        "[?] {} ({}) wants to use {}: {shown}{ellipsis}",
+        request.agent, request.phase, request.tool_name
+    );
+}
+
+/// Ask the user to allow or deny a tool call.
+async fn ask_permission() -> Result<PermissionDecision> {
+    loop {
+        let answer = read_line("    Allow? [y]es once / [a]lways this run / [n]o: ").await?;
+        if answer.is_empty() {
+            // stdin closed, nobody can approve
+            return Ok(PermissionDecision::Deny {
+                reason: "no answer from the user".to_owned(),
+            });
+        }
+        match answer.trim().to_lowercase().as_str() {
+            "y" | "yes" => return Ok(PermissionDecision::AllowOnce),
+            "a" | "always" => return Ok(PermissionDecision::AllowAlways),
+            "n" | "no" => {
+                let reason = read_line("    Reason: ").await?;
+                let reason = match reason.trim() {
+                    "" => "denied by the user".to_owned(),
+                    reason => reason.to_owned(),
+                };
+                return Ok(PermissionDecision::Deny { reason });
+            }
+            _ => continue,
+        }
+    }
+}
+
+/// Read a feature brief from `path`, or from stdin when `path` is `-`.
+async fn read_brief(path: &Path) -> Result<String> {
+    let brief = if path == Path::new("-") {
+        tokio::task::spawn_blocking(|| std::io::read_to_string(std::io::stdin()))
+            .await?
+            .context("failed to read brief from stdin")?
+    } else {
+        tokio::fs::read_to_string(path)
+            .await
+            .with_context(|| format!("failed to read brief {}", path.display()))?
+    };
+    if brief.trim().is_empty() {
+        anyhow::bail!("brief is empty");
+    }
+    Ok(brief)
+}
+
+/// Read a template context from a JSON file, or from stdin when `path` is
+/// `-`.
+async fn read_context(path: &Path) -> Result<serde_json::Value> {
+    let json = if path == Path::new("-") {
+        tokio::task::spawn_blocking(|| std::io::read_to_string(std::io::stdin()))
+            .await?
+            .context("failed to read context from stdin")?
+    } else {
+        tokio::fs::read_to_string(path)
+            .await
+            .with_context(|| format!("failed to read context {}", path.display()))?
+    };
+    serde_json::from_str(&json).context("context is not valid JSON")
+}
+
+/// Print `text`, ending it with a newline if it has none.
+fn print_text(text: &str) {
+    if text.ends_with('\n') {
+        print!("{text}");
+    } else {
+        println!("{text}");
+    }
+}
+
+/// Print `prompt` and read a line from stdin without blocking the runtime.
+async fn read_line(prompt: &'static str) -> Result<String> {
+    tokio::task::spawn_blocking(move || {
+        use std::io::Write;
+        print!("{prompt}");
+        std::io::stdout().flush().ok();
+        let mut line = String::new();
+        std::io::stdin().read_line(&mut line).map(|_| line)
+    })
+    .await?
+    .context("failed to read input")
+}
+
 /// Build an [`EngineConfig`] from CLI arguments.
 ///
-/// The typed-builder pattern changes the type on each setter call, so
-/// conditional model setting must be handled by building different configs.
-fn build_engine_config(repo: PathBuf, model: Option<String>) -> EngineConfig {
-    match model {
-        Some(m) => EngineConfig::builder().repo_path(repo).model(m).build(),
-        None => EngineConfig::builder().repo_path(repo).build(),
+/// Optional overrides use the builder's `*_opt` setters so that unset flags
+/// fall through to the layered project configuration.
+fn build_engine_config(
+    repo: PathBuf,
+    model: Option<String>,
+    permission_mode: Option<PermissionMode>,
+) -> EngineConfig {
+    EngineConfig::builder()
+        .repo_path(repo)
+        .model_opt(model)
+        .permission_mode_opt(permission_mode)
+        .build()
+}
+
+/// Parse a `--permission-mode` value (`auto`, `manual`, or `none`).
+fn parse_permission_mode(value: &str) -> Result<PermissionMode, String> {
+    serde_json::from_value(serde_json::Value::String(value.to_owned()))
+        .map_err(|_| format!("invalid permission mode {value:?} (expected auto, manual, none)"))
+}
+
+#[cfg(test)]
+mod tests {
+    use gba_core::{CoreError, StageKind};
+
+    use super::*;
+
+    fn exit_after(events: Vec<RunEvent>) -> JsonExit {
+        let mut state = RunExit::new();
+        for event in &events {
+            state.observe(event);
+        }
+        state.exit
+    }
+
+    #[test]
+    fn test_should_map_run_events_to_exit_codes() {
+        let verified = |passed| RunEvent::VerificationCompleted {
+            passed,
+            details: String::new(),
+        };
+
+        assert_eq!(
+            exit_after(vec![verified(true), RunEvent::Finished]),
+            JsonExit::Success
+        );
+        assert_eq!(
+            exit_after(vec![verified(false), RunEvent::Finished]),
+            JsonExit::VerificationFailed
+        );
+        assert_eq!(
+            exit_after(vec![RunEvent::Error(CoreError::NotInitialized)]),
+            JsonExit::Failed
+        );
+        let pr_failed = RunEvent::StageFinished {
+            name: "pr".to_owned(),
+            kind: StageKind::Pr,
+            status: StepStatus::Failed,
+            summary: "gh failed".to_owned(),
+        };
+        assert_eq!(
+            exit_after(vec![verified(true), pr_failed, RunEvent::Finished]),
+            JsonExit::StageFailed
+        );
+        assert_eq!(exit_after(vec![verified(true)]), JsonExit::Interrupted);
+        assert_eq!(exit_after(Vec::new()), JsonExit::Interrupted);
+        assert_eq!(
+            ExitCode::from(JsonExit::VerificationFailed),
+            ExitCode::from(3)
+        );
     }
 }
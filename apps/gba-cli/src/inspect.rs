@@ -0,0 +1,626 @@
+//! Rendering for `gba inspect`.
+//!
+//! Turns archived agent sessions into [`Entry`] blocks -- prompts, agent
+//! text, tool calls with their commands, outputs and file diffs, the
+//! result and a per-session summary of the tools used and time spent --
+//! printed as plain text or shown in the [`tui::inspect`] viewer, where
+//! prompts and tool calls expand one at a time.
+//!
+//! [`tui::inspect`]: crate::tui::inspect
+
+use std::collections::BTreeMap;
+
+use gba_core::{SessionItem, SessionRecord, ToolCall};
+use serde_json::Value;
+
+use crate::queue::format_duration;
+use crate::status::{aligned, format_age};
+
+/// Column headers of the session list.
+const HEADERS: [&str; 7] = [
+    "SESSION", "STAGE", "PHASE", "AGENT", "TEMPLATE", "COMMIT", "STARTED",
+];
+
+/// Column headers of the tool summary.
+const TOOL_HEADERS: [&str; 4] = ["TOOL", "CALLS", "FAILED", "TIME"];
+
+/// Maximum characters of a tool's target shown on its collapsed line.
+const MAX_TARGET_CHARS: usize = 80;
+
+/// Maximum lines of tool output shown per call.
+const MAX_OUTPUT_LINES: usize = 400;
+
+/// What an [`Entry`] shows.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EntryKind {
+    /// Session title: file, stage or phase, agent, iteration and commit.
+    Header,
+    /// Rendered system and task prompts.
+    Prompt,
+    /// Agent text.
+    Text,
+    /// Extended thinking.
+    Thinking,
+    /// A tool call with its input and output.
+    Tool,
+    /// The session's result message.
+    Result,
+    /// A session failure.
+    Error,
+    /// Tools used and time spent in the session.
+    Summary,
+}
+
+/// Color of a body line.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Tone {
+    /// Regular text.
+    Plain,
+    /// Line added by an edit.
+    Added,
+    /// Line removed by an edit.
+    Removed,
+    /// Command line.
+    Command,
+    /// Secondary text such as tool output.
+    Muted,
+    /// Error output.
+    Error,
+}
+
+/// One block of an inspected session: a heading line and a body that is
+/// hidden while the entry is collapsed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Entry {
+    /// What the entry shows.
+    pub kind: EntryKind,
+    /// Heading line.
+    pub head: String,
+    /// Whether the heading reports a failure.
+    pub failed: bool,
+    /// Body lines with their colors.
+    pub body: Vec<(Tone, String)>,
+}
+
+impl Entry {
+    fn new(kind: EntryKind, head: impl Into<String>) -> Self {
+        Self {
+            kind,
+            head: head.into(),
+            failed: false,
+            body: Vec::new(),
+        }
+    }
+
+    fn with_body(self, body: Vec<(Tone, String)>) -> Self {
+        Self { body, ..self }
+    }
+
+    /// Whether the body can be hidden: prompts, thinking and tool calls.
+    pub fn collapsible(&self) -> bool {
+        matches!(
+            self.kind,
+            EntryKind::Prompt | EntryKind::Thinking | EntryKind::Tool
+        )
+    }
+}
+
+/// Sessions matching every given filter: the one-based `phase`, the
+/// `stage` name and the `session` name (file name with or without
+/// `.jsonl`).
+pub fn select<'a>(
+    records: &'a [SessionRecord],
+    phase: Option<usize>,
+    stage: Option<&str>,
+    session: Option<&str>,
+) -> Vec<&'a SessionRecord> {
+    records
+        .iter()
+        .filter(|r| phase.is_none_or(|n| r.phase_index == Some(n - 1)))
+        .filter(|r| stage.is_none_or(|s| r.stage == s))
+        .filter(|r| session.is_none_or(|s| r.file == s || r.file == format!("{s}.jsonl")))
+        .collect()
+}
+
+/// Render the session list as a table.
+pub fn list(records: &[&SessionRecord], now: u64) -> String {
+    let rows: Vec<[String; 7]> = records
+        .iter()
+        .map(|r| {
+            let phase = match (&r.phase, r.phase_index) {
+                (Some(name), Some(index)) => format!("{}. {name}", index + 1),
+                _ => "-".to_owned(),
+            };
+            let template = if r.iteration > 1 {
+                format!("{} (iteration {})", r.template, r.iteration)
+            } else {
+                r.template.clone()
+            };
+            [
+                session_name(r).to_owned(),
+                r.stage.clone(),
+                phase,
+                r.agent.clone(),
+                template,
+                r.commit
+                    .as_deref()
+                    .map_or_else(|| "-".to_owned(), short_hash),
+                format_age(now.saturating_sub(r.started_at)),
+            ]
+        })
+        .collect();
+    aligned(HEADERS, &rows)
+}
+
+/// Entries of a session: its header, one entry per item and a summary.
+pub fn entries(record: &SessionRecord, items: &[SessionItem]) -> Vec<Entry> {
+    let mut entries = vec![header(record)];
+    entries.extend(items.iter().map(item_entry));
+    entries.push(summary(items));
+    entries
+}
+
+/// Render entries as plain text. Collapsible entries are marked `[+]` and
+/// show only their heading unless `expand` is set, then they are marked
+/// `[-]`.
+pub fn plain(entries: &[Entry], expand: bool) -> String {
+    let mut out = String::new();
+    for entry in entries {
+        let show_body = expand || !entry.collapsible();
+        match entry.kind {
+            EntryKind::Header => {
+                if !out.is_empty() {
+                    out.push('\n');
+                }
+                out.push_str(&format!("== {} ==\n", entry.head));
+            }
+            _ if entry.collapsible() => {
+                let marker = if expand { "[-]" } else { "[+]" };
+                out.push_str(&format!("{marker} {}\n", entry.head));
+            }
+            _ => out.push_str(&format!("{}\n", entry.head)),
+        }
+        if show_body {
+            for (_, line) in &entry.body {
+                out.push_str(format!("    {line}").trim_end());
+                out.push('\n');
+            }
+        }
+    }
+    out
+}
+
+/// Transcript name of a session, e.g. `review-2`.
+fn session_name(record: &SessionRecord) -> &str {
+    record.file.strip_suffix(".jsonl").unwrap_or(&record.file)
+}
+
+fn header(record: &SessionRecord) -> Entry {
+    let place = match (&record.phase, record.phase_index) {
+        (Some(name), Some(index)) => format!("phase {}: {name}", index + 1),
+        _ => format!("stage {}", record.stage),
+    };
+    let iteration = if record.iteration > 1 {
+        format!(", iteration {}", record.iteration)
+    } else {
+        String::new()
+    };
+    let commit = record.commit.as_deref().map_or_else(
+        || "not committed".to_owned(),
+        |hash| format!("commit {}", short_hash(hash)),
+    );
+    Entry::new(
+        EntryKind::Header,
+        format!(
+            "{} -- {place}{iteration}, {} ({}), {commit}",
+            session_name(record),
+            record.agent,
+            record.template
+        ),
+    )
+}
+
+fn item_entry(item: &SessionItem) -> Entry {
+    match item {
+        SessionItem::Prompt { system, task, .. } => {
+            let mut body = vec![(Tone::Muted, "System:".to_owned())];
+            body.extend(lines(system, Tone::Muted));
+            body.push((Tone::Plain, String::new()));
+            body.push((Tone::Plain, "Task:".to_owned()));
+            body.extend(lines(task, Tone::Plain));
+            Entry::new(
+                EntryKind::Prompt,
+                format!("Prompt ({} lines)", task.lines().count()),
+            )
+            .with_body(body)
+        }
+        SessionItem::Text { text, .. } => {
+            Entry::new(EntryKind::Text, "Agent").with_body(lines(text, Tone::Plain))
+        }
+        SessionItem::Thinking { text, .. } => Entry::new(
+            EntryKind::Thinking,
+            format!("Thinking ({} lines)", text.lines().count()),
+        )
+        .with_body(lines(text, Tone::Muted)),
+        SessionItem::ToolCall(call) => tool_entry(call),
+        SessionItem::Result {
+            turns,
+            duration_ms,
+            cost_usd,
+            is_error,
+            ..
+        } => {
+            let outcome = if *is_error { "error" } else { "success" };
+            let cost = cost_usd.map(|c| format!(", ${c:.2}")).unwrap_or_default();
+            Entry {
+                failed: *is_error,
+                ..Entry::new(
+                    EntryKind::Result,
+                    format!(
+                        "Result: {outcome}, {turns} turn(s) in {}{cost}",
+                        format_millis(*duration_ms)
+                    ),
+                )
+            }
+        }
+        SessionItem::Error { message, .. } => Entry {
+            failed: true,
+            ..Entry::new(EntryKind::Error, format!("Error: {message}"))
+        },
+    }
+}
+
+fn tool_entry(call: &ToolCall) -> Entry {
+    let input = &call.input;
+    let mut body = Vec::new();
+    let mut stats = String::new();
+    let target = match call.name.as_str() {
+        "Bash" => {
+            let command = str_field(input, "command");
+            body.extend(
+                command
+                    .lines()
+                    .map(|line| (Tone::Command, format!("$ {line}"))),
+            );
+            command.lines().next().unwrap_or_default().to_owned()
+        }
+        "Edit" | "MultiEdit" | "Write" => {
+            let edits: Vec<(&str, &str)> = match call.name.as_str() {
+                "Edit" => vec![(
+                    str_field(input, "old_string"),
+                    str_field(input, "new_string"),
+                )],
+                "MultiEdit" => input
+                    .get("edits")
+                    .and_then(Value::as_array)
+                    .map(|edits| {
+                        edits
+                            .iter()
+                            .map(|e| (str_field(e, "old_string"), str_field(e, "new_string")))
+                            .collect()
+                    })
+                    .unwrap_or_default(),
+                _ => vec![("", str_field(input, "content"))],
+            };
+            let (mut added, mut removed) = (0, 0);
+            for (i, (old, new)) in edits.into_iter().enumerate() {
+                if i > 0 {
+                    body.push((Tone::Muted, "...".to_owned()));
+                }
+                let hunk = diff(old, new);
+                added += hunk.iter().filter(|(t, _)| *t == Tone::Added).count();
+                removed += hunk.iter().filter(|(t, _)| *t == Tone::Removed).count();
+                body.extend(hunk);
+            }
+            stats = format!(" +{added} -{removed}");
+            str_field(input, "file_path").to_owned()
+        }
+        "Read" | "NotebookEdit" => input
+            .get("file_path")
+            .or_else(|| input.get("notebook_path"))
+            .and_then(Value::as_str)
+            .unwrap_or_default()
+            .to_owned(),
+        "Grep" | "Glob" => match input.get("path").and_then(Value::as_str) {
+            Some(path) => format!("{} in {path}", str_field(input, "pattern")),
+            None => str_field(input, "pattern").to_owned(),
+        },
+        "WebFetch" => str_field(input, "url").to_owned(),
+        "WebSearch" => str_field(input, "query").to_owned(),
+        "Task" => str_field(input, "description").to_owned(),
+        _ => {
+            let pretty = serde_json::to_string_pretty(input).unwrap_or_default();
+            body.extend(lines(&pretty, Tone::Muted));
+            input.to_string()
+        }
+    };
+
+    // Edits and writes are shown as diffs; their output is only a
+    // confirmation unless the tool failed
+    let edits = matches!(call.name.as_str(), "Edit" | "MultiEdit" | "Write");
+    match &call.output {
+        Some(output) if call.is_error || !edits => {
+            let tone = if call.is_error {
+                Tone::Error
+            } else {
+                Tone::Muted
+            };
+            let total = output.lines().count();
+            body.extend(
+                output
+                    .lines()
+                    .take(MAX_OUTPUT_LINES)
+                    .map(|l| (tone, l.to_owned())),
+            );
+            if total > MAX_OUTPUT_LINES {
+                body.push((
+                    Tone::Muted,
+                    format!("... {} more line(s)", total - MAX_OUTPUT_LINES),
+                ));
+            }
+        }
+        _ => {}
+    }
+
+    let timing = match call.finished_at {
+        Some(end) if call.is_error => format!(
+            "failed after {}",
+            format_millis(end.saturating_sub(call.started_at))
+        ),
+        Some(end) => format_millis(end.saturating_sub(call.started_at)),
+        None => "no result".to_owned(),
+    };
+    Entry {
+        failed: call.is_error,
+        ..Entry::new(
+            EntryKind::Tool,
+            format!("{} {}{stats} ({timing})", call.name, truncate(&target)),
+        )
+        .with_body(body)
+    }
+}
+
+/// Line diff of an edit: unchanged leading and trailing lines as context,
+/// the rest as removed and added lines.
+fn diff(old: &str, new: &str) -> Vec<(Tone, String)> {
+    let old: Vec<&str> = old.lines().collect();
+    let new: Vec<&str> = new.lines().collect();
+    let prefix = old.iter().zip(&new).take_while(|(a, b)| a == b).count();
+    let suffix = old[prefix..]
+        .iter()
+        .rev()
+        .zip(new[prefix..].iter().rev())
+        .take_while(|(a, b)| a == b)
+        .count();
+
+    let context = |line: &&str| (Tone::Plain, format!("  {line}"));
+    let mut out: Vec<(Tone, String)> = old[..prefix].iter().map(context).collect();
+    out.extend(
+        old[prefix..old.len() - suffix]
+            .iter()
+            .map(|line| (Tone::Removed, format!("- {line}"))),
+    );
+    out.extend(
+        new[prefix..new.len() - suffix]
+            .iter()
+            .map(|line| (Tone::Added, format!("+ {line}"))),
+    );
+    out.extend(old[old.len() - suffix..].iter().map(context));
+    out
+}
+
+/// Summary of the tools used and the time spent in a session.
+fn summary(items: &[SessionItem]) -> Entry {
+    // (calls, failed, milliseconds) per tool
+    let mut tools: BTreeMap<&str, (usize, usize, u64)> = BTreeMap::new();
+    for item in items {
+        if let SessionItem::ToolCall(call) = item {
+            let stats = tools.entry(&call.name).or_default();
+            stats.0 += 1;
+            stats.1 += usize::from(call.is_error);
+            stats.2 += call
+                .finished_at
+                .map_or(0, |end| end.saturating_sub(call.started_at));
+        }
+    }
+
+    let start = items.iter().map(SessionItem::at).min().unwrap_or(0);
+    let end = items
+        .iter()
+        .map(|item| match item {
+            SessionItem::ToolCall(call) => call.finished_at.unwrap_or(call.started_at),
+            _ => item.at(),
+        })
+        .max()
+        .unwrap_or(0);
+    let calls: usize = tools.values().map(|(calls, ..)| calls).sum();
+
+    let mut rows: Vec<(&str, (usize, usize, u64))> = tools.into_iter().collect();
+    rows.sort_by(|a, b| b.1.0.cmp(&a.1.0).then(a.0.cmp(b.0)));
+    let rows: Vec<[String; 4]> = rows
+        .into_iter()
+        .map(|(name, (calls, failed, ms))| {
+            [
+                name.to_owned(),
+                calls.to_string(),
+                failed.to_string(),
+                format_millis(ms),
+            ]
+        })
+        .collect();
+    let body = if rows.is_empty() {
+        Vec::new()
+    } else {
+        lines(&aligned(TOOL_HEADERS, &rows), Tone::Plain)
+    };
+    Entry::new(
+        EntryKind::Summary,
+        format!(
+            "Summary: {calls} tool call(s) in {}",
+            format_millis(end.saturating_sub(start))
+        ),
+    )
+    .with_body(body)
+}
+
+fn lines(text: &str, tone: Tone) -> Vec<(Tone, String)> {
+    text.lines().map(|line| (tone, line.to_owned())).collect()
+}
+
+fn str_field<'a>(value: &'a Value, key: &str) -> &'a str {
+    value.get(key).and_then(Value::as_str).unwrap_or_default()
+}
+
+fn truncate(text: &str) -> String {
+    if text.chars().count() <= MAX_TARGET_CHARS {
+        return text.to_owned();
+    }
+    let truncated: String = text.chars().take(MAX_TARGET_CHARS).collect();
+    format!("{truncated}...")
+}
+
+fn short_hash(hash: &str) -> String {
+    hash.chars().take(8).collect()
+}
+
+/// Format milliseconds, e.g. `350ms`, `4.2s` or `2m 5s`.
+fn format_millis(ms: u64) -> String {
+    match ms {
+        0..1_000 => format!("{ms}ms"),
+        1_000..60_000 => format!("{:.1}s", ms as f64 / 1_000.0),
+        _ => format_duration(ms / 1_000),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    fn record(file: &str, stage: &str, phase: Option<(usize, &str)>) -> SessionRecord {
+        SessionRecord {
+            file: file.to_owned(),
+            stage: stage.to_owned(),
+            agent: "code".to_owned(),
+            system: "code/system".to_owned(),
+            template: "code/task".to_owned(),
+            phase: phase.map(|(_, name)| name.to_owned()),
+            phase_index: phase.map(|(index, _)| index),
+            iteration: 1,
+            started_at: 1_000,
+            commit: Some("0123456789abcdef".to_owned()),
+        }
+    }
+
+    fn call(name: &str, input: Value, output: &str, started_at: u64, ms: u64) -> SessionItem {
+        SessionItem::ToolCall(ToolCall {
+            id: format!("t{started_at}"),
+            name: name.to_owned(),
+            input,
+            output: Some(output.to_owned()),
+            is_error: false,
+            started_at,
+            finished_at: Some(started_at + ms),
+        })
+    }
+
+    #[test]
+    fn test_should_select_sessions_by_phase_stage_and_name() {
+        let records = [
+            record("phases-1.jsonl", "phases", Some((0, "Setup"))),
+            record("phases-2.jsonl", "phases", Some((1, "Routes"))),
+            record("review-1.jsonl", "review", None),
+        ];
+
+        let files = |selected: Vec<&SessionRecord>| -> Vec<String> {
+            selected.iter().map(|r| r.file.clone()).collect()
+        };
+        assert_eq!(
+            files(select(&records, Some(2), None, None)),
+            ["phases-2.jsonl"]
+        );
+        assert_eq!(
+            files(select(&records, None, Some("review"), None)),
+            ["review-1.jsonl"]
+        );
+        assert_eq!(
+            files(select(&records, None, None, Some("phases-1"))),
+            ["phases-1.jsonl"]
+        );
+        assert_eq!(select(&records, None, None, None).len(), 3);
+        assert!(select(&records, Some(1), Some("review"), None).is_empty());
+
+        let table = list(&select(&records, None, None, None), 1_120);
+        assert!(table.starts_with("SESSION"));
+        assert!(table.contains("phases-2  phases  2. Routes"));
+        assert!(table.contains("01234567"));
+    }
+
+    #[test]
+    fn test_should_render_tool_calls_with_diffs_and_summary() {
+        let items = [
+            SessionItem::Text {
+                at: 10_000,
+                text: "Fixing the handler".to_owned(),
+            },
+            call(
+                "Edit",
+                json!({
+                    "file_path": "src/lib.rs",
+                    "old_string": "fn a() {\n    1\n}",
+                    "new_string": "fn a() {\n    2\n}"
+                }),
+                "ok",
+                10_000,
+                200,
+            ),
+            call(
+                "Bash",
+                json!({"command": "cargo test"}),
+                "test result: ok",
+                11_000,
+                4_200,
+            ),
+            call(
+                "Bash",
+                json!({"command": "git status"}),
+                "clean",
+                16_000,
+                100,
+            ),
+        ];
+
+        let entries = entries(
+            &record("phases-1.jsonl", "phases", Some((0, "Setup"))),
+            &items,
+        );
+        assert_eq!(entries.len(), 6);
+        assert_eq!(
+            entries[0].head,
+            "phases-1 -- phase 1: Setup, code (code/task), commit 01234567"
+        );
+        assert_eq!(entries[2].head, "Edit src/lib.rs +1 -1 (200ms)");
+        assert_eq!(
+            entries[2].body,
+            [
+                (Tone::Plain, "  fn a() {".to_owned()),
+                (Tone::Removed, "-     1".to_owned()),
+                (Tone::Added, "+     2".to_owned()),
+                (Tone::Plain, "  }".to_owned()),
+            ]
+        );
+        assert_eq!(entries[3].head, "Bash cargo test (4.2s)");
+        assert_eq!(entries[5].head, "Summary: 3 tool call(s) in 6.1s");
+        assert_eq!(entries[5].body[1].1, "Bash  2      0       4.3s");
+
+        let collapsed = plain(&entries, false);
+        assert!(collapsed.contains("[+] Bash cargo test (4.2s)\n[+] Bash git status"));
+        assert!(collapsed.contains("Agent\n    Fixing the handler\n"));
+        let expanded = plain(&entries, true);
+        assert!(
+            expanded
+                .contains("[-] Bash cargo test (4.2s)\n    $ cargo test\n    test result: ok\n")
+        );
+    }
+}
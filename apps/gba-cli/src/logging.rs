@@ -151,7 +151,7 @@ fn format_utc_timestamp(time: std::time::SystemTime) -> String {
 }
 
 /// Convert days since Unix epoch to (year, month, day) in the Gregorian calendar.
-fn days_to_date(days_since_epoch: u64) -> (u64, u64, u64) {
+pub fn days_to_date(days_since_epoch: u64) -> (u64, u64, u64) {
     // Algorithm based on civil_from_days from Howard Hinnant's date library.
     // Shifts epoch to 0000-03-01 for easier leap year handling.
     let z = days_since_epoch as i64 + 719_468;
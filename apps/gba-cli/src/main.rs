@@ -4,16 +4,24 @@
 //! clap, and dispatches to the selected subcommand via [`Cli::run`].
 
 mod cli;
+mod inspect;
 mod logging;
+mod prompts;
+mod queue;
+mod report;
+mod serve;
+mod status;
 mod tui;
 
+use std::process::ExitCode;
+
 use anyhow::Result;
 use clap::Parser;
 
 use crate::cli::Cli;
 
 #[tokio::main]
-async fn main() -> Result<()> {
+async fn main() -> Result<ExitCode> {
     let cli = Cli::parse();
 
     // Extract slug and repo_path before consuming cli.
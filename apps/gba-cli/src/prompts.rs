@@ -0,0 +1,93 @@
+//! Rendering for `gba prompts`.
+//!
+//! Formats the effective prompt templates as a table showing whether each is
+//! built in or loaded from a file.
+
+use std::path::Path;
+
+use gba_pm::{PromptTemplate, TemplateOrigin};
+
+use crate::status::aligned;
+
+/// Column headers of the template table.
+const HEADERS: [&str; 3] = ["TEMPLATE", "SOURCE", "FILE"];
+
+/// Render templates as a table. Files are shown relative to `repo` when
+/// they are inside it.
+pub fn table(templates: &[PromptTemplate], repo: &Path) -> String {
+    let rows: Vec<[String; 3]> = templates
+        .iter()
+        .map(|template| {
+            let file = template.origin.path().map_or_else(
+                || "-".to_owned(),
+                |path| {
+                    path.strip_prefix(repo)
+                        .unwrap_or(path)
+                        .display()
+                        .to_string()
+                },
+            );
+            [
+                template.name.clone(),
+                origin_name(&template.origin).to_owned(),
+                file,
+            ]
+        })
+        .collect();
+    aligned(HEADERS, &rows)
+}
+
+fn origin_name(origin: &TemplateOrigin) -> &'static str {
+    match origin {
+        TemplateOrigin::BuiltIn => "built-in",
+        TemplateOrigin::Override(_) => "override",
+        TemplateOrigin::Custom(_) => "custom",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use super::*;
+
+    fn template(name: &str, origin: TemplateOrigin) -> PromptTemplate {
+        PromptTemplate {
+            name: name.to_owned(),
+            source: String::new(),
+            origin,
+            contract: None,
+        }
+    }
+
+    #[test]
+    fn test_should_render_template_origins() {
+        let repo = Path::new("/repo");
+        let table = table(
+            &[
+                template("code/task", TemplateOrigin::BuiltIn),
+                template(
+                    "review/system",
+                    TemplateOrigin::Override(PathBuf::from(
+                        "/repo/.gba/agents/review/system.md.j2",
+                    )),
+                ),
+                template(
+                    "docs/system",
+                    TemplateOrigin::Custom(PathBuf::from("/shared/prompts/docs/system.md.j2")),
+                ),
+            ],
+            repo,
+        );
+        let lines: Vec<&str> = table.lines().collect();
+
+        assert!(lines[0].starts_with("TEMPLATE"));
+        assert!(lines[1].starts_with("code/task"));
+        assert!(lines[1].contains("built-in"));
+        assert!(lines[1].trim_end().ends_with('-'));
+        assert!(lines[2].contains("override"));
+        assert!(lines[2].ends_with(".gba/agents/review/system.md.j2"));
+        assert!(lines[3].contains("custom"));
+        assert!(lines[3].ends_with("/shared/prompts/docs/system.md.j2"));
+    }
+}
@@ -0,0 +1,199 @@
+//! Rendering for `gba queue`.
+//!
+//! Formats the run queue as a table and turns [`QueueEvent`]s into progress
+//! lines prefixed with the feature slug, so the output of parallel runs
+//! stays readable.
+
+use gba_core::{QueueEntry, QueueEvent, QueueStatus, RunEvent, StepStatus};
+
+use crate::status::{aligned, format_age};
+
+/// Column headers of the queue table.
+const HEADERS: [&str; 5] = ["FEATURE", "STATUS", "QUEUED", "DURATION", "RESULT"];
+
+/// Render queue entries as a table.
+pub fn table(entries: &[QueueEntry], now: u64) -> String {
+    let rows: Vec<[String; 5]> = entries
+        .iter()
+        .map(|entry| {
+            let duration = entry
+                .started_at
+                .map(|start| entry.finished_at.unwrap_or(now).saturating_sub(start));
+            [
+                entry.slug.clone(),
+                status_name(entry.status).to_owned(),
+                format_age(now.saturating_sub(entry.added_at)),
+                duration.map_or_else(|| "-".to_owned(), format_duration),
+                // First line only, so multi-line git errors keep the table intact
+                entry
+                    .error
+                    .as_deref()
+                    .and_then(|e| e.lines().next())
+                    .or(entry.pr.as_deref())
+                    .unwrap_or("-")
+                    .to_owned(),
+            ]
+        })
+        .collect();
+    aligned(HEADERS, &rows)
+}
+
+/// Progress line for a queue event, or `None` for events too detailed to
+/// show while several features run at once (agent output, usage, hooks).
+pub fn describe(event: &QueueEvent) -> Option<String> {
+    match event {
+        QueueEvent::FeatureStarted { slug } => Some(format!("[~] {slug}: started")),
+        QueueEvent::Run { slug, event } => describe_run_event(event)
+            .map(|(indicator, text)| format!("[{indicator}] {slug}: {text}")),
+        QueueEvent::FeatureFinished(entry) => Some(match (&entry.error, &entry.pr) {
+            (Some(error), _) => format!("[!] {}: failed: {error}", entry.slug),
+            (None, Some(pr)) => format!("[x] {}: completed, PR: {pr}", entry.slug),
+            (None, None) => format!("[x] {}: completed", entry.slug),
+        }),
+        QueueEvent::Error(e) => Some(format!("[!] Queue error: {e}")),
+        QueueEvent::Finished { .. } => None,
+    }
+}
+
+fn describe_run_event(event: &RunEvent) -> Option<(&'static str, String)> {
+    match event {
+        RunEvent::StageFinished {
+            name,
+            status,
+            summary,
+            ..
+        } => {
+            let indicator = if *status == StepStatus::Failed {
+                "!"
+            } else {
+                "x"
+            };
+            Some((indicator, format!("stage {name}: {summary}")))
+        }
+        RunEvent::PhaseStarted { index, name } => {
+            Some(("~", format!("phase {}: {name}", index + 1)))
+        }
+        RunEvent::PhaseCommitted { index, commit_hash } => {
+            Some(("x", format!("phase {} committed: {commit_hash}", index + 1)))
+        }
+        RunEvent::VerificationCompleted { passed, details } => {
+            let indicator = if *passed { "x" } else { "!" };
+            Some((indicator, format!("verification: {details}")))
+        }
+        RunEvent::PermissionRequested(request) => Some((
+            "!",
+            format!(
+                "denied {} for {} (no prompts in queue runs)",
+                request.tool_name, request.agent
+            ),
+        )),
+        RunEvent::ToolDenied(denial) => Some((
+            "!",
+            format!(
+                "denied {} for {}: {}",
+                denial.tool, denial.agent, denial.reason
+            ),
+        )),
+        RunEvent::PrCreated { url } => Some(("x", format!("PR created: {url}"))),
+        RunEvent::Error(e) => Some(("!", format!("error: {e}"))),
+        _ => None,
+    }
+}
+
+/// Lowercase name of a queue status.
+fn status_name(status: QueueStatus) -> &'static str {
+    match status {
+        QueueStatus::Queued => "queued",
+        QueueStatus::Running => "running",
+        QueueStatus::Completed => "completed",
+        QueueStatus::Failed => "failed",
+    }
+}
+
+/// Format a duration with its two largest units, e.g. `1h 5m`.
+pub fn format_duration(secs: u64) -> String {
+    match secs {
+        0..60 => format!("{secs}s"),
+        60..3_600 => format!("{}m {}s", secs / 60, secs % 60),
+        _ => format!("{}h {}m", secs / 3_600, secs % 3_600 / 60),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(slug: &str, status: QueueStatus) -> QueueEntry {
+        QueueEntry {
+            slug: slug.to_owned(),
+            status,
+            added_at: 1_000,
+            started_at: None,
+            finished_at: None,
+            pr: None,
+            error: None,
+        }
+    }
+
+    #[test]
+    fn test_should_render_queue_table() {
+        let failed = QueueEntry {
+            started_at: Some(1_000),
+            finished_at: Some(1_000 + 125),
+            error: Some("verification failed\ndetails".to_owned()),
+            ..entry("login", QueueStatus::Failed)
+        };
+        let running = QueueEntry {
+            started_at: Some(1_100),
+            ..entry("search", QueueStatus::Running)
+        };
+
+        let table = table(
+            &[failed, running, entry("export", QueueStatus::Queued)],
+            1_200,
+        );
+        let lines: Vec<&str> = table.lines().collect();
+
+        assert_eq!(lines.len(), 4);
+        assert!(lines[0].starts_with("FEATURE  STATUS"));
+        assert!(lines[1].contains("failed"));
+        assert!(lines[1].contains("2m 5s"));
+        assert!(lines[1].ends_with("verification failed"));
+        assert!(lines[2].contains("1m 40s"));
+        assert!(lines[3].ends_with("-"));
+    }
+
+    #[test]
+    fn test_should_describe_key_events_only() {
+        let finished = QueueEvent::FeatureFinished(QueueEntry {
+            pr: Some("https://github.com/o/r/pull/1".to_owned()),
+            ..entry("login", QueueStatus::Completed)
+        });
+        assert_eq!(
+            describe(&finished).as_deref(),
+            Some("[x] login: completed, PR: https://github.com/o/r/pull/1")
+        );
+
+        let output = QueueEvent::Run {
+            slug: "login".to_owned(),
+            event: RunEvent::AgentOutput {
+                agent: "code".to_owned(),
+                phase: "Setup".to_owned(),
+                text: "working".to_owned(),
+            },
+        };
+        assert_eq!(describe(&output), None);
+
+        let committed = QueueEvent::Run {
+            slug: "login".to_owned(),
+            event: RunEvent::PhaseCommitted {
+                index: 0,
+                commit_hash: "abc123".to_owned(),
+            },
+        };
+        assert_eq!(
+            describe(&committed).as_deref(),
+            Some("[x] login: phase 1 committed: abc123")
+        );
+    }
+}
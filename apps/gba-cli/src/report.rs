@@ -0,0 +1,607 @@
+//! Rendering for `gba report`.
+//!
+//! Lays a [`Report`] out as a list of blocks (headings, text, lists, tables,
+//! code) and renders those as Markdown or as a self-contained HTML page, so
+//! both formats carry the same content.
+
+use std::fmt::Write as _;
+
+use gba_core::{CommitSummary, Issue, Report, Severity, StepStatus};
+
+use crate::logging::days_to_date;
+use crate::status::status_name;
+
+/// Styles embedded in the HTML report.
+const STYLE: &str = "\
+body{font-family:-apple-system,'Segoe UI',Helvetica,Arial,sans-serif;\
+max-width:960px;margin:2rem auto;padding:0 1rem;color:#1f2328;line-height:1.5}\
+h1,h2{border-bottom:1px solid #d1d9e0;padding-bottom:.3em}\
+table{border-collapse:collapse;margin:1em 0}\
+th,td{border:1px solid #d1d9e0;padding:.3em .8em;text-align:left;vertical-align:top}\
+th{background:#f6f8fa}\
+pre{background:#f6f8fa;padding:1em;overflow-x:auto;white-space:pre-wrap}";
+
+/// A piece of report content.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Block {
+    /// Heading of the given level (1-3).
+    Heading(usize, String),
+    /// Paragraph of plain text.
+    Text(String),
+    /// Bulleted list.
+    List(Vec<String>),
+    /// Table with a header row.
+    Table {
+        headers: Vec<&'static str>,
+        rows: Vec<Vec<String>>,
+    },
+    /// Preformatted text.
+    Code(String),
+}
+
+/// Render the report as Markdown.
+pub fn markdown(report: &Report) -> String {
+    let mut out = String::new();
+    for block in blocks(report) {
+        match block {
+            Block::Heading(level, text) => {
+                let _ = writeln!(out, "{} {text}\n", "#".repeat(level));
+            }
+            Block::Text(text) => {
+                let _ = writeln!(out, "{text}\n");
+            }
+            Block::List(items) => {
+                for item in items {
+                    let _ = writeln!(out, "- {}", item.replace('\n', " "));
+                }
+                out.push('\n');
+            }
+            Block::Table { headers, rows } => {
+                let _ = writeln!(out, "| {} |", headers.join(" | "));
+                let _ = writeln!(out, "|{}", "---|".repeat(headers.len()));
+                for row in rows {
+                    let cells: Vec<String> = row.iter().map(|c| markdown_cell(c)).collect();
+                    let _ = writeln!(out, "| {} |", cells.join(" | "));
+                }
+                out.push('\n');
+            }
+            Block::Code(text) => {
+                let fence = "`".repeat(longest_backtick_run(&text).max(2) + 1);
+                let _ = writeln!(out, "{fence}\n{}\n{fence}\n", text.trim_end());
+            }
+        }
+    }
+    out.truncate(out.trim_end().len());
+    out.push('\n');
+    out
+}
+
+/// Render the report as a self-contained HTML page.
+pub fn html(report: &Report) -> String {
+    let mut out = format!(
+        "<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">\n\
+         <title>{}</title>\n<style>{STYLE}</style>\n</head>\n<body>\n",
+        escape(&format!("Execution report: {}", report.slug))
+    );
+    for block in blocks(report) {
+        match block {
+            Block::Heading(level, text) => {
+                let _ = writeln!(out, "<h{level}>{}</h{level}>", escape(&text));
+            }
+            Block::Text(text) => {
+                let _ = writeln!(out, "<p>{}</p>", escape(&text));
+            }
+            Block::List(items) => {
+                out.push_str("<ul>\n");
+                for item in items {
+                    let _ = writeln!(out, "<li>{}</li>", escape(&item));
+                }
+                out.push_str("</ul>\n");
+            }
+            Block::Table { headers, rows } => {
+                out.push_str("<table>\n<tr>");
+                for header in headers {
+                    let _ = write!(out, "<th>{}</th>", escape(header));
+                }
+                out.push_str("</tr>\n");
+                for row in rows {
+                    out.push_str("<tr>");
+                    for cell in row {
+                        let _ = write!(out, "<td>{}</td>", escape(&cell));
+                    }
+                    out.push_str("</tr>\n");
+                }
+                out.push_str("</table>\n");
+            }
+            Block::Code(text) => {
+                let _ = writeln!(out, "<pre>{}</pre>", escape(text.trim_end()));
+            }
+        }
+    }
+    out.push_str("</body>\n</html>\n");
+    out
+}
+
+/// Lay out the report's content.
+fn blocks(report: &Report) -> Vec<Block> {
+    let spec = &report.spec;
+    let execution = spec.execution.as_ref();
+    let mut blocks = vec![Block::Heading(
+        1,
+        format!("Execution report: {}", spec.feature),
+    )];
+
+    // ── Summary ──
+    let completed = spec
+        .phases
+        .iter()
+        .filter(|p| p.result.as_ref().map(|r| &r.status) == Some(&StepStatus::Completed))
+        .count();
+    let mut summary = vec![
+        field("Feature", &report.slug),
+        field(
+            "Branch",
+            &format!("{} (from {})", report.branch, report.base_branch),
+        ),
+        field(
+            "Status",
+            execution.map_or("not run to completion", |e| status_name(&e.status)),
+        ),
+        field(
+            "Phases",
+            &format!("{completed}/{} completed", spec.phases.len()),
+        ),
+    ];
+    if let Some(execution) = execution {
+        summary.push(field("Agent turns", &execution.total_turns.to_string()));
+        summary.push(field(
+            "Review",
+            &format!(
+                "{} issue(s) found, {} fixed",
+                execution.review.issues_found, execution.review.issues_fixed
+            ),
+        ));
+        summary.push(field(
+            "Verification",
+            if execution.verification.passed {
+                "passed"
+            } else {
+                "failed"
+            },
+        ));
+        if let Some(pr) = &execution.pr {
+            summary.push(field("Pull request", pr));
+        }
+    }
+    if report.totals.sessions > 0 {
+        summary.push(field(
+            "Tokens",
+            &format!(
+                "{} in / {} out",
+                report.totals.input_tokens, report.totals.output_tokens
+            ),
+        ));
+        summary.push(field("Cost", &format_cost(report.totals.cost_usd)));
+    }
+    summary.push(field("Generated", &format_time(report.generated_at)));
+    blocks.push(Block::List(summary));
+
+    // ── Phases ──
+    blocks.push(Block::Heading(2, "Phases".to_owned()));
+    for (i, phase) in spec.phases.iter().enumerate() {
+        let status = phase.result.as_ref().map(|r| &r.status);
+        blocks.push(Block::Heading(
+            3,
+            format!(
+                "{}. {} ({})",
+                i + 1,
+                phase.name,
+                status.map_or("pending", status_name)
+            ),
+        ));
+        blocks.push(Block::Text(phase.description.clone()));
+        blocks.push(Block::List(phase.tasks.clone()));
+        if let Some(result) = &phase.result {
+            let commit = result.commit.as_deref().map_or("none", short_hash);
+            blocks.push(Block::Text(format!(
+                "{} turn(s), commit {commit}",
+                result.turns
+            )));
+        }
+    }
+
+    // ── Stages ──
+    if let Some(execution) = execution.filter(|e| !e.stages.is_empty()) {
+        blocks.push(Block::Heading(2, "Stages".to_owned()));
+        blocks.push(Block::Table {
+            headers: vec!["Stage", "Kind", "Status", "Turns"],
+            rows: execution
+                .stages
+                .iter()
+                .map(|s| {
+                    vec![
+                        s.name.clone(),
+                        s.kind.to_string(),
+                        status_name(&s.status).to_owned(),
+                        s.turns.to_string(),
+                    ]
+                })
+                .collect(),
+        });
+    }
+
+    // ── Commits ──
+    blocks.push(Block::Heading(2, "Commits".to_owned()));
+    if report.commits.is_empty() {
+        blocks.push(Block::Text(format!(
+            "No commits on {} beyond {}.",
+            report.branch, report.base_branch
+        )));
+    } else {
+        let insertions: u64 = report.commits.iter().map(|c| c.insertions).sum();
+        let deletions: u64 = report.commits.iter().map(|c| c.deletions).sum();
+        blocks.push(Block::Text(format!(
+            "{} commit(s), +{insertions} -{deletions}",
+            report.commits.len()
+        )));
+        blocks.push(Block::Table {
+            headers: vec!["Commit", "Subject", "Files", "+", "-", "Date"],
+            rows: report.commits.iter().map(commit_row).collect(),
+        });
+        for commit in &report.commits {
+            blocks.push(Block::Heading(
+                3,
+                format!("{} {}", short_hash(&commit.hash), commit.subject),
+            ));
+            blocks.push(Block::List(
+                commit
+                    .files
+                    .iter()
+                    .map(|f| match (f.insertions, f.deletions) {
+                        (Some(added), Some(removed)) => {
+                            format!("{} (+{added} -{removed})", f.path)
+                        }
+                        _ => format!("{} (binary)", f.path),
+                    })
+                    .collect(),
+            ));
+        }
+    }
+
+    // ── Review ──
+    blocks.push(Block::Heading(2, "Code review".to_owned()));
+    if report.review_rounds.is_empty() {
+        blocks.push(Block::Text("No review rounds recorded.".to_owned()));
+    }
+    for round in &report.review_rounds {
+        blocks.push(Block::Heading(
+            3,
+            format!(
+                "{} round {} ({})",
+                round.stage,
+                round.iteration,
+                round.reviewers.join(", ")
+            ),
+        ));
+        for failure in &round.failed {
+            blocks.push(Block::Text(format!(
+                "Reviewer {} failed: {}",
+                failure.reviewer, failure.error
+            )));
+        }
+        if round.issues.is_empty() {
+            blocks.push(Block::Text("No issues.".to_owned()));
+        } else {
+            blocks.push(Block::Table {
+                headers: vec!["Severity", "Location", "Issue", "Raised by"],
+                rows: round.issues.iter().map(issue_row).collect(),
+            });
+        }
+    }
+
+    // ── Verification ──
+    blocks.push(Block::Heading(2, "Verification".to_owned()));
+    blocks.push(Block::Heading(3, "Criteria".to_owned()));
+    blocks.push(Block::List(spec.verification.criteria.clone()));
+    blocks.push(Block::Heading(3, "Test commands".to_owned()));
+    blocks.push(Block::Code(spec.verification.test_commands.join("\n")));
+    if let Some(execution) = execution {
+        let verdict = if execution.verification.passed {
+            "passed"
+        } else {
+            "failed"
+        };
+        blocks.push(Block::Text(format!(
+            "Verification {verdict} after {} turn(s).",
+            execution.verification.turns
+        )));
+        if let Some(evidence) = &execution.verification.evidence {
+            blocks.push(Block::Heading(3, "Evidence".to_owned()));
+            blocks.push(Block::Code(evidence.clone()));
+        }
+    }
+
+    // ── Hooks ──
+    if let Some(execution) = execution.filter(|e| !e.hooks.is_empty()) {
+        blocks.push(Block::Heading(2, "Hooks".to_owned()));
+        blocks.push(Block::Table {
+            headers: vec!["Phase", "Hook", "Command", "Attempt", "Result"],
+            rows: execution
+                .hooks
+                .iter()
+                .map(|h| {
+                    vec![
+                        h.phase.clone(),
+                        h.hook.clone(),
+                        h.command.clone(),
+                        h.attempt.to_string(),
+                        if h.passed { "passed" } else { "failed" }.to_owned(),
+                    ]
+                })
+                .collect(),
+        });
+    }
+
+    // ── Tool denials ──
+    if let Some(execution) = execution.filter(|e| !e.denials.is_empty()) {
+        blocks.push(Block::Heading(2, "Denied tool calls".to_owned()));
+        blocks.push(Block::Table {
+            headers: vec!["Agent", "Phase", "Tool", "Reason"],
+            rows: execution
+                .denials
+                .iter()
+                .map(|d| {
+                    vec![
+                        d.agent.clone(),
+                        d.phase.clone(),
+                        d.tool.clone(),
+                        d.reason.clone(),
+                    ]
+                })
+                .collect(),
+        });
+    }
+
+    // ── Costs ──
+    if let Some(execution) = execution.filter(|e| !e.usage.is_empty()) {
+        blocks.push(Block::Heading(2, "Costs".to_owned()));
+        let totals = &report.totals;
+        let mut rows: Vec<Vec<String>> = execution
+            .usage
+            .iter()
+            .map(|u| {
+                vec![
+                    u.agent.clone(),
+                    u.phase.clone(),
+                    u.turns.to_string(),
+                    u.input_tokens.to_string(),
+                    u.output_tokens.to_string(),
+                    format_cost(u.cost_usd),
+                ]
+            })
+            .collect();
+        rows.push(vec![
+            "Total".to_owned(),
+            format!("{} session(s)", totals.sessions),
+            totals.turns.to_string(),
+            totals.input_tokens.to_string(),
+            totals.output_tokens.to_string(),
+            format_cost(totals.cost_usd),
+        ]);
+        blocks.push(Block::Table {
+            headers: vec![
+                "Agent",
+                "Phase",
+                "Turns",
+                "Input tokens",
+                "Output tokens",
+                "Cost",
+            ],
+            rows,
+        });
+    }
+
+    blocks
+}
+
+fn field(label: &str, value: &str) -> String {
+    format!("{label}: {value}")
+}
+
+fn commit_row(commit: &CommitSummary) -> Vec<String> {
+    vec![
+        short_hash(&commit.hash).to_owned(),
+        commit.subject.clone(),
+        commit.files.len().to_string(),
+        commit.insertions.to_string(),
+        commit.deletions.to_string(),
+        format_time(commit.time),
+    ]
+}
+
+fn issue_row(issue: &Issue) -> Vec<String> {
+    let severity = match issue.severity {
+        Severity::Error => "error",
+        Severity::Warning => "warning",
+        Severity::Suggestion => "suggestion",
+    };
+    let file = issue.file.display();
+    let location = match issue.line {
+        Some(line) => format!("{file}:{line}"),
+        None => file.to_string(),
+    };
+    vec![
+        severity.to_owned(),
+        location,
+        issue.description.clone(),
+        issue.reviewers.join(", "),
+    ]
+}
+
+fn short_hash(hash: &str) -> &str {
+    hash.get(..7).unwrap_or(hash)
+}
+
+fn format_cost(cost: Option<f64>) -> String {
+    cost.map_or_else(|| "-".to_owned(), |c| format!("${c:.2}"))
+}
+
+/// Format seconds since the Unix epoch as `YYYY-MM-DD HH:MM UTC`.
+fn format_time(secs: u64) -> String {
+    let (year, month, day) = days_to_date(secs / 86_400);
+    let minutes = secs % 86_400 / 60;
+    format!(
+        "{year:04}-{month:02}-{day:02} {:02}:{:02} UTC",
+        minutes / 60,
+        minutes % 60
+    )
+}
+
+/// Keep a table cell on one line and its pipes from splitting the cell.
+fn markdown_cell(text: &str) -> String {
+    text.replace('|', "\\|").replace('\n', "<br>")
+}
+
+fn longest_backtick_run(text: &str) -> usize {
+    text.split(|c| c != '`').map(str::len).max().unwrap_or(0)
+}
+
+fn escape(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\'' => out.push_str("&#39;"),
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use gba_core::{
+        AgentUsage, Execution, FeatureSpec, FileChange, HookRun, ReviewResult, ReviewRound,
+        UsageTotals, VerificationResult,
+    };
+
+    use super::*;
+
+    fn report() -> Report {
+        let spec: FeatureSpec = serde_yaml::from_str(
+            "feature: Login page\n\
+             phases:\n  - name: Setup\n    description: Scaffold\n    tasks: [Add routes]\n\
+             \x20   result:\n      status: completed\n      turns: 4\n      commit: abcdef1234\n\
+             verification:\n  criteria: [Users can log in]\n  testCommands: [cargo test]\n",
+        )
+        .expect("should parse spec");
+        let usage = vec![AgentUsage {
+            agent: "code".to_owned(),
+            phase: "Setup".to_owned(),
+            turns: 4,
+            input_tokens: 1200,
+            output_tokens: 300,
+            cost_usd: Some(0.42),
+        }];
+        let totals = UsageTotals::from_sessions(&usage);
+        Report {
+            slug: "login".to_owned(),
+            branch: "feat/login".to_owned(),
+            base_branch: "main".to_owned(),
+            generated_at: 1_700_000_000,
+            spec: FeatureSpec {
+                execution: Some(Execution {
+                    status: StepStatus::Completed,
+                    total_turns: 4,
+                    review: ReviewResult {
+                        turns: 0,
+                        issues_found: 1,
+                        issues_fixed: 1,
+                    },
+                    verification: VerificationResult {
+                        turns: 2,
+                        passed: true,
+                        evidence: Some("test result: ok. 3 passed".to_owned()),
+                    },
+                    pr: None,
+                    stages: Vec::new(),
+                    denials: Vec::new(),
+                    hooks: vec![HookRun {
+                        phase: "Setup".to_owned(),
+                        hook: "fmt".to_owned(),
+                        command: "cargo fmt --check".to_owned(),
+                        attempt: 1,
+                        passed: true,
+                    }],
+                    usage,
+                }),
+                ..spec
+            },
+            commits: vec![CommitSummary {
+                hash: "abcdef1234".to_owned(),
+                time: 1_700_000_000,
+                subject: "feat(login): phase 1 - Setup".to_owned(),
+                insertions: 10,
+                deletions: 2,
+                files: vec![FileChange {
+                    path: "src/a|b.rs".to_owned(),
+                    insertions: Some(10),
+                    deletions: Some(2),
+                }],
+            }],
+            review_rounds: vec![ReviewRound {
+                stage: "review".to_owned(),
+                iteration: 1,
+                reviewers: vec!["security".to_owned()],
+                failed: Vec::new(),
+                issues: vec![Issue {
+                    severity: Severity::Warning,
+                    file: "src/<main>.rs".into(),
+                    line: Some(3),
+                    description: "Unchecked input".to_owned(),
+                    reviewers: vec!["security".to_owned()],
+                }],
+            }],
+            totals,
+        }
+    }
+
+    #[test]
+    fn test_should_render_markdown_report() {
+        let md = markdown(&report());
+
+        assert!(md.starts_with("# Execution report: Login page\n"));
+        assert!(md.contains("- Status: completed\n"));
+        assert!(md.contains("- Generated: 2023-11-14 22:13 UTC\n"));
+        assert!(md.contains("### 1. Setup (completed)\n"));
+        assert!(md.contains("4 turn(s), commit abcdef1\n"));
+        assert!(md.contains("| abcdef1 | feat(login): phase 1 - Setup | 1 | 10 | 2 |"));
+        assert!(md.contains("- src/a|b.rs (+10 -2)\n"));
+        assert!(md.contains("| warning | src/<main>.rs:3 | Unchecked input | security |"));
+        assert!(md.contains("```\ntest result: ok. 3 passed\n```\n"));
+        assert!(md.contains("| Setup | fmt | cargo fmt --check | 1 | passed |"));
+        assert!(md.contains("| Total | 1 session(s) | 4 | 1200 | 300 | $0.42 |"));
+    }
+
+    #[test]
+    fn test_should_render_escaped_html_report() {
+        let html = html(&report());
+
+        assert!(html.starts_with("<!DOCTYPE html>"));
+        assert!(html.contains("<style>"));
+        assert!(html.contains("<h1>Execution report: Login page</h1>"));
+        assert!(html.contains("<td>src/&lt;main&gt;.rs:3</td>"));
+        assert!(html.contains("<pre>test result: ok. 3 passed</pre>"));
+        assert!(!html.contains("<main>"));
+        assert!(html.trim_end().ends_with("</html>"));
+    }
+
+    #[test]
+    fn test_should_fence_code_longer_than_its_backticks() {
+        assert_eq!(longest_backtick_run("a ``` b ` c"), 3);
+        assert_eq!(markdown_cell("a|b\nc"), "a\\|b<br>c");
+    }
+}
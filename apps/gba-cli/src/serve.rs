@@ -0,0 +1,1028 @@
+//! Local HTTP API for `gba serve`.
+//!
+//! Exposes [`Engine`] operations to dashboards and editor integrations:
+//! feature status, runs and plan sessions. Each run or plan session is
+//! driven by a background task that records its events; clients stream them
+//! as Server-Sent Events in their versioned JSON form ([`EventRecord`]) and
+//! may connect late or reconnect with `Last-Event-ID` without missing any.
+//!
+//! | Method   | Path                          | Description                          |
+//! |----------|-------------------------------|--------------------------------------|
+//! | `GET`    | `/features`                   | Status of every planned feature      |
+//! | `GET`    | `/features/{slug}`            | Status of one feature                |
+//! | `GET`    | `/runs`, `/plans`             | Sessions started by this server      |
+//! | `POST`   | `/runs`                       | Start a run: `{"slug"}`              |
+//! | `POST`   | `/plans`                      | Start a plan session: `{"slug", "brief"?, "resume"?, "amend"?}` |
+//! | `GET`    | `/runs/{id}`, `/plans/{id}`   | One session                          |
+//! | `GET`    | `/runs/{id}/events`           | Run events (SSE)                     |
+//! | `GET`    | `/plans/{id}/events`          | Plan events (SSE)                    |
+//! | `POST`   | `/runs/{id}/permissions`      | Answer a permission request: `{"requestId", "decision", "reason"?}` |
+//! | `POST`   | `/plans/{id}/messages`        | Reply to the planning agent: `{"text"}` |
+//! | `POST`   | `/plans/{id}/finish`          | Finish the plan session              |
+//! | `DELETE` | `/runs/{id}`, `/plans/{id}`   | Cancel the session                   |
+//!
+//! A cancelled run stays `running` until the engine has stopped it. A feature
+//! runs at most once at a time, also across processes: starting a run of a
+//! feature that `gba run` or a queue run is running fails with `409`.
+//!
+//! Every request must be addressed (`Host`) to the listening address or
+//! localhost, which keeps DNS-rebound pages out. Browser requests are only
+//! accepted from the configured CORS origins, and with a token configured
+//! every request must carry it as `Authorization: Bearer <token>`.
+//!
+//! Errors are returned as `{"error": {"kind", "message"}}`.
+
+use std::collections::BTreeMap;
+use std::convert::Infallible;
+use std::net::SocketAddr;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard};
+
+use anyhow::{Context, Result};
+use axum::extract::{Path, Request, State};
+use axum::http::{HeaderMap, HeaderValue, Method, StatusCode, header};
+use axum::middleware::{self, Next};
+use axum::response::sse::{Event, KeepAlive, Sse};
+use axum::response::{IntoResponse, Response};
+use axum::routing::{get, post};
+use axum::{Json, Router};
+use futures::{Stream, StreamExt, stream};
+use serde::{Deserialize, Serialize};
+use tokio::net::TcpListener;
+use tokio::sync::{broadcast, mpsc};
+use tracing::{info, warn};
+
+use gba_core::{
+    CoreError, Engine, EventRecord, FeatureStatus, PermissionDecision, PlanEvent, PlanSession,
+    RunEvent, RunStream,
+};
+
+/// Capacity of the live event and command channels of a session. Clients
+/// that fall further behind are disconnected and catch up on reconnect.
+const CHANNEL_SIZE: usize = 1024;
+
+/// An event's id and versioned JSON form.
+type RecordedEvent = (usize, Arc<str>);
+
+/// Who may call the API, checked on every request.
+#[derive(Debug, Clone, Default)]
+pub struct Access {
+    /// Token requests must send as `Authorization: Bearer <token>`, if any.
+    pub token: Option<String>,
+    /// Browser origins allowed to call the API. Requests from any other
+    /// origin are rejected.
+    pub cors_origins: Vec<String>,
+}
+
+/// Serve the API for `engine` on `listener` until the process is stopped.
+///
+/// # Errors
+///
+/// Returns an error if the listener's address cannot be read or the server
+/// fails.
+pub async fn serve(engine: Engine, listener: TcpListener, access: Access) -> Result<()> {
+    let addr = listener.local_addr().context("failed to read address")?;
+    info!(%addr, token = access.token.is_some(), origins = ?access.cors_origins, "serving API");
+
+    let guard = Arc::new(Guard::new(access, addr));
+    axum::serve(listener, router(Arc::new(AppState::new(engine)), guard))
+        .await
+        .context("server failed")
+}
+
+fn router(state: Arc<AppState>, guard: Arc<Guard>) -> Router {
+    Router::new()
+        .route("/features", get(list_features))
+        .route("/features/{slug}", get(feature_status))
+        .route("/runs", get(list_runs).post(start_run))
+        .route("/runs/{id}", get(get_run).delete(cancel_run))
+        .route("/runs/{id}/events", get(run_events))
+        .route("/runs/{id}/permissions", post(answer_permission))
+        .route("/plans", get(list_plans).post(start_plan))
+        .route("/plans/{id}", get(get_plan).delete(cancel_plan))
+        .route("/plans/{id}/events", get(plan_events))
+        .route("/plans/{id}/messages", post(send_message))
+        .route("/plans/{id}/finish", post(finish_plan))
+        .layer(middleware::from_fn_with_state(guard, check_access))
+        .with_state(state)
+}
+
+// ── Access ───────────────────────────────────────────────────
+
+/// Request checks derived from [`Access`] and the bound address.
+#[derive(Debug)]
+struct Guard {
+    /// Configured access.
+    access: Access,
+    /// `Host` header values the server answers to.
+    hosts: Vec<String>,
+}
+
+impl Guard {
+    fn new(access: Access, addr: SocketAddr) -> Self {
+        let port = addr.port();
+        let mut hosts = vec![
+            format!("localhost:{port}"),
+            format!("127.0.0.1:{port}"),
+            format!("[::1]:{port}"),
+        ];
+        let bound = addr.to_string();
+        if !hosts.contains(&bound) {
+            hosts.push(bound);
+        }
+        Self { access, hosts }
+    }
+
+    /// Check a request's `Host`, `Origin` and token. Returns the origin to
+    /// allow in the CORS headers of the response, if any.
+    ///
+    /// A `Host` other than the bound address or localhost means the request
+    /// was sent to another name that resolves here (DNS rebinding).
+    /// Preflight requests carry no token and are not checked for one.
+    fn check(&self, headers: &HeaderMap, preflight: bool) -> Result<Option<HeaderValue>, ApiError> {
+        let host = headers
+            .get(header::HOST)
+            .and_then(|v| v.to_str().ok())
+            .unwrap_or_default();
+        if !self.hosts.iter().any(|h| h.eq_ignore_ascii_case(host)) {
+            return Err(ApiError::Forbidden(format!("host {host:?} is not allowed")));
+        }
+
+        let origin = match headers.get(header::ORIGIN) {
+            None => None,
+            Some(origin) => {
+                let allowed = origin.to_str().is_ok_and(|origin| {
+                    self.access
+                        .cors_origins
+                        .iter()
+                        .any(|o| o.trim_end_matches('/').eq_ignore_ascii_case(origin))
+                });
+                if !allowed {
+                    return Err(ApiError::Forbidden(format!(
+                        "origin {origin:?} is not allowed"
+                    )));
+                }
+                Some(origin.clone())
+            }
+        };
+
+        if let Some(token) = &self.access.token
+            && !preflight
+        {
+            let sent = headers
+                .get(header::AUTHORIZATION)
+                .and_then(|v| v.to_str().ok())
+                .and_then(|v| v.strip_prefix("Bearer "));
+            if !sent.is_some_and(|sent| constant_time_eq(sent.as_bytes(), token.as_bytes())) {
+                return Err(ApiError::Unauthorized(
+                    "missing or wrong bearer token".to_owned(),
+                ));
+            }
+        }
+        Ok(origin)
+    }
+}
+
+/// Reject requests that fail [`Guard::check`], answer CORS preflight
+/// requests and add CORS headers for allowed origins.
+async fn check_access(State(guard): State<Arc<Guard>>, request: Request, next: Next) -> Response {
+    let preflight = request.method() == Method::OPTIONS
+        && request
+            .headers()
+            .contains_key(header::ACCESS_CONTROL_REQUEST_METHOD);
+    let origin = match guard.check(request.headers(), preflight) {
+        Ok(origin) => origin,
+        Err(e) => {
+            warn!(error = ?e, "rejected request");
+            return e.into_response();
+        }
+    };
+
+    let mut response = if preflight && origin.is_some() {
+        let mut response = StatusCode::NO_CONTENT.into_response();
+        let headers = response.headers_mut();
+        headers.insert(
+            header::ACCESS_CONTROL_ALLOW_METHODS,
+            HeaderValue::from_static("GET, POST, DELETE"),
+        );
+        headers.insert(
+            header::ACCESS_CONTROL_ALLOW_HEADERS,
+            HeaderValue::from_static("authorization, content-type, last-event-id"),
+        );
+        headers.insert(
+            header::ACCESS_CONTROL_MAX_AGE,
+            HeaderValue::from_static("600"),
+        );
+        response
+    } else {
+        next.run(request).await
+    };
+    if let Some(origin) = origin {
+        let headers = response.headers_mut();
+        headers.insert(header::ACCESS_CONTROL_ALLOW_ORIGIN, origin);
+        headers.insert(header::VARY, HeaderValue::from_static("origin"));
+    }
+    response
+}
+
+/// Compare secrets in time independent of where they differ.
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+// ── State ────────────────────────────────────────────────────
+
+/// Shared server state.
+struct AppState {
+    /// Engine all sessions are started from.
+    engine: Engine,
+    /// Next session id, shared by runs and plans.
+    next_id: AtomicU64,
+    /// Serializes session starts so a slug cannot be started twice.
+    start_lock: tokio::sync::Mutex<()>,
+    /// Runs started by this server, by id.
+    runs: Mutex<BTreeMap<u64, Arc<Session<RunCommand>>>>,
+    /// Plan sessions started by this server, by id.
+    plans: Mutex<BTreeMap<u64, Arc<Session<PlanCommand>>>>,
+}
+
+impl AppState {
+    fn new(engine: Engine) -> Self {
+        Self {
+            engine,
+            next_id: AtomicU64::new(1),
+            start_lock: tokio::sync::Mutex::new(()),
+            runs: Mutex::new(BTreeMap::new()),
+            plans: Mutex::new(BTreeMap::new()),
+        }
+    }
+
+    fn run(&self, id: u64) -> Result<Arc<Session<RunCommand>>, ApiError> {
+        lock(&self.runs)
+            .get(&id)
+            .cloned()
+            .ok_or_else(|| ApiError::NotFound(format!("no run with id {id}")))
+    }
+
+    fn plan(&self, id: u64) -> Result<Arc<Session<PlanCommand>>, ApiError> {
+        lock(&self.plans)
+            .get(&id)
+            .cloned()
+            .ok_or_else(|| ApiError::NotFound(format!("no plan session with id {id}")))
+    }
+
+    /// Fail if a run or plan session for `slug` is still running.
+    fn ensure_idle(&self, slug: &str) -> Result<(), ApiError> {
+        let busy = lock(&self.runs)
+            .values()
+            .map(|s| s.info())
+            .chain(lock(&self.plans).values().map(|s| s.info()))
+            .any(|info| info.slug == slug && info.state == SessionState::Running);
+        if busy {
+            return Err(ApiError::Conflict(format!(
+                "feature {slug:?} already has a running session"
+            )));
+        }
+        Ok(())
+    }
+}
+
+/// Lifecycle of a run or plan session.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+enum SessionState {
+    /// Events are still coming.
+    Running,
+    /// The run finished or the plan completed.
+    Completed,
+    /// The session ended with an error.
+    Failed,
+    /// A client cancelled the session.
+    Cancelled,
+}
+
+/// Summary of a session, as returned by the API.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SessionInfo {
+    /// Session id.
+    id: u64,
+    /// Feature slug.
+    slug: String,
+    /// Current state.
+    state: SessionState,
+    /// Number of events so far.
+    events: usize,
+}
+
+/// A run or plan session driven by a background task, which takes commands
+/// of type `C` from the API handlers.
+struct Session<C> {
+    /// Session id.
+    id: u64,
+    /// Feature slug.
+    slug: String,
+    /// Recorded events and state.
+    log: Mutex<EventLog>,
+    /// Commands for the driving task.
+    commands: mpsc::Sender<C>,
+}
+
+/// Events of a session, kept for clients that connect late.
+struct EventLog {
+    /// Current state.
+    state: SessionState,
+    /// Every event so far as versioned JSON, indexed by event id.
+    events: Vec<Arc<str>>,
+    /// Live events for connected clients; `None` once the session ended.
+    live: Option<broadcast::Sender<RecordedEvent>>,
+}
+
+impl<C> Session<C> {
+    fn new(id: u64, slug: &str, commands: mpsc::Sender<C>) -> Self {
+        Self {
+            id,
+            slug: slug.to_owned(),
+            log: Mutex::new(EventLog {
+                state: SessionState::Running,
+                events: Vec::new(),
+                live: Some(broadcast::channel(CHANNEL_SIZE).0),
+            }),
+            commands,
+        }
+    }
+
+    fn info(&self) -> SessionInfo {
+        let log = lock(&self.log);
+        SessionInfo {
+            id: self.id,
+            slug: self.slug.clone(),
+            state: log.state,
+            events: log.events.len(),
+        }
+    }
+
+    /// Record an event; `terminal` is the state it ends the session in, if
+    /// no further events follow.
+    fn push(&self, event: &impl Serialize, terminal: Option<SessionState>) {
+        let json: Arc<str> = match serde_json::to_string(&EventRecord::new(event)) {
+            Ok(json) => json.into(),
+            Err(e) => {
+                warn!(id = self.id, error = %e, "failed to serialize event");
+                return;
+            }
+        };
+        let mut log = lock(&self.log);
+        let index = log.events.len();
+        log.events.push(Arc::clone(&json));
+        if let Some(state) = terminal {
+            log.state = state;
+        }
+        if let Some(live) = &log.live {
+            // No receivers is fine: nobody is connected
+            let _ = live.send((index, json));
+        }
+    }
+
+    /// Mark the session as ended. Sessions that end without a terminal event
+    /// count as failed unless `cancelled`.
+    fn close(&self, cancelled: bool) {
+        let mut log = lock(&self.log);
+        log.live = None;
+        if cancelled {
+            log.state = SessionState::Cancelled;
+        } else if log.state == SessionState::Running {
+            log.state = SessionState::Failed;
+        }
+    }
+
+    /// Events after the event with id `after` (all if `None`), and a
+    /// receiver for the following ones unless the session has ended.
+    fn subscribe(
+        &self,
+        after: Option<usize>,
+    ) -> (
+        Vec<RecordedEvent>,
+        Option<broadcast::Receiver<RecordedEvent>>,
+    ) {
+        let log = lock(&self.log);
+        let start = after.map_or(0, |id| id + 1);
+        let history = log
+            .events
+            .iter()
+            .enumerate()
+            .skip(start)
+            .map(|(i, json)| (i, Arc::clone(json)))
+            .collect();
+        (history, log.live.as_ref().map(broadcast::Sender::subscribe))
+    }
+
+    /// Send a command to the driving task.
+    async fn send(&self, command: C) -> Result<(), ApiError> {
+        self.commands
+            .send(command)
+            .await
+            .map_err(|_| ApiError::Conflict(format!("session {} has ended", self.id)))
+    }
+}
+
+/// Lock a mutex, ignoring poisoning: the guarded data stays consistent
+/// because no code panics while holding a lock.
+fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    mutex.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+// ── Drivers ──────────────────────────────────────────────────
+
+/// Commands for a run's driving task.
+enum RunCommand {
+    /// Answer the permission request with the given id.
+    Permission(u64, PermissionDecision),
+    /// Stop the run and wait for the engine to end it.
+    Cancel,
+}
+
+/// Commands for a plan session's driving task.
+enum PlanCommand {
+    /// Send a reply to the planning agent.
+    Respond(String),
+    /// Finish the session.
+    Finish,
+    /// Stop the session by dropping it.
+    Cancel,
+}
+
+async fn drive_run(
+    session: Arc<Session<RunCommand>>,
+    mut stream: RunStream,
+    mut commands: mpsc::Receiver<RunCommand>,
+) {
+    loop {
+        tokio::select! {
+            event = stream.next() => {
+                let Some(event) = event else { break };
+                let terminal = match &event {
+                    RunEvent::Finished => Some(SessionState::Completed),
+                    RunEvent::Error(_) => Some(SessionState::Failed),
+                    _ => None,
+                };
+                session.push(&event, terminal);
+            }
+            command = commands.recv() => match command {
+                Some(RunCommand::Permission(id, decision)) => {
+                    if let Err(e) = stream.respond_permission(id, decision).await {
+                        warn!(run = session.id, error = %e, "failed to answer permission request");
+                    }
+                }
+                Some(RunCommand::Cancel) | None => {
+                    // The session stays running until the engine has stopped
+                    stream.cancel().await;
+                    info!(run = session.id, "run cancelled");
+                    session.close(true);
+                    return;
+                }
+            },
+        }
+    }
+    session.close(false);
+}
+
+async fn drive_plan(
+    session: Arc<Session<PlanCommand>>,
+    mut plan: PlanSession,
+    mut commands: mpsc::Receiver<PlanCommand>,
+) {
+    loop {
+        tokio::select! {
+            event = plan.next() => {
+                let Some(event) = event else { break };
+                let terminal = match &event {
+                    PlanEvent::Completed => Some(SessionState::Completed),
+                    PlanEvent::Error(_) => Some(SessionState::Failed),
+                    _ => None,
+                };
+                session.push(&event, terminal);
+            }
+            command = commands.recv() => match command {
+                Some(PlanCommand::Respond(text)) => {
+                    if let Err(e) = plan.respond(&text).await {
+                        warn!(plan = session.id, error = %e, "failed to send reply");
+                    }
+                }
+                Some(PlanCommand::Finish) => plan.finish(),
+                Some(PlanCommand::Cancel) | None => {
+                    info!(plan = session.id, "plan session cancelled");
+                    session.close(true);
+                    return;
+                }
+            },
+        }
+    }
+    session.close(false);
+}
+
+// ── Handlers ─────────────────────────────────────────────────
+
+/// Body of `POST /runs`.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+struct StartRun {
+    /// Feature slug.
+    slug: String,
+}
+
+/// Body of `POST /plans`. Without `brief`, `resume` or `amend` a new
+/// interactive session starts.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+struct StartPlan {
+    /// Feature slug.
+    slug: String,
+    /// Plan from this brief without asking questions.
+    #[serde(default)]
+    brief: Option<String>,
+    /// Resume the feature's last plan session.
+    #[serde(default)]
+    resume: bool,
+    /// Amend the existing plan.
+    #[serde(default)]
+    amend: bool,
+}
+
+/// Body of `POST /runs/{id}/permissions`, e.g.
+/// `{"requestId": 3, "decision": "allowOnce"}`.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct PermissionAnswer {
+    /// Id of the `permissionRequested` event's request.
+    request_id: u64,
+    /// The answer.
+    #[serde(flatten)]
+    decision: PermissionDecision,
+}
+
+/// Body of `POST /plans/{id}/messages`.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+struct Message {
+    /// Reply text.
+    text: String,
+}
+
+async fn list_features(
+    State(state): State<Arc<AppState>>,
+) -> Result<Json<Vec<FeatureStatus>>, ApiError> {
+    Ok(Json(state.engine.list_features().await?))
+}
+
+async fn feature_status(
+    State(state): State<Arc<AppState>>,
+    Path(slug): Path<String>,
+) -> Result<Json<FeatureStatus>, ApiError> {
+    Ok(Json(state.engine.feature_status(&slug).await?))
+}
+
+async fn list_runs(State(state): State<Arc<AppState>>) -> Json<Vec<SessionInfo>> {
+    Json(lock(&state.runs).values().map(|s| s.info()).collect())
+}
+
+async fn list_plans(State(state): State<Arc<AppState>>) -> Json<Vec<SessionInfo>> {
+    Json(lock(&state.plans).values().map(|s| s.info()).collect())
+}
+
+async fn get_run(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<u64>,
+) -> Result<Json<SessionInfo>, ApiError> {
+    Ok(Json(state.run(id)?.info()))
+}
+
+async fn get_plan(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<u64>,
+) -> Result<Json<SessionInfo>, ApiError> {
+    Ok(Json(state.plan(id)?.info()))
+}
+
+async fn start_run(
+    State(state): State<Arc<AppState>>,
+    Json(body): Json<StartRun>,
+) -> Result<(StatusCode, Json<SessionInfo>), ApiError> {
+    let _guard = state.start_lock.lock().await;
+    state.ensure_idle(&body.slug)?;
+    let stream = state.engine.run(&body.slug).await?;
+
+    let id = state.next_id.fetch_add(1, Ordering::Relaxed);
+    let (tx, rx) = mpsc::channel(CHANNEL_SIZE);
+    let session = Arc::new(Session::new(id, &body.slug, tx));
+    lock(&state.runs).insert(id, Arc::clone(&session));
+    tokio::spawn(drive_run(Arc::clone(&session), stream, rx));
+    info!(run = id, slug = body.slug, "run started");
+
+    Ok((StatusCode::CREATED, Json(session.info())))
+}
+
+async fn start_plan(
+    State(state): State<Arc<AppState>>,
+    Json(body): Json<StartPlan>,
+) -> Result<(StatusCode, Json<SessionInfo>), ApiError> {
+    let _guard = state.start_lock.lock().await;
+    state.ensure_idle(&body.slug)?;
+    let plan = match (&body.brief, body.resume, body.amend) {
+        (Some(brief), false, false) => state.engine.plan_from_brief(&body.slug, brief).await?,
+        (None, true, false) => state.engine.resume_plan(&body.slug).await?,
+        (None, false, true) => state.engine.amend_plan(&body.slug).await?,
+        (None, false, false) => state.engine.plan(&body.slug).await?,
+        _ => {
+            return Err(ApiError::BadRequest(
+                "brief, resume and amend are mutually exclusive".to_owned(),
+            ));
+        }
+    };
+
+    let id = state.next_id.fetch_add(1, Ordering::Relaxed);
+    let (tx, rx) = mpsc::channel(CHANNEL_SIZE);
+    let session = Arc::new(Session::new(id, &body.slug, tx));
+    lock(&state.plans).insert(id, Arc::clone(&session));
+    tokio::spawn(drive_plan(Arc::clone(&session), plan, rx));
+    info!(plan = id, slug = body.slug, "plan session started");
+
+    Ok((StatusCode::CREATED, Json(session.info())))
+}
+
+async fn run_events(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<u64>,
+    headers: HeaderMap,
+) -> Result<Sse<impl Stream<Item = Result<Event, Infallible>>>, ApiError> {
+    Ok(sse(state.run(id)?, last_event_id(&headers)))
+}
+
+async fn plan_events(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<u64>,
+    headers: HeaderMap,
+) -> Result<Sse<impl Stream<Item = Result<Event, Infallible>>>, ApiError> {
+    Ok(sse(state.plan(id)?, last_event_id(&headers)))
+}
+
+async fn answer_permission(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<u64>,
+    Json(body): Json<PermissionAnswer>,
+) -> Result<StatusCode, ApiError> {
+    state
+        .run(id)?
+        .send(RunCommand::Permission(body.request_id, body.decision))
+        .await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+async fn send_message(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<u64>,
+    Json(body): Json<Message>,
+) -> Result<StatusCode, ApiError> {
+    if body.text.trim().is_empty() {
+        return Err(ApiError::BadRequest("message text is empty".to_owned()));
+    }
+    state
+        .plan(id)?
+        .send(PlanCommand::Respond(body.text.trim().to_owned()))
+        .await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+async fn finish_plan(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<u64>,
+) -> Result<StatusCode, ApiError> {
+    state.plan(id)?.send(PlanCommand::Finish).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+async fn cancel_run(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<u64>,
+) -> Result<StatusCode, ApiError> {
+    state.run(id)?.send(RunCommand::Cancel).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+async fn cancel_plan(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<u64>,
+) -> Result<StatusCode, ApiError> {
+    state.plan(id)?.send(PlanCommand::Cancel).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Id of the last event a reconnecting client received.
+fn last_event_id(headers: &HeaderMap) -> Option<usize> {
+    headers
+        .get("last-event-id")
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.parse().ok())
+}
+
+/// Stream a session's events after the event with id `after`. Once the
+/// session has ended, a final `end` event carries its [`SessionState`] so
+/// clients know not to reconnect.
+fn sse<C: Send + Sync + 'static>(
+    session: Arc<Session<C>>,
+    after: Option<usize>,
+) -> Sse<impl Stream<Item = Result<Event, Infallible>>> {
+    let (history, live) = session.subscribe(after);
+
+    // A lagging receiver ends the stream without `end`; the client
+    // reconnects and catches up from the history.
+    let live = stream::unfold(live, |mut live| async move {
+        let event = live.as_mut()?.recv().await.ok()?;
+        Some((event, live))
+    });
+    let end = stream::once(async move { session.info().state })
+        .filter(|state| std::future::ready(*state != SessionState::Running))
+        .map(|state| {
+            Event::default()
+                .event("end")
+                .json_data(state)
+                .unwrap_or_default()
+        });
+    let events = stream::iter(history)
+        .chain(live)
+        .map(|(id, json)| Event::default().id(id.to_string()).data(&*json))
+        .chain(end)
+        .map(Ok);
+
+    Sse::new(events).keep_alive(KeepAlive::default())
+}
+
+// ── Errors ───────────────────────────────────────────────────
+
+/// Error response of the API.
+#[derive(Debug)]
+enum ApiError {
+    /// An engine operation failed.
+    Core(CoreError),
+    /// The request is malformed.
+    BadRequest(String),
+    /// No session with the given id exists.
+    NotFound(String),
+    /// The request conflicts with a session's state.
+    Conflict(String),
+    /// The request lacks the configured bearer token.
+    Unauthorized(String),
+    /// The request's `Host` or `Origin` is not allowed.
+    Forbidden(String),
+}
+
+impl From<CoreError> for ApiError {
+    fn from(e: CoreError) -> Self {
+        Self::Core(e)
+    }
+}
+
+impl ApiError {
+    fn status(&self) -> StatusCode {
+        match self {
+            Self::Core(CoreError::FeatureNotFound(_)) | Self::NotFound(_) => StatusCode::NOT_FOUND,
+            Self::Core(
+                CoreError::NotInitialized | CoreError::AlreadyInitialized | CoreError::Locked(_),
+            )
+            | Self::Conflict(_) => StatusCode::CONFLICT,
+            Self::Core(CoreError::InvalidSpec(_)) => StatusCode::UNPROCESSABLE_ENTITY,
+            Self::BadRequest(_) => StatusCode::BAD_REQUEST,
+            Self::Unauthorized(_) => StatusCode::UNAUTHORIZED,
+            Self::Forbidden(_) => StatusCode::FORBIDDEN,
+            Self::Core(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+}
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        #[derive(Serialize)]
+        struct Body<E> {
+            error: E,
+        }
+        #[derive(Serialize)]
+        struct Detail<'a> {
+            kind: &'a str,
+            message: &'a str,
+        }
+
+        let status = self.status();
+        let body = match &self {
+            Self::Core(e) => Json(Body { error: e }).into_response(),
+            Self::BadRequest(message)
+            | Self::NotFound(message)
+            | Self::Conflict(message)
+            | Self::Unauthorized(message)
+            | Self::Forbidden(message) => {
+                let kind = match self {
+                    Self::BadRequest(_) => "badRequest",
+                    Self::NotFound(_) => "notFound",
+                    Self::Unauthorized(_) => "unauthorized",
+                    Self::Forbidden(_) => "forbidden",
+                    _ => "conflict",
+                };
+                Json(Body {
+                    error: Detail { kind, message },
+                })
+                .into_response()
+            }
+        };
+        if matches!(self, Self::Unauthorized(_)) {
+            return (
+                status,
+                [(header::WWW_AUTHENTICATE, HeaderValue::from_static("Bearer"))],
+                body,
+            )
+                .into_response();
+        }
+        (status, body).into_response()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn session() -> Session<()> {
+        Session::new(1, "login", mpsc::channel(1).0)
+    }
+
+    #[test]
+    fn test_should_replay_events_after_last_event_id() {
+        let session = session();
+        session.push(
+            &RunEvent::PhaseStarted {
+                index: 0,
+                name: "Setup".to_owned(),
+            },
+            None,
+        );
+        let (_, live) = session.subscribe(None);
+        let mut live = live.expect("should subscribe while running");
+        session.push(&RunEvent::Finished, Some(SessionState::Completed));
+
+        let (history, _) = session.subscribe(Some(0));
+        assert_eq!(history.len(), 1);
+        assert_eq!(history[0].0, 1);
+        assert!(history[0].1.contains(r#""type":"finished""#));
+        let (index, json) = live.try_recv().expect("should receive live event");
+        assert_eq!((index, json), history[0].clone());
+
+        let info = session.info();
+        assert_eq!((info.state, info.events), (SessionState::Completed, 2));
+    }
+
+    #[test]
+    fn test_should_close_session_with_final_state() {
+        let session = session();
+        let (_, live) = session.subscribe(None);
+        session.close(false);
+        assert_eq!(session.info().state, SessionState::Failed);
+        assert!(matches!(
+            live.expect("should subscribe while running").try_recv(),
+            Err(broadcast::error::TryRecvError::Closed)
+        ));
+        assert!(session.subscribe(None).1.is_none());
+
+        let finished = Session::<()>::new(2, "login", mpsc::channel(1).0);
+        finished.push(&RunEvent::Finished, Some(SessionState::Completed));
+        finished.close(false);
+        assert_eq!(finished.info().state, SessionState::Completed);
+    }
+
+    fn headers(pairs: &[(header::HeaderName, &str)]) -> HeaderMap {
+        pairs
+            .iter()
+            .map(|(name, value)| {
+                (
+                    name.clone(),
+                    HeaderValue::from_str(value).expect("should be a header value"),
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_should_only_answer_to_bound_address_and_localhost() {
+        let guard = Guard::new(Access::default(), "127.0.0.1:4380".parse().expect("addr"));
+        for host in [
+            "127.0.0.1:4380",
+            "localhost:4380",
+            "LOCALHOST:4380",
+            "[::1]:4380",
+        ] {
+            assert!(
+                guard
+                    .check(&headers(&[(header::HOST, host)]), false)
+                    .is_ok(),
+                "{host} should be allowed"
+            );
+        }
+        for host in ["evil.example:4380", "localhost:80", "localhost"] {
+            assert!(
+                matches!(
+                    guard.check(&headers(&[(header::HOST, host)]), false),
+                    Err(ApiError::Forbidden(_))
+                ),
+                "{host} should be rejected"
+            );
+        }
+        assert!(matches!(
+            guard.check(&HeaderMap::new(), false),
+            Err(ApiError::Forbidden(_))
+        ));
+
+        let guard = Guard::new(Access::default(), "10.0.0.5:80".parse().expect("addr"));
+        assert!(
+            guard
+                .check(&headers(&[(header::HOST, "10.0.0.5:80")]), false)
+                .is_ok()
+        );
+    }
+
+    #[test]
+    fn test_should_check_origin_and_token() {
+        let access = Access {
+            token: Some("secret".to_owned()),
+            cors_origins: vec!["http://localhost:3000/".to_owned()],
+        };
+        let guard = Guard::new(access, "127.0.0.1:4380".parse().expect("addr"));
+        let host = (header::HOST, "127.0.0.1:4380");
+        let bearer = (header::AUTHORIZATION, "Bearer secret");
+
+        assert!(
+            guard
+                .check(&headers(&[host.clone(), bearer.clone()]), false)
+                .is_ok()
+        );
+        assert!(matches!(
+            guard.check(&headers(std::slice::from_ref(&host)), false),
+            Err(ApiError::Unauthorized(_))
+        ));
+        assert!(matches!(
+            guard.check(
+                &headers(&[host.clone(), (header::AUTHORIZATION, "Bearer secreT")]),
+                false
+            ),
+            Err(ApiError::Unauthorized(_))
+        ));
+
+        // Allowed origins are echoed; preflight requests need no token
+        let origin = (header::ORIGIN, "http://localhost:3000");
+        let allowed = guard
+            .check(
+                &headers(&[host.clone(), origin.clone(), bearer.clone()]),
+                false,
+            )
+            .expect("should allow origin");
+        assert_eq!(
+            allowed.as_ref().and_then(|v| v.to_str().ok()),
+            Some("http://localhost:3000")
+        );
+        assert!(guard.check(&headers(&[host.clone(), origin]), true).is_ok());
+        assert!(matches!(
+            guard.check(
+                &headers(&[host, (header::ORIGIN, "http://evil.example"), bearer]),
+                false
+            ),
+            Err(ApiError::Forbidden(_))
+        ));
+    }
+
+    #[test]
+    fn test_should_map_errors_to_status_codes() {
+        assert_eq!(
+            ApiError::from(CoreError::FeatureNotFound("x".to_owned())).status(),
+            StatusCode::NOT_FOUND
+        );
+        assert_eq!(
+            ApiError::from(CoreError::NotInitialized).status(),
+            StatusCode::CONFLICT
+        );
+        assert_eq!(
+            ApiError::from(CoreError::Locked("x".to_owned())).status(),
+            StatusCode::CONFLICT
+        );
+        assert_eq!(
+            ApiError::from(CoreError::Agent("x".to_owned())).status(),
+            StatusCode::INTERNAL_SERVER_ERROR
+        );
+        assert_eq!(
+            ApiError::Conflict("busy".to_owned()).status(),
+            StatusCode::CONFLICT
+        );
+        assert_eq!(
+            ApiError::Unauthorized("x".to_owned()).status(),
+            StatusCode::UNAUTHORIZED
+        );
+        assert_eq!(
+            ApiError::Forbidden("x".to_owned()).status(),
+            StatusCode::FORBIDDEN
+        );
+    }
+}
@@ -0,0 +1,202 @@
+//! Rendering for `gba status`.
+//!
+//! Formats [`FeatureStatus`] values as an overview table of all features or
+//! a detailed view of one feature.
+
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use gba_core::{FeatureStatus, StepStatus};
+
+/// Column headers of the overview table.
+const HEADERS: [&str; 8] = [
+    "FEATURE", "PHASES", "STATUS", "REVIEW", "VERIFY", "WORKTREE", "ACTIVITY", "PR",
+];
+
+/// Labels of the detail view, for every column but the slug.
+const DETAIL_LABELS: [&str; 7] = [
+    "Phases",
+    "Status",
+    "Review",
+    "Verification",
+    "Worktree",
+    "Last activity",
+    "PR",
+];
+
+/// Render an overview table with one row per feature.
+pub fn table(statuses: &[FeatureStatus], now: u64) -> String {
+    let rows: Vec<[String; 8]> = statuses.iter().map(|s| row(s, now)).collect();
+    aligned(HEADERS, &rows)
+}
+
+/// Render rows under `headers` with left-aligned columns.
+pub fn aligned<const N: usize>(headers: [&str; N], rows: &[[String; N]]) -> String {
+    let mut widths = headers.map(str::len);
+    for row in rows {
+        for (width, cell) in widths.iter_mut().zip(row) {
+            *width = (*width).max(cell.chars().count());
+        }
+    }
+
+    let mut out = String::new();
+    let headers = headers.map(str::to_owned);
+    for row in std::iter::once(&headers).chain(rows) {
+        let line: Vec<String> = row
+            .iter()
+            .zip(widths)
+            .map(|(cell, width)| format!("{cell:<width$}"))
+            .collect();
+        out.push_str(line.join("  ").trim_end());
+        out.push('\n');
+    }
+    out
+}
+
+/// Render the detailed status of one feature, including its phases.
+pub fn detail(status: &FeatureStatus, now: u64) -> String {
+    let mut out = format!("Feature: {}\n", status.slug);
+    if !status.feature.is_empty() {
+        out.push_str(&format!("  {}\n", status.feature));
+    }
+    if let Some(error) = &status.error {
+        out.push_str(&format!("[!] Invalid spec: {error}\n"));
+    }
+    for (label, value) in DETAIL_LABELS.iter().zip(&row(status, now)[1..]) {
+        out.push_str(&format!("{label:<14}{value}\n"));
+    }
+    if !status.phases.is_empty() {
+        out.push('\n');
+    }
+    for (i, phase) in status.phases.iter().enumerate() {
+        let indicator = match phase.status {
+            StepStatus::Pending => " ",
+            StepStatus::InProgress => "~",
+            StepStatus::Completed => "x",
+            StepStatus::Failed => "!",
+            StepStatus::Skipped => "-",
+        };
+        out.push_str(&format!("[{indicator}] {}. {}\n", i + 1, phase.name));
+    }
+    out
+}
+
+/// Seconds since the Unix epoch.
+pub fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default()
+}
+
+fn row(status: &FeatureStatus, now: u64) -> [String; 8] {
+    let dash = || "-".to_owned();
+    let phases = if status.error.is_some() {
+        "invalid".to_owned()
+    } else {
+        format!("{}/{}", status.completed_phases(), status.phases.len())
+    };
+    [
+        status.slug.clone(),
+        phases,
+        status
+            .execution
+            .as_ref()
+            .map_or_else(|| "not run".to_owned(), |s| status_name(s).to_owned()),
+        status.review.as_ref().map_or_else(dash, |r| {
+            format!("{} found, {} fixed", r.issues_found, r.issues_fixed)
+        }),
+        status.verification.as_ref().map_or_else(dash, |v| {
+            if v.passed { "passed" } else { "failed" }.to_owned()
+        }),
+        status.worktree.to_string(),
+        status
+            .last_activity
+            .map_or_else(dash, |t| format_age(now.saturating_sub(t))),
+        status.pr.clone().unwrap_or_else(dash),
+    ]
+}
+
+/// Lowercase name of a step status.
+pub fn status_name(status: &StepStatus) -> &'static str {
+    match status {
+        StepStatus::Pending => "pending",
+        StepStatus::InProgress => "in progress",
+        StepStatus::Completed => "completed",
+        StepStatus::Failed => "failed",
+        StepStatus::Skipped => "skipped",
+    }
+}
+
+/// Format an elapsed duration as its largest whole unit, e.g. `5m ago`.
+pub fn format_age(secs: u64) -> String {
+    match secs {
+        0..60 => "just now".to_owned(),
+        60..3_600 => format!("{}m ago", secs / 60),
+        3_600..86_400 => format!("{}h ago", secs / 3_600),
+        _ => format!("{}d ago", secs / 86_400),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use gba_core::{PhaseSummary, ReviewResult, WorktreeState};
+
+    use super::*;
+
+    fn status() -> FeatureStatus {
+        FeatureStatus {
+            slug: "login".to_owned(),
+            feature: "Login page".to_owned(),
+            phases: vec![
+                PhaseSummary {
+                    name: "Setup".to_owned(),
+                    status: StepStatus::Completed,
+                },
+                PhaseSummary {
+                    name: "Routes".to_owned(),
+                    status: StepStatus::Pending,
+                },
+            ],
+            execution: Some(StepStatus::InProgress),
+            review: Some(ReviewResult {
+                turns: 1,
+                issues_found: 3,
+                issues_fixed: 2,
+            }),
+            verification: None,
+            pr: None,
+            worktree: WorktreeState::Dirty,
+            last_activity: Some(1_000),
+            error: None,
+        }
+    }
+
+    #[test]
+    fn test_should_format_age_in_largest_unit() {
+        assert_eq!(format_age(5), "just now");
+        assert_eq!(format_age(5 * 60 + 30), "5m ago");
+        assert_eq!(format_age(3 * 3_600), "3h ago");
+        assert_eq!(format_age(2 * 86_400 + 5), "2d ago");
+    }
+
+    #[test]
+    fn test_should_render_aligned_table() {
+        let table = table(&[status()], 1_000 + 120);
+        let lines: Vec<&str> = table.lines().collect();
+        assert_eq!(lines.len(), 2);
+        assert!(lines[0].starts_with("FEATURE  PHASES  STATUS"));
+        assert_eq!(lines[0].find("PHASES"), lines[1].find("1/2"));
+        assert!(lines[1].contains("in progress"));
+        assert!(lines[1].contains("3 found, 2 fixed"));
+        assert!(lines[1].contains("dirty"));
+        assert!(lines[1].contains("2m ago"));
+    }
+
+    #[test]
+    fn test_should_render_detail_with_phases() {
+        let detail = detail(&status(), 1_000);
+        assert!(detail.contains("Worktree      dirty\n"));
+        assert!(detail.contains("[x] 1. Setup\n"));
+        assert!(detail.contains("[ ] 2. Routes\n"));
+    }
+}
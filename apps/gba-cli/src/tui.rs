@@ -1,57 +1,288 @@
-//! TUI module for future interactive terminal UI.
+//! Terminal user interfaces.
 //!
-//! This module provides a placeholder for the ratatui-based TUI that will
-//! be implemented in a later phase. Currently contains the `App` struct
-//! with a minimal event loop skeleton.
+//! [`plan`] drives an interactive planning session, [`run`] shows a
+//! dashboard for feature runs and [`inspect`] browses archived agent
+//! sessions. This module holds the pieces shared by the TUIs: the TTY
+//! check, a background reader that forwards terminal events to the async
+//! event loop, and a scrollable log pane.
+
+pub mod inspect;
+pub mod plan;
+pub mod run;
+
+use std::io::IsTerminal;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::thread::JoinHandle;
+use std::time::Duration;
 
 use anyhow::Result;
-use crossterm::event::{self, Event, KeyCode};
-use ratatui::DefaultTerminal;
+use crossterm::event::{self, Event};
+use ratatui::Frame;
+use ratatui::layout::Rect;
+use ratatui::style::Style;
+use ratatui::text::Line;
+use ratatui::widgets::{Block, Paragraph};
+use tokio::sync::mpsc;
+use unicode_width::UnicodeWidthChar;
 
-/// Main TUI application state.
+/// How long the terminal reader waits for an event before checking whether
+/// it should stop.
+const POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Whether stdin and stdout are both terminals, so a TUI can be shown.
+pub fn is_interactive() -> bool {
+    std::io::stdin().is_terminal() && std::io::stdout().is_terminal()
+}
+
+/// Terminal events read on a background thread.
 ///
-/// Manages the running state and drives the terminal event loop.
-/// Full TUI rendering will be implemented in a later phase.
-#[allow(dead_code)] // Entire TUI module is scaffolding for a future phase
+/// Crossterm reads block, so a thread polls the terminal and forwards
+/// events to a channel the async event loop can `select!` on. Stop the
+/// reader before handing the terminal to another program (e.g. `$EDITOR`).
 #[derive(Debug)]
-pub struct App {
-    /// Whether the application is currently running.
-    pub running: bool,
-}
-
-#[allow(dead_code)] // Entire TUI module is scaffolding for a future phase
-impl App {
-    /// Create a new TUI application instance.
-    pub fn new() -> Self {
-        Self { running: true }
-    }
-
-    /// Run the TUI event loop.
-    ///
-    /// Draws frames and handles keyboard events until the user presses 'q'.
-    ///
-    /// # Errors
-    ///
-    /// Returns an error if terminal drawing or event reading fails.
-    pub fn run(mut self, terminal: &mut DefaultTerminal) -> Result<()> {
-        while self.running {
-            terminal.draw(|frame| self.draw(frame))?;
-            self.handle_events()?;
+pub struct TerminalEvents {
+    /// Events read from the terminal.
+    rx: mpsc::UnboundedReceiver<Event>,
+    /// Set to ask the reader thread to exit.
+    stop: Arc<AtomicBool>,
+    /// Reader thread.
+    handle: Option<JoinHandle<()>>,
+}
+
+impl TerminalEvents {
+    /// Start reading terminal events.
+    pub fn start() -> Self {
+        let (tx, rx) = mpsc::unbounded_channel();
+        let stop = Arc::new(AtomicBool::new(false));
+        let handle = std::thread::spawn({
+            let stop = Arc::clone(&stop);
+            move || {
+                while !stop.load(Ordering::Relaxed) {
+                    match event::poll(POLL_INTERVAL) {
+                        Ok(true) => {
+                            let Ok(event) = event::read() else { return };
+                            if tx.send(event).is_err() {
+                                return;
+                            }
+                        }
+                        Ok(false) => {}
+                        Err(_) => return,
+                    }
+                }
+            }
+        });
+        Self {
+            rx,
+            stop,
+            handle: Some(handle),
         }
-        Ok(())
     }
 
-    fn draw(&self, frame: &mut ratatui::Frame) {
-        // Placeholder: full TUI rendering will be implemented in a later phase.
-        let _ = frame;
+    /// Next terminal event, or `None` if the reader stopped.
+    pub async fn next(&mut self) -> Option<Event> {
+        self.rx.recv().await
     }
 
-    fn handle_events(&mut self) -> Result<()> {
-        if let Event::Key(key) = event::read()?
-            && key.code == KeyCode::Char('q')
-        {
-            self.running = false;
+    /// Stop the reader and wait for its thread to exit.
+    pub fn stop(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+        if let Some(handle) = self.handle.take() {
+            let _ = handle.join();
         }
-        Ok(())
+    }
+}
+
+impl Drop for TerminalEvents {
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
+
+/// Run `f` with the terminal restored to normal mode, then set the TUI up
+/// again. Used to hand the terminal to an external program.
+pub fn suspend<T>(
+    terminal: &mut ratatui::DefaultTerminal,
+    events: &mut TerminalEvents,
+    f: impl FnOnce() -> T,
+) -> Result<T> {
+    events.stop();
+    ratatui::restore();
+    let result = f();
+    *terminal = ratatui::try_init()?;
+    *events = TerminalEvents::start();
+    Ok(result)
+}
+
+/// Scrollable log of titled text entries.
+///
+/// Follows the latest entry until the user scrolls back, and again once
+/// they scroll to the bottom.
+#[derive(Debug, Default)]
+pub struct LogView {
+    /// Entries, oldest first.
+    entries: Vec<LogEntry>,
+    /// First visible row, or `None` to follow the latest entry.
+    scroll: Option<usize>,
+    /// Visible rows at the last render.
+    height: usize,
+    /// Total wrapped rows at the last render.
+    rows: usize,
+}
+
+/// One entry of a [`LogView`].
+#[derive(Debug)]
+struct LogEntry {
+    /// Title line, e.g. the author.
+    title: String,
+    /// Style of the title line.
+    title_style: Style,
+    /// Entry text, wrapped to the pane width.
+    text: String,
+    /// Style of the text.
+    style: Style,
+}
+
+impl LogView {
+    /// Append an entry.
+    pub fn push(
+        &mut self,
+        title: impl Into<String>,
+        title_style: Style,
+        text: impl Into<String>,
+        style: Style,
+    ) {
+        self.entries.push(LogEntry {
+            title: title.into(),
+            title_style,
+            text: text.into(),
+            style,
+        });
+    }
+
+    /// Follow the latest entry again.
+    pub fn follow(&mut self) {
+        self.scroll = None;
+    }
+
+    /// Scroll back half a page.
+    pub fn page_up(&mut self) {
+        self.scroll = Some(self.top().saturating_sub(self.page()));
+    }
+
+    /// Scroll forward half a page, following the latest entry once the
+    /// bottom is reached.
+    pub fn page_down(&mut self) {
+        let top = self.top() + self.page();
+        self.scroll = (top < self.bottom()).then_some(top);
+    }
+
+    /// Render the visible rows inside `block`.
+    pub fn render(&mut self, frame: &mut Frame, area: Rect, block: Block) {
+        let inner = block.inner(area);
+        let width = inner.width as usize;
+
+        let mut rows = Vec::new();
+        for entry in &self.entries {
+            rows.push(Line::styled(entry.title.as_str(), entry.title_style));
+            rows.extend(
+                wrap(&entry.text, width)
+                    .into_iter()
+                    .map(|line| Line::styled(line, entry.style)),
+            );
+            rows.push(Line::default());
+        }
+
+        self.height = inner.height as usize;
+        self.rows = rows.len();
+        let top = self.top();
+        let visible: Vec<Line> = rows.into_iter().skip(top).take(self.height).collect();
+        frame.render_widget(Paragraph::new(visible).block(block), area);
+    }
+
+    /// First visible row.
+    fn top(&self) -> usize {
+        self.scroll
+            .map_or(self.bottom(), |top| top.min(self.bottom()))
+    }
+
+    /// First row when following the latest entry.
+    fn bottom(&self) -> usize {
+        self.rows.saturating_sub(self.height)
+    }
+
+    /// Rows scrolled by one page.
+    fn page(&self) -> usize {
+        (self.height / 2).max(1)
+    }
+}
+
+/// Wrap `text` into lines at most `width` columns wide, breaking at the
+/// last space when possible.
+pub fn wrap(text: &str, width: usize) -> Vec<String> {
+    let width = width.max(1);
+    let mut lines = Vec::new();
+    for paragraph in text.lines() {
+        let mut line = String::new();
+        let mut line_width = 0;
+        for c in paragraph.chars() {
+            let c = if c == '\t' { ' ' } else { c };
+            let w = c.width().unwrap_or(0);
+            if line_width + w > width {
+                match line.rfind(' ').filter(|&i| i > 0) {
+                    Some(i) => {
+                        let rest = line.split_off(i + 1);
+                        lines.push(line.trim_end().to_owned());
+                        line_width = rest.chars().filter_map(|c| c.width()).sum();
+                        line = rest;
+                    }
+                    None => {
+                        lines.push(std::mem::take(&mut line));
+                        line_width = 0;
+                    }
+                }
+            }
+            line.push(c);
+            line_width += w;
+        }
+        lines.push(line);
+    }
+    lines
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_should_wrap_at_spaces() {
+        assert_eq!(wrap("the quick brown fox", 10), ["the quick", "brown fox"]);
+        assert_eq!(wrap("abcdefghij", 4), ["abcd", "efgh", "ij"]);
+        assert_eq!(wrap("one\n\ntwo", 10), ["one", "", "two"]);
+    }
+
+    #[test]
+    fn test_should_wrap_wide_characters_by_width() {
+        assert_eq!(wrap("设计文档", 4), ["设计", "文档"]);
+    }
+
+    #[test]
+    fn test_should_scroll_log_and_follow_bottom() {
+        let mut log = LogView::default();
+        for i in 0..20 {
+            log.push("Agent", Style::new(), format!("message {i}"), Style::new());
+        }
+        log.height = 10;
+        log.rows = 60;
+        assert_eq!(log.top(), 50);
+
+        log.page_up();
+        assert_eq!(log.scroll, Some(45));
+        log.page_down();
+        assert_eq!(log.scroll, None);
+
+        log.page_up();
+        log.follow();
+        assert_eq!(log.top(), 50);
     }
 }
@@ -0,0 +1,321 @@
+//! Transcript viewer TUI.
+//!
+//! Shows the entries of inspected agent sessions in one scrollable pane.
+//! Prompts, thinking and tool calls start collapsed to their heading line;
+//! the selected entry expands and collapses with Enter.
+
+use anyhow::{Context, Result};
+use crossterm::event::{Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+use ratatui::layout::{Constraint, Layout};
+use ratatui::style::Style;
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, Paragraph};
+use ratatui::{DefaultTerminal, Frame};
+
+use super::{TerminalEvents, wrap};
+use crate::inspect::{Entry, EntryKind, Tone};
+
+/// Columns body lines are indented by.
+const BODY_INDENT: usize = 4;
+
+/// Show `entries` until the user leaves.
+///
+/// # Errors
+///
+/// Returns an error if the terminal cannot be set up or drawn to.
+pub async fn run(title: &str, entries: Vec<Entry>) -> Result<()> {
+    let mut viewer = Viewer::new(title, entries);
+    let mut terminal = ratatui::try_init().context("failed to set up terminal")?;
+    let mut events = TerminalEvents::start();
+    let result = event_loop(&mut viewer, &mut terminal, &mut events).await;
+    events.stop();
+    ratatui::restore();
+    result
+}
+
+async fn event_loop(
+    viewer: &mut Viewer,
+    terminal: &mut DefaultTerminal,
+    events: &mut TerminalEvents,
+) -> Result<()> {
+    loop {
+        terminal.draw(|frame| viewer.draw(frame))?;
+        match events.next().await {
+            Some(Event::Key(key)) if key.kind == KeyEventKind::Press => {
+                if viewer.handle_key(key) {
+                    return Ok(());
+                }
+            }
+            Some(_) => {}
+            None => return Ok(()),
+        }
+    }
+}
+
+/// Viewer state.
+#[derive(Debug)]
+struct Viewer {
+    /// Title shown in the header line.
+    title: String,
+    /// Entries of all inspected sessions.
+    entries: Vec<Entry>,
+    /// Whether each entry's body is shown.
+    expanded: Vec<bool>,
+    /// Selected entry.
+    cursor: usize,
+    /// First visible row.
+    top: usize,
+    /// Visible rows at the last render.
+    height: usize,
+    /// Row of each entry's heading at the last render.
+    heads: Vec<usize>,
+}
+
+impl Viewer {
+    fn new(title: &str, entries: Vec<Entry>) -> Self {
+        let expanded = entries.iter().map(|e| !e.collapsible()).collect();
+        Self {
+            title: title.to_owned(),
+            entries,
+            expanded,
+            cursor: 0,
+            top: 0,
+            height: 0,
+            heads: Vec::new(),
+        }
+    }
+
+    /// Handle a key press; returns `true` when the user leaves.
+    fn handle_key(&mut self, key: KeyEvent) -> bool {
+        let last = self.entries.len().saturating_sub(1);
+        match key.code {
+            KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => return true,
+            KeyCode::Char('q') | KeyCode::Esc => return true,
+            KeyCode::Up | KeyCode::Char('k') => self.cursor = self.cursor.saturating_sub(1),
+            KeyCode::Down | KeyCode::Char('j') => self.cursor = (self.cursor + 1).min(last),
+            KeyCode::Home | KeyCode::Char('g') => self.cursor = 0,
+            KeyCode::End | KeyCode::Char('G') => self.cursor = last,
+            KeyCode::PageUp => self.page(false),
+            KeyCode::PageDown => self.page(true),
+            KeyCode::Enter | KeyCode::Char(' ') => {
+                if self
+                    .entries
+                    .get(self.cursor)
+                    .is_some_and(Entry::collapsible)
+                {
+                    self.expanded[self.cursor] = !self.expanded[self.cursor];
+                }
+            }
+            KeyCode::Char('e') => self.expand_all(true),
+            KeyCode::Char('c') => self.expand_all(false),
+            _ => {}
+        }
+        false
+    }
+
+    fn expand_all(&mut self, expand: bool) {
+        for (expanded, entry) in self.expanded.iter_mut().zip(&self.entries) {
+            if entry.collapsible() {
+                *expanded = expand;
+            }
+        }
+    }
+
+    /// Move the selection by about half a page.
+    fn page(&mut self, forward: bool) {
+        let Some(&row) = self.heads.get(self.cursor) else {
+            return;
+        };
+        let step = (self.height / 2).max(1);
+        self.cursor = if forward {
+            self.heads
+                .iter()
+                .position(|&head| head >= row + step)
+                .unwrap_or(self.entries.len().saturating_sub(1))
+        } else {
+            self.heads
+                .iter()
+                .rposition(|&head| head + step <= row)
+                .unwrap_or(0)
+        };
+    }
+
+    fn draw(&mut self, frame: &mut Frame) {
+        let [header, main, footer] = Layout::vertical([
+            Constraint::Length(1),
+            Constraint::Min(3),
+            Constraint::Length(1),
+        ])
+        .areas(frame.area());
+
+        let block = Block::bordered();
+        let inner = block.inner(main);
+        let rows = self.rows(inner.width as usize);
+
+        // Keep the selected heading in view
+        self.height = inner.height as usize;
+        self.top = self.top.min(rows.len().saturating_sub(self.height));
+        if let Some(&head) = self.heads.get(self.cursor) {
+            if head < self.top {
+                self.top = head;
+            } else if head >= self.top + self.height {
+                self.top = head + 1 - self.height;
+            }
+        }
+        let visible: Vec<Line> = rows.into_iter().skip(self.top).take(self.height).collect();
+
+        frame.render_widget(
+            Paragraph::new(Line::from(vec![
+                Span::styled(format!(" {} ", self.title), Style::new().bold()),
+                Span::raw(format!(
+                    "| entry {}/{}",
+                    self.cursor + 1,
+                    self.entries.len()
+                )),
+            ])),
+            header,
+        );
+        frame.render_widget(Paragraph::new(visible).block(block), main);
+        frame.render_widget(
+            Paragraph::new(Span::styled(
+                " Up/Down select | Enter expand/collapse | e/c expand/collapse all | PgUp/PgDn page | q exit",
+                Style::new().dark_gray(),
+            )),
+            footer,
+        );
+    }
+
+    /// All rows at `width` columns, recording where each heading is.
+    fn rows(&mut self, width: usize) -> Vec<Line<'static>> {
+        let mut rows = Vec::new();
+        self.heads.clear();
+        for (i, entry) in self.entries.iter().enumerate() {
+            if entry.kind == EntryKind::Header && i > 0 {
+                rows.push(Line::default());
+            }
+            self.heads.push(rows.len());
+
+            let marker = match (entry.collapsible(), self.expanded[i]) {
+                (false, _) => "  ",
+                (true, false) => "> ",
+                (true, true) => "v ",
+            };
+            let mut style = head_style(entry);
+            if i == self.cursor {
+                style = style.reversed();
+            }
+            rows.push(Line::from(vec![
+                Span::raw(marker),
+                Span::styled(entry.head.clone(), style),
+            ]));
+
+            if self.expanded[i] {
+                let indent = " ".repeat(BODY_INDENT);
+                for (tone, text) in &entry.body {
+                    for line in wrap(text, width.saturating_sub(BODY_INDENT)) {
+                        rows.push(Line::styled(format!("{indent}{line}"), tone_style(*tone)));
+                    }
+                }
+            }
+        }
+        rows
+    }
+}
+
+fn head_style(entry: &Entry) -> Style {
+    if entry.failed {
+        return Style::new().red().bold();
+    }
+    match entry.kind {
+        EntryKind::Header => Style::new().magenta().bold(),
+        EntryKind::Prompt | EntryKind::Thinking => Style::new().dark_gray(),
+        EntryKind::Text => Style::new().bold(),
+        EntryKind::Tool => Style::new().cyan(),
+        EntryKind::Result | EntryKind::Summary => Style::new().green(),
+        EntryKind::Error => Style::new().red().bold(),
+    }
+}
+
+fn tone_style(tone: Tone) -> Style {
+    match tone {
+        Tone::Plain => Style::new(),
+        Tone::Added => Style::new().green(),
+        Tone::Removed => Style::new().red(),
+        Tone::Command => Style::new().yellow(),
+        Tone::Muted => Style::new().dark_gray(),
+        Tone::Error => Style::new().red(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ratatui::Terminal;
+    use ratatui::backend::TestBackend;
+
+    use super::*;
+
+    fn key(code: KeyCode) -> KeyEvent {
+        KeyEvent::new(code, KeyModifiers::NONE)
+    }
+
+    fn entry(kind: EntryKind, head: &str, body: &[&str]) -> Entry {
+        Entry {
+            kind,
+            head: head.to_owned(),
+            failed: false,
+            body: body
+                .iter()
+                .map(|l| (Tone::Muted, (*l).to_owned()))
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn test_should_expand_selected_tool_call() {
+        let mut viewer = Viewer::new(
+            "gba inspect login",
+            vec![
+                entry(EntryKind::Header, "phases-1", &[]),
+                entry(EntryKind::Text, "Agent", &["Running the tests"]),
+                entry(
+                    EntryKind::Tool,
+                    "Bash cargo test (4.2s)",
+                    &["$ cargo test", "ok"],
+                ),
+            ],
+        );
+        let mut terminal = Terminal::new(TestBackend::new(60, 12)).expect("should create terminal");
+        let screen = |terminal: &Terminal<TestBackend>| -> String {
+            let buffer = terminal.backend().buffer();
+            buffer
+                .content()
+                .chunks(buffer.area.width as usize)
+                .map(|row| row.iter().map(|cell| cell.symbol()).collect::<String>())
+                .collect::<Vec<_>>()
+                .join("\n")
+        };
+
+        terminal
+            .draw(|frame| viewer.draw(frame))
+            .expect("should draw");
+        assert!(screen(&terminal).contains("> Bash cargo test"));
+        assert!(screen(&terminal).contains("Running the tests"));
+        assert!(!screen(&terminal).contains("$ cargo test"));
+
+        // Headers have no body to collapse
+        viewer.handle_key(key(KeyCode::Enter));
+        assert!(viewer.expanded[0]);
+        viewer.handle_key(key(KeyCode::End));
+        viewer.handle_key(key(KeyCode::Enter));
+        terminal
+            .draw(|frame| viewer.draw(frame))
+            .expect("should draw");
+        assert!(screen(&terminal).contains("v Bash cargo test"));
+        assert!(screen(&terminal).contains("$ cargo test"));
+
+        viewer.handle_key(key(KeyCode::Char('c')));
+        assert!(!viewer.expanded[2]);
+        assert!(viewer.expanded[1]);
+        assert!(viewer.handle_key(key(KeyCode::Char('q'))));
+    }
+}
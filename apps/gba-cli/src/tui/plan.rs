@@ -0,0 +1,696 @@
+//! Planning session TUI.
+//!
+//! Shows the conversation with the plan agent in a scrollable chat pane,
+//! a multi-line editor for replies, and a side panel with the spec files
+//! as the agent writes them. The user can open a spec in `$EDITOR` and end
+//! the conversation with a key, which lets the engine validate the specs
+//! and complete the session.
+
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use anyhow::{Context, Result};
+use crossterm::event::{Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+use ratatui::layout::{Constraint, Layout, Rect};
+use ratatui::style::{Color, Style, Stylize};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, Paragraph, Wrap};
+use ratatui::{DefaultTerminal, Frame};
+use unicode_width::UnicodeWidthStr;
+
+use gba_core::{PlanEvent, PlanSession};
+
+use super::{LogView, TerminalEvents, suspend};
+
+/// Key help shown in the footer.
+const HELP: &str = "Enter send | Alt+Enter newline | Tab spec | Ctrl+E edit | Ctrl+D finish | PgUp/PgDn scroll | Esc quit";
+
+/// Maximum number of input lines shown before the editor scrolls.
+const MAX_INPUT_LINES: usize = 8;
+
+/// Maximum number of spec files listed before the list is cut.
+const MAX_SPEC_LIST_LINES: usize = 6;
+
+/// How a planning TUI session ended.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Outcome {
+    /// The plan was validated and saved.
+    Completed,
+    /// The session failed with the given error.
+    Failed(String),
+    /// The user left before the plan was complete.
+    Quit,
+}
+
+/// Drive `session` in a full-screen TUI until it completes, fails or the
+/// user quits.
+///
+/// `feature_dir` is used to show spec paths relative to the feature.
+///
+/// # Errors
+///
+/// Returns an error if the terminal cannot be set up or drawn to.
+pub async fn run(session: &mut PlanSession, feature_dir: &Path) -> Result<Outcome> {
+    let mut app = App::new(feature_dir);
+    let mut terminal = ratatui::try_init().context("failed to set up terminal")?;
+    let mut events = TerminalEvents::start();
+    let result = event_loop(&mut app, session, &mut terminal, &mut events).await;
+    events.stop();
+    ratatui::restore();
+    result
+}
+
+async fn event_loop(
+    app: &mut App,
+    session: &mut PlanSession,
+    terminal: &mut DefaultTerminal,
+    events: &mut TerminalEvents,
+) -> Result<Outcome> {
+    loop {
+        terminal.draw(|frame| app.draw(frame))?;
+        if let Some(outcome) = app.outcome.take() {
+            return Ok(outcome);
+        }
+
+        let action = tokio::select! {
+            event = session.next() => {
+                match event {
+                    Some(event) => app.handle_plan_event(event),
+                    None => {
+                        app.outcome = Some(Outcome::Failed(
+                            "plan session ended unexpectedly".to_owned(),
+                        ));
+                    }
+                }
+                Action::None
+            }
+            event = events.next() => match event {
+                Some(Event::Key(key)) if key.kind == KeyEventKind::Press => app.handle_key(key),
+                Some(_) => Action::None,
+                None => Action::Quit,
+            },
+        };
+
+        match action {
+            Action::None => {}
+            Action::Send(text) => {
+                if let Err(e) = session.respond(&text).await {
+                    app.status = format!("Failed to send reply: {e}");
+                }
+            }
+            Action::Finish => session.finish(),
+            Action::Edit(path) => {
+                let result = suspend(terminal, events, || open_in_editor(&path))?;
+                app.edited(&path, result);
+            }
+            Action::Quit => return Ok(Outcome::Quit),
+        }
+    }
+}
+
+/// Open `path` in `$VISUAL` or `$EDITOR`, falling back to `vi`, and wait
+/// for the editor to exit.
+fn open_in_editor(path: &Path) -> Result<()> {
+    let editor = std::env::var("VISUAL")
+        .or_else(|_| std::env::var("EDITOR"))
+        .unwrap_or_else(|_| "vi".to_owned());
+    let mut parts = editor.split_whitespace();
+    let program = parts.next().unwrap_or("vi");
+    let status = Command::new(program)
+        .args(parts)
+        .arg(path)
+        .status()
+        .with_context(|| format!("failed to start editor {program}"))?;
+    if !status.success() {
+        anyhow::bail!("editor {program} exited with {status}");
+    }
+    Ok(())
+}
+
+/// Who a chat entry is from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Role {
+    /// The plan agent.
+    Agent,
+    /// The user.
+    You,
+    /// Progress notes such as written or validated specs.
+    Info,
+}
+
+/// What the event loop should do after a key press.
+#[derive(Debug, PartialEq, Eq)]
+enum Action {
+    /// Nothing beyond redrawing.
+    None,
+    /// Send a reply to the agent.
+    Send(String),
+    /// End the conversation and let the engine validate the specs.
+    Finish,
+    /// Open a spec file in the editor.
+    Edit(PathBuf),
+    /// Leave the TUI.
+    Quit,
+}
+
+/// Planning TUI state.
+#[derive(Debug)]
+struct App {
+    /// Feature directory spec paths are shown relative to.
+    feature_dir: PathBuf,
+    /// Conversation with the agent.
+    chat: LogView,
+    /// Reply editor.
+    input: InputEditor,
+    /// Whether the agent is waiting for a reply.
+    waiting: bool,
+    /// Spec files written so far, keyed by path.
+    specs: BTreeMap<PathBuf, String>,
+    /// Index of the spec shown in the side panel.
+    selected: usize,
+    /// First visible row of the shown spec.
+    spec_scroll: u16,
+    /// Status line text.
+    status: String,
+    /// Set when the session is over.
+    outcome: Option<Outcome>,
+}
+
+impl App {
+    fn new(feature_dir: &Path) -> Self {
+        Self {
+            feature_dir: feature_dir.to_path_buf(),
+            chat: LogView::default(),
+            input: InputEditor::default(),
+            waiting: false,
+            specs: BTreeMap::new(),
+            selected: 0,
+            spec_scroll: 0,
+            status: "Agent is working...".to_owned(),
+            outcome: None,
+        }
+    }
+
+    fn handle_plan_event(&mut self, event: PlanEvent) {
+        match event {
+            PlanEvent::Message(text) => self.push(Role::Agent, text),
+            PlanEvent::WaitingForInput => {
+                self.waiting = true;
+                self.status = "Waiting for your reply".to_owned();
+            }
+            PlanEvent::SpecGenerated { path, content } => {
+                self.push(Role::Info, format!("Wrote {}", self.label(&path)));
+                self.specs.insert(path.clone(), content);
+                self.selected = self
+                    .specs
+                    .keys()
+                    .position(|p| *p == path)
+                    .unwrap_or_default();
+                self.spec_scroll = 0;
+            }
+            PlanEvent::SpecValidated {
+                attempt,
+                diagnostics,
+            } => {
+                if diagnostics.is_empty() {
+                    self.push(Role::Info, "Specs valid".to_owned());
+                } else {
+                    let mut text = format!(
+                        "Specs invalid (round {attempt}, {} problem(s)):",
+                        diagnostics.len()
+                    );
+                    for diagnostic in &diagnostics {
+                        text.push_str(&format!("\n  {diagnostic}"));
+                    }
+                    self.push(Role::Info, text);
+                    self.status = "Agent is repairing the specs...".to_owned();
+                }
+            }
+            PlanEvent::Completed => self.outcome = Some(Outcome::Completed),
+            PlanEvent::Error(e) => self.outcome = Some(Outcome::Failed(e.to_string())),
+        }
+    }
+
+    fn handle_key(&mut self, key: KeyEvent) -> Action {
+        let ctrl = key.modifiers.contains(KeyModifiers::CONTROL);
+        let alt = key.modifiers.contains(KeyModifiers::ALT);
+        match key.code {
+            KeyCode::Esc => Action::Quit,
+            KeyCode::Char('c') if ctrl => Action::Quit,
+            KeyCode::Char('d') if ctrl => {
+                self.waiting = false;
+                self.status = "Validating specs...".to_owned();
+                Action::Finish
+            }
+            KeyCode::Char('e') if ctrl => match self.specs.keys().nth(self.selected) {
+                Some(path) => Action::Edit(path.clone()),
+                None => {
+                    self.status = "No spec file written yet".to_owned();
+                    Action::None
+                }
+            },
+            KeyCode::Char('j') if ctrl => {
+                self.input.newline();
+                Action::None
+            }
+            KeyCode::Enter if alt => {
+                self.input.newline();
+                Action::None
+            }
+            KeyCode::Enter => self.send(),
+            KeyCode::Tab => {
+                self.select_spec(1);
+                Action::None
+            }
+            KeyCode::BackTab => {
+                self.select_spec(self.specs.len().saturating_sub(1));
+                Action::None
+            }
+            KeyCode::PageUp => {
+                self.chat.page_up();
+                Action::None
+            }
+            KeyCode::PageDown => {
+                self.chat.page_down();
+                Action::None
+            }
+            KeyCode::Up if ctrl => {
+                self.spec_scroll = self.spec_scroll.saturating_sub(1);
+                Action::None
+            }
+            KeyCode::Down if ctrl => {
+                self.spec_scroll = self.spec_scroll.saturating_add(1);
+                Action::None
+            }
+            _ => {
+                self.input.handle_key(key);
+                Action::None
+            }
+        }
+    }
+
+    /// Send the reply in the editor if the agent is waiting for one.
+    fn send(&mut self) -> Action {
+        if !self.waiting {
+            self.status = "The agent is still working; wait for its question".to_owned();
+            return Action::None;
+        }
+        let text = self.input.text().trim().to_owned();
+        if text.is_empty() {
+            return Action::None;
+        }
+        self.input = InputEditor::default();
+        self.waiting = false;
+        self.chat.follow();
+        self.status = "Agent is working...".to_owned();
+        self.push(Role::You, text.clone());
+        Action::Send(text)
+    }
+
+    /// Reload a spec after the user edited it.
+    fn edited(&mut self, path: &Path, result: Result<()>) {
+        let content = result.and_then(|()| {
+            std::fs::read_to_string(path)
+                .with_context(|| format!("failed to read {}", path.display()))
+        });
+        match content {
+            Ok(content) => {
+                self.push(Role::Info, format!("Edited {}", self.label(path)));
+                self.specs.insert(path.to_path_buf(), content);
+            }
+            Err(e) => self.status = format!("{e:#}"),
+        }
+    }
+
+    fn select_spec(&mut self, step: usize) {
+        if !self.specs.is_empty() {
+            self.selected = (self.selected + step) % self.specs.len();
+            self.spec_scroll = 0;
+        }
+    }
+
+    fn push(&mut self, role: Role, text: String) {
+        let (title, color) = match role {
+            Role::Agent => ("Agent", Color::Cyan),
+            Role::You => ("You", Color::Green),
+            Role::Info => ("gba", Color::DarkGray),
+        };
+        let style = match role {
+            Role::Info => Style::new().dark_gray(),
+            _ => Style::new(),
+        };
+        self.chat
+            .push(title, Style::new().fg(color).bold(), text, style);
+    }
+
+    /// Path shown for a spec file.
+    fn label(&self, path: &Path) -> String {
+        path.strip_prefix(&self.feature_dir)
+            .unwrap_or(path)
+            .display()
+            .to_string()
+    }
+
+    fn draw(&mut self, frame: &mut Frame) {
+        let input_height = self.input.lines.len().clamp(1, MAX_INPUT_LINES) as u16 + 2;
+        let [main, input, footer] = Layout::vertical([
+            Constraint::Min(3),
+            Constraint::Length(input_height),
+            Constraint::Length(1),
+        ])
+        .areas(frame.area());
+        let [chat, specs] =
+            Layout::horizontal([Constraint::Percentage(60), Constraint::Percentage(40)])
+                .areas(main);
+
+        self.chat
+            .render(frame, chat, Block::bordered().title(" Plan "));
+        self.draw_specs(frame, specs);
+        self.draw_input(frame, input);
+
+        let footer_line = Line::from(vec![
+            Span::styled(format!("{} ", self.status), Style::new().yellow()),
+            Span::styled(HELP, Style::new().dark_gray()),
+        ]);
+        frame.render_widget(Paragraph::new(footer_line), footer);
+    }
+
+    fn draw_specs(&self, frame: &mut Frame, area: Rect) {
+        if self.specs.is_empty() {
+            let placeholder = Paragraph::new("Spec files appear here as the agent writes them.")
+                .dark_gray()
+                .wrap(Wrap { trim: true })
+                .block(Block::bordered().title(" Specs "));
+            frame.render_widget(placeholder, area);
+            return;
+        }
+
+        let list_height = self.specs.len().min(MAX_SPEC_LIST_LINES) as u16 + 2;
+        let [list, content] =
+            Layout::vertical([Constraint::Length(list_height), Constraint::Min(3)]).areas(area);
+
+        let skip = (self.selected + 1).saturating_sub(MAX_SPEC_LIST_LINES);
+        let names: Vec<Line> = self
+            .specs
+            .keys()
+            .enumerate()
+            .skip(skip)
+            .take(MAX_SPEC_LIST_LINES)
+            .map(|(i, path)| {
+                let label = self.label(path);
+                if i == self.selected {
+                    Line::styled(format!("> {label}"), Style::new().bold())
+                } else {
+                    Line::raw(format!("  {label}"))
+                }
+            })
+            .collect();
+        let title = format!(" Specs ({}/{}) ", self.selected + 1, self.specs.len());
+        frame.render_widget(
+            Paragraph::new(names).block(Block::bordered().title(title)),
+            list,
+        );
+
+        if let Some((path, text)) = self.specs.iter().nth(self.selected) {
+            let spec = Paragraph::new(text.as_str())
+                .wrap(Wrap { trim: false })
+                .scroll((self.spec_scroll, 0))
+                .block(Block::bordered().title(format!(" {} ", self.label(path))));
+            frame.render_widget(spec, content);
+        }
+    }
+
+    fn draw_input(&self, frame: &mut Frame, area: Rect) {
+        let title = if self.waiting {
+            " Your reply "
+        } else {
+            " Your reply (agent is working) "
+        };
+        let block = Block::bordered().title(title);
+        let inner = block.inner(area);
+
+        let (row, col) = self.input.cursor_offset();
+        let top = row.saturating_sub((inner.height as usize).saturating_sub(1));
+        let left = col.saturating_sub((inner.width as usize).saturating_sub(1));
+        let lines: Vec<Line> = self
+            .input
+            .lines
+            .iter()
+            .map(|l| Line::raw(l.as_str()))
+            .collect();
+        frame.render_widget(
+            Paragraph::new(lines)
+                .scroll((top as u16, left as u16))
+                .block(block),
+            area,
+        );
+        frame.set_cursor_position((inner.x + (col - left) as u16, inner.y + (row - top) as u16));
+    }
+}
+
+/// Multi-line text editor for replies.
+#[derive(Debug)]
+struct InputEditor {
+    /// Text lines; never empty.
+    lines: Vec<String>,
+    /// Cursor line.
+    row: usize,
+    /// Cursor position within the line, in characters.
+    col: usize,
+}
+
+impl Default for InputEditor {
+    fn default() -> Self {
+        Self {
+            lines: vec![String::new()],
+            row: 0,
+            col: 0,
+        }
+    }
+}
+
+impl InputEditor {
+    /// Apply an editing key. Keys without an editing meaning are ignored.
+    fn handle_key(&mut self, key: KeyEvent) {
+        if key
+            .modifiers
+            .intersects(KeyModifiers::CONTROL | KeyModifiers::ALT)
+        {
+            return;
+        }
+        match key.code {
+            KeyCode::Char(c) => self.insert(c),
+            KeyCode::Backspace => self.backspace(),
+            KeyCode::Delete => self.delete(),
+            KeyCode::Left => self.left(),
+            KeyCode::Right => self.right(),
+            KeyCode::Up if self.row > 0 => {
+                self.row -= 1;
+                self.col = self.col.min(self.line_len());
+            }
+            KeyCode::Down if self.row + 1 < self.lines.len() => {
+                self.row += 1;
+                self.col = self.col.min(self.line_len());
+            }
+            KeyCode::Home => self.col = 0,
+            KeyCode::End => self.col = self.line_len(),
+            _ => {}
+        }
+    }
+
+    /// Full text, lines joined with `\n`.
+    fn text(&self) -> String {
+        self.lines.join("\n")
+    }
+
+    fn insert(&mut self, c: char) {
+        let at = self.byte_index();
+        self.lines[self.row].insert(at, c);
+        self.col += 1;
+    }
+
+    fn newline(&mut self) {
+        let at = self.byte_index();
+        let rest = self.lines[self.row].split_off(at);
+        self.row += 1;
+        self.lines.insert(self.row, rest);
+        self.col = 0;
+    }
+
+    fn backspace(&mut self) {
+        if self.col > 0 {
+            self.col -= 1;
+            let at = self.byte_index();
+            self.lines[self.row].remove(at);
+        } else if self.row > 0 {
+            let line = self.lines.remove(self.row);
+            self.row -= 1;
+            self.col = self.line_len();
+            self.lines[self.row].push_str(&line);
+        }
+    }
+
+    fn delete(&mut self) {
+        if self.col < self.line_len() {
+            let at = self.byte_index();
+            self.lines[self.row].remove(at);
+        } else if self.row + 1 < self.lines.len() {
+            let line = self.lines.remove(self.row + 1);
+            self.lines[self.row].push_str(&line);
+        }
+    }
+
+    fn left(&mut self) {
+        if self.col > 0 {
+            self.col -= 1;
+        } else if self.row > 0 {
+            self.row -= 1;
+            self.col = self.line_len();
+        }
+    }
+
+    fn right(&mut self) {
+        if self.col < self.line_len() {
+            self.col += 1;
+        } else if self.row + 1 < self.lines.len() {
+            self.row += 1;
+            self.col = 0;
+        }
+    }
+
+    /// Cursor line and display column.
+    fn cursor_offset(&self) -> (usize, usize) {
+        let before = &self.lines[self.row][..self.byte_index()];
+        (self.row, before.width())
+    }
+
+    fn line_len(&self) -> usize {
+        self.lines[self.row].chars().count()
+    }
+
+    fn byte_index(&self) -> usize {
+        let line = &self.lines[self.row];
+        line.char_indices()
+            .nth(self.col)
+            .map_or(line.len(), |(i, _)| i)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ratatui::Terminal;
+    use ratatui::backend::TestBackend;
+
+    use super::*;
+
+    fn key(code: KeyCode) -> KeyEvent {
+        KeyEvent::new(code, KeyModifiers::NONE)
+    }
+
+    fn ctrl(c: char) -> KeyEvent {
+        KeyEvent::new(KeyCode::Char(c), KeyModifiers::CONTROL)
+    }
+
+    fn type_text(app: &mut App, text: &str) {
+        for c in text.chars() {
+            app.handle_key(key(KeyCode::Char(c)));
+        }
+    }
+
+    #[test]
+    fn test_should_edit_multiline_input() {
+        let mut editor = InputEditor::default();
+        for c in "héllo".chars() {
+            editor.handle_key(key(KeyCode::Char(c)));
+        }
+        editor.handle_key(key(KeyCode::Left));
+        editor.handle_key(key(KeyCode::Left));
+        editor.newline();
+        assert_eq!(editor.text(), "hél\nlo");
+        assert_eq!((editor.row, editor.col), (1, 0));
+
+        editor.handle_key(key(KeyCode::Backspace));
+        assert_eq!(editor.text(), "héllo");
+        assert_eq!((editor.row, editor.col), (0, 3));
+
+        editor.handle_key(key(KeyCode::Delete));
+        editor.handle_key(key(KeyCode::End));
+        editor.handle_key(key(KeyCode::Char('!')));
+        assert_eq!(editor.text(), "hélo!");
+        assert_eq!(editor.cursor_offset(), (0, 5));
+    }
+
+    #[test]
+    fn test_should_send_reply_only_when_waiting() {
+        let mut app = App::new(Path::new("/repo/.gba/features/login"));
+        type_text(&mut app, "GitHub");
+        assert_eq!(app.handle_key(key(KeyCode::Enter)), Action::None);
+
+        app.handle_plan_event(PlanEvent::WaitingForInput);
+        app.handle_key(KeyEvent::new(KeyCode::Enter, KeyModifiers::ALT));
+        type_text(&mut app, "OAuth");
+        assert_eq!(
+            app.handle_key(key(KeyCode::Enter)),
+            Action::Send("GitHub\nOAuth".to_owned())
+        );
+        assert!(!app.waiting);
+        assert_eq!(app.input.text(), "");
+        assert_eq!(
+            app.chat.entries.last().map(|e| e.title.as_str()),
+            Some("You")
+        );
+    }
+
+    #[test]
+    fn test_should_track_generated_specs() {
+        let mut app = App::new(Path::new("/repo/.gba/features/login"));
+        assert_eq!(app.handle_key(ctrl('e')), Action::None);
+
+        for name in ["specs/design.md", "phases.yaml"] {
+            app.handle_plan_event(PlanEvent::SpecGenerated {
+                path: PathBuf::from("/repo/.gba/features/login").join(name),
+                content: format!("# {name}"),
+            });
+        }
+        // The latest spec is selected
+        assert_eq!(
+            app.handle_key(ctrl('e')),
+            Action::Edit(PathBuf::from("/repo/.gba/features/login/phases.yaml"))
+        );
+        app.handle_key(key(KeyCode::Tab));
+        assert_eq!(
+            app.handle_key(ctrl('e')),
+            Action::Edit(PathBuf::from("/repo/.gba/features/login/specs/design.md"))
+        );
+        assert_eq!(app.handle_key(ctrl('d')), Action::Finish);
+
+        app.handle_plan_event(PlanEvent::Completed);
+        assert_eq!(app.outcome, Some(Outcome::Completed));
+    }
+
+    #[test]
+    fn test_should_render_chat_and_specs() {
+        let mut app = App::new(Path::new("/repo/.gba/features/login"));
+        app.handle_plan_event(PlanEvent::Message("Which providers?".to_owned()));
+        app.handle_plan_event(PlanEvent::SpecGenerated {
+            path: PathBuf::from("/repo/.gba/features/login/specs/design.md"),
+            content: "# Login design".to_owned(),
+        });
+
+        let mut terminal =
+            Terminal::new(TestBackend::new(100, 20)).expect("should create terminal");
+        terminal.draw(|frame| app.draw(frame)).expect("should draw");
+
+        let screen: String = terminal
+            .backend()
+            .buffer()
+            .content()
+            .iter()
+            .map(|cell| cell.symbol())
+            .collect();
+        assert!(screen.contains("Which providers?"));
+        assert!(screen.contains("specs/design.md"));
+        assert!(screen.contains("# Login design"));
+    }
+}
@@ -0,0 +1,882 @@
+//! Run dashboard TUI.
+//!
+//! Shows a feature run at a glance: the phase list with status, the
+//! pipeline stages, the current activity (coding, hooks, review, verify,
+//! PR), live agent output, hook results, review issues and running
+//! turn/token/cost totals. Tool calls waiting for approval in `manual`
+//! permission mode are answered in a popup.
+
+use std::collections::VecDeque;
+
+use anyhow::{Context, Result};
+use crossterm::event::{Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+use ratatui::layout::{Constraint, Flex, Layout, Rect};
+use ratatui::style::{Color, Style, Stylize};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, Clear, Paragraph, Wrap};
+use ratatui::{DefaultTerminal, Frame};
+
+use gba_core::{
+    AgentUsage, Issue, PermissionDecision, PermissionRequest, RunEvent, RunStream, Severity,
+    StageKind, StepStatus,
+};
+
+use super::{LogView, TerminalEvents};
+
+/// Height of the hooks and review panes.
+const BOTTOM_PANE_HEIGHT: u16 = 8;
+
+/// Maximum characters of tool input shown in the permission popup.
+const MAX_TOOL_INPUT_CHARS: usize = 500;
+
+/// How a run shown in the dashboard ended.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Outcome {
+    /// The run finished; `pr` is the pull request URL if one was created.
+    Finished {
+        /// Pull request URL.
+        pr: Option<String>,
+    },
+    /// The run failed with the given error.
+    Failed(String),
+    /// The user stopped the run.
+    Stopped,
+}
+
+/// Show `stream` in a full-screen dashboard until the user leaves.
+///
+/// The dashboard stays open after the run ends so the results can be read;
+/// leaving while the run is in progress stops it.
+///
+/// # Errors
+///
+/// Returns an error if the terminal cannot be set up or drawn to, or a
+/// permission decision cannot be sent.
+pub async fn run(stream: &mut RunStream) -> Result<Outcome> {
+    let mut app = App::default();
+    let mut terminal = ratatui::try_init().context("failed to set up terminal")?;
+    let mut events = TerminalEvents::start();
+    let result = event_loop(&mut app, stream, &mut terminal, &mut events).await;
+    events.stop();
+    ratatui::restore();
+    result
+}
+
+async fn event_loop(
+    app: &mut App,
+    stream: &mut RunStream,
+    terminal: &mut DefaultTerminal,
+    events: &mut TerminalEvents,
+) -> Result<Outcome> {
+    let mut stream_open = true;
+    loop {
+        terminal.draw(|frame| app.draw(frame))?;
+
+        let action = tokio::select! {
+            event = stream.next(), if stream_open => {
+                match event {
+                    Some(event) => app.handle_run_event(event),
+                    None => {
+                        stream_open = false;
+                        app.stream_closed();
+                    }
+                }
+                Action::None
+            }
+            event = events.next() => match event {
+                Some(Event::Key(key)) if key.kind == KeyEventKind::Press => app.handle_key(key),
+                Some(_) => Action::None,
+                None => Action::Quit,
+            },
+        };
+
+        match action {
+            Action::None => {}
+            Action::Answer(id, decision) => stream
+                .respond_permission(id, decision)
+                .await
+                .context("failed to send permission decision")?,
+            Action::Quit => return Ok(app.outcome()),
+        }
+    }
+}
+
+/// What the event loop should do after a key press.
+#[derive(Debug, PartialEq, Eq)]
+enum Action {
+    /// Nothing beyond redrawing.
+    None,
+    /// Answer the permission request with the given id.
+    Answer(u64, PermissionDecision),
+    /// Leave the dashboard.
+    Quit,
+}
+
+/// A phase row of the dashboard.
+#[derive(Debug)]
+struct PhaseRow {
+    /// Phase name.
+    name: String,
+    /// Current status.
+    status: StepStatus,
+    /// Commit made for the phase.
+    commit: Option<String>,
+}
+
+/// A stage row of the dashboard.
+#[derive(Debug)]
+struct StageRow {
+    /// Stage name.
+    name: String,
+    /// Stage kind.
+    kind: StageKind,
+    /// Current status.
+    status: StepStatus,
+    /// Outcome once finished.
+    summary: String,
+}
+
+/// A hook result row.
+#[derive(Debug)]
+struct HookRow {
+    /// Hook name.
+    hook: String,
+    /// Phase or stage the hook checked.
+    context: String,
+    /// Whether the hook passed.
+    passed: bool,
+}
+
+/// Turn, token and cost totals across all agent sessions.
+#[derive(Debug, Default)]
+struct Totals {
+    /// Agent turns.
+    turns: u32,
+    /// Input tokens.
+    input_tokens: u64,
+    /// Output tokens.
+    output_tokens: u64,
+    /// Cost in USD of the sessions that reported one.
+    cost_usd: f64,
+}
+
+impl Totals {
+    fn add(&mut self, usage: &AgentUsage) {
+        self.turns = self.turns.saturating_add(usage.turns);
+        self.input_tokens = self.input_tokens.saturating_add(usage.input_tokens);
+        self.output_tokens = self.output_tokens.saturating_add(usage.output_tokens);
+        self.cost_usd += usage.cost_usd.unwrap_or(0.0);
+    }
+}
+
+/// How far the run has got.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum RunState {
+    /// Waiting for the run to start or progressing.
+    Running,
+    /// The run finished.
+    Finished,
+    /// The run failed with the given error.
+    Failed(String),
+}
+
+/// Dashboard state.
+#[derive(Debug)]
+struct App {
+    /// Feature description.
+    feature: String,
+    /// Phases of the feature.
+    phases: Vec<PhaseRow>,
+    /// Stages started so far.
+    stages: Vec<StageRow>,
+    /// Index of the phase being coded.
+    current_phase: Option<usize>,
+    /// What the run is doing right now, e.g. `coding` or `hooks`.
+    activity: String,
+    /// Agent output and run progress.
+    log: LogView,
+    /// Hook results, oldest first.
+    hooks: Vec<HookRow>,
+    /// Issues found by the last review.
+    issues: Vec<Issue>,
+    /// Pull request URL.
+    pr: Option<String>,
+    /// Usage totals.
+    totals: Totals,
+    /// Permission requests waiting for an answer, oldest first.
+    permissions: VecDeque<PermissionRequest>,
+    /// Run progress.
+    state: RunState,
+    /// Whether the user pressed quit once while the run is in progress.
+    confirm_quit: bool,
+}
+
+impl Default for App {
+    fn default() -> Self {
+        Self {
+            feature: String::new(),
+            phases: Vec::new(),
+            stages: Vec::new(),
+            current_phase: None,
+            activity: "starting".to_owned(),
+            log: LogView::default(),
+            hooks: Vec::new(),
+            issues: Vec::new(),
+            pr: None,
+            totals: Totals::default(),
+            permissions: VecDeque::new(),
+            state: RunState::Running,
+            confirm_quit: false,
+        }
+    }
+}
+
+impl App {
+    fn handle_run_event(&mut self, event: RunEvent) {
+        match event {
+            RunEvent::Started { feature, phases } => {
+                self.feature = feature;
+                self.phases = phases
+                    .into_iter()
+                    .map(|phase| PhaseRow {
+                        name: phase.name,
+                        status: phase.status,
+                        commit: None,
+                    })
+                    .collect();
+            }
+            RunEvent::StageStarted { name, kind } => {
+                self.activity = activity(kind).to_owned();
+                self.info(format!("Stage {name} ({kind}) started"));
+                self.stages.push(StageRow {
+                    name,
+                    kind,
+                    status: StepStatus::InProgress,
+                    summary: String::new(),
+                });
+            }
+            RunEvent::StageFinished {
+                name,
+                status,
+                summary,
+                ..
+            } => {
+                self.info(format!("Stage {name}: {summary}"));
+                if let Some(stage) = self.stages.iter_mut().rev().find(|s| s.name == name) {
+                    stage.status = status;
+                    stage.summary = summary;
+                }
+            }
+            RunEvent::PhaseStarted { index, name } => {
+                self.activity = "coding".to_owned();
+                self.current_phase = Some(index);
+                if let Some(phase) = self.phases.get_mut(index) {
+                    phase.status = StepStatus::InProgress;
+                }
+                self.info(format!("Phase {}: {name}", index + 1));
+            }
+            RunEvent::AgentOutput { agent, phase, text } => {
+                self.log.push(
+                    format!("{agent} · {phase}"),
+                    Style::new().cyan().bold(),
+                    text,
+                    Style::new(),
+                );
+            }
+            RunEvent::UsageReported(usage) => self.totals.add(&usage),
+            RunEvent::HookResult { hook, passed } => {
+                self.activity = "hooks".to_owned();
+                let context = self.context();
+                self.hooks.push(HookRow {
+                    hook,
+                    context,
+                    passed,
+                });
+            }
+            RunEvent::PhaseCommitted { index, commit_hash } => {
+                if let Some(phase) = self.phases.get_mut(index) {
+                    phase.status = StepStatus::Completed;
+                    phase.commit = Some(commit_hash.clone());
+                }
+                self.current_phase = None;
+                self.info(format!("Phase {} committed: {commit_hash}", index + 1));
+            }
+            RunEvent::ReviewCompleted { issues } => {
+                self.info(format!("Code review completed ({} issues)", issues.len()));
+                self.issues = issues;
+            }
+            RunEvent::VerificationCompleted { passed, details } => {
+                let verdict = if passed { "passed" } else { "failed" };
+                self.info(format!("Verification {verdict}: {details}"));
+            }
+            RunEvent::PermissionRequested(request) => self.permissions.push_back(request),
+            RunEvent::ToolDenied(denial) => {
+                self.info(format!(
+                    "Denied {} for {} ({}): {}",
+                    denial.tool, denial.agent, denial.phase, denial.reason
+                ));
+            }
+            RunEvent::PrCreated { url } => {
+                self.info(format!("PR created: {url}"));
+                self.pr = Some(url);
+            }
+            RunEvent::Finished => {
+                self.state = RunState::Finished;
+                self.activity = "done".to_owned();
+            }
+            RunEvent::Error(e) => self.fail(e.to_string()),
+        }
+    }
+
+    /// The run stream closed; a run that did not report its end failed.
+    fn stream_closed(&mut self) {
+        if self.state == RunState::Running {
+            self.fail("run ended unexpectedly".to_owned());
+        }
+    }
+
+    fn fail(&mut self, error: String) {
+        if let Some(phase) = self.current_phase.and_then(|i| self.phases.get_mut(i)) {
+            phase.status = StepStatus::Failed;
+        }
+        if let Some(stage) = self
+            .stages
+            .iter_mut()
+            .rev()
+            .find(|s| s.status == StepStatus::InProgress)
+        {
+            stage.status = StepStatus::Failed;
+        }
+        self.log.push(
+            "error",
+            Style::new().red().bold(),
+            error.clone(),
+            Style::new().red(),
+        );
+        self.activity = "failed".to_owned();
+        self.state = RunState::Failed(error);
+    }
+
+    fn handle_key(&mut self, key: KeyEvent) -> Action {
+        let ctrl = key.modifiers.contains(KeyModifiers::CONTROL);
+        if ctrl && key.code == KeyCode::Char('c') {
+            return Action::Quit;
+        }
+
+        if let Some(request) = self.permissions.front() {
+            let decision = match key.code {
+                KeyCode::Char('y') => PermissionDecision::AllowOnce,
+                KeyCode::Char('a') => PermissionDecision::AllowAlways,
+                KeyCode::Char('n') => PermissionDecision::Deny {
+                    reason: "denied by the user".to_owned(),
+                },
+                _ => return Action::None,
+            };
+            let id = request.id;
+            self.permissions.pop_front();
+            return Action::Answer(id, decision);
+        }
+
+        match key.code {
+            KeyCode::Char('q') | KeyCode::Esc => {
+                if self.state != RunState::Running || self.confirm_quit {
+                    Action::Quit
+                } else {
+                    self.confirm_quit = true;
+                    Action::None
+                }
+            }
+            KeyCode::PageUp => {
+                self.log.page_up();
+                Action::None
+            }
+            KeyCode::PageDown => {
+                self.log.page_down();
+                Action::None
+            }
+            KeyCode::End => {
+                self.log.follow();
+                Action::None
+            }
+            _ => {
+                self.confirm_quit = false;
+                Action::None
+            }
+        }
+    }
+
+    /// How the run ended, as seen when the user leaves.
+    fn outcome(&self) -> Outcome {
+        match &self.state {
+            RunState::Running => Outcome::Stopped,
+            RunState::Finished => Outcome::Finished {
+                pr: self.pr.clone(),
+            },
+            RunState::Failed(e) => Outcome::Failed(e.clone()),
+        }
+    }
+
+    /// Phase or stage the run is working on.
+    fn context(&self) -> String {
+        if let Some(phase) = self.current_phase.and_then(|i| self.phases.get(i)) {
+            return phase.name.clone();
+        }
+        self.stages
+            .last()
+            .map(|stage| stage.name.clone())
+            .unwrap_or_default()
+    }
+
+    fn info(&mut self, text: String) {
+        self.log.push(
+            "gba",
+            Style::new().dark_gray().bold(),
+            text,
+            Style::new().dark_gray(),
+        );
+    }
+
+    fn draw(&mut self, frame: &mut Frame) {
+        let [header, main, bottom, footer] = Layout::vertical([
+            Constraint::Length(1),
+            Constraint::Min(5),
+            Constraint::Length(BOTTOM_PANE_HEIGHT),
+            Constraint::Length(1),
+        ])
+        .areas(frame.area());
+        let [left, output] =
+            Layout::horizontal([Constraint::Percentage(35), Constraint::Percentage(65)])
+                .areas(main);
+        let stages_height = self.stages.len().max(1) as u16 + 2;
+        let [phases, stages] =
+            Layout::vertical([Constraint::Min(3), Constraint::Length(stages_height)]).areas(left);
+        let [hooks, review] =
+            Layout::horizontal([Constraint::Percentage(40), Constraint::Percentage(60)])
+                .areas(bottom);
+
+        frame.render_widget(Paragraph::new(self.header_line()), header);
+        self.draw_phases(frame, phases);
+        self.draw_stages(frame, stages);
+        self.log
+            .render(frame, output, Block::bordered().title(" Output "));
+        self.draw_hooks(frame, hooks);
+        self.draw_review(frame, review);
+        frame.render_widget(Paragraph::new(self.footer_line()), footer);
+
+        if let Some(request) = self.permissions.front() {
+            draw_permission(frame, request, self.permissions.len());
+        }
+    }
+
+    fn header_line(&self) -> Line<'_> {
+        let stage = self
+            .stages
+            .last()
+            .map(|s| format!("{} ({})", s.name, s.kind))
+            .unwrap_or_else(|| "-".to_owned());
+        let done = self
+            .phases
+            .iter()
+            .filter(|p| p.status == StepStatus::Completed)
+            .count();
+        let totals = &self.totals;
+        Line::from(vec![
+            Span::styled(format!(" {} ", self.feature), Style::new().bold()),
+            Span::raw(format!(
+                "| stage {stage} | {} | phases {done}/{} | {} turns | {} in / {} out tokens | ${:.2}",
+                self.activity,
+                self.phases.len(),
+                totals.turns,
+                format_tokens(totals.input_tokens),
+                format_tokens(totals.output_tokens),
+                totals.cost_usd,
+            )),
+        ])
+    }
+
+    fn footer_line(&self) -> Line<'_> {
+        let (status, style) = match &self.state {
+            RunState::Running if self.confirm_quit => (
+                "Press q again to stop the run".to_owned(),
+                Style::new().yellow(),
+            ),
+            RunState::Running => ("Running...".to_owned(), Style::new().yellow()),
+            RunState::Finished => match &self.pr {
+                Some(url) => (format!("Done! PR: {url}"), Style::new().green()),
+                None => ("Done!".to_owned(), Style::new().green()),
+            },
+            RunState::Failed(e) => (format!("Failed: {e}"), Style::new().red()),
+        };
+        let help = if self.state == RunState::Running {
+            "PgUp/PgDn scroll | End follow | q stop"
+        } else {
+            "PgUp/PgDn scroll | End follow | q exit"
+        };
+        Line::from(vec![
+            Span::styled(format!("{status} "), style),
+            Span::styled(help, Style::new().dark_gray()),
+        ])
+    }
+
+    fn draw_phases(&self, frame: &mut Frame, area: Rect) {
+        let lines: Vec<Line> = self
+            .phases
+            .iter()
+            .enumerate()
+            .map(|(i, phase)| {
+                let (marker, style) = status_marker(&phase.status);
+                let mut spans = vec![
+                    Span::styled(format!("{marker} "), style),
+                    Span::raw(format!("{}. {}", i + 1, phase.name)),
+                ];
+                if let Some(commit) = &phase.commit {
+                    let short: String = commit.chars().take(7).collect();
+                    spans.push(Span::styled(format!(" {short}"), Style::new().dark_gray()));
+                }
+                Line::from(spans)
+            })
+            .collect();
+        frame.render_widget(
+            Paragraph::new(lines).block(Block::bordered().title(" Phases ")),
+            area,
+        );
+    }
+
+    fn draw_stages(&self, frame: &mut Frame, area: Rect) {
+        let lines: Vec<Line> = self
+            .stages
+            .iter()
+            .map(|stage| {
+                let (marker, style) = status_marker(&stage.status);
+                let mut spans = vec![
+                    Span::styled(format!("{marker} "), style),
+                    Span::raw(stage.name.clone()),
+                ];
+                if !stage.summary.is_empty() {
+                    spans.push(Span::styled(
+                        format!(": {}", stage.summary),
+                        Style::new().dark_gray(),
+                    ));
+                }
+                Line::from(spans)
+            })
+            .collect();
+        frame.render_widget(
+            Paragraph::new(lines).block(Block::bordered().title(" Stages ")),
+            area,
+        );
+    }
+
+    fn draw_hooks(&self, frame: &mut Frame, area: Rect) {
+        let visible = (area.height as usize).saturating_sub(2);
+        let skip = self.hooks.len().saturating_sub(visible);
+        let lines: Vec<Line> = self
+            .hooks
+            .iter()
+            .skip(skip)
+            .map(|row| {
+                let (marker, style) = if row.passed {
+                    ("[x]", Style::new().green())
+                } else {
+                    ("[!]", Style::new().red())
+                };
+                Line::from(vec![
+                    Span::styled(format!("{marker} "), style),
+                    Span::raw(row.hook.clone()),
+                    Span::styled(format!(" ({})", row.context), Style::new().dark_gray()),
+                ])
+            })
+            .collect();
+        frame.render_widget(
+            Paragraph::new(lines).block(Block::bordered().title(" Hooks ")),
+            area,
+        );
+    }
+
+    fn draw_review(&self, frame: &mut Frame, area: Rect) {
+        let lines: Vec<Line> = self
+            .issues
+            .iter()
+            .map(|issue| {
+                let (label, style) = match issue.severity {
+                    Severity::Error => ("error", Style::new().red()),
+                    Severity::Warning => ("warning", Style::new().yellow()),
+                    Severity::Suggestion => ("suggestion", Style::new().dark_gray()),
+                };
+                let location = match issue.line {
+                    Some(line) => format!("{}:{line}", issue.file.display()),
+                    None => issue.file.display().to_string(),
+                };
+                Line::from(vec![
+                    Span::styled(format!("{label} "), style),
+                    Span::styled(format!("{location} "), Style::new().bold()),
+                    Span::raw(issue.description.clone()),
+                ])
+            })
+            .collect();
+        let title = format!(" Review issues ({}) ", self.issues.len());
+        frame.render_widget(
+            Paragraph::new(lines).block(Block::bordered().title(title)),
+            area,
+        );
+    }
+}
+
+/// Popup asking to approve the oldest pending tool call.
+fn draw_permission(frame: &mut Frame, request: &PermissionRequest, pending: usize) {
+    let input = match request.input.get("command").and_then(|c| c.as_str()) {
+        Some(command) => command.to_owned(),
+        None => request.input.to_string(),
+    };
+    let shown: String = input.chars().take(MAX_TOOL_INPUT_CHARS).collect();
+    let ellipsis = if shown.len() < input.len() { "..." } else { "" };
+
+    let lines = vec![
+        Line::from(vec![
+            Span::styled(request.agent.clone(), Style::new().bold()),
+            Span::raw(format!(" ({}) wants to use ", request.phase)),
+            Span::styled(request.tool_name.clone(), Style::new().bold()),
+        ]),
+        Line::default(),
+        Line::raw(format!("{shown}{ellipsis}")),
+        Line::default(),
+        Line::styled(
+            "[y] allow once  [a] allow for this run  [n] deny",
+            Style::new().yellow(),
+        ),
+    ];
+    let title = if pending > 1 {
+        format!(" Permission (1 of {pending}) ")
+    } else {
+        " Permission ".to_owned()
+    };
+
+    let area = centered(frame.area(), 70, 12);
+    frame.render_widget(Clear, area);
+    frame.render_widget(
+        Paragraph::new(lines)
+            .wrap(Wrap { trim: false })
+            .block(Block::bordered().title(title).fg(Color::White)),
+        area,
+    );
+}
+
+/// A rectangle `percent_x` wide and `height` rows high, centered in `area`.
+fn centered(area: Rect, percent_x: u16, height: u16) -> Rect {
+    let [row] = Layout::vertical([Constraint::Length(height)])
+        .flex(Flex::Center)
+        .areas(area);
+    let [rect] = Layout::horizontal([Constraint::Percentage(percent_x)])
+        .flex(Flex::Center)
+        .areas(row);
+    rect
+}
+
+/// What the run is doing while a stage of `kind` runs.
+fn activity(kind: StageKind) -> &'static str {
+    match kind {
+        StageKind::Phases => "coding",
+        StageKind::Review => "review",
+        StageKind::Verification => "verify",
+        StageKind::Agent => "agent",
+        StageKind::Pr => "PR",
+    }
+}
+
+/// Marker and style of a phase or stage status.
+fn status_marker(status: &StepStatus) -> (&'static str, Style) {
+    match status {
+        StepStatus::Pending => ("[ ]", Style::new().dark_gray()),
+        StepStatus::InProgress => ("[~]", Style::new().yellow()),
+        StepStatus::Completed => ("[x]", Style::new().green()),
+        StepStatus::Failed => ("[!]", Style::new().red()),
+        StepStatus::Skipped => ("[-]", Style::new().dark_gray()),
+    }
+}
+
+/// Short token count, e.g. `950`, `12.3k` or `1.2M`.
+fn format_tokens(tokens: u64) -> String {
+    match tokens {
+        0..1_000 => tokens.to_string(),
+        1_000..1_000_000 => format!("{:.1}k", tokens as f64 / 1_000.0),
+        _ => format!("{:.1}M", tokens as f64 / 1_000_000.0),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use gba_core::{CoreError, PhaseSummary};
+    use ratatui::Terminal;
+    use ratatui::backend::TestBackend;
+
+    use super::*;
+
+    fn key(c: char) -> KeyEvent {
+        KeyEvent::new(KeyCode::Char(c), KeyModifiers::NONE)
+    }
+
+    fn started(app: &mut App) {
+        app.handle_run_event(RunEvent::Started {
+            feature: "Login page".to_owned(),
+            phases: vec![
+                PhaseSummary {
+                    name: "Setup".to_owned(),
+                    status: StepStatus::Completed,
+                },
+                PhaseSummary {
+                    name: "Routes".to_owned(),
+                    status: StepStatus::Pending,
+                },
+            ],
+        });
+        app.handle_run_event(RunEvent::StageStarted {
+            name: "phases".to_owned(),
+            kind: StageKind::Phases,
+        });
+    }
+
+    #[test]
+    fn test_should_track_phases_hooks_and_totals() {
+        let mut app = App::default();
+        started(&mut app);
+        app.handle_run_event(RunEvent::PhaseStarted {
+            index: 1,
+            name: "Routes".to_owned(),
+        });
+        assert_eq!(app.phases[1].status, StepStatus::InProgress);
+        assert_eq!(app.activity, "coding");
+
+        for cost in [Some(0.5), None] {
+            app.handle_run_event(RunEvent::UsageReported(AgentUsage {
+                agent: "code".to_owned(),
+                phase: "Routes".to_owned(),
+                turns: 3,
+                input_tokens: 1_500,
+                output_tokens: 200,
+                cost_usd: cost,
+            }));
+        }
+        app.handle_run_event(RunEvent::HookResult {
+            hook: "cargo fmt".to_owned(),
+            passed: false,
+        });
+        assert_eq!(app.activity, "hooks");
+        assert_eq!(app.hooks[0].context, "Routes");
+
+        app.handle_run_event(RunEvent::PhaseCommitted {
+            index: 1,
+            commit_hash: "abc1234def".to_owned(),
+        });
+        assert_eq!(app.phases[1].status, StepStatus::Completed);
+        assert_eq!(app.totals.turns, 6);
+        assert_eq!(app.totals.input_tokens, 3_000);
+        assert!((app.totals.cost_usd - 0.5).abs() < f64::EPSILON);
+    }
+
+    #[test]
+    fn test_should_mark_running_phase_and_stage_failed_on_error() {
+        let mut app = App::default();
+        started(&mut app);
+        app.handle_run_event(RunEvent::PhaseStarted {
+            index: 1,
+            name: "Routes".to_owned(),
+        });
+        app.handle_run_event(RunEvent::Error(CoreError::Agent("boom".to_owned())));
+
+        assert_eq!(app.phases[1].status, StepStatus::Failed);
+        assert_eq!(app.stages[0].status, StepStatus::Failed);
+        assert!(matches!(app.outcome(), Outcome::Failed(ref e) if e.contains("boom")));
+        // The dashboard stays open until the user leaves
+        assert_eq!(app.handle_key(key('q')), Action::Quit);
+    }
+
+    #[test]
+    fn test_should_answer_permission_requests_in_order() {
+        let mut app = App::default();
+        for id in [1, 2] {
+            app.handle_run_event(RunEvent::PermissionRequested(PermissionRequest {
+                id,
+                agent: "code".to_owned(),
+                phase: "Routes".to_owned(),
+                tool_name: "Bash".to_owned(),
+                input: serde_json::json!({"command": "cargo test"}),
+            }));
+        }
+
+        assert_eq!(app.handle_key(key('x')), Action::None);
+        assert_eq!(
+            app.handle_key(key('a')),
+            Action::Answer(1, PermissionDecision::AllowAlways)
+        );
+        assert!(matches!(
+            app.handle_key(key('n')),
+            Action::Answer(2, PermissionDecision::Deny { .. })
+        ));
+        assert!(app.permissions.is_empty());
+    }
+
+    #[test]
+    fn test_should_confirm_before_stopping_a_running_run() {
+        let mut app = App::default();
+        started(&mut app);
+        assert_eq!(app.handle_key(key('q')), Action::None);
+        assert_eq!(app.handle_key(key('q')), Action::Quit);
+        assert_eq!(app.outcome(), Outcome::Stopped);
+
+        app.handle_run_event(RunEvent::PrCreated {
+            url: "https://github.com/o/r/pull/1".to_owned(),
+        });
+        app.handle_run_event(RunEvent::Finished);
+        assert_eq!(
+            app.outcome(),
+            Outcome::Finished {
+                pr: Some("https://github.com/o/r/pull/1".to_owned())
+            }
+        );
+    }
+
+    #[test]
+    fn test_should_render_dashboard() {
+        let mut app = App::default();
+        started(&mut app);
+        app.handle_run_event(RunEvent::AgentOutput {
+            agent: "code".to_owned(),
+            phase: "Routes".to_owned(),
+            text: "Adding the login route".to_owned(),
+        });
+        app.handle_run_event(RunEvent::ReviewCompleted {
+            issues: vec![Issue {
+                severity: Severity::Warning,
+                file: PathBuf::from("src/login.rs"),
+                line: Some(12),
+                description: "unwrap on user input".to_owned(),
+                reviewers: Vec::new(),
+            }],
+        });
+
+        let mut terminal =
+            Terminal::new(TestBackend::new(120, 30)).expect("should create terminal");
+        terminal.draw(|frame| app.draw(frame)).expect("should draw");
+
+        let screen: String = terminal
+            .backend()
+            .buffer()
+            .content()
+            .iter()
+            .map(|cell| cell.symbol())
+            .collect();
+        assert!(screen.contains("Login page"));
+        assert!(screen.contains("2. Routes"));
+        assert!(screen.contains("Adding the login route"));
+        assert!(screen.contains("src/login.rs:12"));
+    }
+
+    #[test]
+    fn test_should_format_token_counts() {
+        assert_eq!(format_tokens(950), "950");
+        assert_eq!(format_tokens(12_345), "12.3k");
+        assert_eq!(format_tokens(1_234_567), "1.2M");
+    }
+}
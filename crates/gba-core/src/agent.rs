@@ -2,18 +2,31 @@
 //!
 //! Wraps `claude-agent-sdk-rs` to provide a unified interface for running
 //! agent sessions. Handles system prompt construction, permission mode mapping,
-//! and tool configuration based on the agent's `config.yml`.
+//! tool configuration based on the agent's `config.yml`, and enforcement of
+//! the project's tool-use policy.
 
-use std::path::Path;
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
 
 use claude_agent_sdk_rs::{
-    ClaudeAgentOptions, Message, PermissionMode as SdkPermissionMode, ResultMessage, SystemPrompt,
+    ClaudeAgentOptions, ClaudeClient, HookCallback, HookEvent, HookInput, HookJsonOutput,
+    HookMatcher, HookSpecificOutput, Message, PermissionMode as SdkPermissionMode,
+    PreToolUseHookSpecificOutput, ResultMessage, SyncHookJsonOutput, SystemPrompt,
     SystemPromptPreset, Tools,
 };
-use tracing::{debug, error, instrument};
+use futures::FutureExt as _;
+use gba_pm::AgentConfig;
+use tracing::{debug, error, instrument, warn};
 
-use crate::config::{EngineConfig, PermissionMode, ProjectConfig};
+use crate::config::{AgentOverride, EngineConfig, PermissionMode, ProjectConfig, StageKind};
 use crate::error::CoreError;
+use crate::events::PermissionDecision;
+use crate::permission::ToolApprover;
+use crate::policy::ToolPolicy;
+
+/// Seconds a tool call may wait for the user's approval.
+const APPROVAL_TIMEOUT_SECS: f64 = 3600.0;
 
 /// Wraps the Claude Agent SDK to run agent sessions.
 ///
@@ -24,60 +37,129 @@ use crate::error::CoreError;
 pub(crate) struct AgentRunner {
     /// Prompt manager for rendering templates.
     prompt_manager: gba_pm::PromptManager,
-    /// Resolved model name (CLI override > project config > SDK default).
+    /// Repository root, used to resolve relative `addDirs`.
+    repo_path: PathBuf,
+    /// Model from the `--model` CLI flag; wins over every per-agent setting.
+    cli_model: Option<String>,
+    /// Engine-wide default model from project config.
     model: Option<String>,
     /// Resolved max tokens (CLI override > project config).
     #[allow(dead_code)] // Retained for future use when agent token limits are enforced
     max_tokens: Option<u32>,
-    /// Permission mode from project config.
+    /// Permission mode from the `--permission-mode` CLI flag; wins over
+    /// every per-agent setting.
+    cli_permission_mode: Option<PermissionMode>,
+    /// Engine-wide default permission mode from project config.
     permission_mode: PermissionMode,
+    /// Per-agent overrides from project config, keyed by agent name.
+    agent_overrides: BTreeMap<String, AgentOverride>,
+    /// Tool-use policy enforced on every agent session.
+    policy: Arc<ToolPolicy>,
 }
 
 impl AgentRunner {
     /// Create a new agent runner from engine and project configuration.
     ///
-    /// Loads prompt templates (built-in and any custom override directories),
-    /// and merges model/token settings with CLI overrides taking precedence.
+    /// Loads prompt templates and agent configs (built-in, `.gba/agents/` and
+    /// any custom override directories), and merges model/token settings with
+    /// CLI overrides taking precedence.
     ///
     /// # Errors
     ///
-    /// Returns `CoreError::Prompt` if templates cannot be loaded.
+    /// Returns `CoreError::Prompt` if templates or agent configs cannot be loaded.
+    /// Returns `CoreError::Config` if `agents` overrides an unknown agent, a
+    /// pipeline stage references a missing agent or template, a template does
+    /// not match its contract or the contexts it is rendered with, or the
+    /// tool policy is invalid.
     #[instrument(skip_all)]
     pub(crate) fn new(
         config: &EngineConfig,
         project_config: &ProjectConfig,
     ) -> Result<Self, CoreError> {
-        let mut pm = gba_pm::PromptManager::new()?;
-
-        // Load custom prompt directories from project config
-        for dir in &project_config.prompts.include {
-            let resolved = if dir.is_absolute() {
-                dir.clone()
-            } else {
-                config.repo_path().join(dir)
-            };
-            if resolved.is_dir() {
-                pm.load_dir(&resolved)?;
-                debug!(dir = %resolved.display(), "loaded custom prompt directory");
-            }
-        }
+        let pm = load_prompt_manager(config, project_config)?;
 
-        // CLI overrides take precedence over project config
-        let model = config
-            .model()
-            .map(String::from)
-            .or_else(|| project_config.agent.model.clone());
+        check_agent_references(&pm, project_config)?;
+        let issues = crate::prompts::template_issues(&pm, project_config);
+        if !issues.is_empty() {
+            return Err(CoreError::Config(format!(
+                "prompt templates do not match their contracts (run `gba prompts check`): {}",
+                issues
+                    .iter()
+                    .map(ToString::to_string)
+                    .collect::<Vec<_>>()
+                    .join("; ")
+            )));
+        }
+        let policy = ToolPolicy::new(&project_config.tool_policy)?;
 
         let max_tokens = config.max_tokens().or(project_config.agent.max_tokens);
 
         Ok(Self {
             prompt_manager: pm,
-            model,
+            repo_path: config.repo_path().clone(),
+            cli_model: config.model().map(String::from),
+            model: project_config.agent.model.clone(),
             max_tokens,
+            cli_permission_mode: config.permission_mode().cloned(),
             permission_mode: project_config.agent.permission_mode.clone(),
+            agent_overrides: project_config.agents.clone(),
+            policy: Arc::new(policy),
         })
     }
 
+    /// Look up an agent's `config.yml` and apply its project config override.
+    ///
+    /// # Errors
+    ///
+    /// Returns `CoreError::Agent` if no agent with that name exists.
+    pub(crate) fn agent_config(&self, agent_name: &str) -> Result<AgentConfig, CoreError> {
+        let mut agent_config = self
+            .prompt_manager
+            .agent_config(agent_name)
+            .map_err(|e| {
+                CoreError::Agent(format!("failed to load agent config for {agent_name}: {e}"))
+            })?
+            .clone();
+
+        if let Some(overrides) = self.agent_overrides.get(agent_name) {
+            if let Some(model) = &overrides.model {
+                agent_config.model = Some(model.clone());
+            }
+            if let Some(max_turns) = overrides.max_turns {
+                agent_config.max_turns = Some(max_turns);
+            }
+            if let Some(mode) = &overrides.permission_mode {
+                agent_config.permission_mode = Some(mode.clone());
+            }
+            agent_config.env.extend(overrides.env.clone());
+            agent_config
+                .add_dirs
+                .extend(overrides.add_dirs.iter().cloned());
+        }
+
+        Ok(agent_config)
+    }
+
+    /// Resolve the model for an agent.
+    ///
+    /// Precedence: CLI flag > agent setting > project default > SDK default.
+    fn model_for(&self, agent_config: &AgentConfig) -> Option<String> {
+        self.cli_model
+            .clone()
+            .or_else(|| agent_config.model.clone())
+            .or_else(|| self.model.clone())
+    }
+
+    /// Resolve the permission mode for an agent.
+    ///
+    /// Precedence: CLI flag > agent setting > project default.
+    fn permission_mode_for(&self, agent_config: &AgentConfig) -> PermissionMode {
+        self.cli_permission_mode
+            .clone()
+            .or_else(|| agent_config.permission_mode.clone())
+            .unwrap_or_else(|| self.permission_mode.clone())
+    }
+
     /// Run an agent session and collect all messages.
     ///
     /// Renders the system and task prompts from templates, constructs SDK
@@ -95,19 +177,87 @@ impl AgentRunner {
         context: &serde_json::Value,
         cwd: Option<&Path>,
     ) -> Result<Vec<Message>, CoreError> {
-        let options = self.build_options(agent_name, context, cwd)?;
+        let system_template = format!("{agent_name}/system");
+        self.run_agent_with_system(
+            agent_name,
+            &system_template,
+            task_template,
+            context,
+            cwd,
+            None,
+        )
+        .await
+    }
+
+    /// Run an agent session with a system template other than the agent's
+    /// own `<agent>/system`.
+    ///
+    /// Used by review stages to run one agent under several reviewer
+    /// personas. Tool calls denied by the tool policy, the agent's text and
+    /// the session's usage are reported through `approver`. When the agent
+    /// runs in `manual` permission mode and an `approver` is given, every
+    /// other tool call waits for its decision. The rendered prompts, every
+    /// message and a failure are recorded to the approver's transcript.
+    ///
+    /// # Errors
+    ///
+    /// Returns `CoreError::Prompt` if template rendering fails.
+    /// Returns `CoreError::Agent` if the SDK query fails.
+    #[instrument(skip(self, context, approver))]
+    pub(crate) async fn run_agent_with_system(
+        &self,
+        agent_name: &str,
+        system_template: &str,
+        task_template: &str,
+        context: &serde_json::Value,
+        cwd: Option<&Path>,
+        approver: Option<&ToolApprover>,
+    ) -> Result<Vec<Message>, CoreError> {
+        let mut options =
+            self.build_options_with_system(agent_name, system_template, context, cwd)?;
         let task_prompt = self.prompt_manager.render(task_template, context)?;
+        if let Some(approver) = approver {
+            approver.record_prompt(
+                system_prompt_text(options.system_prompt.as_ref()),
+                &task_prompt,
+            );
+        }
+        let manual =
+            self.permission_mode_for(&self.agent_config(agent_name)?) == PermissionMode::Manual;
+        let ask = manual && approver.is_some();
 
-        debug!(agent = agent_name, task = task_template, "running agent");
+        debug!(
+            agent = agent_name,
+            system = system_template,
+            task = task_template,
+            "running agent"
+        );
 
-        let messages = claude_agent_sdk_rs::query(&task_prompt, Some(options))
-            .await
-            .map_err(|e| {
-                error!(agent = agent_name, error = %e, "agent query failed");
-                CoreError::Agent(format!(
-                    "agent {agent_name} failed: {e}. Check your network connection and API credentials."
-                ))
-            })?;
+        let guarded = ask || !self.policy.is_empty();
+        let result = if guarded || approver.is_some() {
+            if guarded {
+                let guard = ToolGuard {
+                    policy: Arc::clone(&self.policy),
+                    agent: agent_name.to_owned(),
+                    cwd: cwd.map(Path::to_path_buf),
+                    approver: approver.cloned(),
+                    ask,
+                };
+                options.hooks = Some(guard.hooks());
+            }
+            query_with_client(&task_prompt, options, approver).await
+        } else {
+            claude_agent_sdk_rs::query(&task_prompt, Some(options)).await
+        };
+        let messages = result.map_err(|e| {
+            error!(agent = agent_name, error = %e, "agent query failed");
+            if let Some(approver) = approver {
+                approver.record_error(&e.to_string());
+            }
+            CoreError::Agent(format!(
+                "agent {agent_name} failed: {e}. Check your network connection and API credentials."
+            ))
+        })?;
 
         Ok(messages)
     }
@@ -167,8 +317,12 @@ impl AgentRunner {
             .ok_or_else(|| CoreError::Agent(format!("agent {agent_name} ended without result")))
     }
 
+    /// Names of all available agents, built-in and project-defined.
+    pub(crate) fn agents(&self) -> Vec<&str> {
+        self.prompt_manager.list_agents()
+    }
+
     /// Returns a reference to the internal prompt manager.
-    #[allow(dead_code)] // Used in tests and will be used for template introspection
     pub(crate) fn prompt_manager(&self) -> &gba_pm::PromptManager {
         &self.prompt_manager
     }
@@ -177,7 +331,8 @@ impl AgentRunner {
     ///
     /// This is the public variant of the internal `build_options` method,
     /// exposed so that the plan workflow can construct `ClaudeAgentOptions`
-    /// for direct use with `ClaudeClient` (bidirectional streaming).
+    /// for direct use with `ClaudeClient` (bidirectional streaming). The
+    /// options carry the hook enforcing the tool policy, if any.
     ///
     /// # Errors
     ///
@@ -189,7 +344,18 @@ impl AgentRunner {
         context: &serde_json::Value,
         cwd: Option<&Path>,
     ) -> Result<ClaudeAgentOptions, CoreError> {
-        self.build_options(agent_name, context, cwd)
+        let mut options = self.build_options(agent_name, context, cwd)?;
+        if !self.policy.is_empty() {
+            let guard = ToolGuard {
+                policy: Arc::clone(&self.policy),
+                agent: agent_name.to_owned(),
+                cwd: cwd.map(Path::to_path_buf),
+                approver: None,
+                ask: false,
+            };
+            options.hooks = Some(guard.hooks());
+        }
+        Ok(options)
     }
 
     /// Render a prompt template with the given context.
@@ -214,13 +380,23 @@ impl AgentRunner {
         context: &serde_json::Value,
         cwd: Option<&Path>,
     ) -> Result<ClaudeAgentOptions, CoreError> {
-        let agent_config = gba_pm::PromptManager::load_agent_config(agent_name).map_err(|e| {
-            CoreError::Agent(format!("failed to load agent config for {agent_name}: {e}"))
-        })?;
+        let system_template = format!("{agent_name}/system");
+        self.build_options_with_system(agent_name, &system_template, context, cwd)
+    }
+
+    /// Build SDK options for an agent session, rendering the system prompt
+    /// from `system_template`.
+    fn build_options_with_system(
+        &self,
+        agent_name: &str,
+        system_template: &str,
+        context: &serde_json::Value,
+        cwd: Option<&Path>,
+    ) -> Result<ClaudeAgentOptions, CoreError> {
+        let agent_config = self.agent_config(agent_name)?;
 
         // Render the system prompt
-        let system_template = format!("{agent_name}/system");
-        let rendered_system = self.prompt_manager.render(&system_template, context)?;
+        let rendered_system = self.prompt_manager.render(system_template, context)?;
 
         // Build system prompt based on preset flag
         let system_prompt = if agent_config.preset {
@@ -233,12 +409,14 @@ impl AgentRunner {
         };
 
         // Map permission mode
-        let sdk_permission_mode = match self.permission_mode {
+        let sdk_permission_mode = match self.permission_mode_for(&agent_config) {
             PermissionMode::Auto => SdkPermissionMode::BypassPermissions,
             PermissionMode::Manual => SdkPermissionMode::Default,
             PermissionMode::None => SdkPermissionMode::Plan,
         };
 
+        let model = self.model_for(&agent_config);
+
         // Build options using struct initialization because typed-builder
         // changes type on each setter call, making conditional fields awkward.
         let tools = if agent_config.tools.is_empty() {
@@ -247,12 +425,27 @@ impl AgentRunner {
             Some(Tools::from(agent_config.tools))
         };
 
+        let add_dirs = agent_config
+            .add_dirs
+            .iter()
+            .map(|dir| {
+                if dir.is_absolute() {
+                    dir.clone()
+                } else {
+                    self.repo_path.join(dir)
+                }
+            })
+            .collect();
+
         let options = ClaudeAgentOptions {
             system_prompt: Some(system_prompt),
             permission_mode: Some(sdk_permission_mode),
+            model,
+            max_turns: agent_config.max_turns,
+            env: agent_config.env.into_iter().collect(),
+            add_dirs,
             disallowed_tools: agent_config.disallowed_tools,
             tools,
-            model: self.model.clone(),
             cwd: cwd.map(Path::to_path_buf),
             ..Default::default()
         };
@@ -261,11 +454,262 @@ impl AgentRunner {
     }
 }
 
+/// Text gba contributes to a system prompt: the whole prompt, or the text
+/// appended to a preset.
+fn system_prompt_text(prompt: Option<&SystemPrompt>) -> &str {
+    match prompt {
+        Some(SystemPrompt::Text(text)) => text,
+        Some(SystemPrompt::Preset(preset)) => preset.append.as_deref().unwrap_or_default(),
+        None => "",
+    }
+}
+
+/// Run a one-shot query through a `ClaudeClient` session.
+///
+/// Unlike [`claude_agent_sdk_rs::query`], a client session serves the
+/// control protocol, so hook callbacks in `options` are invoked. Messages
+/// are reported through `approver` while the session runs.
+async fn query_with_client(
+    prompt: &str,
+    options: ClaudeAgentOptions,
+    approver: Option<&ToolApprover>,
+) -> claude_agent_sdk_rs::Result<Vec<Message>> {
+    let mut client = ClaudeClient::new(options);
+    client.connect().await?;
+    let result = collect_response(&mut client, prompt, approver).await;
+    if let Err(e) = client.disconnect().await {
+        warn!(error = %e, "failed to disconnect agent cleanly");
+    }
+    result
+}
+
+/// Send `prompt` and collect messages up to and including the result,
+/// reporting each one through `approver` as it arrives.
+async fn collect_response(
+    client: &mut ClaudeClient,
+    prompt: &str,
+    approver: Option<&ToolApprover>,
+) -> claude_agent_sdk_rs::Result<Vec<Message>> {
+    use futures::StreamExt as _;
+
+    client.query(prompt).await?;
+    let mut messages = Vec::new();
+    let mut stream = client.receive_response();
+    while let Some(msg) = stream.next().await {
+        let msg = msg?;
+        if let Some(approver) = approver {
+            approver.report_message(&msg).await;
+        }
+        messages.push(msg);
+    }
+    Ok(messages)
+}
+
+/// Decides on the tool calls of one agent session.
+#[derive(Debug, Clone)]
+struct ToolGuard {
+    /// Tool-use policy checked first.
+    policy: Arc<ToolPolicy>,
+    /// Agent making the tool calls.
+    agent: String,
+    /// Working directory of the session, used by path rules.
+    cwd: Option<PathBuf>,
+    /// Reports denials to the run and asks the user in `manual` mode.
+    approver: Option<ToolApprover>,
+    /// Whether calls the policy allows still need the user's approval.
+    ask: bool,
+}
+
+impl ToolGuard {
+    /// Decide on one tool call.
+    ///
+    /// Returns `None` when the guard has no opinion and the session's
+    /// permission mode applies.
+    async fn decide(
+        &self,
+        tool_name: &str,
+        input: serde_json::Value,
+    ) -> Option<PermissionDecision> {
+        if let Some(reason) = self
+            .policy
+            .check(&self.agent, tool_name, &input, self.cwd.as_deref())
+        {
+            warn!(agent = %self.agent, tool = tool_name, %reason, "tool call denied by policy");
+            if let Some(approver) = &self.approver {
+                approver.report_denial(tool_name, &reason).await;
+            }
+            return Some(PermissionDecision::Deny { reason });
+        }
+
+        match &self.approver {
+            Some(approver) if self.ask => Some(approver.approve(tool_name, input).await),
+            _ => None,
+        }
+    }
+
+    /// `PreToolUse` hook that runs every tool call through the guard.
+    ///
+    /// The SDK's `can_use_tool` callback is not served by its control
+    /// protocol, so decisions are made from a hook instead.
+    fn hooks(self) -> HashMap<HookEvent, Vec<HookMatcher>> {
+        let callback: HookCallback = Arc::new(move |input, _tool_use_id, _context| {
+            let guard = self.clone();
+            async move {
+                let HookInput::PreToolUse(input) = input else {
+                    return HookJsonOutput::Sync(SyncHookJsonOutput::default());
+                };
+                match guard.decide(&input.tool_name, input.tool_input).await {
+                    Some(decision) => pre_tool_use_output(&decision),
+                    None => HookJsonOutput::Sync(SyncHookJsonOutput::default()),
+                }
+            }
+            .boxed()
+        });
+
+        HashMap::from([(
+            HookEvent::PreToolUse,
+            vec![
+                HookMatcher::builder()
+                    .hooks(vec![callback])
+                    .timeout(APPROVAL_TIMEOUT_SECS)
+                    .build(),
+            ],
+        )])
+    }
+}
+
+/// Translate a permission decision into `PreToolUse` hook output.
+fn pre_tool_use_output(decision: &PermissionDecision) -> HookJsonOutput {
+    let (verdict, reason) = match decision {
+        PermissionDecision::AllowOnce => ("allow", "approved by the user".to_owned()),
+        PermissionDecision::AllowAlways => {
+            ("allow", "approved by the user for this run".to_owned())
+        }
+        PermissionDecision::Deny { reason } => ("deny", reason.clone()),
+    };
+    HookJsonOutput::Sync(SyncHookJsonOutput {
+        hook_specific_output: Some(HookSpecificOutput::PreToolUse(
+            PreToolUseHookSpecificOutput {
+                permission_decision: Some(verdict.to_owned()),
+                permission_decision_reason: Some(reason),
+                updated_input: None,
+            },
+        )),
+        ..Default::default()
+    })
+}
+
+/// Load the built-in templates and agents, then the project's
+/// `.gba/agents/` directory and the `prompts.include` directories, later
+/// ones overriding earlier ones.
+///
+/// # Errors
+///
+/// Returns `CoreError::Prompt` if templates or agent configs cannot be loaded.
+pub(crate) fn load_prompt_manager(
+    config: &EngineConfig,
+    project_config: &ProjectConfig,
+) -> Result<gba_pm::PromptManager, CoreError> {
+    let mut pm = gba_pm::PromptManager::new()?;
+
+    // Agent definitions in `.gba/agents/<name>/` override built-ins and
+    // may add new agents
+    let agents_dir = config.gba_dir().join("agents");
+    if agents_dir.is_dir() {
+        pm.load_dir(&agents_dir)?;
+        debug!(dir = %agents_dir.display(), "loaded project agents");
+    }
+
+    // Load custom prompt directories from project config
+    for dir in &project_config.prompts.include {
+        let resolved = if dir.is_absolute() {
+            dir.clone()
+        } else {
+            config.repo_path().join(dir)
+        };
+        if resolved.is_dir() {
+            pm.load_dir(&resolved)?;
+            debug!(dir = %resolved.display(), "loaded custom prompt directory");
+        }
+    }
+
+    Ok(pm)
+}
+
+/// Check that agent overrides and pipeline stages only reference agents and
+/// templates that exist, so typos fail at startup rather than mid-run.
+fn check_agent_references(
+    pm: &gba_pm::PromptManager,
+    project_config: &ProjectConfig,
+) -> Result<(), CoreError> {
+    let agents = pm.list_agents();
+    let unknown_agent = |key: &str, name: &str| {
+        CoreError::Config(format!(
+            "{key}: unknown agent {name:?} (available: {})",
+            agents.join(", ")
+        ))
+    };
+
+    if let Some(name) = project_config
+        .agents
+        .keys()
+        .find(|name| !agents.contains(&name.as_str()))
+    {
+        return Err(unknown_agent(&format!("agents.{name}"), name));
+    }
+
+    if let Some(name) = project_config
+        .tool_policy
+        .allowed_tools
+        .keys()
+        .find(|name| !agents.contains(&name.as_str()))
+    {
+        return Err(unknown_agent(
+            &format!("toolPolicy.allowedTools.{name}"),
+            name,
+        ));
+    }
+
+    let templates = pm.list_templates();
+    for (i, reviewer) in project_config.review.reviewers.iter().enumerate() {
+        let key = format!("review.reviewers[{i}]");
+        if !agents.contains(&reviewer.agent()) {
+            return Err(unknown_agent(&key, reviewer.agent()));
+        }
+        let system_template = reviewer.system_template();
+        if !templates.contains(&system_template.as_str()) {
+            return Err(CoreError::Config(format!(
+                "{key}: template {system_template:?} not found for reviewer {:?}",
+                reviewer.name
+            )));
+        }
+    }
+
+    for stage in &project_config.pipeline.stages {
+        let key = format!("pipeline.stages.{}", stage.name());
+        if !agents.contains(&stage.agent()) {
+            return Err(unknown_agent(&key, stage.agent()));
+        }
+        let mut required: Vec<String> = stage.template().into_iter().collect();
+        if matches!(stage.kind, StageKind::Review | StageKind::Verification) {
+            required.push(stage.fix_template().to_owned());
+        }
+        if let Some(missing) = required.iter().find(|t| !templates.contains(&t.as_str())) {
+            return Err(CoreError::Config(format!(
+                "{key}: template {missing:?} not found"
+            )));
+        }
+    }
+
+    Ok(())
+}
+
 #[cfg(test)]
 mod tests {
     use std::path::PathBuf;
 
     use super::*;
+    use crate::config::{ReviewerConfig, StageConfig};
 
     #[test]
     fn test_should_create_agent_runner_with_defaults() {
@@ -279,6 +723,7 @@ mod tests {
 
         let runner = runner.expect("runner should be ok");
         assert!(runner.model.is_none());
+        assert!(runner.cli_model.is_none());
         assert!(runner.max_tokens.is_none());
         assert_eq!(runner.permission_mode, PermissionMode::Auto);
     }
@@ -296,7 +741,10 @@ mod tests {
             AgentRunner::new(&engine_config, &project_config).expect("should create runner");
 
         // CLI override takes precedence
-        assert_eq!(runner.model.as_deref(), Some("cli-model"));
+        let options = runner
+            .build_options("init", &serde_json::json!({"repo_path": "/r"}), None)
+            .expect("should build options");
+        assert_eq!(options.model.as_deref(), Some("cli-model"));
     }
 
     #[test]
@@ -356,4 +804,302 @@ mod tests {
         let templates = runner.prompt_manager().list_templates();
         assert!(!templates.is_empty(), "should have templates available");
     }
+
+    #[test]
+    fn test_should_load_project_agents_from_gba_dir() {
+        let dir = tempfile::TempDir::new().expect("should create temp dir");
+        let agent_dir = dir.path().join(".gba").join("agents").join("docs");
+        std::fs::create_dir_all(&agent_dir).expect("should create agent dir");
+        std::fs::write(agent_dir.join("config.yml"), "preset: false\nmaxTurns: 3\n")
+            .expect("should write config");
+        std::fs::write(
+            agent_dir.join("system.md.j2"),
+            "Write docs for {{ repo_path }}",
+        )
+        .expect("should write template");
+
+        let engine_config = EngineConfig::builder()
+            .repo_path(dir.path().to_path_buf())
+            .build();
+        let runner = AgentRunner::new(&engine_config, &ProjectConfig::default())
+            .expect("should create runner");
+
+        assert!(runner.agents().contains(&"docs"));
+        let options = runner
+            .build_options("docs", &serde_json::json!({"repo_path": "/r"}), None)
+            .expect("should build options for project agent");
+        assert_eq!(options.max_turns, Some(3));
+        assert!(matches!(
+            options.system_prompt,
+            Some(SystemPrompt::Text(ref text)) if text == "Write docs for /r"
+        ));
+    }
+
+    #[test]
+    fn test_should_reject_override_for_unknown_agent() {
+        let engine_config = EngineConfig::builder()
+            .repo_path(PathBuf::from("/tmp/test"))
+            .build();
+        let mut project_config = ProjectConfig::default();
+        project_config
+            .agents
+            .insert("reveiw".to_owned(), AgentOverride::default());
+
+        let result = AgentRunner::new(&engine_config, &project_config);
+        assert!(matches!(result, Err(CoreError::Config(_))));
+    }
+
+    #[test]
+    fn test_should_reject_pipeline_stage_with_unknown_agent_or_template() {
+        let engine_config = EngineConfig::builder()
+            .repo_path(PathBuf::from("/tmp/test"))
+            .build();
+
+        let mut project_config = ProjectConfig::default();
+        let mut stage = StageConfig::new(StageKind::Agent);
+        stage.agent = Some("docs".to_owned());
+        project_config.pipeline.stages.push(stage);
+        let result = AgentRunner::new(&engine_config, &project_config);
+        assert!(
+            matches!(result, Err(CoreError::Config(ref msg)) if msg.contains("unknown agent")),
+            "got: {result:?}"
+        );
+
+        let mut project_config = ProjectConfig::default();
+        let mut stage = StageConfig::new(StageKind::Review);
+        stage.name = Some("final-review".to_owned());
+        stage.fix_template = Some("review/missing".to_owned());
+        project_config.pipeline.stages.push(stage);
+        let result = AgentRunner::new(&engine_config, &project_config);
+        assert!(
+            matches!(result, Err(CoreError::Config(ref msg)) if msg.contains("review/missing")),
+            "got: {result:?}"
+        );
+    }
+
+    #[test]
+    fn test_should_reject_templates_that_break_their_contracts() {
+        let dir = tempfile::TempDir::new().expect("should create temp dir");
+        let code_dir = dir.path().join(".gba").join("agents").join("code");
+        std::fs::create_dir_all(&code_dir).expect("should create agent dir");
+        std::fs::write(
+            code_dir.join("task.md.j2"),
+            "Implement {{ phase.name }} for {{ feature_slgu }}",
+        )
+        .expect("should write template");
+
+        let engine_config = EngineConfig::builder()
+            .repo_path(dir.path().to_path_buf())
+            .build();
+        let result = AgentRunner::new(&engine_config, &ProjectConfig::default());
+        assert!(
+            matches!(result, Err(CoreError::Config(ref msg)) if msg.contains("`feature_slgu`")),
+            "got: {result:?}"
+        );
+    }
+
+    #[test]
+    fn test_should_check_reviewer_personas() {
+        let engine_config = EngineConfig::builder()
+            .repo_path(PathBuf::from("/tmp/test"))
+            .build();
+        let reviewer = |name: &str| ReviewerConfig {
+            name: name.to_owned(),
+            agent: None,
+            system_template: None,
+        };
+
+        let mut project_config = ProjectConfig::default();
+        project_config.review.reviewers = vec![reviewer("security"), reviewer("performance")];
+        let runner =
+            AgentRunner::new(&engine_config, &project_config).expect("should create runner");
+        let context = serde_json::json!({"repo_path": "/tmp/test", "feature_slug": "f"});
+        let options = runner
+            .build_options_with_system("review", "review/personas/security", &context, None)
+            .expect("should build options");
+        assert!(matches!(
+            options.system_prompt,
+            Some(SystemPrompt::Text(ref text)) if text.starts_with("You are a security reviewer.")
+        ));
+
+        project_config.review.reviewers = vec![reviewer("accessibility")];
+        let result = AgentRunner::new(&engine_config, &project_config);
+        assert!(
+            matches!(result, Err(CoreError::Config(ref msg)) if msg.contains("review/personas/accessibility")),
+            "got: {result:?}"
+        );
+    }
+
+    #[test]
+    fn test_should_translate_permission_decisions_to_hook_output() {
+        let allow = serde_json::to_value(pre_tool_use_output(&PermissionDecision::AllowOnce))
+            .expect("should serialize");
+        assert_eq!(allow["hookSpecificOutput"]["hookEventName"], "PreToolUse");
+        assert_eq!(allow["hookSpecificOutput"]["permissionDecision"], "allow");
+
+        let deny = serde_json::to_value(pre_tool_use_output(&PermissionDecision::Deny {
+            reason: "no network access".to_owned(),
+        }))
+        .expect("should serialize");
+        assert_eq!(deny["hookSpecificOutput"]["permissionDecision"], "deny");
+        assert_eq!(
+            deny["hookSpecificOutput"]["permissionDecisionReason"],
+            "no network access"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_should_deny_and_report_tool_calls_against_policy() {
+        use crate::config::ToolPolicyConfig;
+        use crate::events::RunEvent;
+        use crate::permission::PermissionBroker;
+
+        let engine_config = EngineConfig::builder()
+            .repo_path(PathBuf::from("/tmp/test"))
+            .build();
+        let mut project_config = ProjectConfig {
+            tool_policy: ToolPolicyConfig {
+                deny_commands: vec![r"\bcurl\b".to_owned()],
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let runner =
+            AgentRunner::new(&engine_config, &project_config).expect("should create runner");
+        let options = runner
+            .build_agent_options(
+                "plan",
+                &serde_json::json!({"repo_path": "/tmp/test", "feature_slug": "f"}),
+                None,
+            )
+            .expect("should build options");
+        assert!(options.hooks.is_some(), "policy should install a hook");
+
+        let (event_tx, mut event_rx) = tokio::sync::mpsc::channel(8);
+        let (_response_tx, response_rx) = tokio::sync::mpsc::channel(8);
+        let broker = PermissionBroker::spawn(event_tx, response_rx);
+        let guard = ToolGuard {
+            policy: Arc::clone(&runner.policy),
+            agent: "code".to_owned(),
+            cwd: None,
+            approver: Some(ToolApprover::new(Arc::clone(&broker), "code", "Phase 1")),
+            ask: false,
+        };
+
+        let decision = guard
+            .decide("Bash", serde_json::json!({"command": "curl example.com"}))
+            .await;
+        assert!(matches!(decision, Some(PermissionDecision::Deny { .. })));
+        assert!(
+            guard
+                .decide("Bash", serde_json::json!({"command": "cargo test"}))
+                .await
+                .is_none()
+        );
+
+        let Some(RunEvent::ToolDenied(denial)) = event_rx.recv().await else {
+            panic!("expected a denial event");
+        };
+        assert_eq!(denial.agent, "code");
+        assert_eq!(denial.phase, "Phase 1");
+        assert_eq!(broker.take_denials(), vec![denial]);
+
+        project_config
+            .tool_policy
+            .allowed_tools
+            .insert("reveiw".to_owned(), vec!["Read".to_owned()]);
+        let result = AgentRunner::new(&engine_config, &project_config);
+        assert!(
+            matches!(result, Err(CoreError::Config(ref msg)) if msg.contains("toolPolicy.allowedTools.reveiw")),
+            "got: {result:?}"
+        );
+    }
+
+    #[test]
+    fn test_should_apply_per_agent_overrides() {
+        let engine_config = EngineConfig::builder()
+            .repo_path(PathBuf::from("/tmp/test"))
+            .build();
+        let mut project_config = ProjectConfig::default();
+        project_config.agent.model = Some("default-model".to_owned());
+        project_config.agents.insert(
+            "review".to_owned(),
+            AgentOverride {
+                model: Some("cheap-model".to_owned()),
+                max_turns: Some(5),
+                env: BTreeMap::from([("RUST_LOG".to_owned(), "debug".to_owned())]),
+                add_dirs: vec![PathBuf::from("docs"), PathBuf::from("/opt/shared")],
+                permission_mode: Some(PermissionMode::None),
+            },
+        );
+        let runner =
+            AgentRunner::new(&engine_config, &project_config).expect("should create runner");
+        let context = serde_json::json!({
+            "repo_path": "/tmp/test",
+            "feature_slug": "f",
+            "design_spec": "",
+        });
+
+        let review = runner
+            .build_options("review", &context, None)
+            .expect("should build review options");
+        assert_eq!(review.model.as_deref(), Some("cheap-model"));
+        assert_eq!(review.max_turns, Some(5));
+        assert_eq!(
+            review.env.get("RUST_LOG").map(String::as_str),
+            Some("debug")
+        );
+        assert_eq!(
+            review.add_dirs,
+            vec![
+                PathBuf::from("/tmp/test/docs"),
+                PathBuf::from("/opt/shared")
+            ]
+        );
+        assert!(matches!(
+            review.permission_mode,
+            Some(SdkPermissionMode::Plan)
+        ));
+
+        // Agents without an override keep the engine-wide defaults
+        let code = runner
+            .build_options("code", &context, None)
+            .expect("should build code options");
+        assert_eq!(code.model.as_deref(), Some("default-model"));
+        assert_eq!(code.max_turns, None);
+        assert!(code.env.is_empty());
+        assert!(matches!(
+            code.permission_mode,
+            Some(SdkPermissionMode::BypassPermissions)
+        ));
+    }
+
+    #[test]
+    fn test_should_prefer_cli_flags_over_per_agent_overrides() {
+        let engine_config = EngineConfig::builder()
+            .repo_path(PathBuf::from("/tmp/test"))
+            .model("cli-model")
+            .permission_mode(PermissionMode::Manual)
+            .build();
+        let mut project_config = ProjectConfig::default();
+        project_config.agents.insert(
+            "review".to_owned(),
+            AgentOverride {
+                model: Some("cheap-model".to_owned()),
+                permission_mode: Some(PermissionMode::None),
+                ..AgentOverride::default()
+            },
+        );
+        let runner =
+            AgentRunner::new(&engine_config, &project_config).expect("should create runner");
+
+        let options = runner
+            .build_options("review", &serde_json::json!({"repo_path": "/r"}), None)
+            .expect("should build options");
+        assert_eq!(options.model.as_deref(), Some("cli-model"));
+        assert!(matches!(
+            options.permission_mode,
+            Some(SdkPermissionMode::Default)
+        ));
+    }
 }
@@ -0,0 +1,788 @@
+//! Agent session transcripts of runs (internal).
+//!
+//! Every agent session of a run -- the rendered prompts, each SDK message
+//! as it arrives (assistant text, tool calls and results, the result
+//! message) and any error -- is appended to
+//! `.gba/features/<slug>/transcripts/<stage>-<n>.jsonl`. The
+//! `transcripts/index.yaml` file links each transcript to its stage, phase,
+//! iteration and the commit its changes ended up in.
+//!
+//! Archived sessions are read back as [`SessionItem`]s: prompts, agent
+//! text, tool calls paired with their results, the result message and
+//! errors, each with the time it was recorded.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, PoisonError};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use claude_agent_sdk_rs::{ContentBlock, Message, ToolResultContent, UserMessage};
+use serde::{Deserialize, Serialize};
+use tracing::{debug, instrument, warn};
+
+use crate::error::CoreError;
+use crate::jsonl;
+
+/// Directory of the transcripts inside the feature directory.
+const TRANSCRIPTS_DIR: &str = "transcripts";
+
+/// File name of the transcript index inside the transcripts directory.
+const INDEX_FILE: &str = "index.yaml";
+
+/// Where in a run an agent session takes place.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct SessionScope {
+    /// Pipeline stage name.
+    pub(crate) stage: String,
+    /// Zero-based index and name of the phase, for sessions of the phases
+    /// stage.
+    pub(crate) phase: Option<(usize, String)>,
+    /// One-based review or verification iteration; 1 for other stages.
+    pub(crate) iteration: u32,
+}
+
+impl SessionScope {
+    /// Scope of a session of `stage` outside any phase.
+    pub(crate) fn stage(stage: &str) -> Self {
+        Self {
+            stage: stage.to_owned(),
+            phase: None,
+            iteration: 1,
+        }
+    }
+
+    /// Scope of a session working on phase `index` of `stage`.
+    pub(crate) fn phase(stage: &str, index: usize, name: &str) -> Self {
+        Self {
+            phase: Some((index, name.to_owned())),
+            ..Self::stage(stage)
+        }
+    }
+
+    /// The same scope in iteration `iteration`.
+    pub(crate) fn with_iteration(self, iteration: u32) -> Self {
+        Self { iteration, ..self }
+    }
+
+    /// Phase name, or the stage name outside phases; shown to the user
+    /// alongside the agent name.
+    pub(crate) fn label(&self) -> &str {
+        self.phase.as_ref().map_or(&self.stage, |(_, name)| name)
+    }
+}
+
+/// Index of the transcripts of a feature, persisted as `index.yaml`.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct TranscriptIndex {
+    /// Sessions in the order they started, across all runs.
+    #[serde(default)]
+    pub(crate) sessions: Vec<SessionRecord>,
+}
+
+/// Index entry of one agent session.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SessionRecord {
+    /// Transcript file name, e.g. `phases-1.jsonl`.
+    pub file: String,
+    /// Pipeline stage name.
+    pub stage: String,
+    /// Agent that ran the session.
+    pub agent: String,
+    /// System prompt template.
+    pub system: String,
+    /// Task prompt template.
+    pub template: String,
+    /// Phase name, for sessions of the phases stage.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub phase: Option<String>,
+    /// Zero-based phase index, for sessions of the phases stage.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub phase_index: Option<usize>,
+    /// One-based review or verification iteration; 1 for other stages.
+    pub iteration: u32,
+    /// When the session started, in seconds since the Unix epoch.
+    pub started_at: u64,
+    /// Commit containing the session's changes, if any were committed.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub commit: Option<String>,
+}
+
+impl SessionRecord {
+    fn in_scope(&self, scope: &SessionScope) -> bool {
+        self.stage == scope.stage
+            && self.phase_index == scope.phase.as_ref().map(|(index, _)| *index)
+            && self.iteration == scope.iteration
+    }
+}
+
+/// One line of a session transcript.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub(crate) struct SessionLine {
+    /// When the entry was recorded, in milliseconds since the Unix epoch.
+    pub(crate) at: u64,
+    /// The recorded entry.
+    #[serde(flatten)]
+    pub(crate) entry: SessionEntry,
+}
+
+/// Entry of a session transcript.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "camelCase")]
+pub(crate) enum SessionEntry {
+    /// The rendered prompts the session started with.
+    Prompt {
+        /// System prompt text, or the text appended to a preset.
+        system: String,
+        /// Task prompt.
+        task: String,
+    },
+    /// A message received from the agent.
+    Message {
+        /// SDK message.
+        message: Message,
+    },
+    /// The session failed.
+    Error {
+        /// Error message.
+        message: String,
+    },
+}
+
+/// Transcripts of a feature's agent sessions.
+///
+/// Shared by all sessions of a run, including concurrent reviewers. Index
+/// write failures are logged rather than returned, like transcript writes
+/// (see [`jsonl::append()`]).
+#[derive(Debug)]
+pub(crate) struct TranscriptArchive {
+    /// Transcripts directory.
+    dir: PathBuf,
+    /// Index of all sessions.
+    index: Mutex<TranscriptIndex>,
+    /// Position of the first session of this run in the index.
+    run_start: usize,
+}
+
+impl TranscriptArchive {
+    /// Open the archive in `feature_dir`, continuing an existing index.
+    ///
+    /// An index that cannot be read is moved aside to `index.yaml.broken`
+    /// and a new one is started; existing transcripts are never reused.
+    ///
+    /// # Errors
+    ///
+    /// Returns `CoreError::Io` if a broken index cannot be moved aside.
+    pub(crate) fn open(feature_dir: &Path) -> Result<Self, CoreError> {
+        let dir = feature_dir.join(TRANSCRIPTS_DIR);
+        let index = match load_index(&dir) {
+            Ok(index) => index,
+            Err(e) => {
+                let path = dir.join(INDEX_FILE);
+                let broken = dir.join(format!("{INDEX_FILE}.broken"));
+                warn!(
+                    path = %path.display(),
+                    moved_to = %broken.display(),
+                    error = %e,
+                    "failed to read transcript index, starting a new one"
+                );
+                fs::rename(&path, &broken)?;
+                TranscriptIndex::default()
+            }
+        };
+        let run_start = index.sessions.len();
+        Ok(Self {
+            dir,
+            index: Mutex::new(index),
+            run_start,
+        })
+    }
+
+    /// Allocate the transcript of a new session and add it to the index.
+    ///
+    /// Sessions are numbered per stage across runs: `phases-1.jsonl`,
+    /// `phases-2.jsonl`, `review-1.jsonl`, ... Numbers whose transcript
+    /// exists without an index entry are skipped.
+    pub(crate) fn open_session(
+        &self,
+        scope: &SessionScope,
+        agent: &str,
+        system_template: &str,
+        task_template: &str,
+    ) -> Arc<SessionTranscript> {
+        let mut index = self.index.lock().unwrap_or_else(PoisonError::into_inner);
+        let stage = file_stem(&scope.stage);
+        let mut number = index
+            .sessions
+            .iter()
+            .filter(|s| file_stem(&s.stage) == stage)
+            .count()
+            + 1;
+        while self.dir.join(format!("{stage}-{number}.jsonl")).exists() {
+            number += 1;
+        }
+        let record = SessionRecord {
+            file: format!("{stage}-{number}.jsonl"),
+            stage: scope.stage.clone(),
+            agent: agent.to_owned(),
+            system: system_template.to_owned(),
+            template: task_template.to_owned(),
+            phase: scope.phase.as_ref().map(|(_, name)| name.clone()),
+            phase_index: scope.phase.as_ref().map(|(index, _)| *index),
+            iteration: scope.iteration,
+            started_at: now_millis() / 1_000,
+            commit: None,
+        };
+        let path = self.dir.join(&record.file);
+        index.sessions.push(record);
+        self.save(&index);
+        Arc::new(SessionTranscript { path })
+    }
+
+    /// Link the sessions of this run in `scope` that have no commit yet to
+    /// `commit`.
+    pub(crate) fn link_commit(&self, scope: &SessionScope, commit: &str) {
+        let mut index = self.index.lock().unwrap_or_else(PoisonError::into_inner);
+        let mut linked = false;
+        for record in &mut index.sessions[self.run_start..] {
+            if record.commit.is_none() && record.in_scope(scope) {
+                record.commit = Some(commit.to_owned());
+                linked = true;
+            }
+        }
+        if linked {
+            self.save(&index);
+        }
+    }
+
+    fn save(&self, index: &TranscriptIndex) {
+        let path = self.dir.join(INDEX_FILE);
+        let result = fs::create_dir_all(&self.dir)
+            .map_err(CoreError::from)
+            .and_then(|()| Ok(fs::write(&path, serde_yaml::to_string(index)?)?));
+        match result {
+            Ok(()) => {
+                debug!(path = %path.display(), sessions = index.sessions.len(), "saved transcript index")
+            }
+            Err(e) => warn!(path = %path.display(), error = %e, "failed to write transcript index"),
+        }
+    }
+}
+
+/// Append-only transcript of one agent session.
+#[derive(Debug)]
+pub(crate) struct SessionTranscript {
+    /// Path of the JSON Lines transcript.
+    path: PathBuf,
+}
+
+impl SessionTranscript {
+    /// Append an entry stamped with the current time.
+    pub(crate) fn record(&self, entry: SessionEntry) {
+        let line = SessionLine {
+            at: now_millis(),
+            entry,
+        };
+        jsonl::append(&self.path, &line);
+    }
+}
+
+/// Read the transcript index in `dir`, or an empty index if there is none.
+///
+/// # Errors
+///
+/// Returns `CoreError::Io` if the file cannot be read.
+/// Returns `CoreError::Yaml` if the file cannot be parsed.
+pub(crate) fn load_index(dir: &Path) -> Result<TranscriptIndex, CoreError> {
+    let path = dir.join(INDEX_FILE);
+    if !path.exists() {
+        return Ok(TranscriptIndex::default());
+    }
+    Ok(serde_yaml::from_str(&fs::read_to_string(&path)?)?)
+}
+
+// ── Reading ──────────────────────────────────────────────────
+
+/// One step of an archived agent session.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(
+    tag = "type",
+    content = "data",
+    rename_all = "camelCase",
+    rename_all_fields = "camelCase"
+)]
+pub enum SessionItem {
+    /// The rendered prompts the session started with.
+    Prompt {
+        /// Time in milliseconds since the Unix epoch.
+        at: u64,
+        /// System prompt text, or the text appended to a preset.
+        system: String,
+        /// Task prompt.
+        task: String,
+    },
+    /// Text written by the agent.
+    Text {
+        /// Time in milliseconds since the Unix epoch.
+        at: u64,
+        /// Agent text.
+        text: String,
+    },
+    /// Extended thinking of the agent.
+    Thinking {
+        /// Time in milliseconds since the Unix epoch.
+        at: u64,
+        /// Thinking text.
+        text: String,
+    },
+    /// A tool call with its result.
+    ToolCall(ToolCall),
+    /// The session's result message.
+    Result {
+        /// Time in milliseconds since the Unix epoch.
+        at: u64,
+        /// Agent API round-trips.
+        turns: u32,
+        /// Session duration in milliseconds as reported by the agent.
+        duration_ms: u64,
+        /// Cost in USD, if reported.
+        cost_usd: Option<f64>,
+        /// Whether the session ended with an error.
+        is_error: bool,
+        /// Final result text.
+        text: Option<String>,
+    },
+    /// The session failed.
+    Error {
+        /// Time in milliseconds since the Unix epoch.
+        at: u64,
+        /// Error message.
+        message: String,
+    },
+}
+
+impl SessionItem {
+    /// When the item was recorded, in milliseconds since the Unix epoch;
+    /// for a tool call, when it was made.
+    pub fn at(&self) -> u64 {
+        match self {
+            Self::Prompt { at, .. }
+            | Self::Text { at, .. }
+            | Self::Thinking { at, .. }
+            | Self::Result { at, .. }
+            | Self::Error { at, .. } => *at,
+            Self::ToolCall(call) => call.started_at,
+        }
+    }
+}
+
+/// A tool call made by an agent.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ToolCall {
+    /// Tool use id.
+    pub id: String,
+    /// Tool name.
+    pub name: String,
+    /// Tool input.
+    pub input: serde_json::Value,
+    /// Result text, or `None` if no result was recorded.
+    pub output: Option<String>,
+    /// Whether the tool reported an error.
+    pub is_error: bool,
+    /// When the call was made, in milliseconds since the Unix epoch.
+    pub started_at: u64,
+    /// When its result arrived, in milliseconds since the Unix epoch.
+    pub finished_at: Option<u64>,
+}
+
+/// Read the index of a feature's archived sessions.
+///
+/// Returns no sessions if the feature has not been run since sessions
+/// were archived.
+///
+/// # Errors
+///
+/// Returns `CoreError::FeatureNotFound` if the feature directory does not exist.
+/// Returns `CoreError::Io` if the index cannot be read.
+/// Returns `CoreError::Yaml` if the index cannot be parsed.
+#[instrument(skip(gba_dir))]
+pub(crate) fn load_sessions(gba_dir: &Path, slug: &str) -> Result<Vec<SessionRecord>, CoreError> {
+    let feature_dir = gba_dir.join("features").join(slug);
+    if !feature_dir.is_dir() {
+        return Err(CoreError::FeatureNotFound(slug.to_owned()));
+    }
+    Ok(load_index(&feature_dir.join(TRANSCRIPTS_DIR))?.sessions)
+}
+
+/// Read the transcript of an archived session (see [`jsonl::load()`]).
+///
+/// # Errors
+///
+/// Returns `CoreError::FeatureNotFound` if the transcript does not exist.
+/// Returns `CoreError::Io` if the transcript cannot be read.
+#[instrument(skip(gba_dir, record), fields(file = %record.file))]
+pub(crate) fn load_session(
+    gba_dir: &Path,
+    slug: &str,
+    record: &SessionRecord,
+) -> Result<Vec<SessionItem>, CoreError> {
+    let path = gba_dir
+        .join("features")
+        .join(slug)
+        .join(TRANSCRIPTS_DIR)
+        .join(&record.file);
+    if !path.is_file() {
+        return Err(CoreError::FeatureNotFound(format!(
+            "transcript {} not found for {slug}",
+            record.file
+        )));
+    }
+    Ok(session_items(jsonl::load(&path)?))
+}
+
+/// Turn transcript lines into session items, attaching each tool result to
+/// its call.
+fn session_items(lines: Vec<SessionLine>) -> Vec<SessionItem> {
+    let mut items = Vec::new();
+    for SessionLine { at, entry } in lines {
+        match entry {
+            SessionEntry::Prompt { system, task } => {
+                items.push(SessionItem::Prompt { at, system, task });
+            }
+            SessionEntry::Error { message } => items.push(SessionItem::Error { at, message }),
+            SessionEntry::Message { message } => match message {
+                Message::Assistant(assistant) => {
+                    for block in assistant.message.content {
+                        match block {
+                            ContentBlock::Text(text) if !text.text.trim().is_empty() => {
+                                items.push(SessionItem::Text {
+                                    at,
+                                    text: text.text,
+                                });
+                            }
+                            ContentBlock::Thinking(thinking) => {
+                                items.push(SessionItem::Thinking {
+                                    at,
+                                    text: thinking.thinking,
+                                });
+                            }
+                            ContentBlock::ToolUse(tool) => {
+                                items.push(SessionItem::ToolCall(ToolCall {
+                                    id: tool.id,
+                                    name: tool.name,
+                                    input: tool.input,
+                                    output: None,
+                                    is_error: false,
+                                    started_at: at,
+                                    finished_at: None,
+                                }));
+                            }
+                            _ => {}
+                        }
+                    }
+                }
+                Message::User(user) => {
+                    for block in user_blocks(user) {
+                        let ContentBlock::ToolResult(result) = block else {
+                            continue;
+                        };
+                        let call = items.iter_mut().rev().find_map(|item| match item {
+                            SessionItem::ToolCall(call) if call.id == result.tool_use_id => {
+                                Some(call)
+                            }
+                            _ => None,
+                        });
+                        if let Some(call) = call {
+                            call.output = Some(result.content.map(result_text).unwrap_or_default());
+                            call.is_error = result.is_error.unwrap_or(false);
+                            call.finished_at = Some(at);
+                        }
+                    }
+                }
+                Message::Result(result) => items.push(SessionItem::Result {
+                    at,
+                    turns: result.num_turns,
+                    duration_ms: result.duration_ms,
+                    cost_usd: result.total_cost_usd,
+                    is_error: result.is_error,
+                    text: result.result,
+                }),
+                _ => {}
+            },
+        }
+    }
+    items
+}
+
+/// Content blocks of a user message. The CLI sends tool results as
+/// `{"message": {"content": [...]}}`, which the SDK keeps in `extra`.
+fn user_blocks(user: UserMessage) -> Vec<ContentBlock> {
+    if let Some(content) = user.content {
+        return content;
+    }
+    user.extra
+        .get("message")
+        .and_then(|message| message.get("content"))
+        .and_then(|content| serde_json::from_value(content.clone()).ok())
+        .unwrap_or_default()
+}
+
+/// Text of a tool result.
+fn result_text(content: ToolResultContent) -> String {
+    match content {
+        ToolResultContent::Text(text) => text,
+        ToolResultContent::Blocks(blocks) => blocks
+            .iter()
+            .filter_map(|block| block.get("text").and_then(serde_json::Value::as_str))
+            .collect::<Vec<_>>()
+            .join("\n"),
+    }
+}
+
+/// Stage name usable in a file name.
+fn file_stem(stage: &str) -> String {
+    stage
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '-' || c == '_' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect()
+}
+
+fn now_millis() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_should_number_sessions_per_stage_across_runs() {
+        let feature_dir = tempfile::TempDir::new().expect("should create temp dir");
+        let archive = TranscriptArchive::open(feature_dir.path()).expect("should open archive");
+        let setup = SessionScope::phase("phases", 0, "Setup");
+
+        archive.open_session(&setup, "code", "code/system", "code/task");
+        archive.open_session(&setup, "code", "code/system", "code/hook_fix");
+        archive.open_session(
+            &SessionScope::stage("security review"),
+            "review",
+            "review/system",
+            "review/task",
+        );
+
+        let archive = TranscriptArchive::open(feature_dir.path()).expect("should open archive");
+        archive.open_session(
+            &SessionScope::phase("phases", 1, "API"),
+            "code",
+            "code/system",
+            "code/resume",
+        );
+
+        let index =
+            load_index(&feature_dir.path().join(TRANSCRIPTS_DIR)).expect("should load index");
+        let files: Vec<&str> = index.sessions.iter().map(|s| s.file.as_str()).collect();
+        assert_eq!(
+            files,
+            [
+                "phases-1.jsonl",
+                "phases-2.jsonl",
+                "security_review-1.jsonl",
+                "phases-3.jsonl"
+            ]
+        );
+        assert_eq!(index.sessions[0].phase.as_deref(), Some("Setup"));
+        assert_eq!(index.sessions[3].phase_index, Some(1));
+        assert_eq!(index.sessions[2].phase, None);
+    }
+
+    #[test]
+    fn test_should_move_broken_index_aside_without_reusing_transcripts() {
+        let feature_dir = tempfile::TempDir::new().expect("should create temp dir");
+        let dir = feature_dir.path().join(TRANSCRIPTS_DIR);
+        let setup = SessionScope::phase("phases", 0, "Setup");
+        TranscriptArchive::open(feature_dir.path())
+            .expect("should open archive")
+            .open_session(&setup, "code", "code/system", "code/task")
+            .record(SessionEntry::Error {
+                message: "earlier run".to_owned(),
+            });
+        fs::write(dir.join(INDEX_FILE), "sessions: [").expect("should break index");
+
+        let archive = TranscriptArchive::open(feature_dir.path()).expect("should open archive");
+        archive.open_session(&setup, "code", "code/system", "code/task");
+
+        assert_eq!(
+            fs::read_to_string(dir.join("index.yaml.broken")).expect("should keep broken index"),
+            "sessions: ["
+        );
+        let index = load_index(&dir).expect("should load index");
+        assert_eq!(index.sessions.len(), 1);
+        assert_eq!(index.sessions[0].file, "phases-2.jsonl");
+    }
+
+    #[test]
+    fn test_should_link_commits_to_sessions_of_this_run_in_scope() {
+        let feature_dir = tempfile::TempDir::new().expect("should create temp dir");
+        let setup = SessionScope::phase("phases", 0, "Setup");
+        // Left over from an earlier, failed run
+        TranscriptArchive::open(feature_dir.path())
+            .expect("should open archive")
+            .open_session(&setup, "code", "code/system", "code/task");
+
+        let archive = TranscriptArchive::open(feature_dir.path()).expect("should open archive");
+        archive.open_session(&setup, "code", "code/system", "code/task");
+        let review = SessionScope::stage("review");
+        archive.open_session(&review, "review", "review/system", "review/task");
+        archive.open_session(&review, "code", "code/system", "code/review_fix");
+        archive.open_session(
+            &review.clone().with_iteration(2),
+            "review",
+            "review/system",
+            "review/task",
+        );
+
+        archive.link_commit(&setup, "abc123");
+        archive.link_commit(&review, "def456");
+
+        let index =
+            load_index(&feature_dir.path().join(TRANSCRIPTS_DIR)).expect("should load index");
+        let commits: Vec<Option<&str>> =
+            index.sessions.iter().map(|s| s.commit.as_deref()).collect();
+        assert_eq!(
+            commits,
+            [None, Some("abc123"), Some("def456"), Some("def456"), None]
+        );
+    }
+
+    #[test]
+    fn test_should_read_session_items_with_tool_results() {
+        let gba_dir = tempfile::TempDir::new().expect("should create temp dir");
+        let feature_dir = gba_dir.path().join("features/login");
+        let archive = TranscriptArchive::open(&feature_dir).expect("should open archive");
+        let transcript = archive.open_session(
+            &SessionScope::phase("phases", 0, "Setup"),
+            "code",
+            "code/system",
+            "code/task",
+        );
+        let message = |value: serde_json::Value| SessionEntry::Message {
+            message: serde_json::from_value(value).expect("should parse message"),
+        };
+
+        transcript.record(SessionEntry::Prompt {
+            system: "You write code.".to_owned(),
+            task: "Add a login route.".to_owned(),
+        });
+        transcript.record(message(serde_json::json!({
+            "type": "assistant",
+            "message": {"content": [
+                {"type": "text", "text": "Running the tests"},
+                {"type": "tool_use", "id": "t1", "name": "Bash", "input": {"command": "cargo test"}}
+            ]}
+        })));
+        transcript.record(message(serde_json::json!({
+            "type": "user",
+            "message": {"role": "user", "content": [
+                {"type": "tool_result", "tool_use_id": "t1", "content": "test result: ok", "is_error": false}
+            ]}
+        })));
+        transcript.record(message(serde_json::json!({
+            "type": "result",
+            "subtype": "success",
+            "duration_ms": 1500,
+            "duration_api_ms": 1000,
+            "is_error": false,
+            "num_turns": 2,
+            "session_id": "s-1",
+            "result": "Done"
+        })));
+
+        let sessions = load_sessions(gba_dir.path(), "login").expect("should load sessions");
+        assert_eq!(sessions.len(), 1);
+        let items =
+            load_session(gba_dir.path(), "login", &sessions[0]).expect("should load session");
+
+        assert_eq!(items.len(), 4);
+        assert!(matches!(&items[1], SessionItem::Text { text, .. } if text == "Running the tests"));
+        let SessionItem::ToolCall(call) = &items[2] else {
+            panic!("expected tool call");
+        };
+        assert_eq!(call.name, "Bash");
+        assert_eq!(call.output.as_deref(), Some("test result: ok"));
+        assert!(call.finished_at.is_some_and(|end| end >= call.started_at));
+        assert!(matches!(items[3], SessionItem::Result { turns: 2, .. }));
+        assert!(matches!(
+            load_sessions(gba_dir.path(), "missing"),
+            Err(CoreError::FeatureNotFound(_))
+        ));
+    }
+
+    #[test]
+    fn test_should_append_timestamped_entries() {
+        let feature_dir = tempfile::TempDir::new().expect("should create temp dir");
+        let archive = TranscriptArchive::open(feature_dir.path()).expect("should open archive");
+        let transcript = archive.open_session(
+            &SessionScope::stage("verification"),
+            "verify",
+            "verify/system",
+            "verify/task",
+        );
+
+        transcript.record(SessionEntry::Prompt {
+            system: "You verify features.".to_owned(),
+            task: "Run the tests.".to_owned(),
+        });
+        transcript.record(SessionEntry::Message {
+            message: serde_json::from_value(serde_json::json!({
+                "type": "assistant",
+                "message": {"content": [
+                    {"type": "tool_use", "id": "t1", "name": "Bash", "input": {"command": "cargo test"}}
+                ]}
+            }))
+            .expect("should parse message"),
+        });
+        transcript.record(SessionEntry::Error {
+            message: "connection lost".to_owned(),
+        });
+
+        let content = fs::read_to_string(
+            feature_dir
+                .path()
+                .join(TRANSCRIPTS_DIR)
+                .join("verification-1.jsonl"),
+        )
+        .expect("should read transcript");
+        let lines: Vec<SessionLine> = content
+            .lines()
+            .map(|line| serde_json::from_str(line).expect("should parse line"))
+            .collect();
+        assert_eq!(lines.len(), 3);
+        assert!(lines[0].at > 0);
+        assert!(
+            matches!(&lines[0].entry, SessionEntry::Prompt { task, .. } if task == "Run the tests.")
+        );
+        assert!(matches!(
+            &lines[1].entry,
+            SessionEntry::Message {
+                message: Message::Assistant(_)
+            }
+        ));
+        assert!(
+            content
+                .lines()
+                .nth(2)
+                .is_some_and(|l| l.contains(r#""kind":"error""#))
+        );
+    }
+}
@@ -2,11 +2,15 @@
 //!
 //! This module defines [`EngineConfig`] (CLI-level overrides), [`ProjectConfig`]
 //! (from `.gba/config.yaml`), and all sub-configuration types. During engine
-//! initialization, CLI flags in `EngineConfig` take precedence over values read
-//! from `ProjectConfig`.
+//! initialization, the project config is resolved from several layers (see
+//! [`ResolvedConfig`](crate::ResolvedConfig)), with CLI flags in `EngineConfig`
+//! taking precedence over every file and environment layer.
 
+use std::collections::BTreeMap;
 use std::path::PathBuf;
 
+pub use gba_pm::PermissionMode;
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use typed_builder::TypedBuilder;
 
@@ -14,9 +18,9 @@ use typed_builder::TypedBuilder;
 
 /// Engine configuration provided by the CLI layer.
 ///
-/// Contains repository path and optional overrides for model and token limits.
-/// When the engine starts, these values are merged with [`ProjectConfig`] from
-/// `.gba/config.yaml`, with `EngineConfig` values taking precedence.
+/// Contains repository path and optional overrides for model, token limits
+/// and permission mode. When the engine starts, these values are merged with
+/// the layered [`ProjectConfig`], with `EngineConfig` values taking precedence.
 ///
 /// # Examples
 ///
@@ -35,14 +39,19 @@ pub struct EngineConfig {
     repo_path: PathBuf,
 
     /// Override Claude model (takes precedence over config.yaml).
-    #[builder(default, setter(strip_option, into))]
+    #[builder(default, setter(strip_option(fallback = model_opt), into))]
     #[serde(skip_serializing_if = "Option::is_none")]
     model: Option<String>,
 
     /// Override max tokens per agent response (takes precedence over config.yaml).
-    #[builder(default, setter(strip_option))]
+    #[builder(default, setter(strip_option(fallback = max_tokens_opt)))]
     #[serde(skip_serializing_if = "Option::is_none")]
     max_tokens: Option<u32>,
+
+    /// Override the permission mode (takes precedence over config.yaml).
+    #[builder(default, setter(strip_option(fallback = permission_mode_opt)))]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    permission_mode: Option<PermissionMode>,
 }
 
 impl EngineConfig {
@@ -61,6 +70,11 @@ impl EngineConfig {
         self.max_tokens
     }
 
+    /// Returns the permission mode override, if set.
+    pub fn permission_mode(&self) -> Option<&PermissionMode> {
+        self.permission_mode.as_ref()
+    }
+
     /// Returns the `.gba` directory path for this repository.
     pub fn gba_dir(&self) -> PathBuf {
         self.repo_path.join(".gba")
@@ -75,6 +89,11 @@ impl EngineConfig {
     pub fn config_path(&self) -> PathBuf {
         self.gba_dir().join("config.yaml")
     }
+
+    /// Returns the path to the uncommitted `config.local.yaml` override file.
+    pub fn local_config_path(&self) -> PathBuf {
+        self.gba_dir().join("config.local.yaml")
+    }
 }
 
 // ── Project Configuration (.gba/config.yaml) ────────────────
@@ -82,18 +101,27 @@ impl EngineConfig {
 /// Project-level GBA configuration, deserialized from `.gba/config.yaml`.
 ///
 /// All fields have serde defaults so that missing keys in the YAML file
-/// produce valid configuration with sensible defaults.
-#[derive(Debug, Clone, Default, Serialize, Deserialize)]
-#[serde(rename_all = "camelCase")]
+/// produce valid configuration with sensible defaults. The engine resolves
+/// the final value from user, project, local, environment and CLI layers.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
 pub struct ProjectConfig {
     /// Agent-level settings (model, tokens, permission mode).
     #[serde(default)]
     pub agent: AgentProjectConfig,
 
+    /// Per-agent overrides keyed by agent name (e.g. `code`, `review`).
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub agents: BTreeMap<String, AgentOverride>,
+
     /// Prompt template search paths.
     #[serde(default)]
     pub prompts: PromptsConfig,
 
+    /// Planning settings.
+    #[serde(default)]
+    pub plan: PlanConfig,
+
     /// Git workflow settings (branching, auto-commit).
     #[serde(default)]
     pub git: GitConfig,
@@ -109,6 +137,14 @@ pub struct ProjectConfig {
     /// Precommit hook settings.
     #[serde(default)]
     pub hooks: HooksConfig,
+
+    /// Run pipeline stages.
+    #[serde(default)]
+    pub pipeline: PipelineConfig,
+
+    /// Rules for the tools agents may use.
+    #[serde(default)]
+    pub tool_policy: ToolPolicyConfig,
 }
 
 // ── Sub-configuration types ──────────────────────────────────
@@ -117,10 +153,10 @@ pub struct ProjectConfig {
 ///
 /// Controls which model, token limit, and permission mode the engine uses
 /// by default. CLI overrides in [`EngineConfig`] take precedence.
-#[derive(Debug, Clone, Default, Serialize, Deserialize)]
-#[serde(rename_all = "camelCase")]
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
 pub struct AgentProjectConfig {
-    /// Claude model to use.
+    /// Claude model to use for agents without their own model.
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub model: Option<String>,
 
@@ -133,20 +169,33 @@ pub struct AgentProjectConfig {
     pub permission_mode: PermissionMode,
 }
 
-/// Permission mode for agent tool invocations.
+/// Per-agent settings from the project config file.
 ///
-/// Controls whether the agent runs tools automatically or requires
-/// user approval for each invocation.
-#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
-#[serde(rename_all = "camelCase")]
-pub enum PermissionMode {
-    /// Agent runs tools without asking (default).
-    #[default]
-    Auto,
-    /// Agent asks the user before each tool invocation.
-    Manual,
-    /// Agent cannot use tools (prompt-only mode).
-    None,
+/// Overrides the values from the agent's built-in `config.yml`. Unset
+/// fields keep the agent's own value; `env` entries are merged over the
+/// agent's environment and `addDirs` are appended to its directories.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq, JsonSchema)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct AgentOverride {
+    /// Claude model for this agent.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub model: Option<String>,
+
+    /// Maximum number of conversation turns for this agent.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_turns: Option<u32>,
+
+    /// Additional environment variables for the agent process.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub env: BTreeMap<String, String>,
+
+    /// Additional directories the agent may read, relative to the repository.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub add_dirs: Vec<PathBuf>,
+
+    /// Permission mode for this agent's tool use.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub permission_mode: Option<PermissionMode>,
 }
 
 /// Prompt template configuration.
@@ -154,8 +203,8 @@ pub enum PermissionMode {
 /// Specifies additional directories to search for prompt template overrides.
 /// Templates found in these directories replace built-in templates with the
 /// same name.
-#[derive(Debug, Clone, Default, Serialize, Deserialize)]
-#[serde(rename_all = "camelCase")]
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
 pub struct PromptsConfig {
     /// Additional template directories to search (in order).
     #[serde(default)]
@@ -166,8 +215,8 @@ pub struct PromptsConfig {
 ///
 /// Controls branch naming, auto-commit behavior, and the base branch
 /// used when creating feature worktrees.
-#[derive(Debug, Clone, Serialize, Deserialize)]
-#[serde(rename_all = "camelCase")]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
 pub struct GitConfig {
     /// Automatically commit after each phase completes.
     #[serde(default = "default_true")]
@@ -192,12 +241,32 @@ impl Default for GitConfig {
     }
 }
 
+/// Planning configuration.
+///
+/// Controls how often the plan agent is asked to repair spec files that fail
+/// validation when a plan session completes.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct PlanConfig {
+    /// Maximum repair rounds before the session fails. `0` only validates.
+    #[serde(default = "default_max_repair_attempts")]
+    pub max_repair_attempts: u32,
+}
+
+impl Default for PlanConfig {
+    fn default() -> Self {
+        Self {
+            max_repair_attempts: default_max_repair_attempts(),
+        }
+    }
+}
+
 /// Code review configuration.
 ///
 /// Controls whether the code review step runs after all phases complete
 /// and how many review-fix iterations are allowed.
-#[derive(Debug, Clone, Serialize, Deserialize)]
-#[serde(rename_all = "camelCase")]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
 pub struct ReviewConfig {
     /// Enable the code review step.
     #[serde(default = "default_true")]
@@ -206,6 +275,11 @@ pub struct ReviewConfig {
     /// Maximum review-fix iterations before proceeding.
     #[serde(default = "default_max_iterations")]
     pub max_iterations: u32,
+
+    /// Reviewer personas run concurrently on the same diff. Empty means a
+    /// single reviewer using the stage's agent.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub reviewers: Vec<ReviewerConfig>,
 }
 
 impl Default for ReviewConfig {
@@ -213,6 +287,44 @@ impl Default for ReviewConfig {
         Self {
             enabled: true,
             max_iterations: default_max_iterations(),
+            reviewers: Vec::new(),
+        }
+    }
+}
+
+/// A reviewer persona.
+///
+/// Built-in personas are `security`, `performance`, `api_design` and
+/// `spec_compliance`. Other names need a `systemTemplate`, an `agent`, or a
+/// `review/personas/<name>.md.j2` template in a custom prompt directory.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, JsonSchema)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct ReviewerConfig {
+    /// Reviewer name, used to attribute issues.
+    pub name: String,
+
+    /// Agent that runs the review. Defaults to `review`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub agent: Option<String>,
+
+    /// System template. Defaults to `<agent>/system` when `agent` is set,
+    /// otherwise `review/personas/<name>`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub system_template: Option<String>,
+}
+
+impl ReviewerConfig {
+    /// Agent that runs the review.
+    pub fn agent(&self) -> &str {
+        self.agent.as_deref().unwrap_or("review")
+    }
+
+    /// System template for the reviewer.
+    pub fn system_template(&self) -> String {
+        match (&self.system_template, &self.agent) {
+            (Some(template), _) => template.clone(),
+            (None, Some(agent)) => format!("{agent}/system"),
+            (None, None) => format!("review/personas/{}", self.name),
         }
     }
 }
@@ -221,8 +333,8 @@ impl Default for ReviewConfig {
 ///
 /// Controls whether the verification step runs after code review
 /// and how many verify-fix iterations are allowed.
-#[derive(Debug, Clone, Serialize, Deserialize)]
-#[serde(rename_all = "camelCase")]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
 pub struct VerificationConfig {
     /// Enable the verification step.
     #[serde(default = "default_true")]
@@ -246,8 +358,8 @@ impl Default for VerificationConfig {
 ///
 /// Defines the hooks that run after each phase's code is written (before commit)
 /// and the maximum number of hook-fix-retry cycles per phase.
-#[derive(Debug, Clone, Serialize, Deserialize)]
-#[serde(rename_all = "camelCase")]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
 pub struct HooksConfig {
     /// Hooks to run before committing each phase.
     #[serde(default)]
@@ -256,6 +368,12 @@ pub struct HooksConfig {
     /// Maximum hook-fix-retry cycles per phase.
     #[serde(default = "default_max_retries")]
     pub max_retries: u32,
+
+    /// Reuse hooks from a pre-commit framework config (`.pre-commit-config.yaml`).
+    ///
+    /// When set, the framework hooks run in addition to `pre_commit`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pre_commit_framework: Option<PreCommitFrameworkConfig>,
 }
 
 impl Default for HooksConfig {
@@ -263,17 +381,60 @@ impl Default for HooksConfig {
         Self {
             pre_commit: Vec::new(),
             max_retries: default_max_retries(),
+            pre_commit_framework: None,
+        }
+    }
+}
+
+/// Settings for importing hooks from the pre-commit framework.
+///
+/// The config file path is resolved relative to the worktree root so that
+/// the agent's changes are checked against the same hooks a human commit
+/// would trigger.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct PreCommitFrameworkConfig {
+    /// How the framework hooks are executed.
+    #[serde(default)]
+    pub mode: PreCommitMode,
+
+    /// Path to the pre-commit config, relative to the worktree root.
+    #[serde(default = "default_pre_commit_config_path")]
+    pub config_path: PathBuf,
+}
+
+impl Default for PreCommitFrameworkConfig {
+    fn default() -> Self {
+        Self {
+            mode: PreCommitMode::default(),
+            config_path: default_pre_commit_config_path(),
         }
     }
 }
 
+/// Execution strategy for pre-commit framework hooks.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub enum PreCommitMode {
+    /// Run `pre-commit run --files <changed>` (requires the `pre-commit` binary).
+    #[default]
+    Run,
+    /// Translate `repo: local` hooks into plain [`Hook`] entries.
+    ///
+    /// Remote repository hooks need the framework's managed environments
+    /// and are skipped in this mode, as are `pygrep` and Docker hooks and
+    /// hooks filtering on file types other than `file`, `text`, `binary`
+    /// and common extension-based tags. `fail` hooks are emulated.
+    Translate,
+}
+
 /// A single precommit hook definition.
 ///
 /// Each hook is a named shell command executed in the worktree root.
 /// If the command exits with a non-zero status, the agent attempts to fix
 /// the issues and re-run the hook.
-#[derive(Debug, Clone, Serialize, Deserialize)]
-#[serde(rename_all = "camelCase")]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
 pub struct Hook {
     /// Human-readable hook name (e.g., "build", "fmt", "lint").
     pub name: String,
@@ -282,6 +443,194 @@ pub struct Hook {
     pub command: String,
 }
 
+/// Run pipeline configuration.
+///
+/// `gba run` executes the stages in order. The default pipeline is
+/// `phases`, `review`, `verification`, `pr`; a project can reorder, drop,
+/// repeat or add stages (for example a custom `docs` agent after review).
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct PipelineConfig {
+    /// Stages executed in order.
+    #[serde(default = "default_stages")]
+    pub stages: Vec<StageConfig>,
+}
+
+impl Default for PipelineConfig {
+    fn default() -> Self {
+        Self {
+            stages: default_stages(),
+        }
+    }
+}
+
+/// A single run pipeline stage.
+///
+/// Only `kind` is required. Built-in kinds use their usual agent and
+/// templates unless overridden; `agent` stages run the given agent with
+/// its `task` template in the worktree, then run hooks and commit.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, JsonSchema)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct StageConfig {
+    /// What the stage does.
+    pub kind: StageKind,
+
+    /// Display name, unique within the pipeline. Defaults to the agent
+    /// name for `agent` stages and to the kind otherwise.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+
+    /// Agent that runs the stage. Required for `agent` stages.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub agent: Option<String>,
+
+    /// Task template, e.g. `docs/task`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub template: Option<String>,
+
+    /// Template the code agent uses to fix findings (`review` and
+    /// `verification` stages).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fix_template: Option<String>,
+}
+
+impl StageConfig {
+    /// Create a stage of the given kind with default agent and templates.
+    pub fn new(kind: StageKind) -> Self {
+        Self {
+            kind,
+            name: None,
+            agent: None,
+            template: None,
+            fix_template: None,
+        }
+    }
+
+    /// Display name of the stage.
+    pub fn name(&self) -> &str {
+        match (&self.name, &self.agent, self.kind) {
+            (Some(name), _, _) => name,
+            (None, Some(agent), StageKind::Agent) => agent,
+            (None, _, kind) => kind.as_str(),
+        }
+    }
+
+    /// Agent that runs the stage.
+    pub fn agent(&self) -> &str {
+        self.agent.as_deref().unwrap_or(match self.kind {
+            StageKind::Phases | StageKind::Pr | StageKind::Agent => "code",
+            StageKind::Review => "review",
+            StageKind::Verification => "verify",
+        })
+    }
+
+    /// Task template of the stage, or `None` for the `phases` stage without
+    /// an override (which picks `code/task` or `code/resume` itself).
+    pub fn template(&self) -> Option<String> {
+        if let Some(template) = &self.template {
+            return Some(template.clone());
+        }
+        match self.kind {
+            StageKind::Phases => None,
+            StageKind::Review => Some("review/task".to_owned()),
+            StageKind::Verification => Some("verify/task".to_owned()),
+            StageKind::Pr => Some("code/pr".to_owned()),
+            StageKind::Agent => Some(format!("{}/task", self.agent())),
+        }
+    }
+
+    /// Fix template of the stage.
+    pub fn fix_template(&self) -> &str {
+        self.fix_template.as_deref().unwrap_or(match self.kind {
+            StageKind::Verification => "verify/fix",
+            _ => "review/fix",
+        })
+    }
+}
+
+/// Kind of a run pipeline stage.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub enum StageKind {
+    /// Execute the feature's phases with the code agent, running hooks and
+    /// committing after each phase.
+    Phases,
+    /// Review the branch diff and let the code agent fix the findings.
+    Review,
+    /// Verify the acceptance criteria and let the code agent fix failures.
+    Verification,
+    /// Run a custom agent in the worktree, then run hooks and commit.
+    Agent,
+    /// Create the pull request.
+    Pr,
+}
+
+impl StageKind {
+    /// Config name of the kind.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Phases => "phases",
+            Self::Review => "review",
+            Self::Verification => "verification",
+            Self::Agent => "agent",
+            Self::Pr => "pr",
+        }
+    }
+}
+
+impl std::fmt::Display for StageKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Tool-use policy applied to every agent session, on top of the
+/// permission mode.
+///
+/// A tool call that breaks a rule is denied before it runs, and the agent
+/// is told why.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq, JsonSchema)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct ToolPolicyConfig {
+    /// Regular expressions; a `Bash` command matching any of them is denied
+    /// (e.g. `git push --force`, `rm -rf /`, `\bcurl\b`).
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub deny_commands: Vec<String>,
+
+    /// Globs, relative to the agent's working directory, of files agents may
+    /// not write (e.g. `.github/**`, `Cargo.lock`). A glob without `/`
+    /// matches the file name at any depth.
+    ///
+    /// Path rules cover the file-writing tools and the files a `Bash`
+    /// command visibly writes (redirections, `tee`, `cp`, `mv`, `sed -i`,
+    /// ...), after resolving symlinks. Writes made by scripts or
+    /// interpreters a command runs are not seen; deny those commands with
+    /// `denyCommands`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub protected_paths: Vec<String>,
+
+    /// Deny writes outside the agent's working directory (the feature
+    /// worktree during `gba run`), including `Bash` redirections and file
+    /// commands as for `protectedPaths`.
+    #[serde(default)]
+    pub worktree_writes_only: bool,
+
+    /// Tools each agent may use, keyed by agent name. Agents without an
+    /// entry may use any tool.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub allowed_tools: BTreeMap<String, Vec<String>>,
+}
+
+impl ToolPolicyConfig {
+    /// Whether the policy has no rules.
+    pub fn is_empty(&self) -> bool {
+        self.deny_commands.is_empty()
+            && self.protected_paths.is_empty()
+            && !self.worktree_writes_only
+            && self.allowed_tools.is_empty()
+    }
+}
+
 // ── Default value functions for serde ────────────────────────
 
 fn default_true() -> bool {
@@ -300,29 +649,28 @@ fn default_max_iterations() -> u32 {
     3
 }
 
+fn default_max_repair_attempts() -> u32 {
+    3
+}
+
 fn default_max_retries() -> u32 {
     5
 }
 
-// ── Config loading ───────────────────────────────────────────
+fn default_stages() -> Vec<StageConfig> {
+    [
+        StageKind::Phases,
+        StageKind::Review,
+        StageKind::Verification,
+        StageKind::Pr,
+    ]
+    .into_iter()
+    .map(StageConfig::new)
+    .collect()
+}
 
-/// Load [`ProjectConfig`] from the `.gba/config.yaml` file.
-///
-/// If the file does not exist, returns the default configuration.
-///
-/// # Errors
-///
-/// Returns `CoreError::Io` if the file exists but cannot be read.
-/// Returns `CoreError::Yaml` if the file contains invalid YAML.
-pub fn load_project_config(
-    config_path: &std::path::Path,
-) -> Result<ProjectConfig, crate::CoreError> {
-    if !config_path.exists() {
-        return Ok(ProjectConfig::default());
-    }
-    let content = std::fs::read_to_string(config_path)?;
-    let config: ProjectConfig = serde_yaml::from_str(&content)?;
-    Ok(config)
+fn default_pre_commit_config_path() -> PathBuf {
+    PathBuf::from(".pre-commit-config.yaml")
 }
 
 #[cfg(test)]
@@ -332,6 +680,7 @@ mod tests {
     use serde_json::json;
 
     use super::*;
+    use crate::layers::{ConfigSources, resolve};
 
     #[test]
     fn test_should_build_engine_config_with_defaults() {
@@ -371,6 +720,10 @@ mod tests {
             config.config_path(),
             PathBuf::from("/home/user/project/.gba/config.yaml")
         );
+        assert_eq!(
+            config.local_config_path(),
+            PathBuf::from("/home/user/project/.gba/config.local.yaml")
+        );
     }
 
     #[test]
@@ -389,6 +742,7 @@ mod tests {
         assert_eq!(config.verification.max_iterations, 3);
         assert!(config.hooks.pre_commit.is_empty());
         assert_eq!(config.hooks.max_retries, 5);
+        assert_eq!(config.plan.max_repair_attempts, 3);
     }
 
     #[test]
@@ -466,8 +820,12 @@ hooks:
 
     #[test]
     fn test_should_load_default_when_config_file_missing() {
-        let path = PathBuf::from("/nonexistent/config.yaml");
-        let config = load_project_config(&path).expect("should return default");
+        let engine_config = EngineConfig::builder()
+            .repo_path(PathBuf::from("/nonexistent"))
+            .build();
+        let config = resolve(&engine_config, &ConfigSources::default())
+            .expect("should return default")
+            .into_config();
         assert!(config.agent.model.is_none());
         assert!(config.git.auto_commit);
     }
@@ -475,20 +833,41 @@ hooks:
     #[test]
     fn test_should_load_config_from_tempfile() {
         let dir = tempfile::TempDir::new().expect("should create temp dir");
-        let config_path = dir.path().join("config.yaml");
+        let engine_config = EngineConfig::builder()
+            .repo_path(dir.path().to_path_buf())
+            .build();
+        std::fs::create_dir_all(engine_config.gba_dir()).expect("should create .gba dir");
         std::fs::write(
-            &config_path,
+            engine_config.config_path(),
             "agent:\n  model: test-model\ngit:\n  baseBranch: develop\n",
         )
         .expect("should write config");
 
-        let config = load_project_config(&config_path).expect("should load config");
+        let config = resolve(&engine_config, &ConfigSources::default())
+            .expect("should load config")
+            .into_config();
         assert_eq!(config.agent.model.as_deref(), Some("test-model"));
         assert_eq!(config.git.base_branch, "develop");
         // Defaults should still apply for unspecified fields
         assert!(config.git.auto_commit);
     }
 
+    #[test]
+    fn test_should_deserialize_pre_commit_framework_config() {
+        let yaml = "hooks:\n  preCommitFramework:\n    mode: translate\n";
+        let config: ProjectConfig = serde_yaml::from_str(yaml).expect("should parse YAML");
+
+        let framework = config
+            .hooks
+            .pre_commit_framework
+            .expect("should have framework config");
+        assert_eq!(framework.mode, PreCommitMode::Translate);
+        assert_eq!(
+            framework.config_path,
+            PathBuf::from(".pre-commit-config.yaml")
+        );
+    }
+
     #[test]
     fn test_should_serialize_hook() {
         let hook = Hook {
@@ -499,4 +878,44 @@ hooks:
         assert_eq!(value["name"], "build");
         assert_eq!(value["command"], "cargo build");
     }
+
+    #[test]
+    fn test_should_default_to_standard_pipeline() {
+        let config = ProjectConfig::default();
+        let names: Vec<&str> = config
+            .pipeline
+            .stages
+            .iter()
+            .map(StageConfig::name)
+            .collect();
+        assert_eq!(names, vec!["phases", "review", "verification", "pr"]);
+    }
+
+    #[test]
+    fn test_should_deserialize_custom_pipeline() {
+        let yaml = r#"
+pipeline:
+  stages:
+    - kind: phases
+    - kind: verification
+    - kind: agent
+      agent: docs
+    - kind: review
+      name: final-review
+      agent: security-review
+"#;
+        let config: ProjectConfig = serde_yaml::from_str(yaml).expect("should parse YAML");
+        let stages = &config.pipeline.stages;
+
+        assert_eq!(stages.len(), 4);
+        assert_eq!(stages[0].template(), None);
+        assert_eq!(stages[1].agent(), "verify");
+        assert_eq!(stages[1].template().as_deref(), Some("verify/task"));
+        assert_eq!(stages[1].fix_template(), "verify/fix");
+        assert_eq!(stages[2].name(), "docs");
+        assert_eq!(stages[2].template().as_deref(), Some("docs/task"));
+        assert_eq!(stages[3].name(), "final-review");
+        assert_eq!(stages[3].agent(), "security-review");
+        assert_eq!(stages[3].template().as_deref(), Some("review/task"));
+    }
 }
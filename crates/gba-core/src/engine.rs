@@ -7,13 +7,21 @@
 use std::path::PathBuf;
 use std::sync::Arc;
 
+use gba_pm::PromptTemplate;
 use tracing::{info, instrument, warn};
 
 use crate::agent::AgentRunner;
-use crate::config::{EngineConfig, ProjectConfig, load_project_config};
+use crate::archive::{SessionItem, SessionRecord};
+use crate::config::{EngineConfig, ProjectConfig};
 use crate::error::CoreError;
 use crate::events::{PlanSession, RunStream};
 use crate::git::GitOps;
+use crate::layers::ResolvedConfig;
+use crate::plan::PlanStart;
+use crate::queue::{Queue, QueueRun};
+use crate::report::Report;
+use crate::status::FeatureStatus;
+use crate::validate;
 
 /// Core execution engine that drives all GBA workflows.
 ///
@@ -54,20 +62,24 @@ pub struct Engine {
 impl Engine {
     /// Create a new engine with the given configuration.
     ///
-    /// Loads the project configuration from `.gba/config.yaml` (if it exists),
-    /// initializes the prompt manager with built-in and custom templates, and
+    /// Resolves the layered project configuration (user-global config,
+    /// `.gba/config.yaml`, `.gba/config.local.yaml`, `GBA_*` environment
+    /// variables and CLI overrides), initializes the prompt manager with built-in and custom templates, and
     /// sets up the git operations helper.
     ///
     /// # Errors
     ///
-    /// Returns `CoreError::Config` if the config file exists but is invalid.
+    /// Returns `CoreError::Config` if a config layer is invalid or the merged
+    /// configuration violates a validation rule.
     /// Returns `CoreError::Prompt` if prompt templates cannot be loaded.
     #[instrument(skip_all)]
     pub async fn new(config: EngineConfig) -> Result<Self, CoreError> {
         info!(repo = %config.repo_path().display(), "initializing engine");
 
-        // Load project config (defaults if file doesn't exist)
-        let project_config = load_project_config(&config.config_path())?;
+        // Resolve project config from defaults, user, project, local, env and CLI layers
+        let resolved = ResolvedConfig::load(&config)?;
+        validate::ensure_valid_config(&resolved)?;
+        let project_config = resolved.into_config();
 
         // Initialize agent runner with merged configuration
         let agent_runner = AgentRunner::new(&config, &project_config)?;
@@ -118,7 +130,63 @@ impl Engine {
     #[instrument(skip(self))]
     pub async fn plan(&self, slug: &str) -> Result<PlanSession, CoreError> {
         let slug = normalize_slug(slug);
-        crate::plan::run_plan(self, &slug).await
+        crate::plan::run_plan(self, &slug, PlanStart::New).await
+    }
+
+    /// Plan a feature from a written brief without asking questions.
+    ///
+    /// The planning agent answers its own open questions with assumptions,
+    /// records them in `design.md`, and writes the spec files in a single
+    /// turn. The returned [`PlanSession`] completes without waiting for
+    /// input, so this suits batch jobs.
+    ///
+    /// The slug is normalized before use (see [`plan()`](Engine::plan)).
+    ///
+    /// # Errors
+    ///
+    /// Same as [`plan()`](Engine::plan).
+    #[instrument(skip(self, brief))]
+    pub async fn plan_from_brief(&self, slug: &str, brief: &str) -> Result<PlanSession, CoreError> {
+        let slug = normalize_slug(slug);
+        crate::plan::run_plan(self, &slug, PlanStart::Brief(brief)).await
+    }
+
+    /// Resume a feature's last plan session.
+    ///
+    /// Reconnects to the SDK session recorded in the feature's
+    /// `plan-transcript.jsonl`, so the agent continues with the prior
+    /// conversation. New turns are appended to the same transcript.
+    ///
+    /// The slug is normalized before use (see [`plan()`](Engine::plan)).
+    ///
+    /// # Errors
+    ///
+    /// Returns `CoreError::FeatureNotFound` if no plan session was recorded
+    /// for the feature; otherwise the same errors as [`plan()`](Engine::plan).
+    #[instrument(skip(self))]
+    pub async fn resume_plan(&self, slug: &str) -> Result<PlanSession, CoreError> {
+        let slug = normalize_slug(slug);
+        crate::plan::run_plan(self, &slug, PlanStart::Resume).await
+    }
+
+    /// Amend a feature's existing plan.
+    ///
+    /// Starts an interactive session with the current design and phases,
+    /// including which phases are completed. The agent may add, split,
+    /// reorder or edit pending phases; its plan is merged back into
+    /// `phases.yaml` keeping completed phases' results and commits.
+    ///
+    /// The slug is normalized before use (see [`plan()`](Engine::plan)).
+    ///
+    /// # Errors
+    ///
+    /// Returns `CoreError::FeatureNotFound` if the feature has no
+    /// `phases.yaml`, `CoreError::InvalidSpec` if it is invalid; otherwise
+    /// the same errors as [`plan()`](Engine::plan).
+    #[instrument(skip(self))]
+    pub async fn amend_plan(&self, slug: &str) -> Result<PlanSession, CoreError> {
+        let slug = normalize_slug(slug);
+        crate::plan::run_plan(self, &slug, PlanStart::Amend).await
     }
 
     /// Execute a feature's development plan phase by phase.
@@ -138,6 +206,135 @@ impl Engine {
         crate::run::run_execution(self, &slug).await
     }
 
+    /// Status of every planned feature, sorted by slug.
+    ///
+    /// Features whose `phases.yaml` cannot be loaded are still listed, with
+    /// [`FeatureStatus::error`] describing the problem.
+    ///
+    /// # Errors
+    ///
+    /// Returns `CoreError::NotInitialized` if the repo is not initialized.
+    /// Returns `CoreError::Git` if a worktree's state cannot be read.
+    #[instrument(skip(self))]
+    pub async fn list_features(&self) -> Result<Vec<FeatureStatus>, CoreError> {
+        crate::status::list_features(&self.gba_dir(), &self.git).await
+    }
+
+    /// Status of one feature: phase progress, execution outcome, PR,
+    /// worktree state and last activity.
+    ///
+    /// The slug is normalized before use (see [`plan()`](Engine::plan)).
+    ///
+    /// # Errors
+    ///
+    /// Returns `CoreError::FeatureNotFound` if the feature spec doesn't exist.
+    /// Returns `CoreError::InvalidSpec` if the feature spec is invalid.
+    /// Returns `CoreError::Git` if the worktree's state cannot be read.
+    #[instrument(skip(self))]
+    pub async fn feature_status(&self, slug: &str) -> Result<FeatureStatus, CoreError> {
+        let slug = normalize_slug(slug);
+        crate::status::feature_status(&self.gba_dir(), &self.git, &slug).await
+    }
+
+    /// Build the execution report of a feature: spec and phase results,
+    /// commits on the feature branch with diffstats, review rounds,
+    /// verification evidence, hook history and agent costs.
+    ///
+    /// The slug is normalized before use (see [`plan()`](Engine::plan)).
+    ///
+    /// # Errors
+    ///
+    /// Returns `CoreError::FeatureNotFound` if the feature spec doesn't exist.
+    /// Returns `CoreError::InvalidSpec` if the feature spec is invalid.
+    /// Returns `CoreError::Git` if the feature branch's log cannot be read.
+    #[instrument(skip(self))]
+    pub async fn report(&self, slug: &str) -> Result<Report, CoreError> {
+        let slug = normalize_slug(slug);
+        crate::report::build_report(
+            &self.gba_dir(),
+            &self.git,
+            &self.project_config.git.base_branch,
+            &slug,
+        )
+        .await
+    }
+
+    /// The archived agent sessions of a feature's runs, in the order they
+    /// started.
+    ///
+    /// The slug is normalized before use (see [`plan()`](Engine::plan)).
+    ///
+    /// # Errors
+    ///
+    /// Returns `CoreError::FeatureNotFound` if the feature doesn't exist.
+    /// Returns `CoreError::Yaml` if the transcript index cannot be parsed.
+    pub fn transcripts(&self, slug: &str) -> Result<Vec<SessionRecord>, CoreError> {
+        crate::archive::load_sessions(&self.gba_dir(), &normalize_slug(slug))
+    }
+
+    /// The transcript of an archived agent session.
+    ///
+    /// The slug is normalized before use (see [`plan()`](Engine::plan)).
+    ///
+    /// # Errors
+    ///
+    /// Returns `CoreError::FeatureNotFound` if the transcript doesn't exist.
+    /// Returns `CoreError::Io` if the transcript cannot be read.
+    pub fn transcript(
+        &self,
+        slug: &str,
+        session: &SessionRecord,
+    ) -> Result<Vec<SessionItem>, CoreError> {
+        crate::archive::load_session(&self.gba_dir(), &normalize_slug(slug), session)
+    }
+
+    /// The feature run queue from `.gba/queue.yaml`.
+    ///
+    /// # Errors
+    ///
+    /// Returns `CoreError::NotInitialized` if the repo is not initialized.
+    /// Returns `CoreError::Yaml` if `queue.yaml` cannot be parsed.
+    pub fn queue(&self) -> Result<Queue, CoreError> {
+        crate::queue::load_queue(&self.gba_dir())
+    }
+
+    /// Append features to the run queue, skipping those already waiting.
+    /// Returns the normalized slugs that were added.
+    ///
+    /// # Errors
+    ///
+    /// Returns `CoreError::NotInitialized` if the repo is not initialized.
+    /// Returns `CoreError::FeatureNotFound` or `CoreError::InvalidSpec` if a
+    /// feature cannot be loaded; nothing is queued then.
+    #[instrument(skip(self))]
+    pub fn queue_add(&self, slugs: &[String]) -> Result<Vec<String>, CoreError> {
+        let slugs: Vec<String> = slugs.iter().map(|s| normalize_slug(s)).collect();
+        crate::queue::add_to_queue(&self.gba_dir(), &slugs)
+    }
+
+    /// Run the queued features, up to `jobs` at once, each in its own
+    /// worktree. Features left running by an interrupted queue run are
+    /// queued again first.
+    ///
+    /// Only one queue run can be in progress; it holds `.gba/queue.lock`
+    /// until the returned [`QueueRun`] is dropped.
+    ///
+    /// Runs cannot ask for permission: in `manual` permission mode every
+    /// request is denied.
+    ///
+    /// # Errors
+    ///
+    /// Returns `CoreError::NotInitialized` if the repo is not initialized.
+    /// Returns `CoreError::Locked` if another queue run is in progress.
+    /// Returns `CoreError::Yaml` if `queue.yaml` cannot be parsed.
+    #[instrument(skip(self))]
+    pub fn run_queue(&self, jobs: usize) -> Result<QueueRun<'_>, CoreError> {
+        let gba_dir = self.gba_dir();
+        let lock = crate::queue::lock_queue_run(&gba_dir)?;
+        crate::queue::requeue_interrupted(&gba_dir)?;
+        Ok(QueueRun::new(self, jobs, lock))
+    }
+
     /// Returns a reference to the engine configuration.
     pub fn config(&self) -> &EngineConfig {
         &self.config
@@ -148,6 +345,67 @@ impl Engine {
         &self.project_config
     }
 
+    /// Names of all available agents: the built-in five plus any defined in
+    /// `.gba/agents/<name>/` or a `prompts.include` directory.
+    pub fn agents(&self) -> Vec<&str> {
+        self.agent_runner.agents()
+    }
+
+    /// Effective prompt templates, sorted by name: the built-ins and those
+    /// loaded from `.gba/agents/` and `prompts.include` directories.
+    pub fn prompts(&self) -> Vec<PromptTemplate> {
+        let pm = self.agent_runner.prompt_manager();
+        pm.list_templates()
+            .into_iter()
+            .filter_map(|name| pm.template(name).ok())
+            .collect()
+    }
+
+    /// The effective source of a prompt template and where it came from.
+    ///
+    /// # Errors
+    ///
+    /// Returns `CoreError::Prompt` if no template with that name exists.
+    pub fn prompt(&self, name: &str) -> Result<PromptTemplate, CoreError> {
+        Ok(self.agent_runner.prompt_manager().template(name)?)
+    }
+
+    /// Render a prompt template with the given context.
+    ///
+    /// # Errors
+    ///
+    /// Returns `CoreError::Prompt` if the template doesn't exist or fails to
+    /// render.
+    pub fn render_prompt(&self, name: &str, ctx: &serde_json::Value) -> Result<String, CoreError> {
+        Ok(self.agent_runner.prompt_manager().render(name, ctx)?)
+    }
+
+    /// The context `gba run` would render `template` with for a feature in
+    /// its current state.
+    ///
+    /// `stage` names the pipeline stage; by default it is the first stage
+    /// using the template, or run by the template's agent. `phase` picks the
+    /// one-based phase for `phases` stages and defaults to the next phase a
+    /// run would execute. Review and custom agent stages see the worktree's
+    /// current diff. The slug is normalized before use (see
+    /// [`plan()`](Engine::plan)).
+    ///
+    /// # Errors
+    ///
+    /// Returns `CoreError::NotInitialized` if `.gba/` does not exist.
+    /// Returns `CoreError::FeatureNotFound` if the feature doesn't exist.
+    /// Returns `CoreError::Config` if no pipeline stage matches.
+    /// Returns `CoreError::InvalidSpec` if there is no phase to preview.
+    pub async fn prompt_context(
+        &self,
+        slug: &str,
+        template: &str,
+        stage: Option<&str>,
+        phase: Option<usize>,
+    ) -> Result<serde_json::Value, CoreError> {
+        crate::prompts::stage_context(self, &normalize_slug(slug), template, stage, phase).await
+    }
+
     /// Returns a reference to the internal git operations helper.
     pub(crate) fn git(&self) -> &GitOps {
         &self.git
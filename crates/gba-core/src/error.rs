@@ -3,6 +3,7 @@
 //! Defines [`CoreError`], the unified error type used across all core engine
 //! operations including init, plan, and run workflows.
 
+use serde::ser::{Serialize, SerializeStruct, Serializer};
 use thiserror::Error;
 
 /// Core engine errors.
@@ -39,6 +40,10 @@ pub enum CoreError {
     #[error("configuration error: {0}")]
     Config(String),
 
+    /// Another run or queue run holds the lock on the resource.
+    #[error("already in use: {0}")]
+    Locked(String),
+
     /// A precommit hook failed after exhausting retries.
     #[error("hook failed: {0}")]
     Hook(String),
@@ -59,3 +64,70 @@ pub enum CoreError {
     #[error(transparent)]
     Other(#[from] anyhow::Error),
 }
+
+impl CoreError {
+    /// Stable camelCase name of the variant, used as `kind` in the error's
+    /// JSON form.
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Self::NotInitialized => "notInitialized",
+            Self::AlreadyInitialized => "alreadyInitialized",
+            Self::FeatureNotFound(_) => "featureNotFound",
+            Self::InvalidSpec(_) => "invalidSpec",
+            Self::Agent(_) => "agent",
+            Self::Git(_) => "git",
+            Self::Config(_) => "config",
+            Self::Locked(_) => "locked",
+            Self::Hook(_) => "hook",
+            Self::Prompt(_) => "prompt",
+            Self::Yaml(_) => "yaml",
+            Self::Io(_) => "io",
+            Self::Other(_) => "other",
+        }
+    }
+}
+
+/// Serializes as `{"kind": "...", "message": "..."}`, where `message` is the
+/// error followed by its sources, separated by `: `.
+impl Serialize for CoreError {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut message = self.to_string();
+        let mut source = std::error::Error::source(self);
+        while let Some(error) = source {
+            let text = error.to_string();
+            if !message.ends_with(&text) {
+                message.push_str(": ");
+                message.push_str(&text);
+            }
+            source = error.source();
+        }
+
+        let mut state = serializer.serialize_struct("CoreError", 2)?;
+        state.serialize_field("kind", self.kind())?;
+        state.serialize_field("message", &message)?;
+        state.end()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_should_serialize_error_with_kind_and_sources() {
+        let error = CoreError::FeatureNotFound("login".to_owned());
+        assert_eq!(
+            serde_json::to_value(&error).expect("should serialize"),
+            serde_json::json!({"kind": "featureNotFound", "message": "feature not found: login"})
+        );
+
+        let error = CoreError::Prompt(gba_pm::PmError::TemplateNotFound("code/x".to_owned()));
+        let json = serde_json::to_value(&error).expect("should serialize");
+        assert_eq!(json["kind"], "prompt");
+        assert!(
+            json["message"]
+                .as_str()
+                .is_some_and(|m| m.starts_with("prompt error: ") && m.contains("code/x"))
+        );
+    }
+}
@@ -4,12 +4,65 @@
 //! progress during plan and run operations. [`PlanSession`] provides a
 //! bidirectional conversation handle for interactive planning, while
 //! [`RunStream`] provides a unidirectional event stream for run progress.
+//!
+//! Both event types serialize to a stable JSON form, wrapped in an
+//! [`EventRecord`] that carries the [`EVENT_SCHEMA_VERSION`].
 
 use std::path::PathBuf;
 
 use serde::{Deserialize, Serialize};
+use tracing::warn;
 
+use crate::config::StageKind;
 use crate::error::CoreError;
+use crate::spec::{AgentUsage, StepStatus, ToolDenial};
+use crate::validate::Diagnostic;
+
+// ── JSON Form ────────────────────────────────────────────────
+
+/// Version of the JSON form of [`PlanEvent`] and [`RunEvent`].
+///
+/// Bumped when an event type or field is renamed or removed, or its meaning
+/// changes. New event types and fields may be added within a version, so
+/// consumers should ignore what they do not know.
+pub const EVENT_SCHEMA_VERSION: u32 = 1;
+
+/// An event in its versioned JSON form.
+///
+/// Serializes as `{"version": 1, "type": "stageStarted", "data": {...}}`,
+/// where `type` is the camelCase variant name and `data` holds the
+/// variant's fields (absent for variants without fields).
+///
+/// # Examples
+///
+/// ```
+/// use gba_core::{EventRecord, RunEvent};
+///
+/// let event = RunEvent::PrCreated { url: "https://example.com/pr/1".to_owned() };
+/// let json = serde_json::to_value(EventRecord::new(&event)).expect("serializable");
+/// assert_eq!(json["version"], 1);
+/// assert_eq!(json["type"], "prCreated");
+/// assert_eq!(json["data"]["url"], "https://example.com/pr/1");
+/// ```
+#[derive(Debug, Serialize)]
+pub struct EventRecord<'a, E> {
+    /// Schema version, [`EVENT_SCHEMA_VERSION`].
+    pub version: u32,
+
+    /// The event.
+    #[serde(flatten)]
+    pub event: &'a E,
+}
+
+impl<'a, E> EventRecord<'a, E> {
+    /// Wrap `event` with the current schema version.
+    pub fn new(event: &'a E) -> Self {
+        Self {
+            version: EVENT_SCHEMA_VERSION,
+            event,
+        }
+    }
+}
 
 // ── Plan Session ─────────────────────────────────────────────
 
@@ -23,8 +76,9 @@ pub struct PlanSession {
     /// Receiver for plan events from the agent.
     event_rx: tokio::sync::mpsc::Receiver<PlanEvent>,
 
-    /// Sender for user input back to the agent.
-    input_tx: tokio::sync::mpsc::Sender<String>,
+    /// Sender for user input back to the agent. `None` once the user has
+    /// finished the session.
+    input_tx: Option<tokio::sync::mpsc::Sender<String>>,
 }
 
 impl PlanSession {
@@ -33,7 +87,10 @@ impl PlanSession {
         event_rx: tokio::sync::mpsc::Receiver<PlanEvent>,
         input_tx: tokio::sync::mpsc::Sender<String>,
     ) -> Self {
-        Self { event_rx, input_tx }
+        Self {
+            event_rx,
+            input_tx: Some(input_tx),
+        }
     }
 
     /// Get the next event from the planning agent.
@@ -48,18 +105,38 @@ impl PlanSession {
     ///
     /// # Errors
     ///
-    /// Returns `CoreError::Agent` if the agent has already finished and
-    /// the input channel is closed.
+    /// Returns `CoreError::Agent` if the agent has already finished, the
+    /// input channel is closed, or the session was finished.
     pub async fn respond(&mut self, input: &str) -> Result<(), CoreError> {
-        self.input_tx
+        let Some(input_tx) = &self.input_tx else {
+            return Err(CoreError::Agent(
+                "failed to send user input: session already finished".to_owned(),
+            ));
+        };
+        input_tx
             .send(input.to_owned())
             .await
             .map_err(|e| CoreError::Agent(format!("failed to send user input: {e}")))
     }
+
+    /// Tell the planning agent that the user is done.
+    ///
+    /// The session validates the spec files and ends with
+    /// [`PlanEvent::Completed`] or [`PlanEvent::Error`]; keep calling
+    /// [`next()`](Self::next) until then.
+    pub fn finish(&mut self) {
+        self.input_tx = None;
+    }
 }
 
 /// Events emitted during a planning session.
-#[derive(Debug)]
+#[derive(Debug, Serialize)]
+#[serde(
+    tag = "type",
+    content = "data",
+    rename_all = "camelCase",
+    rename_all_fields = "camelCase"
+)]
 pub enum PlanEvent {
     /// Agent produced a text message to display.
     Message(String),
@@ -75,6 +152,16 @@ pub enum PlanEvent {
         content: String,
     },
 
+    /// The generated spec files were validated. Empty `diagnostics` means
+    /// they passed; otherwise the agent is asked to repair them until the
+    /// retry limit is hit.
+    SpecValidated {
+        /// Validation round, starting at 1.
+        attempt: u32,
+        /// Problems found in this round.
+        diagnostics: Vec<Diagnostic>,
+    },
+
     /// Planning session completed successfully.
     Completed,
 
@@ -87,17 +174,55 @@ pub enum PlanEvent {
 /// Handle for consuming run execution progress.
 ///
 /// The CLI reads events from this stream to update the progress display
-/// during phased feature execution.
+/// during phased feature execution. In `manual` permission mode it answers
+/// each [`RunEvent::PermissionRequested`] with
+/// [`respond_permission()`](RunStream::respond_permission).
+///
+/// Dropping the stream stops the run at its next event; call
+/// [`cancel()`](RunStream::cancel) to stop it right away.
 #[derive(Debug)]
 pub struct RunStream {
     /// Receiver for run events.
     event_rx: tokio::sync::mpsc::Receiver<RunEvent>,
+
+    /// Sender for answers to permission requests.
+    permission_tx: tokio::sync::mpsc::Sender<(u64, PermissionDecision)>,
+
+    /// Background task executing the run; `None` once cancelled.
+    task: Option<tokio::task::JoinHandle<()>>,
 }
 
 impl RunStream {
-    /// Create a new run stream with the given channel.
-    pub(crate) fn new(event_rx: tokio::sync::mpsc::Receiver<RunEvent>) -> Self {
-        Self { event_rx }
+    /// Create a new run stream with the given channels and the task sending
+    /// on them.
+    pub(crate) fn new(
+        event_rx: tokio::sync::mpsc::Receiver<RunEvent>,
+        permission_tx: tokio::sync::mpsc::Sender<(u64, PermissionDecision)>,
+        task: tokio::task::JoinHandle<()>,
+    ) -> Self {
+        Self {
+            event_rx,
+            permission_tx,
+            task: Some(task),
+        }
+    }
+
+    /// Stop the run and wait until its background task has ended.
+    ///
+    /// The run stops at its next await point: the agent session and any
+    /// precommit hook are killed, and the current phase is left unfinished
+    /// so that the next run resumes it. No more events are sent.
+    pub async fn cancel(&mut self) {
+        let Some(task) = self.task.take() else {
+            return;
+        };
+        task.abort();
+        if let Err(e) = task.await
+            && !e.is_cancelled()
+        {
+            warn!(error = %e, "run task failed");
+        }
+        self.event_rx.close();
     }
 
     /// Get the next event from the run execution.
@@ -107,17 +232,61 @@ impl RunStream {
     pub async fn next(&mut self) -> Option<RunEvent> {
         self.event_rx.recv().await
     }
+
+    /// Answer the permission request with the given id.
+    ///
+    /// # Errors
+    ///
+    /// Returns `CoreError::Agent` if execution has already finished and the
+    /// permission channel is closed.
+    pub async fn respond_permission(
+        &mut self,
+        id: u64,
+        decision: PermissionDecision,
+    ) -> Result<(), CoreError> {
+        self.permission_tx
+            .send((id, decision))
+            .await
+            .map_err(|e| CoreError::Agent(format!("failed to send permission decision: {e}")))
+    }
 }
 
 /// Events emitted during feature execution.
-#[derive(Debug)]
+#[derive(Debug, Serialize)]
+#[serde(
+    tag = "type",
+    content = "data",
+    rename_all = "camelCase",
+    rename_all_fields = "camelCase"
+)]
 pub enum RunEvent {
     /// Execution started.
     Started {
         /// Feature description.
         feature: String,
-        /// Total number of phases.
-        total_phases: usize,
+        /// All phases of the feature, with the status they start the run
+        /// in (completed phases are skipped).
+        phases: Vec<PhaseSummary>,
+    },
+
+    /// A pipeline stage started.
+    StageStarted {
+        /// Stage name.
+        name: String,
+        /// Stage kind.
+        kind: StageKind,
+    },
+
+    /// A pipeline stage finished.
+    StageFinished {
+        /// Stage name.
+        name: String,
+        /// Stage kind.
+        kind: StageKind,
+        /// Final status (`Completed` or `Failed`).
+        status: StepStatus,
+        /// Human-readable outcome, e.g. `2 issues found, 2 fixed`.
+        summary: String,
     },
 
     /// A development phase started.
@@ -128,8 +297,19 @@ pub enum RunEvent {
         name: String,
     },
 
-    /// Coding agent is producing output.
-    CodingOutput(String),
+    /// An agent produced text while working.
+    AgentOutput {
+        /// Agent producing the text.
+        agent: String,
+        /// Phase the agent works on, or the stage name outside the phases
+        /// stage.
+        phase: String,
+        /// Text of one assistant message.
+        text: String,
+    },
+
+    /// An agent session finished and reported its usage.
+    UsageReported(AgentUsage),
 
     /// Precommit hook result.
     HookResult {
@@ -147,18 +327,12 @@ pub enum RunEvent {
         commit_hash: String,
     },
 
-    /// Code review started.
-    ReviewStarted,
-
     /// Code review completed.
     ReviewCompleted {
-        /// Issues found during review.
+        /// Merged issues found by the review stage, across iterations.
         issues: Vec<Issue>,
     },
 
-    /// Verification started.
-    VerificationStarted,
-
     /// Verification completed.
     VerificationCompleted {
         /// Whether all criteria passed.
@@ -167,6 +341,13 @@ pub enum RunEvent {
         details: String,
     },
 
+    /// An agent wants to use a tool and waits for the user's approval
+    /// (`manual` permission mode only).
+    PermissionRequested(PermissionRequest),
+
+    /// The tool policy denied a tool call.
+    ToolDenied(ToolDenial),
+
     /// Pull request created.
     PrCreated {
         /// PR URL.
@@ -180,6 +361,58 @@ pub enum RunEvent {
     Error(CoreError),
 }
 
+/// A phase of the feature being run.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PhaseSummary {
+    /// Phase name.
+    pub name: String,
+
+    /// Phase status when the run started.
+    pub status: StepStatus,
+}
+
+/// A tool call waiting for the user's approval.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PermissionRequest {
+    /// Request id, passed back to [`RunStream::respond_permission`].
+    pub id: u64,
+
+    /// Agent that wants to use the tool.
+    pub agent: String,
+
+    /// Phase the agent works on, or the stage name outside the phases
+    /// stage.
+    pub phase: String,
+
+    /// Tool name (e.g. `Bash`, `Edit`).
+    pub tool_name: String,
+
+    /// Tool input as sent by the agent.
+    pub input: serde_json::Value,
+}
+
+/// The user's answer to a [`PermissionRequest`].
+///
+/// In JSON: `{"decision": "allowOnce"}`, `{"decision": "allowAlways"}` or
+/// `{"decision": "deny", "reason": "..."}`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "decision", rename_all = "camelCase")]
+pub enum PermissionDecision {
+    /// Allow this call only.
+    AllowOnce,
+
+    /// Allow this and every later call of the same tool during the run.
+    AllowAlways,
+
+    /// Deny the call; the reason is passed to the agent.
+    Deny {
+        /// Why the call was denied.
+        reason: String,
+    },
+}
+
 // ── Code Review Types ────────────────────────────────────────
 
 /// A code review issue found by the review agent.
@@ -192,12 +425,22 @@ pub struct Issue {
     /// File path where the issue was found.
     pub file: PathBuf,
 
+    /// Line number in the file, if the reviewer gave one.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub line: Option<u32>,
+
     /// Human-readable description of the issue.
     pub description: String,
+
+    /// Reviewers that raised the issue.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub reviewers: Vec<String>,
 }
 
 /// Severity level for a code review issue.
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+///
+/// Variants are ordered from most to least severe.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord)]
 #[serde(rename_all = "camelCase")]
 pub enum Severity {
     /// A critical issue that must be fixed.
@@ -217,13 +460,17 @@ mod tests {
         let issue = Issue {
             severity: Severity::Error,
             file: PathBuf::from("src/main.rs"),
+            line: Some(3),
             description: "Unused import".to_owned(),
+            reviewers: vec!["security".to_owned()],
         };
 
         let json = serde_json::to_value(&issue).expect("should serialize");
         assert_eq!(json["severity"], "error");
         assert_eq!(json["file"], "src/main.rs");
+        assert_eq!(json["line"], 3);
         assert_eq!(json["description"], "Unused import");
+        assert_eq!(json["reviewers"], serde_json::json!(["security"]));
     }
 
     #[test]
@@ -237,6 +484,8 @@ mod tests {
         let issue: Issue = serde_json::from_value(json).expect("should deserialize");
         assert_eq!(issue.severity, Severity::Warning);
         assert_eq!(issue.file, PathBuf::from("lib.rs"));
+        assert_eq!(issue.line, None);
+        assert!(issue.reviewers.is_empty());
     }
 
     #[test]
@@ -251,6 +500,85 @@ mod tests {
         assert_eq!(suggestion_json, "suggestion");
     }
 
+    #[test]
+    fn test_should_serialize_run_events_in_versioned_form() {
+        let json = |event: &RunEvent| {
+            serde_json::to_value(EventRecord::new(event)).expect("should serialize")
+        };
+
+        assert_eq!(
+            json(&RunEvent::PhaseCommitted {
+                index: 1,
+                commit_hash: "abc123".to_owned(),
+            }),
+            serde_json::json!({
+                "version": EVENT_SCHEMA_VERSION,
+                "type": "phaseCommitted",
+                "data": {"index": 1, "commitHash": "abc123"}
+            })
+        );
+        assert_eq!(
+            json(&RunEvent::Finished),
+            serde_json::json!({"version": EVENT_SCHEMA_VERSION, "type": "finished"})
+        );
+
+        let usage = json(&RunEvent::UsageReported(AgentUsage {
+            agent: "code".to_owned(),
+            phase: "Setup".to_owned(),
+            turns: 2,
+            input_tokens: 10,
+            output_tokens: 5,
+            cost_usd: None,
+        }));
+        assert_eq!(usage["type"], "usageReported");
+        assert_eq!(usage["data"]["inputTokens"], 10);
+
+        let error = json(&RunEvent::Error(CoreError::Hook("clippy".to_owned())));
+        assert_eq!(error["data"]["kind"], "hook");
+        assert_eq!(error["data"]["message"], "hook failed: clippy");
+    }
+
+    #[test]
+    fn test_should_serialize_plan_events_in_versioned_form() {
+        let json = |event: &PlanEvent| {
+            serde_json::to_value(EventRecord::new(event)).expect("should serialize")
+        };
+
+        let message = json(&PlanEvent::Message("Which database?".to_owned()));
+        assert_eq!(message["type"], "message");
+        assert_eq!(message["data"], "Which database?");
+
+        let validated = json(&PlanEvent::SpecValidated {
+            attempt: 1,
+            diagnostics: vec![Diagnostic {
+                source: "phases.yaml".to_owned(),
+                line: Some(4),
+                column: None,
+                message: "missing field `tasks`".to_owned(),
+            }],
+        });
+        assert_eq!(validated["type"], "specValidated");
+        assert_eq!(validated["data"]["diagnostics"][0]["line"], 4);
+        assert!(validated["data"]["diagnostics"][0].get("column").is_none());
+    }
+
+    #[test]
+    fn test_should_roundtrip_permission_decisions() {
+        let deny: PermissionDecision =
+            serde_json::from_str(r#"{"decision": "deny", "reason": "no network"}"#)
+                .expect("should deserialize");
+        assert_eq!(
+            deny,
+            PermissionDecision::Deny {
+                reason: "no network".to_owned()
+            }
+        );
+        assert_eq!(
+            serde_json::to_value(PermissionDecision::AllowOnce).expect("should serialize"),
+            serde_json::json!({"decision": "allowOnce"})
+        );
+    }
+
     #[tokio::test]
     async fn test_should_create_and_recv_plan_session_events() {
         let (event_tx, event_rx) = tokio::sync::mpsc::channel(16);
@@ -272,18 +600,46 @@ mod tests {
 
         let input = input_rx.recv().await;
         assert_eq!(input.as_deref(), Some("User reply"));
+
+        // Finishing closes the input channel
+        session.finish();
+        assert!(input_rx.recv().await.is_none());
+        assert!(session.respond("late").await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_should_stop_run_task_on_cancel() {
+        let (event_tx, event_rx) = tokio::sync::mpsc::channel(16);
+        let (permission_tx, _permission_rx) = tokio::sync::mpsc::channel(1);
+        let (ended_tx, ended_rx) = tokio::sync::oneshot::channel::<()>();
+        let task = tokio::spawn(async move {
+            let _ended = ended_tx;
+            let _events = event_tx;
+            std::future::pending::<()>().await;
+        });
+        let mut stream = RunStream::new(event_rx, permission_tx, task);
+
+        stream.cancel().await;
+        // The task and everything it held are gone once cancel returns
+        assert!(ended_rx.await.is_err());
+        assert!(stream.next().await.is_none());
+        stream.cancel().await;
     }
 
     #[tokio::test]
     async fn test_should_create_and_recv_run_stream_events() {
         let (event_tx, event_rx) = tokio::sync::mpsc::channel(16);
 
-        let mut stream = RunStream::new(event_rx);
+        let (permission_tx, _permission_rx) = tokio::sync::mpsc::channel(1);
+        let mut stream = RunStream::new(event_rx, permission_tx, tokio::spawn(async {}));
 
         event_tx
             .send(RunEvent::Started {
                 feature: "test".to_owned(),
-                total_phases: 3,
+                phases: vec![PhaseSummary {
+                    name: "Setup".to_owned(),
+                    status: StepStatus::Pending,
+                }],
             })
             .await
             .expect("should send");
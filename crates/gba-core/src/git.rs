@@ -185,6 +185,52 @@ impl GitOps {
         Ok(diff)
     }
 
+    /// Log of the commits on `slug`'s branch that are not on the base
+    /// branch, oldest first, with per-file line counts (`--numstat`). Each
+    /// commit starts with a record separator followed by the hash, commit
+    /// time and subject separated by unit separators.
+    ///
+    /// Returns `None` if the feature branch does not exist.
+    ///
+    /// # Errors
+    ///
+    /// Returns `CoreError::Git` if `git log` fails.
+    #[instrument(skip(self))]
+    pub(crate) async fn branch_log(&self, slug: &str) -> Result<Option<String>, CoreError> {
+        let branch = self.branch_name(slug);
+        let exists = tokio::process::Command::new("git")
+            .args(["rev-parse", "--verify", "--quiet"])
+            .arg(format!("refs/heads/{branch}"))
+            .current_dir(&self.repo_path)
+            .output()
+            .await?
+            .status
+            .success();
+        if !exists {
+            debug!(branch = %branch, "feature branch does not exist");
+            return Ok(None);
+        }
+
+        let output = tokio::process::Command::new("git")
+            .args([
+                "log",
+                "--reverse",
+                "--numstat",
+                "--format=%x1e%H%x1f%ct%x1f%s",
+            ])
+            .arg(format!("{}..{branch}", self.git_config.base_branch))
+            .current_dir(&self.repo_path)
+            .output()
+            .await?;
+
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            return Err(CoreError::Git(format!("git log failed: {stderr}")));
+        }
+
+        Ok(Some(String::from_utf8_lossy(&output.stdout).into_owned()))
+    }
+
     /// Get the current branch name in a worktree.
     ///
     /// # Errors
@@ -211,6 +257,97 @@ impl GitOps {
     }
 }
 
+/// List files changed in a worktree relative to `HEAD`.
+///
+/// Includes staged and unstaged modifications as well as untracked files
+/// that are not ignored. Deleted files are excluded since they cannot be
+/// checked by hooks. Paths are relative to the worktree root.
+///
+/// # Errors
+///
+/// Returns `CoreError::Git` if a git command fails.
+#[instrument]
+pub(crate) async fn list_changed_files(worktree: &Path) -> Result<Vec<String>, CoreError> {
+    let mut files = Vec::new();
+
+    for args in [
+        &["diff", "--name-only", "--diff-filter=d", "HEAD"][..],
+        &["ls-files", "--others", "--exclude-standard"][..],
+    ] {
+        let output = tokio::process::Command::new("git")
+            .args(args)
+            .current_dir(worktree)
+            .output()
+            .await?;
+
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            return Err(CoreError::Git(format!(
+                "git {} failed: {stderr}",
+                args.join(" ")
+            )));
+        }
+
+        files.extend(
+            String::from_utf8_lossy(&output.stdout)
+                .lines()
+                .filter(|line| !line.is_empty())
+                .map(str::to_owned),
+        );
+    }
+
+    files.sort_unstable();
+    files.dedup();
+    Ok(files)
+}
+
+/// Whether a worktree has uncommitted changes, including untracked files
+/// that are not ignored.
+///
+/// # Errors
+///
+/// Returns `CoreError::Git` if `git status` fails.
+#[instrument]
+pub(crate) async fn is_dirty(worktree: &Path) -> Result<bool, CoreError> {
+    let output = tokio::process::Command::new("git")
+        .args(["status", "--porcelain"])
+        .current_dir(worktree)
+        .output()
+        .await?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(CoreError::Git(format!("git status failed: {stderr}")));
+    }
+
+    Ok(!output.stdout.iter().all(u8::is_ascii_whitespace))
+}
+
+/// Commit time of `HEAD` in a worktree, in seconds since the Unix epoch.
+/// Returns `None` for a repository without commits.
+///
+/// # Errors
+///
+/// Returns `CoreError::Git` if `git log` fails.
+#[instrument]
+pub(crate) async fn last_commit_time(worktree: &Path) -> Result<Option<u64>, CoreError> {
+    let output = tokio::process::Command::new("git")
+        .args(["log", "-1", "--format=%ct"])
+        .current_dir(worktree)
+        .output()
+        .await?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        if stderr.contains("does not have any commits") {
+            return Ok(None);
+        }
+        return Err(CoreError::Git(format!("git log failed: {stderr}")));
+    }
+
+    Ok(String::from_utf8_lossy(&output.stdout).trim().parse().ok())
+}
+
 /// Extract the numeric ID prefix from a feature slug.
 ///
 /// For example, "0001_web_frontend" returns "0001".
@@ -284,4 +421,62 @@ mod tests {
         let ops = GitOps::new(PathBuf::from("/repo"), config);
         assert_eq!(ops.branch_name("0001_login"), "feature/0001_login");
     }
+
+    #[tokio::test]
+    async fn test_should_list_changed_and_untracked_files() {
+        let dir = tempfile::TempDir::new().expect("should create temp dir");
+        let root = dir.path();
+        let git = |args: &[&str]| {
+            let status = std::process::Command::new("git")
+                .args(["-c", "user.name=test", "-c", "user.email=test@example.com"])
+                .args(args)
+                .current_dir(root)
+                .output()
+                .expect("should run git");
+            assert!(status.status.success(), "git {args:?} failed");
+        };
+
+        git(&["init", "-q"]);
+        std::fs::write(root.join("tracked.rs"), "fn a() {}").expect("should write");
+        std::fs::write(root.join("removed.rs"), "fn b() {}").expect("should write");
+        git(&["add", "-A"]);
+        git(&["commit", "-q", "-m", "init"]);
+
+        std::fs::write(root.join("tracked.rs"), "fn a() { }").expect("should write");
+        std::fs::remove_file(root.join("removed.rs")).expect("should remove");
+        std::fs::write(root.join("new.rs"), "fn c() {}").expect("should write");
+
+        let files = list_changed_files(root).await.expect("should list files");
+        assert_eq!(files, vec!["new.rs".to_owned(), "tracked.rs".to_owned()]);
+    }
+
+    #[tokio::test]
+    async fn test_should_detect_dirty_worktree_and_commit_time() {
+        let dir = tempfile::TempDir::new().expect("should create temp dir");
+        let root = dir.path();
+        let git = |args: &[&str]| {
+            let status = std::process::Command::new("git")
+                .args(["-c", "user.name=test", "-c", "user.email=test@example.com"])
+                .args(args)
+                .current_dir(root)
+                .output()
+                .expect("should run git");
+            assert!(status.status.success(), "git {args:?} failed");
+        };
+
+        git(&["init", "-q"]);
+        assert_eq!(last_commit_time(root).await.expect("should read log"), None);
+        std::fs::write(root.join("a.rs"), "fn a() {}").expect("should write");
+        assert!(is_dirty(root).await.expect("should read status"));
+
+        git(&["add", "-A"]);
+        git(&["commit", "-q", "-m", "init"]);
+        assert!(!is_dirty(root).await.expect("should read status"));
+        assert!(
+            last_commit_time(root)
+                .await
+                .expect("should read log")
+                .is_some_and(|t| t > 1_600_000_000)
+        );
+    }
 }
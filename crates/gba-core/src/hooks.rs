@@ -4,13 +4,20 @@
 //! after each phase's code is written, before committing. Each hook's stdout
 //! and stderr are captured for display and for the agent to use when fixing
 //! failures.
+//!
+//! Hooks can also be imported from a pre-commit framework config
+//! (`.pre-commit-config.yaml`), either by invoking `pre-commit run` on the
+//! changed files or by translating `repo: local` hooks into plain commands.
 
 use std::path::Path;
 
+use regex::Regex;
+use serde::Deserialize;
 use tracing::{debug, error, instrument, warn};
 
-use crate::config::{Hook, HooksConfig};
+use crate::config::{Hook, HooksConfig, PreCommitFrameworkConfig, PreCommitMode};
 use crate::error::CoreError;
+use crate::git::list_changed_files;
 
 /// Runs precommit hooks in sequence and reports results.
 ///
@@ -23,6 +30,8 @@ pub(crate) struct HookRunner {
     hooks: Vec<Hook>,
     /// Maximum hook-fix-retry cycles (informational, caller enforces).
     max_retries: u32,
+    /// Optional pre-commit framework import settings.
+    framework: Option<PreCommitFrameworkConfig>,
 }
 
 /// Output from running a single hook.
@@ -46,6 +55,7 @@ impl HookRunner {
         Self {
             hooks: config.pre_commit.clone(),
             max_retries: config.max_retries,
+            framework: config.pre_commit_framework.clone(),
         }
     }
 
@@ -56,28 +66,39 @@ impl HookRunner {
 
     /// Returns whether there are any hooks configured.
     pub(crate) fn has_hooks(&self) -> bool {
-        !self.hooks.is_empty()
+        !self.hooks.is_empty() || self.framework.is_some()
     }
 
     /// Run all configured hooks in sequence.
     ///
     /// Executes each hook command in the given working directory. All hooks
     /// run regardless of whether earlier hooks fail -- the caller gets a
-    /// complete picture of what passed and what failed.
+    /// complete picture of what passed and what failed. Pre-commit framework
+    /// hooks are resolved against the current set of changed files on every
+    /// call, so retries pick up files touched by the fix agent.
     ///
     /// # Errors
     ///
     /// Returns `CoreError::Io` if a hook command cannot be spawned.
+    /// Returns `CoreError::Config` if the pre-commit config cannot be parsed.
+    /// Returns `CoreError::Git` if the changed files cannot be listed.
     #[instrument(skip(self))]
     pub(crate) async fn run_all(&self, cwd: &Path) -> Result<Vec<HookOutput>, CoreError> {
-        let mut results = Vec::with_capacity(self.hooks.len());
+        let mut hooks = self.hooks.clone();
+        if let Some(framework) = &self.framework {
+            let changed = list_changed_files(cwd).await?;
+            hooks.extend(framework_hooks(cwd, framework, &changed)?);
+        }
 
-        for hook in &self.hooks {
+        let mut results = Vec::with_capacity(hooks.len());
+
+        for hook in &hooks {
             debug!(hook = %hook.name, command = %hook.command, "running hook");
 
             let output = match tokio::process::Command::new("sh")
                 .args(["-c", &hook.command])
                 .current_dir(cwd)
+                .kill_on_drop(true)
                 .output()
                 .await
             {
@@ -120,8 +141,327 @@ impl HookRunner {
     }
 }
 
+// ── Pre-commit framework import ──────────────────────────────
+
+/// Top level of a `.pre-commit-config.yaml` file.
+///
+/// Only the fields needed to translate local hooks are modeled; unknown
+/// keys are ignored so newer framework options do not break parsing.
+#[derive(Debug, Deserialize)]
+struct PreCommitFile {
+    #[serde(default)]
+    repos: Vec<PreCommitRepo>,
+    #[serde(default)]
+    files: Option<String>,
+    #[serde(default)]
+    exclude: Option<String>,
+    #[serde(default)]
+    default_stages: Option<Vec<String>>,
+}
+
+/// A repository entry in the pre-commit config.
+#[derive(Debug, Deserialize)]
+struct PreCommitRepo {
+    repo: String,
+    #[serde(default)]
+    hooks: Vec<PreCommitHook>,
+}
+
+/// A hook entry in the pre-commit config.
+#[derive(Debug, Deserialize)]
+struct PreCommitHook {
+    id: String,
+    #[serde(default)]
+    name: Option<String>,
+    #[serde(default)]
+    entry: Option<String>,
+    #[serde(default)]
+    args: Vec<String>,
+    #[serde(default)]
+    files: Option<String>,
+    #[serde(default)]
+    exclude: Option<String>,
+    #[serde(default)]
+    language: Option<String>,
+    #[serde(default)]
+    types: Vec<String>,
+    #[serde(default)]
+    types_or: Vec<String>,
+    #[serde(default)]
+    exclude_types: Vec<String>,
+    #[serde(default = "default_pass_filenames")]
+    pass_filenames: bool,
+    #[serde(default)]
+    always_run: bool,
+    #[serde(default)]
+    stages: Option<Vec<String>>,
+}
+
+fn default_pass_filenames() -> bool {
+    true
+}
+
+/// Hook languages whose `entry` is not a command (a regex, a Docker image)
+/// and which cannot be translated.
+const UNSUPPORTED_LANGUAGES: &[&str] = &["pygrep", "docker", "docker_image"];
+
+/// File type tags derived from file extensions, a subset of the tags the
+/// pre-commit framework's `identify` library reports.
+const EXTENSION_TAGS: &[(&str, &[&str])] = &[
+    ("bash", &["shell", "bash"]),
+    ("c", &["c"]),
+    ("cpp", &["c++"]),
+    ("css", &["css"]),
+    ("go", &["go"]),
+    ("h", &["header", "c"]),
+    ("html", &["html"]),
+    ("js", &["javascript"]),
+    ("json", &["json"]),
+    ("jsx", &["jsx"]),
+    ("markdown", &["markdown"]),
+    ("md", &["markdown"]),
+    ("mjs", &["javascript"]),
+    ("py", &["python"]),
+    ("pyi", &["python", "pyi"]),
+    ("rs", &["rust"]),
+    ("sh", &["shell", "sh"]),
+    ("sql", &["sql"]),
+    ("toml", &["toml"]),
+    ("ts", &["ts"]),
+    ("tsx", &["tsx"]),
+    ("yaml", &["yaml"]),
+    ("yml", &["yaml"]),
+];
+
+/// Tags determined from the file itself rather than its extension.
+const CONTENT_TAGS: &[&str] = &["file", "text", "binary"];
+
+/// Resolve pre-commit framework hooks into plain [`Hook`] entries.
+///
+/// In [`PreCommitMode::Run`], a single `pre-commit` hook runs the framework
+/// on the changed files. In [`PreCommitMode::Translate`], each local hook
+/// becomes its own entry. Returns no hooks when there are no changed files
+/// (and no `always_run` hooks), mirroring the framework's own behavior.
+///
+/// # Errors
+///
+/// Returns `CoreError::Config` if the config is missing (translate mode),
+/// unparsable, or contains an invalid `files`/`exclude` pattern.
+pub(crate) fn framework_hooks(
+    cwd: &Path,
+    framework: &PreCommitFrameworkConfig,
+    changed_files: &[String],
+) -> Result<Vec<Hook>, CoreError> {
+    let config_path = framework.config_path.display().to_string();
+
+    match framework.mode {
+        PreCommitMode::Run => {
+            if changed_files.is_empty() {
+                debug!("no changed files, skipping pre-commit run");
+                return Ok(Vec::new());
+            }
+            let files: Vec<String> = changed_files.iter().map(|f| shell_quote(f)).collect();
+            Ok(vec![Hook {
+                name: "pre-commit".to_owned(),
+                command: format!(
+                    "pre-commit run --config {} --files {}",
+                    shell_quote(&config_path),
+                    files.join(" ")
+                ),
+            }])
+        }
+        PreCommitMode::Translate => {
+            let path = cwd.join(&framework.config_path);
+            let content = std::fs::read_to_string(&path).map_err(|e| {
+                CoreError::Config(format!("failed to read {}: {e}", path.display()))
+            })?;
+            translate_local_hooks(&content, cwd, changed_files)
+        }
+    }
+}
+
+/// Translate the `repo: local` hooks of a pre-commit config into [`Hook`]s.
+///
+/// Applies the global and per-hook `files`/`exclude` patterns and the
+/// `types`/`types_or`/`exclude_types` filters to the changed files (relative
+/// to `cwd`), appends matching files when `pass_filenames` is true, and
+/// skips hooks that are not bound to the `pre-commit` stage. `fail` hooks
+/// fail with their entry as the message; hooks in languages whose entry is
+/// not a command (`pygrep`, `docker`, `docker_image`) or that filter on a
+/// type tag without an equivalent here are skipped with a warning.
+fn translate_local_hooks(
+    content: &str,
+    cwd: &Path,
+    changed_files: &[String],
+) -> Result<Vec<Hook>, CoreError> {
+    let file: PreCommitFile = serde_yaml::from_str(content)
+        .map_err(|e| CoreError::Config(format!("invalid pre-commit config: {e}")))?;
+
+    let global_files = compile_pattern(file.files.as_deref())?;
+    let global_exclude = compile_pattern(file.exclude.as_deref())?;
+
+    let mut hooks = Vec::new();
+
+    for repo in &file.repos {
+        if repo.repo != "local" {
+            warn!(
+                repo = %repo.repo,
+                "skipping remote pre-commit repo (use `mode: run` to include it)"
+            );
+            continue;
+        }
+
+        for hook in &repo.hooks {
+            let stages = hook.stages.as_ref().or(file.default_stages.as_ref());
+            if let Some(stages) = stages
+                && !stages.iter().any(|s| s == "pre-commit" || s == "commit")
+            {
+                debug!(hook = %hook.id, "skipping hook not bound to pre-commit stage");
+                continue;
+            }
+
+            let Some(entry) = hook.entry.as_deref() else {
+                warn!(hook = %hook.id, "skipping local hook without entry");
+                continue;
+            };
+
+            let language = hook.language.as_deref().unwrap_or("system");
+            if UNSUPPORTED_LANGUAGES.contains(&language) {
+                warn!(
+                    hook = %hook.id,
+                    language,
+                    "skipping local hook in a language that cannot be translated (use `mode: run` to include it)"
+                );
+                continue;
+            }
+
+            if let Some(tag) = hook
+                .types
+                .iter()
+                .chain(&hook.types_or)
+                .chain(&hook.exclude_types)
+                .find(|tag| !is_known_tag(tag))
+            {
+                warn!(
+                    hook = %hook.id,
+                    tag = %tag,
+                    "skipping local hook filtering on an unsupported file type (use `mode: run` to include it)"
+                );
+                continue;
+            }
+
+            let files = compile_pattern(hook.files.as_deref())?;
+            let exclude = compile_pattern(hook.exclude.as_deref())?;
+            let matched: Vec<&String> = changed_files
+                .iter()
+                .filter(|f| global_files.as_ref().is_none_or(|re| re.is_match(f)))
+                .filter(|f| !global_exclude.as_ref().is_some_and(|re| re.is_match(f)))
+                .filter(|f| files.as_ref().is_none_or(|re| re.is_match(f)))
+                .filter(|f| !exclude.as_ref().is_some_and(|re| re.is_match(f)))
+                .filter(|f| matches_types(hook, cwd, f))
+                .collect();
+
+            if matched.is_empty() && !hook.always_run {
+                debug!(hook = %hook.id, "no matching files, skipping hook");
+                continue;
+            }
+
+            let mut command = if language == "fail" {
+                format!("printf '%s\\n' {}", shell_quote(entry))
+            } else {
+                let mut command = entry.to_owned();
+                for arg in &hook.args {
+                    command.push(' ');
+                    command.push_str(&shell_quote(arg));
+                }
+                command
+            };
+            if hook.pass_filenames {
+                for f in matched {
+                    command.push(' ');
+                    command.push_str(&shell_quote(f));
+                }
+            }
+            if language == "fail" {
+                command.push_str("; exit 1");
+            }
+
+            hooks.push(Hook {
+                name: hook.name.clone().unwrap_or_else(|| hook.id.clone()),
+                command,
+            });
+        }
+    }
+
+    Ok(hooks)
+}
+
+/// Whether a type tag can be determined for a file.
+fn is_known_tag(tag: &str) -> bool {
+    CONTENT_TAGS.contains(&tag) || EXTENSION_TAGS.iter().any(|(_, tags)| tags.contains(&tag))
+}
+
+/// Apply a hook's `types` (all must match), `types_or` (one must match) and
+/// `exclude_types` (none may match) filters to a file.
+fn matches_types(hook: &PreCommitHook, cwd: &Path, file: &str) -> bool {
+    if hook.types.is_empty() && hook.types_or.is_empty() && hook.exclude_types.is_empty() {
+        return true;
+    }
+    let tags = file_tags(&cwd.join(file));
+    hook.types.iter().all(|t| tags.contains(&t.as_str()))
+        && (hook.types_or.is_empty() || hook.types_or.iter().any(|t| tags.contains(&t.as_str())))
+        && !hook
+            .exclude_types
+            .iter()
+            .any(|t| tags.contains(&t.as_str()))
+}
+
+/// Type tags of a file: `file`, `text` or `binary` (a NUL byte in the first
+/// 8 KiB means binary), and the tags of its extension. Files that cannot
+/// be read (e.g. deleted ones) have no tags.
+fn file_tags(path: &Path) -> Vec<&'static str> {
+    let Ok(content) = std::fs::read(path) else {
+        return Vec::new();
+    };
+    let head = &content[..content.len().min(8192)];
+    let mut tags = vec!["file", if head.contains(&0) { "binary" } else { "text" }];
+
+    let extension = path
+        .extension()
+        .map(|e| e.to_string_lossy().to_lowercase())
+        .unwrap_or_default();
+    if let Some((_, extension_tags)) = EXTENSION_TAGS.iter().find(|(e, _)| *e == extension) {
+        tags.extend_from_slice(extension_tags);
+    }
+    tags
+}
+
+/// Compile an optional pre-commit file pattern.
+fn compile_pattern(pattern: Option<&str>) -> Result<Option<Regex>, CoreError> {
+    pattern
+        .filter(|p| !p.is_empty())
+        .map(|p| {
+            Regex::new(p)
+                .map_err(|e| CoreError::Config(format!("invalid pre-commit pattern {p:?}: {e}")))
+        })
+        .transpose()
+}
+
+/// Quote a string for safe use as a single `sh` word.
+fn shell_quote(s: &str) -> String {
+    if !s.is_empty()
+        && s.chars()
+            .all(|c| c.is_ascii_alphanumeric() || "-_./=:@+,".contains(c))
+    {
+        return s.to_owned();
+    }
+    format!("'{}'", s.replace('\'', "'\\''"))
+}
+
 #[cfg(test)]
 mod tests {
+    use std::path::PathBuf;
+
     use crate::config::HooksConfig;
 
     use super::*;
@@ -130,6 +470,7 @@ mod tests {
         HooksConfig {
             pre_commit: hooks,
             max_retries: 3,
+            pre_commit_framework: None,
         }
     }
 
@@ -234,4 +575,206 @@ mod tests {
         assert!(!results[0].passed);
         assert!(results[0].stderr.contains("error_msg"));
     }
+
+    #[test]
+    fn test_should_build_pre_commit_run_hook_for_changed_files() {
+        let framework = PreCommitFrameworkConfig::default();
+        let changed = vec!["src/main.rs".to_owned(), "docs/my file.md".to_owned()];
+
+        let hooks =
+            framework_hooks(Path::new("/tmp"), &framework, &changed).expect("should resolve hooks");
+
+        assert_eq!(hooks.len(), 1);
+        assert_eq!(hooks[0].name, "pre-commit");
+        assert_eq!(
+            hooks[0].command,
+            "pre-commit run --config .pre-commit-config.yaml --files src/main.rs 'docs/my file.md'"
+        );
+    }
+
+    #[test]
+    fn test_should_skip_pre_commit_run_without_changed_files() {
+        let framework = PreCommitFrameworkConfig::default();
+        let hooks =
+            framework_hooks(Path::new("/tmp"), &framework, &[]).expect("should resolve hooks");
+        assert!(hooks.is_empty());
+    }
+
+    #[test]
+    fn test_should_translate_local_hooks() {
+        let content = r#"
+default_stages: [pre-commit, manual]
+repos:
+  - repo: https://github.com/pre-commit/pre-commit-hooks
+    rev: v5.0.0
+    hooks:
+      - id: trailing-whitespace
+  - repo: local
+    hooks:
+      - id: cargo-fmt
+        name: cargo fmt
+        entry: cargo fmt -- --check
+        language: rust
+        files: \.rs$
+        pass_filenames: false
+      - id: rustfmt-files
+        entry: rustfmt --check
+        args: [--edition, "2024"]
+        files: \.rs$
+      - id: typos
+        entry: typos
+        pass_filenames: false
+        always_run: true
+        files: \.md$
+      - id: commit-msg-lint
+        entry: commitlint
+        stages: [commit-msg]
+"#;
+        let changed = vec!["src/lib.rs".to_owned(), "README.txt".to_owned()];
+
+        let hooks = translate_local_hooks(content, Path::new("/nonexistent"), &changed)
+            .expect("should translate");
+
+        let names: Vec<&str> = hooks.iter().map(|h| h.name.as_str()).collect();
+        assert_eq!(names, vec!["cargo fmt", "rustfmt-files", "typos"]);
+        assert_eq!(hooks[0].command, "cargo fmt -- --check");
+        assert_eq!(
+            hooks[1].command,
+            "rustfmt --check --edition 2024 src/lib.rs"
+        );
+        assert_eq!(hooks[2].command, "typos");
+    }
+
+    #[test]
+    fn test_should_skip_translated_hook_without_matching_files() {
+        let content = "repos:\n  - repo: local\n    hooks:\n      - id: clippy\n        entry: cargo clippy\n        files: \\.rs$\n        pass_filenames: false\n";
+        let hooks = translate_local_hooks(
+            content,
+            Path::new("/nonexistent"),
+            &["README.md".to_owned()],
+        )
+        .expect("should translate");
+        assert!(hooks.is_empty());
+    }
+
+    #[test]
+    fn test_should_translate_hooks_by_language() {
+        let content = r#"
+repos:
+  - repo: local
+    hooks:
+      - id: no-todo
+        entry: TODO|FIXME
+        language: pygrep
+      - id: in-docker
+        entry: hadolint/hadolint
+        language: docker_image
+      - id: no-rej
+        name: no .rej files
+        entry: remove the .rej files
+        language: fail
+        files: \.rej$
+      - id: clippy
+        entry: cargo clippy
+        language: system
+        pass_filenames: false
+"#;
+        let changed = vec!["src/lib.rs".to_owned(), "src/lib.rs.rej".to_owned()];
+
+        let hooks = translate_local_hooks(content, Path::new("/nonexistent"), &changed)
+            .expect("should translate");
+
+        let names: Vec<&str> = hooks.iter().map(|h| h.name.as_str()).collect();
+        assert_eq!(names, vec!["no .rej files", "clippy"]);
+        assert_eq!(
+            hooks[0].command,
+            "printf '%s\\n' 'remove the .rej files' src/lib.rs.rej; exit 1"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_should_fail_translated_fail_hook() {
+        let content = "repos:\n  - repo: local\n    hooks:\n      - id: no-rej\n        entry: remove the .rej files\n        language: fail\n";
+        let hooks =
+            translate_local_hooks(content, Path::new("/nonexistent"), &["a.rej".to_owned()])
+                .expect("should translate");
+        let runner = HookRunner::new(&test_hooks_config(hooks));
+
+        let results = runner
+            .run_all(Path::new("/tmp"))
+            .await
+            .expect("should run hooks");
+
+        assert!(!results[0].passed);
+        assert_eq!(results[0].stdout, "remove the .rej files\na.rej\n");
+    }
+
+    #[test]
+    fn test_should_filter_translated_hooks_by_type() {
+        let dir = tempfile::tempdir().expect("should create temp dir");
+        std::fs::write(dir.path().join("lib.rs"), "fn main() {}\n").expect("should write");
+        std::fs::write(dir.path().join("notes.txt"), "notes\n").expect("should write");
+        std::fs::write(dir.path().join("logo.png"), [0x89, b'P', 0, 0]).expect("should write");
+        let content = r#"
+repos:
+  - repo: local
+    hooks:
+      - id: rustfmt
+        entry: rustfmt --check
+        types: [rust]
+      - id: whitespace
+        entry: check-whitespace
+        types: [text]
+        exclude_types: [markdown]
+      - id: configs
+        entry: check-configs
+        types_or: [yaml, toml]
+      - id: executables
+        entry: check-shebang
+        types: [executable]
+"#;
+        let changed = vec![
+            "lib.rs".to_owned(),
+            "notes.txt".to_owned(),
+            "logo.png".to_owned(),
+            "deleted.rs".to_owned(),
+        ];
+
+        let hooks = translate_local_hooks(content, dir.path(), &changed).expect("should translate");
+
+        let commands: Vec<&str> = hooks.iter().map(|h| h.command.as_str()).collect();
+        assert_eq!(
+            commands,
+            vec![
+                "rustfmt --check lib.rs",
+                "check-whitespace lib.rs notes.txt"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_should_reject_invalid_pre_commit_pattern() {
+        let content = "repos:\n  - repo: local\n    hooks:\n      - id: bad\n        entry: echo\n        files: \"(\"\n";
+        let result =
+            translate_local_hooks(content, Path::new("/nonexistent"), &["a.rs".to_owned()]);
+        assert!(matches!(result, Err(CoreError::Config(_))));
+    }
+
+    #[test]
+    fn test_should_report_missing_pre_commit_config_in_translate_mode() {
+        let framework = PreCommitFrameworkConfig {
+            mode: PreCommitMode::Translate,
+            config_path: PathBuf::from("missing.yaml"),
+        };
+        let result = framework_hooks(Path::new("/nonexistent"), &framework, &[]);
+        assert!(matches!(result, Err(CoreError::Config(_))));
+    }
+
+    #[test]
+    fn test_should_shell_quote_special_characters() {
+        assert_eq!(shell_quote("src/main.rs"), "src/main.rs");
+        assert_eq!(shell_quote("a b"), "'a b'");
+        assert_eq!(shell_quote("it's"), "'it'\\''s'");
+        assert_eq!(shell_quote(""), "''");
+    }
 }
@@ -33,7 +33,8 @@ const MAX_TREE_DEPTH: usize = 4;
 /// 1. Verifies the repository is not already initialized
 /// 2. Creates `.gba/` directory with a default `config.yaml`
 /// 3. Creates `.trees/` directory
-/// 4. Adds `.trees/` to `.gitignore` if not already present
+/// 4. Adds `.trees/`, `.gba/config.local.yaml`, transcripts and lock files
+///    to `.gitignore` if not already present
 /// 5. Generates a directory tree listing of the repository
 /// 6. Calls the init agent to analyze the repo and generate context documents
 ///
@@ -75,10 +76,7 @@ pub(crate) async fn run_init(engine: &Engine) -> Result<(), CoreError> {
     );
 
     // Step 6: Call init agent
-    let context = serde_json::json!({
-        "repo_path": repo_path.display().to_string(),
-        "repo_tree": repo_tree,
-    });
+    let context = template_context(&repo_path, &repo_tree);
     match engine
         .agent_runner()
         .run_agent("init", "init/task", &context, Some(&repo_path))
@@ -105,6 +103,14 @@ pub(crate) async fn run_init(engine: &Engine) -> Result<(), CoreError> {
     Ok(())
 }
 
+/// Context of the init agent's session.
+pub(crate) fn template_context(repo_path: &Path, repo_tree: &str) -> serde_json::Value {
+    serde_json::json!({
+        "repo_path": repo_path.display().to_string(),
+        "repo_tree": repo_tree,
+    })
+}
+
 /// Generate a text tree listing of the repository directory structure.
 ///
 /// Walks the directory tree up to [`MAX_TREE_DEPTH`] levels deep, skipping
@@ -136,12 +142,39 @@ pub(crate) fn generate_repo_tree(repo_path: &Path) -> Result<String, CoreError>
 pub(crate) fn write_default_config(gba_dir: &Path) -> Result<(), CoreError> {
     let default_config = r#"# GBA Configuration
 # See documentation for all available options.
+#
+# Values here can be overridden per developer in .gba/config.local.yaml
+# (gitignored), in ~/.config/gba/config.yaml, or via GBA_* environment
+# variables. Run `gba config show --resolved` to see where each value comes from.
 
 agent:
   # model: claude-sonnet-4-20250514
   # maxTokens: 16384
   permissionMode: auto
 
+# Per-agent overrides (init, plan, code, review, verify)
+# agents:
+#   review:
+#     model: claude-haiku-4-5
+#     maxTurns: 20
+#   code:
+#     model: claude-opus-4-1
+#     env:
+#       RUST_BACKTRACE: "1"
+#     addDirs: [../shared-lib]
+
+# Tool-use policy enforced on every agent; denials are recorded in phases.yaml
+# toolPolicy:
+#   denyCommands: ["git push .*--force", "rm -rf /", "\\bcurl\\b"]
+#   protectedPaths: [".github/**", "Cargo.lock"]
+#   worktreeWritesOnly: true
+#   allowedTools:
+#     verify: [Read, Grep, Glob, Bash]
+
+plan:
+  # Rounds in which the plan agent fixes spec files that fail validation
+  maxRepairAttempts: 3
+
 git:
   autoCommit: true
   branchPattern: "feat/{id}-{slug}"
@@ -150,6 +183,11 @@ git:
 review:
   enabled: true
   maxIterations: 3
+  # Reviewer personas run concurrently; their issues are merged and attributed
+  # (built-in: security, performance, api_design, spec_compliance)
+  # reviewers:
+  #   - name: security
+  #   - name: spec_compliance
 
 verification:
   enabled: true
@@ -158,6 +196,20 @@ verification:
 hooks:
   preCommit: []
   maxRetries: 5
+  # Reuse hooks from .pre-commit-config.yaml (mode: run | translate)
+  # preCommitFramework:
+  #   mode: run
+  #   configPath: .pre-commit-config.yaml
+
+# Run pipeline stages, in order (kinds: phases, review, verification, agent, pr)
+# pipeline:
+#   stages:
+#     - kind: phases
+#     - kind: review
+#     - kind: agent
+#       agent: docs          # runs .gba/agents/docs with template docs/task
+#     - kind: verification
+#     - kind: pr
 "#;
 
     let config_path = gba_dir.join("config.yaml");
@@ -165,17 +217,32 @@ hooks:
     Ok(())
 }
 
-/// Add `.trees/` to `.gitignore` if not already present.
+/// Entries that `gba init` adds to `.gitignore`.
+///
+/// `.trees/` holds feature worktrees; `.gba/config.local.yaml` holds
+/// per-developer config overrides that must not be committed; transcripts
+/// record local agent sessions; the lock files mark a running feature,
+/// queue run or queue update.
+const GITIGNORE_ENTRIES: &[&str] = &[
+    ".trees/",
+    ".gba/config.local.yaml",
+    ".gba/features/*/transcripts/",
+    ".gba/features/*/plan-transcript.*",
+    ".gba/features/*/run.lock",
+    ".gba/queue.lock",
+    ".gba/queue.yaml.lock",
+];
+
+/// Add GBA's entries to `.gitignore` if not already present.
 ///
-/// Creates the `.gitignore` file if it does not exist. Appends `.trees/`
-/// on a new line if the entry is not already in the file.
+/// Creates the `.gitignore` file if it does not exist. Appends each entry in
+/// [`GITIGNORE_ENTRIES`] on a new line if it is not already in the file.
 ///
 /// # Errors
 ///
 /// Returns `CoreError::Io` if the file cannot be read or written.
 pub(crate) fn update_gitignore(repo_path: &Path) -> Result<(), CoreError> {
     let gitignore_path = repo_path.join(".gitignore");
-    let entry = ".trees/";
 
     let content = if gitignore_path.exists() {
         fs::read_to_string(&gitignore_path)?
@@ -183,17 +250,22 @@ pub(crate) fn update_gitignore(repo_path: &Path) -> Result<(), CoreError> {
         String::new()
     };
 
-    // Check if .trees/ is already in .gitignore (exact line match)
-    let already_present = content.lines().any(|line| line.trim() == entry);
-
-    if !already_present {
-        let mut new_content = content;
+    let mut new_content = content.clone();
+    for entry in GITIGNORE_ENTRIES {
+        // Check if the entry is already in .gitignore (exact line match)
+        let already_present = content.lines().any(|line| line.trim() == *entry);
+        if already_present {
+            continue;
+        }
         // Ensure we start on a new line if file is non-empty and doesn't end with newline
         if !new_content.is_empty() && !new_content.ends_with('\n') {
             new_content.push('\n');
         }
         new_content.push_str(entry);
         new_content.push('\n');
+    }
+
+    if new_content != content {
         fs::write(&gitignore_path, new_content)?;
     }
 
@@ -337,6 +409,19 @@ mod tests {
             content.contains("target/"),
             "gitignore should still contain target/"
         );
+        assert!(
+            content.contains(".gba/config.local.yaml"),
+            "gitignore should contain the local config override"
+        );
+        assert!(
+            content.contains(".gba/features/*/run.lock") && content.contains(".gba/queue.lock"),
+            "gitignore should contain the lock files"
+        );
+        assert!(
+            content.contains(".gba/features/*/transcripts/")
+                && content.contains(".gba/features/*/plan-transcript.*"),
+            "gitignore should contain the transcripts"
+        );
     }
 
     #[test]
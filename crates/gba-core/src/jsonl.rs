@@ -0,0 +1,82 @@
+//! JSON Lines transcript files (internal).
+//!
+//! Plan transcripts ([`crate::transcript`]) and run session transcripts
+//! ([`crate::archive`]) are both written with [`append()`] and read with
+//! [`load()`].
+
+use std::fs::{self, OpenOptions};
+use std::io::Write as _;
+use std::path::Path;
+
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+use tracing::{debug, warn};
+
+use crate::error::CoreError;
+
+/// Append `record` to the file at `path` as one line, creating the file if
+/// needed.
+///
+/// Write failures are logged rather than returned: losing a transcript must
+/// not end the session it records.
+pub(crate) fn append<T: Serialize>(path: &Path, record: &T) {
+    if let Err(e) = try_append(path, record) {
+        warn!(path = %path.display(), error = %e, "failed to write transcript");
+    }
+}
+
+fn try_append<T: Serialize>(path: &Path, record: &T) -> Result<(), CoreError> {
+    let mut line = serde_json::to_string(record).map_err(|e| CoreError::Other(e.into()))?;
+    line.push('\n');
+    let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+    file.write_all(line.as_bytes())?;
+    Ok(())
+}
+
+/// Read the records of the file at `path`. Lines that cannot be parsed
+/// (e.g. a line cut short by a crash) are skipped.
+///
+/// # Errors
+///
+/// Returns `CoreError::Io` if the file cannot be read.
+pub(crate) fn load<T: DeserializeOwned>(path: &Path) -> Result<Vec<T>, CoreError> {
+    let records = fs::read_to_string(path)?
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .filter_map(|line| match serde_json::from_str(line) {
+            Ok(record) => Some(record),
+            Err(e) => {
+                debug!(path = %path.display(), error = %e, "skipping unreadable transcript line");
+                None
+            }
+        })
+        .collect();
+    Ok(records)
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::{Value, json};
+
+    use super::*;
+
+    #[test]
+    fn test_should_append_and_skip_truncated_lines() {
+        let dir = tempfile::TempDir::new().expect("should create temp dir");
+        let path = dir.path().join("t.jsonl");
+        append(&path, &json!({"n": 1}));
+        fs::write(
+            &path,
+            fs::read_to_string(&path).expect("should read") + "{\"n\":\n",
+        )
+        .expect("should write");
+        append(&path, &json!({"n": 2}));
+
+        let records: Vec<Value> = load(&path).expect("should load");
+        assert_eq!(records, [json!({"n": 1}), json!({"n": 2})]);
+
+        // Writing into a missing directory is logged, not returned
+        append(&dir.path().join("missing").join("t.jsonl"), &json!({}));
+        assert!(load::<Value>(&dir.path().join("missing.jsonl")).is_err());
+    }
+}
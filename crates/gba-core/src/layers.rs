@@ -0,0 +1,767 @@
+//! Layered configuration resolution.
+//!
+//! [`ProjectConfig`] is resolved from several layers, each overriding the
+//! previous one:
+//!
+//! 1. Built-in defaults
+//! 2. User-global config (`~/.config/gba/config.yaml`)
+//! 3. Repository config (`.gba/config.yaml`)
+//! 4. Local repository config (`.gba/config.local.yaml`, gitignored)
+//! 5. `GBA_*` environment variables
+//! 6. CLI flags carried by [`EngineConfig`]
+//!
+//! Layers are merged as YAML documents (mappings merge recursively, all other
+//! values replace) before the result is deserialized, so a layer only needs
+//! to mention the keys it changes. The origin of every leaf value is tracked
+//! so that `gba config show --resolved` can explain where a value came from.
+
+use std::collections::BTreeMap;
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+use serde_yaml::{Mapping, Value};
+use tracing::{debug, instrument};
+
+use crate::config::{EngineConfig, ProjectConfig};
+use crate::error::CoreError;
+use crate::validate::project_config_schema;
+
+/// Environment variables recognized as configuration overrides.
+///
+/// Each entry maps a variable name to the dotted config key it sets and the
+/// type of that key. String values are used verbatim, so `{slug}` or `2024`
+/// stay strings; booleans and integers are parsed.
+const ENV_OVERRIDES: &[(&str, &str, EnvType)] = &[
+    ("GBA_MODEL", "agent.model", EnvType::String),
+    ("GBA_MAX_TOKENS", "agent.maxTokens", EnvType::Integer),
+    (
+        "GBA_PERMISSION_MODE",
+        "agent.permissionMode",
+        EnvType::String,
+    ),
+    ("GBA_AUTO_COMMIT", "git.autoCommit", EnvType::Bool),
+    ("GBA_BRANCH_PATTERN", "git.branchPattern", EnvType::String),
+    ("GBA_BASE_BRANCH", "git.baseBranch", EnvType::String),
+    (
+        "GBA_PLAN_MAX_REPAIR_ATTEMPTS",
+        "plan.maxRepairAttempts",
+        EnvType::Integer,
+    ),
+    ("GBA_REVIEW_ENABLED", "review.enabled", EnvType::Bool),
+    (
+        "GBA_REVIEW_MAX_ITERATIONS",
+        "review.maxIterations",
+        EnvType::Integer,
+    ),
+    (
+        "GBA_VERIFICATION_ENABLED",
+        "verification.enabled",
+        EnvType::Bool,
+    ),
+    (
+        "GBA_VERIFICATION_MAX_ITERATIONS",
+        "verification.maxIterations",
+        EnvType::Integer,
+    ),
+    (
+        "GBA_HOOKS_MAX_RETRIES",
+        "hooks.maxRetries",
+        EnvType::Integer,
+    ),
+];
+
+/// Type of the config key an environment variable sets.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum EnvType {
+    String,
+    Bool,
+    Integer,
+}
+
+impl EnvType {
+    /// Convert the raw value of `var` into a YAML value of this type.
+    fn parse(self, var: &str, raw: &str) -> Result<Value, CoreError> {
+        let invalid =
+            |expected: &str| CoreError::Config(format!("{var}: expected {expected}, got {raw:?}"));
+        match self {
+            Self::String => Ok(Value::String(raw.to_owned())),
+            Self::Bool => match raw.trim() {
+                "true" => Ok(Value::Bool(true)),
+                "false" => Ok(Value::Bool(false)),
+                _ => Err(invalid("true or false")),
+            },
+            Self::Integer => raw
+                .trim()
+                .parse::<u64>()
+                .map(Value::from)
+                .map_err(|_| invalid("a non-negative integer")),
+        }
+    }
+}
+
+/// Where a resolved configuration value came from.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ConfigOrigin {
+    /// Built-in default value.
+    Default,
+    /// User-global config file.
+    User(PathBuf),
+    /// Shared repository config file (`.gba/config.yaml`).
+    Project(PathBuf),
+    /// Local, uncommitted repository config file (`.gba/config.local.yaml`).
+    Local(PathBuf),
+    /// Environment variable with the given name.
+    Env(String),
+    /// Command-line flag with the given name.
+    Cli(String),
+}
+
+impl fmt::Display for ConfigOrigin {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Default => write!(f, "default"),
+            Self::User(path) => write!(f, "user ({})", path.display()),
+            Self::Project(path) => write!(f, "project ({})", path.display()),
+            Self::Local(path) => write!(f, "local ({})", path.display()),
+            Self::Env(var) => write!(f, "env ({var})"),
+            Self::Cli(flag) => write!(f, "cli ({flag})"),
+        }
+    }
+}
+
+/// A single resolved leaf value with its origin.
+#[derive(Debug, Clone)]
+pub struct ResolvedEntry {
+    /// Dotted key path (e.g., `agent.model`).
+    pub key: String,
+    /// Value rendered as inline YAML.
+    pub value: String,
+    /// Layer that supplied the value.
+    pub origin: ConfigOrigin,
+}
+
+/// The external inputs to configuration resolution.
+///
+/// Separated from [`ResolvedConfig::load`] so that tests can supply their
+/// own user config path and environment instead of the process's.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct ConfigSources {
+    /// Path of the user-global config file, if a home directory is known.
+    pub user_config: Option<PathBuf>,
+    /// Environment variables (`GBA_*` entries are consulted).
+    pub env: Vec<(String, String)>,
+}
+
+impl ConfigSources {
+    /// Capture the sources from the current process environment.
+    pub(crate) fn from_process() -> Self {
+        Self {
+            user_config: user_config_path(),
+            env: std::env::vars()
+                .filter(|(k, _)| k.starts_with("GBA_"))
+                .collect(),
+        }
+    }
+}
+
+/// A fully resolved [`ProjectConfig`] together with per-key origins.
+///
+/// # Examples
+///
+/// ```no_run
+/// use std::path::PathBuf;
+/// use gba_core::{EngineConfig, ResolvedConfig};
+///
+/// let config = EngineConfig::builder().repo_path(PathBuf::from(".")).build();
+/// let resolved = ResolvedConfig::load(&config).expect("valid config");
+/// for entry in resolved.entries() {
+///     println!("{} = {} ({})", entry.key, entry.value, entry.origin);
+/// }
+/// ```
+#[derive(Debug, Clone)]
+pub struct ResolvedConfig {
+    /// The merged configuration.
+    config: ProjectConfig,
+    /// Origin of every leaf key explicitly set by a non-default layer.
+    origins: BTreeMap<String, ConfigOrigin>,
+}
+
+impl ResolvedConfig {
+    /// Resolve the configuration for the repository in `engine_config`.
+    ///
+    /// Reads the user-global config and `GBA_*` variables from the current
+    /// process environment.
+    ///
+    /// # Errors
+    ///
+    /// Returns `CoreError::Io` if a config file exists but cannot be read.
+    /// Returns `CoreError::Config` if a layer contains invalid YAML or the
+    /// merged result does not match the config schema.
+    pub fn load(engine_config: &EngineConfig) -> Result<Self, CoreError> {
+        resolve(engine_config, &ConfigSources::from_process())
+    }
+
+    /// Returns the merged configuration.
+    pub fn config(&self) -> &ProjectConfig {
+        &self.config
+    }
+
+    /// Consume the resolved config, returning the merged configuration.
+    pub fn into_config(self) -> ProjectConfig {
+        self.config
+    }
+
+    /// Returns the origin of a dotted key, falling back to
+    /// [`ConfigOrigin::Default`] when no layer set it.
+    pub fn origin(&self, key: &str) -> &ConfigOrigin {
+        self.origins.get(key).unwrap_or(&ConfigOrigin::Default)
+    }
+
+    /// List every leaf value of the merged configuration with its origin.
+    ///
+    /// Sequences (such as `hooks.preCommit`) are reported as a single entry
+    /// since layers replace them wholesale. Keys that are unset (such as
+    /// `agent.model` when the agent's own model applies) are listed with an
+    /// empty value, or `[]`/`{}` for collections. Entries follow the field order of [`ProjectConfig`].
+    pub fn entries(&self) -> Vec<ResolvedEntry> {
+        let value = serde_yaml::to_value(&self.config).unwrap_or(Value::Null);
+        let mut leaves: Vec<(String, Result<Value, &str>)> = Vec::new();
+        let mut set = Vec::new();
+        flatten(&value, "", &mut set);
+        leaves.extend(set.into_iter().map(|(key, value)| (key, Ok(value))));
+
+        let schema = project_config_schema();
+        let mut keys = Vec::new();
+        schema_keys(&schema, &schema, "", Some(&value), &mut keys);
+        for (key, empty) in keys {
+            let nested = format!("{key}.");
+            if leaves
+                .iter()
+                .any(|(k, _)| *k == key || k.starts_with(&nested))
+            {
+                continue;
+            }
+            // Place the key after its last listed sibling
+            let parent = key.rsplit_once('.').map_or("", |(parent, _)| parent);
+            let position = leaves
+                .iter()
+                .rposition(|(k, _)| k.rsplit_once('.').is_some_and(|(p, _)| p == parent))
+                .map_or(leaves.len(), |i| i + 1);
+            leaves.insert(position, (key, Err(empty)));
+        }
+
+        leaves
+            .into_iter()
+            .map(|(key, value)| ResolvedEntry {
+                origin: self.origin(&key).clone(),
+                value: value
+                    .as_ref()
+                    .map_or_else(|empty| (*empty).to_owned(), render_inline),
+                key,
+            })
+            .collect()
+    }
+}
+
+/// Resolve the configuration from explicit sources.
+#[instrument(skip_all)]
+pub(crate) fn resolve(
+    engine_config: &EngineConfig,
+    sources: &ConfigSources,
+) -> Result<ResolvedConfig, CoreError> {
+    let mut merged = Value::Mapping(Mapping::new());
+    let mut origins = BTreeMap::new();
+
+    let file_layers = [
+        sources.user_config.clone().map(ConfigOrigin::User),
+        Some(ConfigOrigin::Project(engine_config.config_path())),
+        Some(ConfigOrigin::Local(engine_config.local_config_path())),
+    ];
+
+    for origin in file_layers.into_iter().flatten() {
+        let path = match &origin {
+            ConfigOrigin::User(p) | ConfigOrigin::Project(p) | ConfigOrigin::Local(p) => p.clone(),
+            _ => continue,
+        };
+        if let Some(layer) = read_layer(&path)? {
+            debug!(path = %path.display(), "applying config layer");
+            apply_layer(&mut merged, &mut origins, layer, &origin);
+        }
+    }
+
+    for (var, key, ty) in ENV_OVERRIDES {
+        if let Some((_, raw)) = sources.env.iter().find(|(k, _)| k == var) {
+            let value = ty.parse(var, raw)?;
+            apply_layer(
+                &mut merged,
+                &mut origins,
+                nested_value(key, value),
+                &ConfigOrigin::Env((*var).to_owned()),
+            );
+        }
+    }
+
+    for (flag, key, value) in cli_overrides(engine_config) {
+        apply_layer(
+            &mut merged,
+            &mut origins,
+            nested_value(key, value),
+            &ConfigOrigin::Cli(flag.to_owned()),
+        );
+    }
+
+    let config: ProjectConfig = serde_yaml::from_value(merged)
+        .map_err(|e| CoreError::Config(format!("invalid merged configuration: {e}")))?;
+
+    Ok(ResolvedConfig { config, origins })
+}
+
+/// Collect the CLI-level overrides carried by [`EngineConfig`].
+fn cli_overrides(engine_config: &EngineConfig) -> Vec<(&'static str, &'static str, Value)> {
+    let mut overrides = Vec::new();
+    if let Some(model) = engine_config.model() {
+        overrides.push(("--model", "agent.model", Value::from(model)));
+    }
+    if let Some(max_tokens) = engine_config.max_tokens() {
+        overrides.push(("--max-tokens", "agent.maxTokens", Value::from(max_tokens)));
+    }
+    if let Some(mode) = engine_config.permission_mode()
+        && let Ok(value) = serde_yaml::to_value(mode)
+    {
+        overrides.push(("--permission-mode", "agent.permissionMode", value));
+    }
+    overrides
+}
+
+/// Read a config layer file, returning `None` if it does not exist or is empty.
+fn read_layer(path: &Path) -> Result<Option<Value>, CoreError> {
+    if !path.is_file() {
+        return Ok(None);
+    }
+    let content = std::fs::read_to_string(path)?;
+    let value: Value = serde_yaml::from_str(&content)
+        .map_err(|e| CoreError::Config(format!("{}: {e}", path.display())))?;
+    match value {
+        Value::Null => Ok(None),
+        Value::Mapping(_) => Ok(Some(value)),
+        _ => Err(CoreError::Config(format!(
+            "{}: expected a mapping at the top level",
+            path.display()
+        ))),
+    }
+}
+
+/// Merge a layer into the accumulated value and record origins for its leaves.
+fn apply_layer(
+    merged: &mut Value,
+    origins: &mut BTreeMap<String, ConfigOrigin>,
+    layer: Value,
+    origin: &ConfigOrigin,
+) {
+    let mut leaves = Vec::new();
+    flatten(&layer, "", &mut leaves);
+    for (key, _) in leaves {
+        // A layer replacing a whole subtree invalidates deeper origins.
+        let prefix = format!("{key}.");
+        origins.retain(|k, _| !k.starts_with(&prefix));
+        origins.insert(key, origin.clone());
+    }
+    deep_merge(merged, layer);
+}
+
+/// Recursively merge `overlay` into `base`.
+///
+/// Mappings merge key by key; any other overlay value replaces the base.
+fn deep_merge(base: &mut Value, overlay: Value) {
+    match (base, overlay) {
+        (Value::Mapping(base_map), Value::Mapping(overlay_map)) => {
+            for (key, value) in overlay_map {
+                match base_map.get_mut(&key) {
+                    Some(existing) => deep_merge(existing, value),
+                    None => {
+                        base_map.insert(key, value);
+                    }
+                }
+            }
+        }
+        (base, overlay) => *base = overlay,
+    }
+}
+
+/// Build a nested mapping from a dotted key and a leaf value.
+fn nested_value(key: &str, value: Value) -> Value {
+    key.rsplit('.').fold(value, |acc, part| {
+        let mut map = Mapping::new();
+        map.insert(Value::String(part.to_owned()), acc);
+        Value::Mapping(map)
+    })
+}
+
+/// Flatten a YAML value into `(dotted_key, leaf)` pairs.
+///
+/// Mappings are descended into; sequences and scalars are leaves.
+fn flatten(value: &Value, prefix: &str, out: &mut Vec<(String, Value)>) {
+    match value {
+        Value::Mapping(map) if !map.is_empty() => {
+            for (key, child) in map {
+                let key = match key {
+                    Value::String(s) => s.clone(),
+                    other => render_inline(other),
+                };
+                let path = if prefix.is_empty() {
+                    key
+                } else {
+                    format!("{prefix}.{key}")
+                };
+                flatten(child, &path, out);
+            }
+        }
+        _ if !prefix.is_empty() => out.push((prefix.to_owned(), value.clone())),
+        _ => {}
+    }
+}
+
+/// Collect the dotted keys of the config leaves described by a JSON Schema,
+/// each with how an unset value displays (`[]`, `{}` or empty).
+///
+/// Structs are descended into. Maps of structs (such as `agents`) are
+/// descended into for the keys present in `value`; other maps, sequences
+/// and scalars are leaves. An absent optional struct is a single leaf.
+fn schema_keys(
+    root: &serde_json::Value,
+    node: &serde_json::Value,
+    prefix: &str,
+    value: Option<&Value>,
+    out: &mut Vec<(String, &'static str)>,
+) {
+    let join = |key: &str| {
+        if prefix.is_empty() {
+            key.to_owned()
+        } else {
+            format!("{prefix}.{key}")
+        }
+    };
+    let (node, nullable) = schema_target(root, node);
+    let absent = value.is_none_or(Value::is_null);
+    let item = node
+        .get("additionalProperties")
+        .filter(|item| item.is_object())
+        .map(|item| schema_target(root, item).0)
+        .filter(|item| item.get("properties").is_some());
+
+    if let Some(properties) = node.get("properties").and_then(|p| p.as_object())
+        && !(nullable && absent)
+    {
+        for (name, child) in properties {
+            let child_value = value.and_then(|v| v.get(name.as_str()));
+            schema_keys(root, child, &join(name), child_value, out);
+        }
+    } else if let Some(item) = item {
+        if let Some(Value::Mapping(map)) = value {
+            for (key, child) in map {
+                if let Value::String(key) = key {
+                    schema_keys(root, item, &join(key), Some(child), out);
+                }
+            }
+        }
+    } else if !prefix.is_empty() {
+        let has_type = |ty: &str| match node.get("type") {
+            Some(serde_json::Value::String(t)) => t == ty,
+            Some(serde_json::Value::Array(types)) => types.iter().any(|t| t == ty),
+            _ => false,
+        };
+        let empty = if nullable {
+            ""
+        } else if has_type("array") {
+            "[]"
+        } else if has_type("object") {
+            "{}"
+        } else {
+            ""
+        };
+        out.push((prefix.to_owned(), empty));
+    }
+}
+
+/// Follow `$ref` and optional (`anyOf` with `null`) wrappers to the schema
+/// describing a value, returning it and whether the value may be null.
+fn schema_target<'a>(
+    root: &'a serde_json::Value,
+    node: &'a serde_json::Value,
+) -> (&'a serde_json::Value, bool) {
+    if let Some(name) = node
+        .get("$ref")
+        .and_then(|r| r.as_str())
+        .and_then(|r| r.strip_prefix("#/$defs/"))
+        && let Some(target) = root.get("$defs").and_then(|defs| defs.get(name))
+    {
+        return schema_target(root, target);
+    }
+    if let Some(variants) = node.get("anyOf").and_then(|a| a.as_array()) {
+        let is_null =
+            |v: &serde_json::Value| v.get("type").and_then(|t| t.as_str()) == Some("null");
+        if variants.iter().any(is_null)
+            && let Some(inner) = variants.iter().find(|v| !is_null(v))
+        {
+            return (schema_target(root, inner).0, true);
+        }
+    }
+    (node, false)
+}
+
+/// Render a YAML value on a single line for display.
+fn render_inline(value: &Value) -> String {
+    match value {
+        Value::Null => "~".to_owned(),
+        Value::String(s) => s.clone(),
+        Value::Bool(b) => b.to_string(),
+        Value::Number(n) => n.to_string(),
+        other => serde_json::to_string(other).unwrap_or_default(),
+    }
+}
+
+/// Locate the user-global config file.
+///
+/// Uses `$XDG_CONFIG_HOME/gba/config.yaml` when set, otherwise
+/// `$HOME/.config/gba/config.yaml`.
+fn user_config_path() -> Option<PathBuf> {
+    let base = std::env::var_os("XDG_CONFIG_HOME")
+        .filter(|v| !v.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| std::env::var_os("HOME").map(|h| PathBuf::from(h).join(".config")))?;
+    Some(base.join("gba").join("config.yaml"))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use super::*;
+    use crate::config::PermissionMode;
+
+    fn setup_repo(project: &str, local: Option<&str>) -> tempfile::TempDir {
+        let dir = tempfile::TempDir::new().expect("should create temp dir");
+        let gba_dir = dir.path().join(".gba");
+        fs::create_dir_all(&gba_dir).expect("should create .gba dir");
+        fs::write(gba_dir.join("config.yaml"), project).expect("should write config");
+        if let Some(local) = local {
+            fs::write(gba_dir.join("config.local.yaml"), local).expect("should write local");
+        }
+        dir
+    }
+
+    #[test]
+    fn test_should_resolve_defaults_without_any_layer() {
+        let dir = tempfile::TempDir::new().expect("should create temp dir");
+        let engine_config = EngineConfig::builder()
+            .repo_path(dir.path().to_path_buf())
+            .build();
+
+        let resolved =
+            resolve(&engine_config, &ConfigSources::default()).expect("should resolve config");
+
+        assert!(resolved.config().agent.model.is_none());
+        assert_eq!(resolved.config().git.base_branch, "main");
+        assert_eq!(resolved.origin("git.baseBranch"), &ConfigOrigin::Default);
+    }
+
+    #[test]
+    fn test_should_apply_layers_in_precedence_order() {
+        let dir = setup_repo(
+            "agent:\n  model: project-model\ngit:\n  baseBranch: develop\nreview:\n  maxIterations: 5\n",
+            Some("agent:\n  model: local-model\n"),
+        );
+        let user_config = dir.path().join("user.yaml");
+        fs::write(
+            &user_config,
+            "agent:\n  model: user-model\n  permissionMode: manual\ngit:\n  baseBranch: trunk\n",
+        )
+        .expect("should write user config");
+
+        let engine_config = EngineConfig::builder()
+            .repo_path(dir.path().to_path_buf())
+            .build();
+        let sources = ConfigSources {
+            user_config: Some(user_config.clone()),
+            env: vec![("GBA_REVIEW_MAX_ITERATIONS".to_owned(), "7".to_owned())],
+        };
+
+        let resolved = resolve(&engine_config, &sources).expect("should resolve config");
+        let config = resolved.config();
+
+        assert_eq!(config.agent.model.as_deref(), Some("local-model"));
+        assert_eq!(config.agent.permission_mode, PermissionMode::Manual);
+        assert_eq!(config.git.base_branch, "develop");
+        assert_eq!(config.review.max_iterations, 7);
+
+        assert!(matches!(
+            resolved.origin("agent.model"),
+            ConfigOrigin::Local(_)
+        ));
+        assert_eq!(
+            resolved.origin("agent.permissionMode"),
+            &ConfigOrigin::User(user_config)
+        );
+        assert!(matches!(
+            resolved.origin("git.baseBranch"),
+            ConfigOrigin::Project(_)
+        ));
+        assert_eq!(
+            resolved.origin("review.maxIterations"),
+            &ConfigOrigin::Env("GBA_REVIEW_MAX_ITERATIONS".to_owned())
+        );
+    }
+
+    #[test]
+    fn test_should_prefer_cli_flags_over_env() {
+        let dir = setup_repo("agent:\n  model: project-model\n", None);
+        let engine_config = EngineConfig::builder()
+            .repo_path(dir.path().to_path_buf())
+            .model("cli-model")
+            .permission_mode(PermissionMode::None)
+            .build();
+        let sources = ConfigSources {
+            user_config: None,
+            env: vec![("GBA_MODEL".to_owned(), "env-model".to_owned())],
+        };
+
+        let resolved = resolve(&engine_config, &sources).expect("should resolve config");
+
+        assert_eq!(resolved.config().agent.model.as_deref(), Some("cli-model"));
+        assert_eq!(
+            resolved.config().agent.permission_mode,
+            PermissionMode::None
+        );
+        assert_eq!(
+            resolved.origin("agent.model"),
+            &ConfigOrigin::Cli("--model".to_owned())
+        );
+    }
+
+    #[test]
+    fn test_should_keep_string_env_values_verbatim() {
+        let dir = tempfile::TempDir::new().expect("should create temp dir");
+        let engine_config = EngineConfig::builder()
+            .repo_path(dir.path().to_path_buf())
+            .build();
+        let sources = ConfigSources {
+            user_config: None,
+            env: vec![
+                ("GBA_BRANCH_PATTERN".to_owned(), "{slug}".to_owned()),
+                ("GBA_BASE_BRANCH".to_owned(), "2024".to_owned()),
+                ("GBA_MODEL".to_owned(), "opus: fast".to_owned()),
+                ("GBA_AUTO_COMMIT".to_owned(), "false".to_owned()),
+                ("GBA_MAX_TOKENS".to_owned(), "4096".to_owned()),
+            ],
+        };
+
+        let resolved = resolve(&engine_config, &sources).expect("should resolve config");
+        let config = resolved.config();
+
+        assert_eq!(config.git.branch_pattern, "{slug}");
+        assert_eq!(config.git.base_branch, "2024");
+        assert_eq!(config.agent.model.as_deref(), Some("opus: fast"));
+        assert!(!config.git.auto_commit);
+        assert_eq!(config.agent.max_tokens, Some(4096));
+    }
+
+    #[test]
+    fn test_should_reject_mistyped_env_value() {
+        let dir = tempfile::TempDir::new().expect("should create temp dir");
+        let engine_config = EngineConfig::builder()
+            .repo_path(dir.path().to_path_buf())
+            .build();
+        let sources = ConfigSources {
+            user_config: None,
+            env: vec![("GBA_REVIEW_MAX_ITERATIONS".to_owned(), "many".to_owned())],
+        };
+
+        let result = resolve(&engine_config, &sources);
+        assert!(
+            matches!(result, Err(CoreError::Config(ref msg)) if msg.contains("GBA_REVIEW_MAX_ITERATIONS"))
+        );
+    }
+
+    #[test]
+    fn test_should_report_entries_with_origins() {
+        let dir = setup_repo(
+            "hooks:\n  preCommit:\n    - name: build\n      command: cargo build\n",
+            None,
+        );
+        let engine_config = EngineConfig::builder()
+            .repo_path(dir.path().to_path_buf())
+            .build();
+
+        let resolved =
+            resolve(&engine_config, &ConfigSources::default()).expect("should resolve config");
+        let entries = resolved.entries();
+
+        let hooks = entries
+            .iter()
+            .find(|e| e.key == "hooks.preCommit")
+            .expect("should list hooks");
+        assert!(matches!(hooks.origin, ConfigOrigin::Project(_)));
+        assert!(hooks.value.contains("cargo build"));
+
+        let auto_commit = entries
+            .iter()
+            .find(|e| e.key == "git.autoCommit")
+            .expect("should list autoCommit");
+        assert_eq!(auto_commit.value, "true");
+        assert_eq!(auto_commit.origin, ConfigOrigin::Default);
+    }
+
+    #[test]
+    fn test_should_list_unset_keys_as_defaults() {
+        let dir = setup_repo("agents:\n  code:\n    maxTurns: 40\n", None);
+        let engine_config = EngineConfig::builder()
+            .repo_path(dir.path().to_path_buf())
+            .build();
+
+        let resolved =
+            resolve(&engine_config, &ConfigSources::default()).expect("should resolve config");
+        let entries = resolved.entries();
+        let entry = |key: &str| {
+            entries
+                .iter()
+                .find(|e| e.key == key)
+                .unwrap_or_else(|| panic!("should list {key}"))
+        };
+
+        for key in [
+            "agent.model",
+            "agent.maxTokens",
+            "agents.code.model",
+            "agents.code.permissionMode",
+        ] {
+            assert_eq!(entry(key).value, "", "{key}");
+            assert_eq!(entry(key).origin, ConfigOrigin::Default, "{key}");
+        }
+        assert_eq!(entry("agents.code.maxTurns").value, "40");
+        assert!(matches!(
+            entry("agents.code.maxTurns").origin,
+            ConfigOrigin::Project(_)
+        ));
+
+        // Unset keys are listed next to their siblings
+        let position = |key: &str| entries.iter().position(|e| e.key == key);
+        assert!(position("agent.model") < position("git.autoCommit"));
+    }
+
+    #[test]
+    fn test_should_reject_invalid_layer() {
+        let dir = setup_repo("agent:\n  model: ok\n", Some("- not a mapping\n"));
+        let engine_config = EngineConfig::builder()
+            .repo_path(dir.path().to_path_buf())
+            .build();
+
+        let result = resolve(&engine_config, &ConfigSources::default());
+        assert!(matches!(result, Err(CoreError::Config(_))));
+    }
+
+    #[test]
+    fn test_should_build_nested_value_from_dotted_key() {
+        let value = nested_value("agent.model", Value::from("m"));
+        assert_eq!(value["agent"]["model"], Value::from("m"));
+    }
+}
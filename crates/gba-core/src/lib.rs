@@ -5,6 +5,7 @@
 //! - **Init**: Initialize a repository for GBA usage
 //! - **Plan**: Interactive planning session to produce feature specs
 //! - **Run**: Automated phase-by-phase execution of the plan
+//! - **Queue**: Runs of several planned features, optionally in parallel
 //!
 //! The CLI layer (`gba-cli`) constructs an [`EngineConfig`], creates an
 //! [`Engine`], and drives it using the event stream APIs ([`PlanSession`],
@@ -17,25 +18,53 @@ mod engine;
 mod error;
 mod events;
 mod init;
+mod layers;
 mod plan;
+mod queue;
+mod report;
 mod run;
 mod spec;
+mod status;
+mod validate;
 
 // Internal modules (not re-exported).
 mod agent;
+mod archive;
 mod git;
 mod hooks;
+mod jsonl;
+mod lock;
+mod permission;
+mod policy;
+mod prompts;
+mod review;
+mod transcript;
 
 // ── Public re-exports ────────────────────────────────────────
 
+pub use archive::{SessionItem, SessionRecord, ToolCall};
 pub use config::{
-    AgentProjectConfig, EngineConfig, GitConfig, Hook, HooksConfig, PermissionMode, ProjectConfig,
-    PromptsConfig, ReviewConfig, VerificationConfig,
+    AgentOverride, AgentProjectConfig, EngineConfig, GitConfig, Hook, HooksConfig, PermissionMode,
+    PipelineConfig, PlanConfig, PreCommitFrameworkConfig, PreCommitMode, ProjectConfig,
+    PromptsConfig, ReviewConfig, ReviewerConfig, StageConfig, StageKind, ToolPolicyConfig,
+    VerificationConfig,
 };
 pub use engine::Engine;
 pub use error::CoreError;
-pub use events::{Issue, PlanEvent, PlanSession, RunEvent, RunStream, Severity};
+pub use events::{
+    EVENT_SCHEMA_VERSION, EventRecord, Issue, PermissionDecision, PermissionRequest, PhaseSummary,
+    PlanEvent, PlanSession, RunEvent, RunStream, Severity,
+};
+pub use layers::{ConfigOrigin, ResolvedConfig, ResolvedEntry};
+pub use queue::{Queue, QueueEntry, QueueEvent, QueueRun, QueueStatus};
+pub use report::{CommitSummary, FileChange, Report, UsageTotals};
+pub use review::{ReviewRound, ReviewerFailure};
 pub use spec::{
-    Execution, FeatureSpec, Phase, PhaseResult, ReviewResult, StepStatus, VerificationPlan,
-    VerificationResult,
+    AgentUsage, Execution, FeatureSpec, HookRun, Phase, PhaseResult, ReviewResult, StageResult,
+    StepStatus, ToolDenial, VerificationPlan, VerificationResult,
+};
+pub use status::{FeatureStatus, WorktreeState};
+pub use validate::{
+    Diagnostic, feature_spec_schema, project_config_schema, validate_feature_spec,
+    validate_project_config, validate_prompts,
 };
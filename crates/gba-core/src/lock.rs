@@ -0,0 +1,175 @@
+//! Pid lock files (internal).
+//!
+//! A lock file holds the pid of the process that took it. It is created
+//! exclusively and removed again when the [`PidLock`] is dropped. A lock file
+//! left behind by a process that no longer runs is stale and is taken over.
+
+use std::fs::{self, OpenOptions};
+use std::io::{ErrorKind, Write as _};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, Instant};
+
+use tracing::{debug, warn};
+
+use crate::error::CoreError;
+
+/// How often [`PidLock::wait()`] checks whether the lock was released.
+const WAIT_INTERVAL: Duration = Duration::from_millis(20);
+
+/// An exclusively held lock file, released on drop.
+#[derive(Debug)]
+pub(crate) struct PidLock {
+    /// Path of the lock file.
+    path: PathBuf,
+}
+
+impl PidLock {
+    /// Take the lock at `path`. `what` names the locked resource in the
+    /// error.
+    ///
+    /// # Errors
+    ///
+    /// Returns `CoreError::Locked` if a running process holds the lock.
+    /// Returns `CoreError::Io` if the lock file cannot be created.
+    pub(crate) fn acquire(path: &Path, what: &str) -> Result<Self, CoreError> {
+        loop {
+            match create_with_pid(path) {
+                Ok(()) => {
+                    debug!(path = %path.display(), "acquired lock");
+                    return Ok(Self {
+                        path: path.to_path_buf(),
+                    });
+                }
+                Err(e) if e.kind() == ErrorKind::AlreadyExists => {}
+                Err(e) => return Err(e.into()),
+            }
+
+            let pid = read_pid(path);
+            if let Some(pid) = pid.filter(|pid| is_alive(*pid)) {
+                return Err(CoreError::Locked(format!("{what} (held by process {pid})")));
+            }
+            // Only remove the file if no one took it over in the meantime
+            if read_pid(path) == pid {
+                warn!(path = %path.display(), ?pid, "removing stale lock");
+                match fs::remove_file(path) {
+                    Ok(()) => {}
+                    Err(e) if e.kind() == ErrorKind::NotFound => {}
+                    Err(e) => return Err(e.into()),
+                }
+            }
+        }
+    }
+
+    /// Take the lock at `path`, waiting up to `timeout` for a running
+    /// holder to release it.
+    ///
+    /// # Errors
+    ///
+    /// Same as [`acquire()`](PidLock::acquire), once `timeout` has passed.
+    pub(crate) fn wait(path: &Path, what: &str, timeout: Duration) -> Result<Self, CoreError> {
+        let deadline = Instant::now() + timeout;
+        loop {
+            match Self::acquire(path, what) {
+                Err(CoreError::Locked(_)) if Instant::now() < deadline => {
+                    std::thread::sleep(WAIT_INTERVAL);
+                }
+                result => return result,
+            }
+        }
+    }
+}
+
+impl Drop for PidLock {
+    fn drop(&mut self) {
+        match fs::remove_file(&self.path) {
+            Ok(()) => debug!(path = %self.path.display(), "released lock"),
+            Err(e) => warn!(path = %self.path.display(), error = %e, "failed to remove lock"),
+        }
+    }
+}
+
+/// Create the lock file at `path` holding this process's pid.
+///
+/// The pid is written to a temporary file first, which is then linked to
+/// `path`, so a lock file is never seen without its pid.
+fn create_with_pid(path: &Path) -> std::io::Result<()> {
+    static NEXT: AtomicU64 = AtomicU64::new(0);
+    let mut tmp = path.as_os_str().to_owned();
+    tmp.push(format!(
+        ".{}.{}",
+        std::process::id(),
+        NEXT.fetch_add(1, Ordering::Relaxed)
+    ));
+    let tmp = PathBuf::from(tmp);
+
+    let mut file = OpenOptions::new().write(true).create_new(true).open(&tmp)?;
+    let linked = write!(file, "{}", std::process::id()).and_then(|()| fs::hard_link(&tmp, path));
+    let _ = fs::remove_file(&tmp);
+    linked
+}
+
+/// Pid written to the lock file. `None` if the file is missing or does not
+/// hold a pid.
+fn read_pid(path: &Path) -> Option<u32> {
+    fs::read_to_string(path).ok()?.trim().parse().ok()
+}
+
+/// Whether the process `pid` is running.
+#[cfg(unix)]
+fn is_alive(pid: u32) -> bool {
+    if pid == std::process::id() {
+        return true;
+    }
+    std::process::Command::new("kill")
+        .args(["-0", &pid.to_string()])
+        .stdout(std::process::Stdio::null())
+        .stderr(std::process::Stdio::null())
+        .status()
+        .is_ok_and(|status| status.success())
+}
+
+/// Whether the process `pid` is running. Without a way to check, every
+/// holder is assumed to be running.
+#[cfg(not(unix))]
+fn is_alive(_pid: u32) -> bool {
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_should_hold_lock_until_dropped() {
+        let dir = tempfile::TempDir::new().expect("should create temp dir");
+        let path = dir.path().join("run.lock");
+
+        let lock = PidLock::acquire(&path, "feature a").expect("should lock");
+        let err = PidLock::acquire(&path, "feature a").expect_err("should be locked");
+        assert!(matches!(&err, CoreError::Locked(msg) if msg.starts_with("feature a")));
+        assert!(matches!(
+            PidLock::wait(&path, "feature a", Duration::from_millis(50)),
+            Err(CoreError::Locked(_))
+        ));
+
+        drop(lock);
+        assert!(!path.exists());
+        PidLock::acquire(&path, "feature a").expect("should lock again");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_should_take_over_stale_lock() {
+        let dir = tempfile::TempDir::new().expect("should create temp dir");
+        let path = dir.path().join("run.lock");
+        // A pid far above any real pid limit
+        fs::write(&path, "4294967").expect("should write lock");
+
+        let _lock = PidLock::acquire(&path, "feature a").expect("should take over");
+        assert_eq!(
+            fs::read_to_string(&path).expect("should read lock"),
+            std::process::id().to_string()
+        );
+    }
+}
@@ -0,0 +1,496 @@
+//! Interactive tool approvals (internal).
+//!
+//! In `manual` permission mode every tool call an agent makes during
+//! `gba run` is sent to the consumer of the [`RunStream`] as a
+//! [`RunEvent::PermissionRequested`], and the agent waits until the answer
+//! comes back through [`RunStream::respond_permission`]. Calls denied by
+//! the tool policy are reported as [`RunEvent::ToolDenied`] and kept for the
+//! run record. The same handle streams each session's text and usage to the
+//! run as [`RunEvent::AgentOutput`] and [`RunEvent::UsageReported`], keeps
+//! the usage for the run record too, and appends the session's prompts and
+//! messages to its transcript.
+//!
+//! [`RunStream`]: crate::events::RunStream
+//! [`RunStream::respond_permission`]: crate::events::RunStream::respond_permission
+
+use std::collections::{HashMap, HashSet};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+
+use claude_agent_sdk_rs::{ContentBlock, Message, ResultMessage};
+use tokio::sync::{mpsc, oneshot};
+use tracing::{debug, instrument};
+
+use crate::archive::{SessionEntry, SessionTranscript};
+use crate::events::{PermissionDecision, PermissionRequest, RunEvent};
+use crate::spec::{AgentUsage, ToolDenial};
+
+/// A user's answer to the permission request with the given id.
+pub(crate) type PermissionResponse = (u64, PermissionDecision);
+
+/// Answer senders of unanswered requests, keyed by id.
+type PendingRequests = HashMap<u64, oneshot::Sender<PermissionDecision>>;
+
+/// Routes permission requests to the run event stream and answers back to
+/// the waiting agents.
+#[derive(Debug)]
+pub(crate) struct PermissionBroker {
+    /// Channel the requests are emitted on.
+    event_tx: mpsc::Sender<RunEvent>,
+    /// Requests waiting for an answer, keyed by id. `None` once the
+    /// response channel has closed.
+    pending: Mutex<Option<PendingRequests>>,
+    /// Id of the next request.
+    next_id: AtomicU64,
+    /// Tools the user allowed for the rest of the run.
+    always_allowed: Mutex<HashSet<String>>,
+    /// Tool calls denied by the tool policy.
+    denials: Mutex<Vec<ToolDenial>>,
+    /// Usage of the finished agent sessions.
+    usage: Mutex<Vec<AgentUsage>>,
+}
+
+impl PermissionBroker {
+    /// Create a broker and spawn the task that delivers answers from
+    /// `response_rx` to the waiting requests.
+    ///
+    /// When `response_rx` closes, pending and later requests are denied.
+    pub(crate) fn spawn(
+        event_tx: mpsc::Sender<RunEvent>,
+        mut response_rx: mpsc::Receiver<PermissionResponse>,
+    ) -> Arc<Self> {
+        let broker = Arc::new(Self {
+            event_tx,
+            pending: Mutex::new(Some(HashMap::new())),
+            next_id: AtomicU64::new(1),
+            always_allowed: Mutex::new(HashSet::new()),
+            denials: Mutex::new(Vec::new()),
+            usage: Mutex::new(Vec::new()),
+        });
+
+        let weak = Arc::downgrade(&broker);
+        tokio::spawn(async move {
+            while let Some((id, decision)) = response_rx.recv().await {
+                let Some(broker) = weak.upgrade() else {
+                    return;
+                };
+                broker.resolve(id, decision);
+            }
+            if let Some(broker) = weak.upgrade() {
+                // Dropping the senders denies every pending request
+                broker.pending_requests().take();
+            }
+        });
+
+        broker
+    }
+
+    /// Ask the user whether `agent` may call `tool_name` with `input`.
+    ///
+    /// Tools allowed with [`PermissionDecision::AllowAlways`] earlier in the
+    /// run are allowed without asking again.
+    #[instrument(skip(self, input))]
+    pub(crate) async fn request(
+        &self,
+        agent: &str,
+        phase: &str,
+        tool_name: &str,
+        input: serde_json::Value,
+    ) -> PermissionDecision {
+        if self.allowed_tools().contains(tool_name) {
+            debug!("tool allowed for the rest of the run");
+            return PermissionDecision::AllowAlways;
+        }
+
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+        let (tx, rx) = oneshot::channel();
+        match self.pending_requests().as_mut() {
+            Some(pending) => pending.insert(id, tx),
+            None => return closed_decision(),
+        };
+
+        let request = PermissionRequest {
+            id,
+            agent: agent.to_owned(),
+            phase: phase.to_owned(),
+            tool_name: tool_name.to_owned(),
+            input,
+        };
+        if self
+            .event_tx
+            .send(RunEvent::PermissionRequested(request))
+            .await
+            .is_err()
+        {
+            if let Some(pending) = self.pending_requests().as_mut() {
+                pending.remove(&id);
+            }
+            return closed_decision();
+        }
+
+        let decision = rx.await.unwrap_or_else(|_| closed_decision());
+        if decision == PermissionDecision::AllowAlways {
+            self.allowed_tools().insert(tool_name.to_owned());
+        }
+        debug!(id, ?decision, "permission answered");
+        decision
+    }
+
+    /// Record a tool call denied by the tool policy and report it on the
+    /// event stream.
+    pub(crate) async fn report_denial(&self, denial: ToolDenial) {
+        debug!(agent = %denial.agent, tool = %denial.tool, reason = %denial.reason, "tool denied");
+        self.denials
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .push(denial.clone());
+        let _ = self.event_tx.send(RunEvent::ToolDenied(denial)).await;
+    }
+
+    /// Record the usage of a finished agent session and report it on the
+    /// event stream.
+    async fn report_usage(&self, usage: AgentUsage) {
+        self.usage
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .push(usage.clone());
+        self.report(RunEvent::UsageReported(usage)).await;
+    }
+
+    /// Send a progress event. A closed stream is ignored; the run notices
+    /// it on its next stage event.
+    async fn report(&self, event: RunEvent) {
+        let _ = self.event_tx.send(event).await;
+    }
+
+    /// Take the denials recorded so far.
+    pub(crate) fn take_denials(&self) -> Vec<ToolDenial> {
+        std::mem::take(&mut *self.denials.lock().unwrap_or_else(PoisonError::into_inner))
+    }
+
+    /// Take the session usage recorded so far.
+    pub(crate) fn take_usage(&self) -> Vec<AgentUsage> {
+        std::mem::take(&mut *self.usage.lock().unwrap_or_else(PoisonError::into_inner))
+    }
+
+    /// Deliver an answer to a pending request. Unknown ids are ignored.
+    fn resolve(&self, id: u64, decision: PermissionDecision) {
+        let tx = self
+            .pending_requests()
+            .as_mut()
+            .and_then(|pending| pending.remove(&id));
+        match tx {
+            Some(tx) => {
+                let _ = tx.send(decision);
+            }
+            None => debug!(id, "answer for unknown permission request"),
+        }
+    }
+
+    fn pending_requests(&self) -> MutexGuard<'_, Option<PendingRequests>> {
+        self.pending.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    fn allowed_tools(&self) -> MutexGuard<'_, HashSet<String>> {
+        self.always_allowed
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+/// Decision used when nobody is left to answer.
+fn closed_decision() -> PermissionDecision {
+    PermissionDecision::Deny {
+        reason: "run stream closed before the request was answered".to_owned(),
+    }
+}
+
+/// Asks the user to approve the tool calls of one agent session.
+#[derive(Debug, Clone)]
+pub(crate) struct ToolApprover {
+    /// Broker of the run the agent belongs to.
+    broker: Arc<PermissionBroker>,
+    /// Agent making the tool calls.
+    agent: String,
+    /// Phase or stage the agent works on.
+    phase: String,
+    /// Transcript the session is recorded to.
+    transcript: Option<Arc<SessionTranscript>>,
+}
+
+impl ToolApprover {
+    /// Create an approver for `agent` working on `phase`.
+    pub(crate) fn new(broker: Arc<PermissionBroker>, agent: &str, phase: &str) -> Self {
+        Self {
+            broker,
+            agent: agent.to_owned(),
+            phase: phase.to_owned(),
+            transcript: None,
+        }
+    }
+
+    /// Record the session's prompts and messages to `transcript`.
+    pub(crate) fn with_transcript(self, transcript: Arc<SessionTranscript>) -> Self {
+        Self {
+            transcript: Some(transcript),
+            ..self
+        }
+    }
+
+    /// Record the rendered prompts the session starts with.
+    pub(crate) fn record_prompt(&self, system: &str, task: &str) {
+        self.record(SessionEntry::Prompt {
+            system: system.to_owned(),
+            task: task.to_owned(),
+        });
+    }
+
+    /// Record that the session failed.
+    pub(crate) fn record_error(&self, message: &str) {
+        self.record(SessionEntry::Error {
+            message: message.to_owned(),
+        });
+    }
+
+    fn record(&self, entry: SessionEntry) {
+        if let Some(transcript) = &self.transcript {
+            transcript.record(entry);
+        }
+    }
+
+    /// Record that the tool policy denied the agent's call of `tool_name`.
+    pub(crate) async fn report_denial(&self, tool_name: &str, reason: &str) {
+        self.broker
+            .report_denial(ToolDenial {
+                agent: self.agent.clone(),
+                phase: self.phase.clone(),
+                tool: tool_name.to_owned(),
+                reason: reason.to_owned(),
+            })
+            .await;
+    }
+
+    /// Report a message of the agent's session: assistant text as
+    /// [`RunEvent::AgentOutput`], the final result as
+    /// [`RunEvent::UsageReported`]. Every message is recorded to the
+    /// transcript.
+    pub(crate) async fn report_message(&self, message: &Message) {
+        self.record(SessionEntry::Message {
+            message: message.clone(),
+        });
+        match message {
+            Message::Assistant(assistant) => {
+                for block in &assistant.message.content {
+                    if let ContentBlock::Text(text) = block
+                        && !text.text.trim().is_empty()
+                    {
+                        self.broker
+                            .report(RunEvent::AgentOutput {
+                                agent: self.agent.clone(),
+                                phase: self.phase.clone(),
+                                text: text.text.clone(),
+                            })
+                            .await;
+                    }
+                }
+            }
+            Message::Result(result) => {
+                let usage = session_usage(&self.agent, &self.phase, result);
+                self.broker.report_usage(usage).await;
+            }
+            _ => {}
+        }
+    }
+
+    /// Ask whether the agent may call `tool_name` with `input`.
+    pub(crate) async fn approve(
+        &self,
+        tool_name: &str,
+        input: serde_json::Value,
+    ) -> PermissionDecision {
+        self.broker
+            .request(&self.agent, &self.phase, tool_name, input)
+            .await
+    }
+}
+
+/// Usage of a finished session from its result message.
+fn session_usage(agent: &str, phase: &str, result: &ResultMessage) -> AgentUsage {
+    let tokens = |field: &str| {
+        result
+            .usage
+            .as_ref()
+            .and_then(|usage| usage.get(field))
+            .and_then(serde_json::Value::as_u64)
+            .unwrap_or(0)
+    };
+    AgentUsage {
+        agent: agent.to_owned(),
+        phase: phase.to_owned(),
+        turns: result.num_turns,
+        input_tokens: tokens("input_tokens")
+            + tokens("cache_read_input_tokens")
+            + tokens("cache_creation_input_tokens"),
+        output_tokens: tokens("output_tokens"),
+        cost_usd: result.total_cost_usd,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn broker() -> (
+        Arc<PermissionBroker>,
+        mpsc::Receiver<RunEvent>,
+        mpsc::Sender<PermissionResponse>,
+    ) {
+        let (event_tx, event_rx) = mpsc::channel(8);
+        let (response_tx, response_rx) = mpsc::channel(8);
+        (
+            PermissionBroker::spawn(event_tx, response_rx),
+            event_rx,
+            response_tx,
+        )
+    }
+
+    /// Answer the next permission request with `decision`.
+    async fn answer(
+        event_rx: &mut mpsc::Receiver<RunEvent>,
+        response_tx: &mpsc::Sender<PermissionResponse>,
+        decision: PermissionDecision,
+    ) -> PermissionRequest {
+        let Some(RunEvent::PermissionRequested(request)) = event_rx.recv().await else {
+            panic!("expected a permission request");
+        };
+        response_tx
+            .send((request.id, decision))
+            .await
+            .expect("should send answer");
+        request
+    }
+
+    #[tokio::test]
+    async fn test_should_route_permission_answers_to_requests() {
+        let (broker, mut event_rx, response_tx) = broker();
+        let approver = ToolApprover::new(broker, "code", "Phase 1");
+
+        let pending = tokio::spawn({
+            let approver = approver.clone();
+            async move {
+                approver
+                    .approve("Bash", serde_json::json!({"command": "ls"}))
+                    .await
+            }
+        });
+        let request = answer(
+            &mut event_rx,
+            &response_tx,
+            PermissionDecision::Deny {
+                reason: "not now".to_owned(),
+            },
+        )
+        .await;
+
+        assert_eq!(request.agent, "code");
+        assert_eq!(request.phase, "Phase 1");
+        assert_eq!(request.tool_name, "Bash");
+        assert_eq!(request.input["command"], "ls");
+        assert_eq!(
+            pending.await.expect("should join"),
+            PermissionDecision::Deny {
+                reason: "not now".to_owned()
+            }
+        );
+    }
+
+    #[tokio::test]
+    async fn test_should_remember_allow_always_for_the_run() {
+        let (broker, mut event_rx, response_tx) = broker();
+        let approver = ToolApprover::new(broker, "code", "Phase 1");
+
+        let pending = tokio::spawn({
+            let approver = approver.clone();
+            async move { approver.approve("Edit", serde_json::Value::Null).await }
+        });
+        answer(&mut event_rx, &response_tx, PermissionDecision::AllowAlways).await;
+        assert_eq!(
+            pending.await.expect("should join"),
+            PermissionDecision::AllowAlways
+        );
+
+        // Second call is allowed without a new request
+        let decision = approver.approve("Edit", serde_json::Value::Null).await;
+        assert_eq!(decision, PermissionDecision::AllowAlways);
+        assert!(event_rx.try_recv().is_err());
+    }
+
+    #[tokio::test]
+    async fn test_should_report_agent_output_and_usage() {
+        let (broker, mut event_rx, _response_tx) = broker();
+        let approver = ToolApprover::new(Arc::clone(&broker), "code", "Phase 1");
+        let message = |value: serde_json::Value| -> Message {
+            serde_json::from_value(value).expect("should parse message")
+        };
+
+        approver
+            .report_message(&message(serde_json::json!({
+                "type": "assistant",
+                "message": {"content": [
+                    {"type": "text", "text": "Adding the login route"},
+                    {"type": "tool_use", "id": "t1", "name": "Edit", "input": {}}
+                ]}
+            })))
+            .await;
+        approver
+            .report_message(&message(serde_json::json!({
+                "type": "result",
+                "subtype": "success",
+                "duration_ms": 10,
+                "duration_api_ms": 8,
+                "is_error": false,
+                "num_turns": 4,
+                "session_id": "s-1",
+                "total_cost_usd": 0.25,
+                "usage": {
+                    "input_tokens": 100,
+                    "cache_read_input_tokens": 900,
+                    "output_tokens": 50
+                }
+            })))
+            .await;
+
+        let Some(RunEvent::AgentOutput { agent, phase, text }) = event_rx.recv().await else {
+            panic!("expected agent output");
+        };
+        assert_eq!((agent.as_str(), phase.as_str()), ("code", "Phase 1"));
+        assert_eq!(text, "Adding the login route");
+        let Some(RunEvent::UsageReported(usage)) = event_rx.recv().await else {
+            panic!("expected usage");
+        };
+        assert_eq!(usage.turns, 4);
+        assert_eq!(usage.input_tokens, 1000);
+        assert_eq!(usage.output_tokens, 50);
+        assert_eq!(usage.cost_usd, Some(0.25));
+        assert_eq!(broker.take_usage(), [usage]);
+    }
+
+    #[tokio::test]
+    async fn test_should_deny_when_response_channel_closes() {
+        let (broker, mut event_rx, response_tx) = broker();
+        let approver = ToolApprover::new(broker, "code", "review");
+
+        let pending =
+            tokio::spawn(async move { approver.approve("Bash", serde_json::Value::Null).await });
+        assert!(matches!(
+            event_rx.recv().await,
+            Some(RunEvent::PermissionRequested(_))
+        ));
+        drop(response_tx);
+
+        assert!(matches!(
+            pending.await.expect("should join"),
+            PermissionDecision::Deny { .. }
+        ));
+    }
+}
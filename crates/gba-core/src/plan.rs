@@ -5,8 +5,23 @@
 //! between the CLI and a Claude planning agent. The agent asks questions,
 //! the user responds, and eventually the agent generates `design.md`,
 //! `verification.md`, and `phases.yaml` under `.gba/features/<slug>/`.
+//!
+//! Given a written brief instead, the agent plans without asking questions,
+//! recording its assumptions in `design.md`, and the session completes after
+//! its first turn.
+//!
+//! Before a session completes, the generated files are validated. Problems
+//! are fed back to the agent in the same session until the files pass or
+//! `plan.maxRepairAttempts` is reached.
+//!
+//! Every session is recorded in `plan-transcript.jsonl` (with a Markdown
+//! export) so an interrupted session can be resumed with its SDK session id.
+//!
+//! An amend session starts from the current design and phases; the agent's
+//! new plan is merged back so completed phases keep their results.
 
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
 
 use claude_agent_sdk_rs::{ClaudeClient, ContentBlock, Message};
 use futures::StreamExt as _;
@@ -14,9 +29,28 @@ use serde_json::json;
 use tokio::sync::mpsc;
 use tracing::{debug, error, info, instrument, warn};
 
+use crate::agent::AgentRunner;
 use crate::engine::Engine;
 use crate::error::CoreError;
 use crate::events::{PlanEvent, PlanSession};
+use crate::spec::{
+    FeatureSpec, load_design_spec, load_feature_spec, merge_amended_spec, save_feature_spec,
+};
+use crate::transcript::{Transcript, TranscriptEntry, load_session_id};
+use crate::validate::{join_diagnostics, validate_plan_output};
+
+/// How a plan session starts.
+#[derive(Debug, Clone, Copy)]
+pub(crate) enum PlanStart<'a> {
+    /// Interactive conversation from scratch.
+    New,
+    /// Non-interactive planning from a written brief.
+    Brief(&'a str),
+    /// Interactive conversation continuing the feature's last session.
+    Resume,
+    /// Interactive conversation changing an existing plan.
+    Amend,
+}
 
 /// Run the plan workflow.
 ///
@@ -29,7 +63,9 @@ use crate::events::{PlanEvent, PlanSession};
 /// 1. Verify the repository is initialized (`.gba/` exists)
 /// 2. Create feature directory `.gba/features/<slug>/specs/`
 /// 3. Create a git worktree for the feature branch
-/// 4. Build agent options and render the task prompt
+/// 4. Build agent options and render the task prompt (`plan/task`,
+///    `plan/brief`, `plan/amend` with the current spec, or `plan/resume`
+///    with the recorded SDK session id)
 /// 5. Spawn a background task with a `ClaudeClient` for bidirectional streaming
 /// 6. Return the session handle
 ///
@@ -39,8 +75,15 @@ use crate::events::{PlanEvent, PlanSession};
 /// Returns `CoreError::Io` if directory creation fails.
 /// Returns `CoreError::Git` if worktree creation fails.
 /// Returns `CoreError::Agent` if agent options cannot be built.
+/// Returns `CoreError::FeatureNotFound` when resuming a feature without a
+/// recorded plan session or amending a feature without `phases.yaml`.
+/// Returns `CoreError::InvalidSpec` when amending an invalid `phases.yaml`.
 #[instrument(skip(engine))]
-pub(crate) async fn run_plan(engine: &Engine, slug: &str) -> Result<PlanSession, CoreError> {
+pub(crate) async fn run_plan(
+    engine: &Engine,
+    slug: &str,
+    start: PlanStart<'_>,
+) -> Result<PlanSession, CoreError> {
     // Step 1: Verify initialized
     let gba_dir = engine.gba_dir();
     if !gba_dir.exists() {
@@ -53,6 +96,19 @@ pub(crate) async fn run_plan(engine: &Engine, slug: &str) -> Result<PlanSession,
     std::fs::create_dir_all(&specs_dir)?;
     info!(feature = slug, "created feature directory");
 
+    let resume_id = match start {
+        PlanStart::Resume => Some(load_session_id(&feature_dir, slug)?),
+        _ => None,
+    };
+    let brief = match start {
+        PlanStart::Brief(brief) => Some(brief),
+        _ => None,
+    };
+    let amend_base = match start {
+        PlanStart::Amend => Some(load_feature_spec(&gba_dir, slug)?),
+        _ => None,
+    };
+
     // Step 3: Create worktree (tolerate if it already exists for resume)
     match engine.git().create_worktree(slug).await {
         Ok(path) => info!(worktree = %path.display(), "created worktree"),
@@ -64,18 +120,29 @@ pub(crate) async fn run_plan(engine: &Engine, slug: &str) -> Result<PlanSession,
 
     // Step 4: Build agent options and task prompt
     let repo_path = engine.config().repo_path().to_path_buf();
-    let context = json!({
-        "repo_path": repo_path.display().to_string(),
-        "feature_slug": slug,
-    });
-
-    let options = engine
-        .agent_runner()
-        .build_agent_options("plan", &context, Some(&repo_path))?;
+    let amend = amend_base
+        .as_ref()
+        .map(|spec| (spec, load_design_spec(&gba_dir, slug).ok()));
+    let context = template_context(&repo_path, slug, brief, amend);
+
+    let mut options =
+        engine
+            .agent_runner()
+            .build_agent_options("plan", &context, Some(&repo_path))?;
+    if let Some(session_id) = &resume_id {
+        info!(session_id = %session_id, "resuming plan session");
+        options.resume = Some(session_id.clone());
+    }
 
+    let task_template = match start {
+        PlanStart::New => "plan/task",
+        PlanStart::Brief(_) => "plan/brief",
+        PlanStart::Resume => "plan/resume",
+        PlanStart::Amend => "plan/amend",
+    };
     let task_prompt = engine
         .agent_runner()
-        .render_template("plan/task", &context)?;
+        .render_template(task_template, &context)?;
 
     debug!(slug, "built agent options and task prompt for plan session");
 
@@ -85,16 +152,18 @@ pub(crate) async fn run_plan(engine: &Engine, slug: &str) -> Result<PlanSession,
     let session = PlanSession::new(event_rx, input_tx);
 
     // Step 6: Spawn background task to drive the ClaudeClient session
-    let feature_dir_for_task = feature_dir.clone();
+    let settings = SessionSettings {
+        agent_runner: engine.agent_runner_arc(),
+        slug: slug.to_owned(),
+        gba_dir: gba_dir.clone(),
+        transcript: Transcript::open(&feature_dir, slug),
+        feature_dir,
+        interactive: brief.is_none(),
+        amend_base,
+        max_repair_attempts: engine.project_config().plan.max_repair_attempts,
+    };
     tokio::spawn(async move {
-        run_plan_session(
-            options,
-            task_prompt,
-            event_tx,
-            input_rx,
-            feature_dir_for_task,
-        )
-        .await;
+        run_plan_session(options, task_prompt, event_tx, input_rx, settings).await;
     });
 
     Ok(session)
@@ -105,14 +174,17 @@ pub(crate) async fn run_plan(engine: &Engine, slug: &str) -> Result<PlanSession,
 /// Connects to the Claude agent, sends the initial task prompt, and enters
 /// a loop: receive agent messages, emit events, wait for user input, and
 /// send the next query. The loop terminates when the user closes the input
-/// channel or the agent signals completion.
+/// channel or the agent signals completion, so the user can keep asking
+/// for changes after seeing the plan. A non-`interactive` session completes
+/// as soon as the agent finishes its first turn. The spec files are
+/// validated (and repaired) before the session reports completion.
 #[instrument(skip_all)]
 async fn run_plan_session(
     options: claude_agent_sdk_rs::ClaudeAgentOptions,
     task_prompt: String,
     event_tx: mpsc::Sender<PlanEvent>,
     mut input_rx: mpsc::Receiver<String>,
-    feature_dir: PathBuf,
+    mut settings: SessionSettings,
 ) {
     // Connect the ClaudeClient
     let mut client = ClaudeClient::new(options);
@@ -139,13 +211,21 @@ async fn run_plan_session(
         return;
     }
     debug!("sent initial task prompt to plan agent");
+    settings
+        .transcript
+        .record(TranscriptEntry::Prompt { text: task_prompt });
 
     // Main conversation loop
     loop {
         // Receive messages for one agent turn
-        let turn_result = receive_turn(&client, &event_tx, &feature_dir).await;
+        let turn_result = receive_turn(&client, &event_tx, &mut settings).await;
 
         match turn_result {
+            // Planning from a brief: nobody answers, the first turn is the plan
+            TurnOutcome::WaitingForInput if !settings.interactive => {
+                finish_session(&mut client, &event_tx, &mut settings, true).await;
+                break;
+            }
             TurnOutcome::WaitingForInput => {
                 // Agent finished its turn, notify the CLI
                 if event_tx.send(PlanEvent::WaitingForInput).await.is_err() {
@@ -157,6 +237,9 @@ async fn run_plan_session(
                 match input_rx.recv().await {
                     Some(input) => {
                         debug!("received user input, sending to agent");
+                        settings.transcript.record(TranscriptEntry::User {
+                            text: input.clone(),
+                        });
                         if let Err(e) = client.query(&input).await {
                             let _ = event_tx
                                 .send(PlanEvent::Error(CoreError::Agent(format!(
@@ -169,13 +252,13 @@ async fn run_plan_session(
                     None => {
                         // Input channel closed, user ended the session
                         debug!("input channel closed, ending plan session");
-                        let _ = event_tx.send(PlanEvent::Completed).await;
+                        finish_session(&mut client, &event_tx, &mut settings, true).await;
                         break;
                     }
                 }
             }
             TurnOutcome::Completed => {
-                let _ = event_tx.send(PlanEvent::Completed).await;
+                finish_session(&mut client, &event_tx, &mut settings, true).await;
                 break;
             }
             TurnOutcome::Error(err) => {
@@ -183,21 +266,209 @@ async fn run_plan_session(
                 break;
             }
             TurnOutcome::StreamEnded => {
-                // Stream ended unexpectedly (e.g., process exited)
+                // Stream ended unexpectedly (e.g., process exited); the
+                // agent is gone, so validate without repairs
                 warn!("plan agent stream ended unexpectedly");
-                let _ = event_tx.send(PlanEvent::Completed).await;
+                finish_session(&mut client, &event_tx, &mut settings, false).await;
                 break;
             }
         }
     }
 
     // Clean up
+    settings.transcript.export_markdown();
     if let Err(e) = client.disconnect().await {
         warn!("failed to disconnect plan agent cleanly: {e}");
     }
     debug!("plan session ended");
 }
 
+/// Settings of one plan session, moved into its background task.
+#[derive(Debug)]
+struct SessionSettings {
+    /// Runner used to render the repair prompt.
+    agent_runner: Arc<AgentRunner>,
+    /// Feature slug.
+    slug: String,
+    /// `.gba/` directory of the repository.
+    gba_dir: PathBuf,
+    /// `.gba/features/<slug>/`, where the spec files are written.
+    feature_dir: PathBuf,
+    /// Record of the conversation.
+    transcript: Transcript,
+    /// Whether a user answers the agent's questions.
+    interactive: bool,
+    /// Spec being amended; the agent's plan is merged into it.
+    amend_base: Option<FeatureSpec>,
+    /// Repair rounds allowed when the spec files fail validation.
+    max_repair_attempts: u32,
+}
+
+/// Validate the spec files, asking the agent to repair them up to
+/// `plan.maxRepairAttempts` times if `repair` is set, then report
+/// completion or failure.
+///
+/// Emits [`PlanEvent::SpecValidated`] for every validation round.
+async fn finish_session(
+    client: &mut ClaudeClient,
+    event_tx: &mpsc::Sender<PlanEvent>,
+    settings: &mut SessionSettings,
+    repair: bool,
+) {
+    let max_repair_attempts = if repair {
+        settings.max_repair_attempts
+    } else {
+        0
+    };
+    let result = match validate_and_repair(client, event_tx, settings, max_repair_attempts).await {
+        Ok(()) => apply_amendment(settings),
+        Err(e) => Err(e),
+    };
+    let event = match result {
+        Ok(()) => PlanEvent::Completed,
+        Err(e) => PlanEvent::Error(e),
+    };
+    let _ = event_tx.send(event).await;
+}
+
+/// Merge the agent's plan into the amended spec and save the result, so
+/// completed phases keep their results and commits.
+///
+/// # Errors
+///
+/// Returns `CoreError::InvalidSpec` if the agent's `phases.yaml` cannot be
+/// loaded, or an I/O error if the merged spec cannot be saved.
+fn apply_amendment(settings: &SessionSettings) -> Result<(), CoreError> {
+    let Some(original) = &settings.amend_base else {
+        return Ok(());
+    };
+    let amended = load_feature_spec(&settings.gba_dir, &settings.slug)?;
+    let merged = merge_amended_spec(original, amended);
+    save_feature_spec(&settings.gba_dir, &settings.slug, &merged)?;
+    info!(
+        slug = %settings.slug,
+        phases = merged.phases.len(),
+        "merged amended plan"
+    );
+    Ok(())
+}
+
+/// Context of a plan session. `brief` is set for non-interactive sessions;
+/// `amend` carries the spec being amended and its design, if any.
+pub(crate) fn template_context(
+    repo_path: &Path,
+    slug: &str,
+    brief: Option<&str>,
+    amend: Option<(&FeatureSpec, Option<String>)>,
+) -> serde_json::Value {
+    let mut context = json!({
+        "repo_path": repo_path.display().to_string(),
+        "feature_slug": slug,
+        "brief": brief,
+    });
+    if let Some((spec, design)) = amend {
+        context["design"] = json!(design);
+        context["phases"] = amend_phases_context(spec);
+    }
+    context
+}
+
+/// Context of the `plan/repair` prompt asking the agent to fix the spec
+/// files that failed validation.
+pub(crate) fn repair_context(
+    slug: &str,
+    attempt: u32,
+    max_attempts: u32,
+    problems: &[String],
+) -> serde_json::Value {
+    json!({
+        "feature_slug": slug,
+        "attempt": attempt,
+        "max_attempts": max_attempts,
+        "problems": problems,
+    })
+}
+
+/// Template context describing each phase of an amended spec.
+fn amend_phases_context(spec: &FeatureSpec) -> serde_json::Value {
+    spec.phases
+        .iter()
+        .map(|phase| {
+            let result = phase.result.as_ref();
+            json!({
+                "name": phase.name,
+                "description": phase.description,
+                "tasks": phase.tasks,
+                "status": result.map(|r| r.status.clone()).unwrap_or_default(),
+                "commit": result.and_then(|r| r.commit.as_deref()),
+            })
+        })
+        .collect()
+}
+
+/// Run validation rounds until the spec files pass.
+///
+/// # Errors
+///
+/// Returns `CoreError::InvalidSpec` if the files are still invalid after
+/// `max_repair_attempts` repairs.
+/// Returns `CoreError::Agent` if the agent fails during a repair.
+/// Returns `CoreError::Prompt` if the repair prompt cannot be rendered.
+async fn validate_and_repair(
+    client: &mut ClaudeClient,
+    event_tx: &mpsc::Sender<PlanEvent>,
+    settings: &mut SessionSettings,
+    max_repair_attempts: u32,
+) -> Result<(), CoreError> {
+    let mut attempt = 0;
+    loop {
+        attempt += 1;
+        let diagnostics = validate_plan_output(&settings.feature_dir);
+        info!(
+            attempt,
+            problems = diagnostics.len(),
+            "validated plan output"
+        );
+        let _ = event_tx
+            .send(PlanEvent::SpecValidated {
+                attempt,
+                diagnostics: diagnostics.clone(),
+            })
+            .await;
+        if diagnostics.is_empty() {
+            return Ok(());
+        }
+        if attempt > max_repair_attempts {
+            return Err(CoreError::InvalidSpec(format!(
+                "spec files still invalid after {max_repair_attempts} repair attempt(s): {}",
+                join_diagnostics(&diagnostics)
+            )));
+        }
+
+        let problems: Vec<String> = diagnostics.iter().map(ToString::to_string).collect();
+        let context = repair_context(&settings.slug, attempt, max_repair_attempts, &problems);
+        let prompt = settings
+            .agent_runner
+            .render_template("plan/repair", &context)?;
+        client
+            .query(&prompt)
+            .await
+            .map_err(|e| CoreError::Agent(format!("failed to send repair request: {e}")))?;
+        settings
+            .transcript
+            .record(TranscriptEntry::Prompt { text: prompt });
+        match receive_turn(client, event_tx, settings).await {
+            TurnOutcome::WaitingForInput | TurnOutcome::Completed => {}
+            TurnOutcome::Error(e) => return Err(e),
+            TurnOutcome::StreamEnded => {
+                return Err(CoreError::Agent(
+                    "plan agent stream ended during spec repair".to_owned(),
+                ));
+            }
+        }
+    }
+}
+
 /// Outcome of receiving a single agent turn.
 #[derive(Debug)]
 enum TurnOutcome {
@@ -217,11 +488,14 @@ enum TurnOutcome {
 /// Consumes messages from the agent until a `Result` message is received
 /// (indicating the turn is done). Emits `PlanEvent::Message` for text
 /// content and `PlanEvent::SpecGenerated` for detected spec file writes.
+/// The turn is recorded in the session transcript.
 async fn receive_turn(
     client: &ClaudeClient,
     event_tx: &mpsc::Sender<PlanEvent>,
-    feature_dir: &PathBuf,
+    settings: &mut SessionSettings,
 ) -> TurnOutcome {
+    let feature_dir = settings.feature_dir.as_path();
+    let transcript = &mut settings.transcript;
     let mut stream = client.receive_messages();
     let mut turn_text = String::new();
 
@@ -241,6 +515,12 @@ async fn receive_turn(
                     if let ContentBlock::Text(text_block) = block {
                         turn_text.push_str(&text_block.text);
                     }
+                    if let ContentBlock::ToolUse(tool_use) = block {
+                        transcript.record(TranscriptEntry::ToolCall {
+                            name: tool_use.name.clone(),
+                            input: tool_use.input.clone(),
+                        });
+                    }
                     // Detect tool use for spec file generation
                     if let ContentBlock::ToolUse(tool_use) = block {
                         check_spec_file_written(
@@ -253,7 +533,20 @@ async fn receive_turn(
                     }
                 }
             }
+            Message::System(ref system) => {
+                if let Some(session_id) = &system.session_id {
+                    transcript.record_session(session_id);
+                }
+            }
             Message::Result(ref result) => {
+                transcript.record_session(&result.session_id);
+                if !turn_text.is_empty() {
+                    transcript.record(TranscriptEntry::Agent {
+                        text: turn_text.clone(),
+                    });
+                }
+                transcript.export_markdown();
+
                 // Turn is complete -- send accumulated text
                 if !turn_text.is_empty()
                     && event_tx
@@ -281,8 +574,12 @@ async fn receive_turn(
 
     // Stream ended without a Result message
     if !turn_text.is_empty() {
+        transcript.record(TranscriptEntry::Agent {
+            text: turn_text.clone(),
+        });
         let _ = event_tx.send(PlanEvent::Message(turn_text)).await;
     }
+    transcript.export_markdown();
     TurnOutcome::StreamEnded
 }
 
@@ -290,29 +587,26 @@ async fn receive_turn(
 ///
 /// When the agent uses the `Write` tool to create files inside the feature
 /// directory, emit a `PlanEvent::SpecGenerated` event so the CLI can
-/// display which spec files were created.
+/// display which spec files were created. Returns the path of the spec file.
 async fn check_spec_file_written(
     tool_name: &str,
     input: &serde_json::Value,
-    feature_dir: &PathBuf,
+    feature_dir: &Path,
     event_tx: &mpsc::Sender<PlanEvent>,
-) {
+) -> Option<PathBuf> {
     // The Write tool has `file_path` and `content` fields
     if tool_name != "Write" {
-        return;
+        return None;
     }
 
-    let file_path_str = match input.get("file_path").and_then(|v| v.as_str()) {
-        Some(p) => p,
-        None => return,
-    };
+    let file_path_str = input.get("file_path").and_then(|v| v.as_str())?;
 
     let file_path = PathBuf::from(file_path_str);
 
     // Check if the file is inside the feature directory
     let is_spec_file = file_path.starts_with(feature_dir);
     if !is_spec_file {
-        return;
+        return None;
     }
 
     let content = input
@@ -324,10 +618,11 @@ async fn check_spec_file_written(
     info!(path = %file_path.display(), "spec file generated by plan agent");
     let _ = event_tx
         .send(PlanEvent::SpecGenerated {
-            path: file_path,
+            path: file_path.clone(),
             content,
         })
         .await;
+    Some(file_path)
 }
 
 #[cfg(test)]
@@ -401,6 +696,26 @@ mod tests {
         }
     }
 
+    #[tokio::test]
+    async fn test_should_require_transcript_to_resume() {
+        let dir = tempfile::TempDir::new().expect("should create temp dir");
+        let gba_dir = dir.path().join(".gba");
+        std::fs::create_dir_all(&gba_dir).expect("should create .gba dir");
+        std::fs::write(gba_dir.join("config.yaml"), "").expect("should write config");
+
+        let config = EngineConfig::builder()
+            .repo_path(dir.path().to_path_buf())
+            .build();
+        let engine = Engine::new(config).await.expect("should create engine");
+        let result = engine.resume_plan("login").await;
+
+        assert!(
+            matches!(result, Err(CoreError::FeatureNotFound(ref msg)) if msg.contains("transcript")),
+            "got: {:?}",
+            result.err()
+        );
+    }
+
     #[tokio::test]
     async fn test_should_send_events_through_plan_session() {
         let (event_tx, event_rx) = tokio::sync::mpsc::channel(16);
@@ -469,6 +784,37 @@ mod tests {
         );
     }
 
+    #[tokio::test]
+    async fn test_should_report_written_spec_files() {
+        let feature_dir = PathBuf::from("/repo/.gba/features/login");
+        let (event_tx, mut event_rx) = tokio::sync::mpsc::channel(4);
+
+        let written = super::check_spec_file_written(
+            "Write",
+            &serde_json::json!({
+                "file_path": "/repo/.gba/features/login/phases.yaml",
+                "content": "feature: Login",
+            }),
+            &feature_dir,
+            &event_tx,
+        )
+        .await;
+        assert_eq!(written, Some(feature_dir.join("phases.yaml")));
+        assert!(matches!(
+            event_rx.recv().await,
+            Some(PlanEvent::SpecGenerated { ref content, .. }) if content == "feature: Login"
+        ));
+
+        let outside = super::check_spec_file_written(
+            "Write",
+            &serde_json::json!({"file_path": "/repo/src/main.rs", "content": ""}),
+            &feature_dir,
+            &event_tx,
+        )
+        .await;
+        assert_eq!(outside, None);
+    }
+
     #[test]
     fn test_should_detect_spec_file_path() {
         let feature_dir = PathBuf::from("/repo/.gba/features/login");
@@ -0,0 +1,636 @@
+//! Prompt previews and checks.
+//!
+//! Builds the template context `gba run` would pass to a pipeline stage's
+//! agent sessions for a feature in its current state, so a template can be
+//! rendered exactly as the next run would render it without starting an
+//! agent.
+//!
+//! Fix templates (`code/hook_fix`, `review/fix`, `verify/fix`) also need the
+//! failures of a run, which only exist while it executes; their previews get
+//! the stage's context without them.
+//!
+//! [`template_issues`] checks the effective templates against their
+//! contracts and against the contexts the workflows render them with.
+
+use std::path::{Path, PathBuf};
+
+use gba_pm::{PromptManager, TemplateIssue};
+use serde_json::Value;
+use tracing::{debug, instrument, warn};
+
+use crate::config::{ProjectConfig, StageConfig, StageKind};
+use crate::engine::Engine;
+use crate::error::CoreError;
+use crate::events::{Issue, Severity};
+use crate::hooks::HookOutput;
+use crate::review::{ReviewRecord, ReviewRound, load_review_record};
+use crate::run::{TemplateContext, collect_completed_phases, phase_template, stage_reviewers};
+use crate::spec::{
+    FeatureSpec, Phase, PhaseResult, ReviewResult, StepStatus, VerificationPlan,
+    VerificationResult, load_design_spec, load_feature_spec,
+};
+use crate::{init, plan};
+
+/// A context a workflow renders templates with, and those templates.
+struct ContextUse {
+    /// Which session the context is for, e.g. `review stage fix`.
+    label: String,
+    /// Sample context with every variable the workflow provides.
+    context: Value,
+    /// System and task templates rendered with the context.
+    templates: Vec<String>,
+}
+
+impl ContextUse {
+    fn new(label: &str, context: Value, templates: &[impl AsRef<str>]) -> Self {
+        Self {
+            label: label.to_owned(),
+            context,
+            templates: templates.iter().map(|t| t.as_ref().to_owned()).collect(),
+        }
+    }
+}
+
+/// Build the context the pipeline stage would render `template` with.
+///
+/// The stage is the pipeline stage named `stage`, or the first stage using
+/// `template` (see [`infer_stage`]). For `phases` stages, `phase` is the
+/// one-based phase to preview; it defaults to the next phase a run would
+/// execute.
+///
+/// # Errors
+///
+/// Returns `CoreError::NotInitialized` if `.gba/` does not exist.
+/// Returns `CoreError::FeatureNotFound` if the feature spec does not exist.
+/// Returns `CoreError::Config` if no pipeline stage matches.
+/// Returns `CoreError::InvalidSpec` if there is no phase to preview.
+#[instrument(skip(engine))]
+pub(crate) async fn stage_context(
+    engine: &Engine,
+    slug: &str,
+    template: &str,
+    stage: Option<&str>,
+    phase: Option<usize>,
+) -> Result<Value, CoreError> {
+    let gba_dir = engine.gba_dir();
+    if !gba_dir.exists() {
+        return Err(CoreError::NotInitialized);
+    }
+
+    let spec = load_feature_spec(&gba_dir, slug)?;
+    let design_spec = match load_design_spec(&gba_dir, slug) {
+        Ok(content) => content,
+        Err(CoreError::FeatureNotFound(msg)) => {
+            warn!(slug, reason = %msg, "design spec missing, previewing with empty context");
+            String::new()
+        }
+        Err(e) => return Err(e),
+    };
+
+    let stages = &engine.project_config().pipeline.stages;
+    let stage = match stage {
+        Some(name) => stages
+            .iter()
+            .find(|s| s.name() == name)
+            .ok_or_else(|| CoreError::Config(format!("pipeline has no stage named {name:?}")))?,
+        None => infer_stage(stages, template).ok_or_else(|| {
+            CoreError::Config(format!(
+                "no pipeline stage uses template {template:?}; name the stage to preview"
+            ))
+        })?,
+    };
+    debug!(stage = stage.name(), "previewing stage context");
+
+    let project_config = engine.project_config();
+    let ctx = TemplateContext {
+        repo_path: engine.config().repo_path(),
+        slug,
+        design_spec: &design_spec,
+        spec: &spec,
+        base_branch: &project_config.git.base_branch,
+    };
+
+    match stage.kind {
+        StageKind::Phases => {
+            let index = phase_index(&spec, phase)?;
+            ctx.phase(index, &collect_completed_phases(&spec))
+        }
+        StageKind::Review => Ok(ctx.review(&diff(engine, slug).await)),
+        StageKind::Verification => Ok(ctx.verification()),
+        StageKind::Agent => Ok(ctx.agent_stage(stage.name(), &diff(engine, slug).await)),
+        StageKind::Pr => {
+            let execution = spec.execution.as_ref();
+            let review = execution.map_or(
+                ReviewResult {
+                    turns: 0,
+                    issues_found: 0,
+                    issues_fixed: 0,
+                },
+                |e| e.review.clone(),
+            );
+            let verification = execution.map_or(
+                VerificationResult {
+                    turns: 0,
+                    passed: true,
+                    evidence: None,
+                },
+                |e| e.verification.clone(),
+            );
+            let record = load_review_record(&gba_dir, slug)?;
+            Ok(ctx.pr(
+                &engine.git().branch_name(slug),
+                &review,
+                &record,
+                &verification,
+            ))
+        }
+    }
+}
+
+/// Check the effective templates against their contracts (see
+/// [`PromptManager::check`]) and against the contexts that `gba init`,
+/// `gba plan` and the configured run pipeline render them with. Templates
+/// without a contract are checked directly against those contexts. Every
+/// template that provides what it needs is then rendered with each sample
+/// context, catching errors such as attribute lookups on a missing value.
+pub(crate) fn template_issues(pm: &PromptManager, config: &ProjectConfig) -> Vec<TemplateIssue> {
+    let mut issues = pm.check();
+    for usage in context_uses(config) {
+        for template in &usage.templates {
+            let found = issues.len();
+            if pm.contract(template).is_some() {
+                for variable in pm.missing_variables(template, &usage.context) {
+                    issues.push(TemplateIssue {
+                        template: template.clone(),
+                        message: format!(
+                            "requires `{variable}`, which the {} context does not provide",
+                            usage.label
+                        ),
+                    });
+                }
+            } else {
+                // Without a contract, every variable the template reads must
+                // be in the context
+                for variable in pm.used_variables(template) {
+                    if usage.context.get(&variable).is_none() {
+                        issues.push(TemplateIssue {
+                            template: template.clone(),
+                            message: format!(
+                                "uses `{variable}`, which the {} context does not provide \
+                                 (declare it optional in a contract header if the template only tests it)",
+                                usage.label
+                            ),
+                        });
+                    }
+                }
+            }
+            if issues.len() > found {
+                continue;
+            }
+            if let Err(e) = pm.render(template, &usage.context) {
+                issues.push(TemplateIssue {
+                    template: template.clone(),
+                    message: format!("fails to render with the {} context: {e}", usage.label),
+                });
+            }
+        }
+    }
+    issues.sort();
+    issues.dedup();
+    issues
+}
+
+/// Every context the workflows render templates with, built with the same
+/// builders as the workflows from placeholder inputs. The inputs cover
+/// optional values both present and missing: one phase has a result and
+/// one has none, and the review has one issue.
+fn context_uses(config: &ProjectConfig) -> Vec<ContextUse> {
+    let repo = Path::new("");
+    let phase = Phase {
+        name: String::new(),
+        description: String::new(),
+        tasks: vec![String::new()],
+        result: None,
+    };
+    let spec = FeatureSpec {
+        feature: String::new(),
+        phases: vec![
+            phase.clone(),
+            Phase {
+                result: Some(PhaseResult {
+                    status: StepStatus::Completed,
+                    turns: 0,
+                    commit: Some(String::new()),
+                }),
+                ..phase
+            },
+        ],
+        verification: VerificationPlan {
+            criteria: vec![String::new()],
+            test_commands: vec![String::new()],
+        },
+        execution: None,
+    };
+    let issue = Issue {
+        severity: Severity::Error,
+        file: PathBuf::new(),
+        line: Some(1),
+        description: String::new(),
+        reviewers: vec![String::new()],
+    };
+    let review_record = ReviewRecord {
+        rounds: vec![ReviewRound {
+            stage: String::new(),
+            iteration: 1,
+            reviewers: vec![String::new()],
+            issues: vec![issue.clone()],
+            failed: Vec::new(),
+        }],
+    };
+
+    let mut uses = vec![
+        ContextUse::new(
+            "init",
+            init::template_context(repo, ""),
+            &["init/system", "init/task"],
+        ),
+        ContextUse::new(
+            "plan",
+            plan::template_context(repo, "", None, None),
+            &["plan/system", "plan/task", "plan/resume"],
+        ),
+        ContextUse::new(
+            "plan brief",
+            plan::template_context(repo, "", Some(""), None),
+            &["plan/system", "plan/brief"],
+        ),
+        ContextUse::new(
+            "plan amend",
+            plan::template_context(repo, "", None, Some((&spec, None))),
+            &["plan/system", "plan/amend"],
+        ),
+        ContextUse::new(
+            "plan repair",
+            plan::repair_context("", 1, 1, &[]),
+            &["plan/repair"],
+        ),
+    ];
+
+    let ctx = TemplateContext {
+        repo_path: repo,
+        slug: "",
+        design_spec: "",
+        spec: &spec,
+        base_branch: "",
+    };
+    let hook_fix = ctx.hook_fix(&HookOutput {
+        name: String::new(),
+        command: String::new(),
+        passed: false,
+        stdout: String::new(),
+        stderr: String::new(),
+    });
+    let fix_templates = |fix: &str| ["code/system".to_owned(), fix.to_owned()];
+
+    for stage in &config.pipeline.stages {
+        let label = format!("`{}` stage", stage.name());
+        let system = format!("{}/system", stage.agent());
+        let task = stage.template().unwrap_or_default();
+
+        match stage.kind {
+            StageKind::Phases => {
+                let mut templates = vec![system];
+                for completed in [Vec::new(), vec![Value::Null]] {
+                    templates.push(phase_template(stage, &completed));
+                }
+                if let Ok(context) = ctx.phase(0, &[]) {
+                    uses.push(ContextUse::new(&label, context, &templates));
+                }
+                uses.push(ContextUse::new(
+                    &format!("{label} hook fix"),
+                    hook_fix.clone(),
+                    &fix_templates("code/hook_fix"),
+                ));
+            }
+            StageKind::Review => {
+                let mut templates: Vec<String> = stage_reviewers(&config.review, stage)
+                    .iter()
+                    .map(|r| r.system_template())
+                    .collect();
+                templates.push(task);
+                uses.push(ContextUse::new(&label, ctx.review(""), &templates));
+                uses.push(ContextUse::new(
+                    &format!("{label} fix"),
+                    ctx.review_fix(std::slice::from_ref(&issue)),
+                    &fix_templates(stage.fix_template()),
+                ));
+            }
+            StageKind::Verification => {
+                uses.push(ContextUse::new(&label, ctx.verification(), &[system, task]));
+                uses.push(ContextUse::new(
+                    &format!("{label} fix"),
+                    ctx.verification_fix(""),
+                    &fix_templates(stage.fix_template()),
+                ));
+            }
+            StageKind::Agent => {
+                let context = ctx.agent_stage(stage.name(), "");
+                uses.push(ContextUse::new(&label, context, &[system, task]));
+                uses.push(ContextUse::new(
+                    &format!("{label} hook fix"),
+                    hook_fix.clone(),
+                    &fix_templates("code/hook_fix"),
+                ));
+            }
+            StageKind::Pr => {
+                let review = ReviewResult {
+                    turns: 0,
+                    issues_found: 0,
+                    issues_fixed: 0,
+                };
+                let verification = VerificationResult {
+                    turns: 0,
+                    passed: true,
+                    evidence: None,
+                };
+                let context = ctx.pr("", &review, &review_record, &verification);
+                uses.push(ContextUse::new(&label, context, &[system, task]));
+            }
+        }
+    }
+    uses
+}
+
+/// The first stage whose task template is `template`, or failing that the
+/// first stage run by the agent the template belongs to (`code/hook_fix`
+/// belongs to the `phases` stage of the default pipeline).
+fn infer_stage<'a>(stages: &'a [StageConfig], template: &str) -> Option<&'a StageConfig> {
+    stages
+        .iter()
+        .find(|s| s.template().as_deref() == Some(template))
+        .or_else(|| {
+            let agent = template.split('/').next()?;
+            stages.iter().find(|s| s.agent() == agent)
+        })
+}
+
+/// Zero-based index of the phase to preview: `phase` (one-based) if given,
+/// otherwise the first phase not yet completed.
+fn phase_index(spec: &FeatureSpec, phase: Option<usize>) -> Result<usize, CoreError> {
+    let total = spec.phases.len();
+    if total == 0 {
+        return Err(CoreError::InvalidSpec("no phases defined".to_owned()));
+    }
+    match phase {
+        Some(n) if (1..=total).contains(&n) => Ok(n - 1),
+        Some(n) => Err(CoreError::InvalidSpec(format!(
+            "phase {n} out of range (1-{total})"
+        ))),
+        None => spec
+            .phases
+            .iter()
+            .position(|p| {
+                p.result
+                    .as_ref()
+                    .is_none_or(|r| r.status != StepStatus::Completed)
+            })
+            .ok_or_else(|| {
+                CoreError::InvalidSpec("all phases are completed; pick one to preview".to_owned())
+            }),
+    }
+}
+
+/// The worktree's diff against the base branch, empty if there is no
+/// worktree yet or the diff fails.
+async fn diff(engine: &Engine, slug: &str) -> String {
+    let worktree = engine.git().worktree_path(slug);
+    if !worktree.is_dir() {
+        return String::new();
+    }
+    engine
+        .git()
+        .get_diff(&worktree, &engine.project_config().git.base_branch)
+        .await
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use super::*;
+    use crate::config::{EngineConfig, PipelineConfig};
+
+    const SPEC: &str = r"
+feature: Login
+phases:
+  - name: Setup
+    description: d
+    tasks: [t]
+    result: {status: completed, turns: 3, commit: abc123}
+  - name: Api
+    description: Add the API
+    tasks: [routes, handlers]
+verification:
+  criteria: [logs in]
+  testCommands: [cargo test]
+";
+
+    #[tokio::test]
+    async fn test_should_build_next_phase_context() {
+        let dir = tempfile::TempDir::new().expect("should create temp dir");
+        let feature_dir = dir.path().join(".gba").join("features").join("login");
+        fs::create_dir_all(feature_dir.join("specs")).expect("should create feature dir");
+        fs::write(feature_dir.join("phases.yaml"), SPEC).expect("should write spec");
+        fs::write(feature_dir.join("specs").join("design.md"), "Use OAuth.")
+            .expect("should write design");
+        let config = EngineConfig::builder()
+            .repo_path(dir.path().to_path_buf())
+            .build();
+        let engine = Engine::new(config).await.expect("should create engine");
+
+        let ctx = engine
+            .prompt_context("login", "code/resume", None, None)
+            .await
+            .expect("should build context");
+        assert_eq!(ctx["phase_index"], 2);
+        assert_eq!(ctx["design_spec"], "Use OAuth.");
+        assert_eq!(ctx["completed_phases"][0]["commit"], "abc123");
+        let rendered = engine
+            .render_prompt("code/resume", &ctx)
+            .expect("should render");
+        assert!(rendered.contains("**Phase 2/2: Api**"));
+        assert!(rendered.contains("- routes\n- handlers\n"));
+
+        let ctx = engine
+            .prompt_context("login", "verify/task", None, None)
+            .await
+            .expect("should build context");
+        assert_eq!(ctx["test_commands"][0], "cargo test");
+        assert!(matches!(
+            engine
+                .prompt_context("login", "code/task", None, Some(3))
+                .await,
+            Err(CoreError::InvalidSpec(_))
+        ));
+        assert!(matches!(
+            engine
+                .prompt_context("login", "code/task", Some("docs"), None)
+                .await,
+            Err(CoreError::Config(_))
+        ));
+    }
+
+    #[test]
+    fn test_should_accept_built_in_templates_for_default_pipeline() {
+        let pm = PromptManager::new().expect("should create prompt manager");
+        let issues = template_issues(&pm, &ProjectConfig::default());
+        assert!(issues.is_empty(), "unexpected: {issues:?}");
+    }
+
+    #[test]
+    fn test_should_check_fix_templates_against_their_stage() {
+        let dir = tempfile::TempDir::new().expect("should create temp dir");
+        fs::create_dir_all(dir.path().join("review")).expect("should create dir");
+        fs::write(
+            dir.path().join("review").join("fix.md.j2"),
+            "{#- contract\nrequired: [issues, output]\n-#}\n{{ issues }} {{ output }}",
+        )
+        .expect("should write template");
+        let mut pm = PromptManager::new().expect("should create prompt manager");
+        pm.load_dir(dir.path()).expect("should load overrides");
+
+        let issues: Vec<String> = template_issues(&pm, &ProjectConfig::default())
+            .iter()
+            .map(ToString::to_string)
+            .collect();
+        assert_eq!(
+            issues,
+            [
+                "review/fix: requires `output`, which the `review` stage fix context does not provide"
+            ]
+        );
+
+        // The same template works as the fix template of a verification stage
+        let mut config = ProjectConfig::default();
+        for stage in &mut config.pipeline.stages {
+            stage.fix_template = None;
+        }
+        config
+            .pipeline
+            .stages
+            .retain(|s| s.kind != StageKind::Review);
+        config.pipeline.stages[1].fix_template = Some("review/fix".to_owned());
+        fs::write(
+            dir.path().join("review").join("fix.md.j2"),
+            "{#- contract\nrequired: [failures, output]\n-#}\n{{ failures }} {{ output }}",
+        )
+        .expect("should rewrite template");
+        pm.load_dir(dir.path()).expect("should reload overrides");
+        assert!(template_issues(&pm, &config).is_empty());
+    }
+
+    #[test]
+    fn test_should_check_templates_without_contract_against_contexts() {
+        let dir = tempfile::TempDir::new().expect("should create temp dir");
+        let docs = dir.path().join("docs");
+        fs::create_dir_all(&docs).expect("should create dir");
+        fs::write(docs.join("config.yml"), "preset: true\n").expect("should write config");
+        fs::write(docs.join("system.md.j2"), "Document {{ repo_path }}.")
+            .expect("should write system");
+        fs::write(
+            docs.join("task.md.j2"),
+            "Update the docs for {{ feature_slgu }}.",
+        )
+        .expect("should write task");
+        let mut pm = PromptManager::new().expect("should create prompt manager");
+        pm.load_dir(dir.path()).expect("should load agent");
+        assert!(pm.contract("docs/task").is_none());
+
+        let mut config = ProjectConfig::default();
+        let mut stage = StageConfig::new(StageKind::Agent);
+        stage.agent = Some("docs".to_owned());
+        config.pipeline.stages.push(stage);
+
+        let issues = template_issues(&pm, &config);
+        assert_eq!(issues.len(), 1, "unexpected: {issues:?}");
+        assert_eq!(issues[0].template, "docs/task");
+        assert!(
+            issues[0].message.starts_with(
+                "uses `feature_slgu`, which the `docs` stage context does not provide"
+            )
+        );
+    }
+
+    #[test]
+    fn test_should_render_templates_with_sample_contexts() {
+        let dir = tempfile::TempDir::new().expect("should create temp dir");
+        fs::create_dir_all(dir.path().join("code")).expect("should create dir");
+        // Provides every required variable, but phases without a result fail
+        fs::write(
+            dir.path().join("code").join("pr.md.j2"),
+            "{#- contract\nrequired: [phases]\n-#}\n\
+             {% for phase in phases %}{{ phase.result.turns }}{% endfor %}",
+        )
+        .expect("should write template");
+        let mut pm = PromptManager::new().expect("should create prompt manager");
+        pm.load_dir(dir.path()).expect("should load overrides");
+
+        let issues = template_issues(&pm, &ProjectConfig::default());
+        assert_eq!(issues.len(), 1, "unexpected: {issues:?}");
+        assert_eq!(issues[0].template, "code/pr");
+        assert!(
+            issues[0]
+                .message
+                .starts_with("fails to render with the `pr` stage context: "),
+            "got: {}",
+            issues[0].message
+        );
+    }
+
+    #[test]
+    fn test_should_render_pr_prompt_for_unrun_phases() {
+        let pm = PromptManager::new().expect("should create prompt manager");
+        let spec: FeatureSpec = serde_yaml::from_str(SPEC).expect("should parse spec");
+        let ctx = TemplateContext {
+            repo_path: Path::new("/repo"),
+            slug: "login",
+            design_spec: "",
+            spec: &spec,
+            base_branch: "main",
+        };
+        let review = ReviewResult {
+            turns: 0,
+            issues_found: 0,
+            issues_fixed: 0,
+        };
+        let verification = VerificationResult {
+            turns: 0,
+            passed: true,
+            evidence: None,
+        };
+        let context = ctx.pr(
+            "feat/login",
+            &review,
+            &ReviewRecord::default(),
+            &verification,
+        );
+
+        let rendered = pm.render("code/pr", &context).expect("should render");
+        assert!(rendered.contains("**Phase 1**: Setup (3 turns, commit `abc123`)"));
+        assert!(rendered.contains("**Phase 2**: Api (not run)"));
+    }
+
+    #[test]
+    fn test_should_infer_stage_from_template() {
+        let stages = PipelineConfig::default().stages;
+        let name = |template| infer_stage(&stages, template).map(StageConfig::name);
+
+        assert_eq!(name("code/task"), Some("phases"));
+        assert_eq!(name("code/hook_fix"), Some("phases"));
+        assert_eq!(name("code/pr"), Some("pr"));
+        assert_eq!(name("review/personas/security"), Some("review"));
+        assert_eq!(name("verify/task"), Some("verification"));
+        assert_eq!(name("docs/task"), None);
+    }
+}
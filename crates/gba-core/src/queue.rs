@@ -0,0 +1,680 @@
+//! Feature run queue.
+//!
+//! Features waiting to run are listed in `.gba/queue.yaml`. A [`QueueRun`]
+//! runs the queued features in order, each in its own worktree, with up to
+//! a given number running at once. A failed feature is recorded and the
+//! queue moves on to the next one.
+//!
+//! Only one queue run at a time: it holds `.gba/queue.lock` until it is
+//! dropped. Every read-modify-write of `queue.yaml` holds
+//! `.gba/queue.yaml.lock`, so `gba queue add` and a running queue do not
+//! overwrite each other's changes.
+
+use std::collections::VecDeque;
+use std::fs;
+use std::path::Path;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+use tracing::{debug, info, instrument, warn};
+
+use crate::engine::Engine;
+use crate::error::CoreError;
+use crate::events::{PermissionDecision, RunEvent, RunStream};
+use crate::lock::PidLock;
+use crate::spec::load_feature_spec;
+
+/// File name of the lock held by a queue run, inside `.gba/`.
+const RUN_LOCK_FILE: &str = "queue.lock";
+
+/// File name of the lock held while `queue.yaml` is updated, inside `.gba/`.
+const FILE_LOCK_FILE: &str = "queue.yaml.lock";
+
+/// How long to wait for another process to finish updating `queue.yaml`.
+const FILE_LOCK_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Why permission requests of queued runs are denied.
+const PERMISSION_DENIED_REASON: &str =
+    "queued runs cannot ask for permission; use the auto or none permission mode";
+
+/// Features waiting to run, persisted as `.gba/queue.yaml`.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Queue {
+    /// Queued and processed features, in queue order.
+    #[serde(default)]
+    pub entries: Vec<QueueEntry>,
+}
+
+/// A feature in the queue.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct QueueEntry {
+    /// Feature slug.
+    pub slug: String,
+
+    /// Where the feature stands.
+    pub status: QueueStatus,
+
+    /// When the feature was queued, in seconds since the Unix epoch.
+    pub added_at: u64,
+
+    /// When the feature's run started.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub started_at: Option<u64>,
+
+    /// When the feature's run ended.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub finished_at: Option<u64>,
+
+    /// PR URL, if the run created one.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pr: Option<String>,
+
+    /// Why the run failed.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// Status of a queued feature.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum QueueStatus {
+    /// Waiting to run.
+    Queued,
+    /// Currently running.
+    Running,
+    /// The run finished and verification passed.
+    Completed,
+    /// The run failed, or verification did not pass.
+    Failed,
+}
+
+/// Events emitted while running the queue.
+#[derive(Debug, Serialize)]
+#[serde(
+    tag = "type",
+    content = "data",
+    rename_all = "camelCase",
+    rename_all_fields = "camelCase"
+)]
+pub enum QueueEvent {
+    /// A feature's run started.
+    FeatureStarted {
+        /// Feature slug.
+        slug: String,
+    },
+
+    /// Progress of a running feature.
+    Run {
+        /// Feature slug.
+        slug: String,
+        /// The run's event.
+        event: RunEvent,
+    },
+
+    /// A feature's run ended, successfully or not.
+    FeatureFinished(QueueEntry),
+
+    /// The queue file could not be updated. Running features finish, but no
+    /// more are started.
+    Error(CoreError),
+
+    /// No queued features are left and all runs have ended.
+    Finished {
+        /// Features that ran, in the order they finished.
+        entries: Vec<QueueEntry>,
+    },
+}
+
+/// Runs the features in `.gba/queue.yaml`.
+///
+/// Created by [`Engine::run_queue()`]. Call [`next()`](QueueRun::next) until
+/// it returns `None`; features only make progress while it is polled.
+/// Features queued while the run is in progress are picked up too.
+#[derive(Debug)]
+pub struct QueueRun<'a> {
+    /// Engine the runs are started from.
+    engine: &'a Engine,
+    /// Maximum number of features running at once.
+    jobs: usize,
+    /// Features currently running.
+    running: Vec<ActiveRun>,
+    /// Events to return before doing more work.
+    pending: VecDeque<QueueEvent>,
+    /// Features that ran, in the order they finished.
+    finished: Vec<QueueEntry>,
+    /// Whether to stop starting features.
+    stopping: bool,
+    /// Whether [`QueueEvent::Finished`] was returned.
+    done: bool,
+    /// Lock on the queue run, released when the run is dropped.
+    _lock: PidLock,
+}
+
+/// A running feature and what its events revealed so far.
+#[derive(Debug)]
+struct ActiveRun {
+    /// Feature slug.
+    slug: String,
+    /// The run's events.
+    stream: RunStream,
+    /// Whether the run reached [`RunEvent::Finished`].
+    finished: bool,
+    /// Whether the last verification passed.
+    verification_passed: bool,
+    /// PR URL, once created.
+    pr: Option<String>,
+    /// Last error the run reported.
+    error: Option<String>,
+}
+
+impl ActiveRun {
+    fn new(slug: String, stream: RunStream) -> Self {
+        Self {
+            slug,
+            stream,
+            finished: false,
+            verification_passed: true,
+            pr: None,
+            error: None,
+        }
+    }
+
+    /// Final status and error once the run's events have ended.
+    fn outcome(&self) -> (QueueStatus, Option<String>) {
+        if !self.finished {
+            let error = self
+                .error
+                .clone()
+                .unwrap_or_else(|| "run ended before finishing".to_owned());
+            (QueueStatus::Failed, Some(error))
+        } else if !self.verification_passed {
+            (QueueStatus::Failed, Some("verification failed".to_owned()))
+        } else {
+            (QueueStatus::Completed, None)
+        }
+    }
+}
+
+impl<'a> QueueRun<'a> {
+    pub(crate) fn new(engine: &'a Engine, jobs: usize, lock: PidLock) -> Self {
+        Self {
+            engine,
+            jobs: jobs.max(1),
+            running: Vec::new(),
+            pending: VecDeque::new(),
+            finished: Vec::new(),
+            stopping: false,
+            done: false,
+            _lock: lock,
+        }
+    }
+
+    /// Get the next event, starting queued features as slots free up.
+    ///
+    /// Returns `None` after [`QueueEvent::Finished`].
+    pub async fn next(&mut self) -> Option<QueueEvent> {
+        loop {
+            if let Some(event) = self.pending.pop_front() {
+                return Some(event);
+            }
+            if self.done {
+                return None;
+            }
+
+            if self.running.len() < self.jobs && !self.stopping {
+                match next_queued(&self.engine.gba_dir()) {
+                    Ok(Some(slug)) => {
+                        self.start(slug).await;
+                        continue;
+                    }
+                    Ok(None) => {}
+                    Err(e) => {
+                        self.fail(e);
+                        continue;
+                    }
+                }
+            }
+
+            if self.running.is_empty() {
+                self.done = true;
+                return Some(QueueEvent::Finished {
+                    entries: std::mem::take(&mut self.finished),
+                });
+            }
+
+            let (event, index, rest) = futures::future::select_all(
+                self.running
+                    .iter_mut()
+                    .map(|run| Box::pin(run.stream.next())),
+            )
+            .await;
+            drop(rest);
+            match event {
+                Some(event) => return Some(self.observe(index, event).await),
+                None => {
+                    let run = self.running.remove(index);
+                    let (status, error) = run.outcome();
+                    self.finish(&run.slug, status, error, run.pr);
+                }
+            }
+        }
+    }
+
+    /// Mark `slug` as running and start its run. Features that cannot be
+    /// started are recorded as failed right away.
+    async fn start(&mut self, slug: String) {
+        let now = now();
+        let marked = update_entry(&self.engine.gba_dir(), &slug, |entry| {
+            entry.status = QueueStatus::Running;
+            entry.started_at = Some(now);
+            entry.finished_at = None;
+            entry.pr = None;
+            entry.error = None;
+        });
+        if let Err(e) = marked {
+            self.fail(e);
+            return;
+        }
+
+        info!(slug, "starting queued feature");
+        self.pending
+            .push_back(QueueEvent::FeatureStarted { slug: slug.clone() });
+        match self.engine.run(&slug).await {
+            Ok(stream) => self.running.push(ActiveRun::new(slug, stream)),
+            Err(e) => {
+                warn!(slug, error = %e, "failed to start queued feature");
+                self.finish(&slug, QueueStatus::Failed, Some(e.to_string()), None);
+            }
+        }
+    }
+
+    /// Track the outcome of the run at `index` and wrap its event.
+    async fn observe(&mut self, index: usize, event: RunEvent) -> QueueEvent {
+        let run = &mut self.running[index];
+        match &event {
+            RunEvent::VerificationCompleted { passed, .. } => run.verification_passed = *passed,
+            RunEvent::PrCreated { url } => run.pr = Some(url.clone()),
+            RunEvent::Error(e) => run.error = Some(e.to_string()),
+            RunEvent::Finished => run.finished = true,
+            RunEvent::PermissionRequested(request) => {
+                let decision = PermissionDecision::Deny {
+                    reason: PERMISSION_DENIED_REASON.to_owned(),
+                };
+                if let Err(e) = run.stream.respond_permission(request.id, decision).await {
+                    debug!(slug = run.slug, error = %e, "run ended before the permission answer");
+                }
+            }
+            _ => {}
+        }
+        QueueEvent::Run {
+            slug: run.slug.clone(),
+            event,
+        }
+    }
+
+    /// Record the end of a feature's run.
+    fn finish(
+        &mut self,
+        slug: &str,
+        status: QueueStatus,
+        error: Option<String>,
+        pr: Option<String>,
+    ) {
+        let now = now();
+        let updated = update_entry(&self.engine.gba_dir(), slug, |entry| {
+            entry.status = status;
+            entry.finished_at = Some(now);
+            entry.error = error;
+            entry.pr = pr;
+        });
+        match updated {
+            Ok(entry) => {
+                info!(slug, ?status, "queued feature finished");
+                self.finished.push(entry.clone());
+                self.pending.push_back(QueueEvent::FeatureFinished(entry));
+            }
+            Err(e) => self.fail(e),
+        }
+    }
+
+    /// Report a queue file error and stop starting features.
+    fn fail(&mut self, error: CoreError) {
+        warn!(error = %error, "failed to update the queue");
+        self.stopping = true;
+        self.pending.push_back(QueueEvent::Error(error));
+    }
+}
+
+/// Read `.gba/queue.yaml`, or an empty queue if it does not exist.
+///
+/// # Errors
+///
+/// Returns `CoreError::NotInitialized` if `.gba/` does not exist.
+/// Returns `CoreError::Io` if the file cannot be read.
+/// Returns `CoreError::Yaml` if the file cannot be parsed.
+pub(crate) fn load_queue(gba_dir: &Path) -> Result<Queue, CoreError> {
+    if !gba_dir.exists() {
+        return Err(CoreError::NotInitialized);
+    }
+    let path = gba_dir.join("queue.yaml");
+    if !path.exists() {
+        return Ok(Queue::default());
+    }
+    Ok(serde_yaml::from_str(&fs::read_to_string(&path)?)?)
+}
+
+/// Take the lock on running the queue.
+///
+/// # Errors
+///
+/// Returns `CoreError::NotInitialized` if `.gba/` does not exist.
+/// Returns `CoreError::Locked` if another queue run is in progress.
+pub(crate) fn lock_queue_run(gba_dir: &Path) -> Result<PidLock, CoreError> {
+    if !gba_dir.exists() {
+        return Err(CoreError::NotInitialized);
+    }
+    PidLock::acquire(&gba_dir.join(RUN_LOCK_FILE), "the queue is running")
+}
+
+/// Take the lock on updating `queue.yaml`, waiting briefly for another
+/// update to finish.
+///
+/// # Errors
+///
+/// Returns `CoreError::NotInitialized` if `.gba/` does not exist.
+/// Returns `CoreError::Locked` if another process keeps holding the lock.
+fn lock_queue_file(gba_dir: &Path) -> Result<PidLock, CoreError> {
+    if !gba_dir.exists() {
+        return Err(CoreError::NotInitialized);
+    }
+    PidLock::wait(
+        &gba_dir.join(FILE_LOCK_FILE),
+        "the queue file is being updated",
+        FILE_LOCK_TIMEOUT,
+    )
+}
+
+/// Write the queue to `.gba/queue.yaml`.
+///
+/// # Errors
+///
+/// Returns `CoreError::Yaml` if serialization fails.
+/// Returns `CoreError::Io` if the file cannot be written.
+fn save_queue(gba_dir: &Path, queue: &Queue) -> Result<(), CoreError> {
+    let path = gba_dir.join("queue.yaml");
+    fs::write(&path, serde_yaml::to_string(queue)?)?;
+    debug!(path = %path.display(), "saved queue");
+    Ok(())
+}
+
+/// Append features to the queue.
+///
+/// Features that are already queued or running are skipped; finished ones
+/// are moved to the end of the queue again. Returns the slugs that were
+/// added.
+///
+/// # Errors
+///
+/// Returns `CoreError::NotInitialized` if `.gba/` does not exist.
+/// Returns `CoreError::FeatureNotFound` if a feature has no `phases.yaml`.
+/// Returns `CoreError::InvalidSpec` if a feature's `phases.yaml` is invalid.
+/// Nothing is queued if any feature fails to load.
+#[instrument(skip(gba_dir))]
+pub(crate) fn add_to_queue(gba_dir: &Path, slugs: &[String]) -> Result<Vec<String>, CoreError> {
+    let _lock = lock_queue_file(gba_dir)?;
+    let mut queue = load_queue(gba_dir)?;
+    for slug in slugs {
+        load_feature_spec(gba_dir, slug)?;
+    }
+
+    let now = now();
+    let mut added = Vec::new();
+    for slug in slugs {
+        let waiting = queue.entries.iter().any(|e| {
+            &e.slug == slug && matches!(e.status, QueueStatus::Queued | QueueStatus::Running)
+        });
+        if waiting || added.contains(slug) {
+            debug!(slug, "feature already queued");
+            continue;
+        }
+        queue.entries.retain(|e| &e.slug != slug);
+        queue.entries.push(QueueEntry {
+            slug: slug.clone(),
+            status: QueueStatus::Queued,
+            added_at: now,
+            started_at: None,
+            finished_at: None,
+            pr: None,
+            error: None,
+        });
+        added.push(slug.clone());
+    }
+
+    save_queue(gba_dir, &queue)?;
+    Ok(added)
+}
+
+/// Queue again the features left running by an interrupted queue run.
+/// Returns their slugs.
+///
+/// Only call this while holding the [`lock_queue_run()`] lock: features
+/// marked running by a live queue run are still executing.
+///
+/// # Errors
+///
+/// Same as [`load_queue()`], plus `CoreError::Io` if the queue cannot be
+/// saved.
+pub(crate) fn requeue_interrupted(gba_dir: &Path) -> Result<Vec<String>, CoreError> {
+    let _lock = lock_queue_file(gba_dir)?;
+    let mut queue = load_queue(gba_dir)?;
+    let mut requeued = Vec::new();
+    for entry in &mut queue.entries {
+        if entry.status == QueueStatus::Running {
+            entry.status = QueueStatus::Queued;
+            entry.started_at = None;
+            requeued.push(entry.slug.clone());
+        }
+    }
+    if !requeued.is_empty() {
+        warn!(
+            ?requeued,
+            "queueing features of an interrupted queue run again"
+        );
+        save_queue(gba_dir, &queue)?;
+    }
+    Ok(requeued)
+}
+
+/// First queued feature, re-reading the file to see newly queued features.
+fn next_queued(gba_dir: &Path) -> Result<Option<String>, CoreError> {
+    Ok(load_queue(gba_dir)?
+        .entries
+        .into_iter()
+        .find(|e| e.status == QueueStatus::Queued)
+        .map(|e| e.slug))
+}
+
+/// Apply `update` to the entry of `slug` and save the queue. Returns the
+/// updated entry.
+fn update_entry(
+    gba_dir: &Path,
+    slug: &str,
+    update: impl FnOnce(&mut QueueEntry),
+) -> Result<QueueEntry, CoreError> {
+    let _lock = lock_queue_file(gba_dir)?;
+    let mut queue = load_queue(gba_dir)?;
+    let entry = queue
+        .entries
+        .iter_mut()
+        .find(|e| e.slug == slug)
+        .ok_or_else(|| CoreError::Other(anyhow::anyhow!("{slug:?} was removed from the queue")))?;
+    update(entry);
+    let entry = entry.clone();
+    save_queue(gba_dir, &queue)?;
+    Ok(entry)
+}
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::EngineConfig;
+
+    const SPEC: &str = "feature: F\nphases:\n  - name: Setup\n    description: d\n    tasks: [t]\n\
+                        verification:\n  criteria: [c]\n  testCommands: [cargo test]\n";
+
+    fn write_feature(gba_dir: &Path, slug: &str) {
+        let dir = gba_dir.join("features").join(slug);
+        fs::create_dir_all(&dir).expect("should create feature dir");
+        fs::write(dir.join("phases.yaml"), SPEC).expect("should write spec");
+    }
+
+    fn slugs(queue: &Queue) -> Vec<(&str, QueueStatus)> {
+        queue
+            .entries
+            .iter()
+            .map(|e| (e.slug.as_str(), e.status))
+            .collect()
+    }
+
+    #[test]
+    fn test_should_add_features_once() {
+        let dir = tempfile::TempDir::new().expect("should create temp dir");
+        let gba_dir = dir.path().join(".gba");
+        assert!(matches!(
+            load_queue(&gba_dir),
+            Err(CoreError::NotInitialized)
+        ));
+        write_feature(&gba_dir, "a");
+        write_feature(&gba_dir, "b");
+
+        let added = add_to_queue(&gba_dir, &["a".to_owned(), "b".to_owned(), "a".to_owned()])
+            .expect("should queue");
+        assert_eq!(added, ["a", "b"]);
+        assert!(
+            add_to_queue(&gba_dir, &["a".to_owned()])
+                .expect("should queue")
+                .is_empty()
+        );
+
+        // Finished features move to the end when queued again
+        update_entry(&gba_dir, "a", |e| e.status = QueueStatus::Failed).expect("should update");
+        assert_eq!(
+            add_to_queue(&gba_dir, &["a".to_owned()]).expect("should queue"),
+            ["a"]
+        );
+        let queue = load_queue(&gba_dir).expect("should load");
+        assert_eq!(
+            slugs(&queue),
+            [("b", QueueStatus::Queued), ("a", QueueStatus::Queued)]
+        );
+
+        // Unknown features queue nothing
+        assert!(matches!(
+            add_to_queue(&gba_dir, &["c".to_owned(), "missing".to_owned()]),
+            Err(CoreError::FeatureNotFound(_))
+        ));
+        assert_eq!(load_queue(&gba_dir).expect("should load"), queue);
+    }
+
+    #[test]
+    fn test_should_requeue_interrupted_features() {
+        let dir = tempfile::TempDir::new().expect("should create temp dir");
+        let gba_dir = dir.path().join(".gba");
+        write_feature(&gba_dir, "a");
+        add_to_queue(&gba_dir, &["a".to_owned()]).expect("should queue");
+        update_entry(&gba_dir, "a", |e| {
+            e.status = QueueStatus::Running;
+            e.started_at = Some(1);
+        })
+        .expect("should update");
+
+        assert_eq!(
+            requeue_interrupted(&gba_dir).expect("should requeue"),
+            ["a"]
+        );
+        let entry = &load_queue(&gba_dir).expect("should load").entries[0];
+        assert_eq!(
+            (entry.status, entry.started_at),
+            (QueueStatus::Queued, None)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_should_not_requeue_features_of_a_live_queue_run() {
+        let dir = tempfile::TempDir::new().expect("should create temp dir");
+        let gba_dir = dir.path().join(".gba");
+        write_feature(&gba_dir, "a");
+        add_to_queue(&gba_dir, &["a".to_owned()]).expect("should queue");
+        update_entry(&gba_dir, "a", |e| e.status = QueueStatus::Running).expect("should update");
+        let config = EngineConfig::builder()
+            .repo_path(dir.path().to_path_buf())
+            .build();
+        let engine = Engine::new(config).await.expect("should create engine");
+
+        let live = lock_queue_run(&gba_dir).expect("should lock");
+        assert!(matches!(engine.run_queue(1), Err(CoreError::Locked(_))));
+        assert_eq!(
+            slugs(&load_queue(&gba_dir).expect("should load")),
+            [("a", QueueStatus::Running)]
+        );
+
+        // Once the other run is gone its features are queued again
+        drop(live);
+        let run = engine.run_queue(1).expect("should run the queue");
+        assert_eq!(
+            slugs(&load_queue(&gba_dir).expect("should load")),
+            [("a", QueueStatus::Queued)]
+        );
+        assert!(matches!(engine.run_queue(1), Err(CoreError::Locked(_))));
+        drop(run);
+        assert!(!gba_dir.join(RUN_LOCK_FILE).exists());
+    }
+
+    #[tokio::test]
+    async fn test_should_continue_after_failed_feature() {
+        // Not a git repository, so every run fails to create its worktree
+        let dir = tempfile::TempDir::new().expect("should create temp dir");
+        let gba_dir = dir.path().join(".gba");
+        write_feature(&gba_dir, "a");
+        write_feature(&gba_dir, "b");
+        add_to_queue(&gba_dir, &["a".to_owned(), "b".to_owned()]).expect("should queue");
+        let config = EngineConfig::builder()
+            .repo_path(dir.path().to_path_buf())
+            .build();
+        let engine = Engine::new(config).await.expect("should create engine");
+
+        let lock = lock_queue_run(&gba_dir).expect("should lock");
+        let mut run = QueueRun::new(&engine, 2, lock);
+        let mut events = Vec::new();
+        while let Some(event) = run.next().await {
+            events.push(event);
+        }
+
+        let Some(QueueEvent::Finished { entries }) = events.last() else {
+            panic!("should end with Finished: {events:?}");
+        };
+        let finished: Vec<(&str, QueueStatus)> = entries
+            .iter()
+            .map(|e| (e.slug.as_str(), e.status))
+            .collect();
+        assert_eq!(
+            finished,
+            [("a", QueueStatus::Failed), ("b", QueueStatus::Failed)]
+        );
+        assert!(entries.iter().all(|e| e.error.is_some()));
+        assert!(matches!(&events[0], QueueEvent::FeatureStarted { slug } if slug == "a"));
+        assert_eq!(
+            slugs(&load_queue(&gba_dir).expect("should load")),
+            [("a", QueueStatus::Failed), ("b", QueueStatus::Failed)]
+        );
+    }
+}
@@ -0,0 +1,319 @@
+//! Execution reports.
+//!
+//! Collects everything recorded about a feature's run -- the spec with its
+//! phase results, the commits on the feature branch with their diffstats,
+//! review rounds, verification evidence, hook history and agent costs --
+//! into one [`Report`] that the CLI renders as Markdown, HTML or JSON.
+
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde::Serialize;
+use tracing::instrument;
+
+use crate::error::CoreError;
+use crate::git::GitOps;
+use crate::review::{ReviewRound, load_review_record};
+use crate::spec::{AgentUsage, FeatureSpec, load_feature_spec};
+
+/// Everything recorded about a feature's plan and run.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Report {
+    /// Feature slug.
+    pub slug: String,
+
+    /// Feature branch.
+    pub branch: String,
+
+    /// Branch the feature branch was created from.
+    pub base_branch: String,
+
+    /// When the report was generated, in seconds since the Unix epoch.
+    pub generated_at: u64,
+
+    /// Feature spec with phase results and the execution record (stages,
+    /// verification evidence, hook runs, tool denials, session usage).
+    pub spec: FeatureSpec,
+
+    /// Commits on the feature branch that are not on the base branch,
+    /// oldest first. Empty if the branch does not exist.
+    pub commits: Vec<CommitSummary>,
+
+    /// Review rounds from `review.yaml`, in execution order.
+    pub review_rounds: Vec<ReviewRound>,
+
+    /// Turns, tokens and cost summed over all agent sessions of the run.
+    pub totals: UsageTotals,
+}
+
+/// A commit on the feature branch with its diffstat.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CommitSummary {
+    /// Full commit hash.
+    pub hash: String,
+
+    /// Commit time in seconds since the Unix epoch.
+    pub time: u64,
+
+    /// First line of the commit message.
+    pub subject: String,
+
+    /// Lines added across all text files.
+    pub insertions: u64,
+
+    /// Lines removed across all text files.
+    pub deletions: u64,
+
+    /// Changed files.
+    pub files: Vec<FileChange>,
+}
+
+/// Line counts of one file changed by a commit.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FileChange {
+    /// File path, `old => new` for renames.
+    pub path: String,
+
+    /// Lines added, or `None` for a binary file.
+    pub insertions: Option<u64>,
+
+    /// Lines removed, or `None` for a binary file.
+    pub deletions: Option<u64>,
+}
+
+/// Usage summed over agent sessions.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UsageTotals {
+    /// Number of agent sessions.
+    pub sessions: usize,
+
+    /// Agent API round-trips.
+    pub turns: u64,
+
+    /// Input tokens, including cache reads and writes.
+    pub input_tokens: u64,
+
+    /// Output tokens.
+    pub output_tokens: u64,
+
+    /// Cost in USD of the sessions that reported one, or `None` if none did.
+    pub cost_usd: Option<f64>,
+}
+
+impl UsageTotals {
+    /// Sum the usage of `sessions`.
+    pub fn from_sessions(sessions: &[AgentUsage]) -> Self {
+        sessions.iter().fold(
+            Self {
+                sessions: sessions.len(),
+                ..Self::default()
+            },
+            |totals, usage| Self {
+                turns: totals.turns + u64::from(usage.turns),
+                input_tokens: totals.input_tokens + usage.input_tokens,
+                output_tokens: totals.output_tokens + usage.output_tokens,
+                cost_usd: match (totals.cost_usd, usage.cost_usd) {
+                    (Some(a), Some(b)) => Some(a + b),
+                    (a, b) => a.or(b),
+                },
+                ..totals
+            },
+        )
+    }
+}
+
+/// Build the report of a feature.
+///
+/// # Errors
+///
+/// Returns `CoreError::FeatureNotFound` if the feature has no `phases.yaml`.
+/// Returns `CoreError::InvalidSpec` if `phases.yaml` is invalid.
+/// Returns `CoreError::Yaml` if `review.yaml` cannot be parsed.
+/// Returns `CoreError::Git` if the feature branch's log cannot be read.
+#[instrument(skip(gba_dir, git))]
+pub(crate) async fn build_report(
+    gba_dir: &Path,
+    git: &GitOps,
+    base_branch: &str,
+    slug: &str,
+) -> Result<Report, CoreError> {
+    let spec = load_feature_spec(gba_dir, slug)?;
+    let review_rounds = load_review_record(gba_dir, slug)?.rounds;
+    let commits = git
+        .branch_log(slug)
+        .await?
+        .map(|log| parse_commit_log(&log))
+        .unwrap_or_default();
+    let totals = spec
+        .execution
+        .as_ref()
+        .map(|e| UsageTotals::from_sessions(&e.usage))
+        .unwrap_or_default();
+    let generated_at = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default();
+
+    Ok(Report {
+        slug: slug.to_owned(),
+        branch: git.branch_name(slug),
+        base_branch: base_branch.to_owned(),
+        generated_at,
+        spec,
+        commits,
+        review_rounds,
+        totals,
+    })
+}
+
+/// Parse the output of [`GitOps::branch_log`].
+fn parse_commit_log(log: &str) -> Vec<CommitSummary> {
+    log.split('\x1e')
+        .filter_map(|entry| {
+            let mut lines = entry.lines();
+            let mut header = lines.next()?.splitn(3, '\x1f');
+            let hash = header.next()?.to_owned();
+            let time = header.next()?.parse().ok()?;
+            let subject = header.next().unwrap_or_default().to_owned();
+
+            let files: Vec<FileChange> = lines
+                .filter_map(|line| {
+                    let mut fields = line.splitn(3, '\t');
+                    let insertions = fields.next()?;
+                    let deletions = fields.next()?;
+                    Some(FileChange {
+                        path: fields.next()?.to_owned(),
+                        insertions: insertions.parse().ok(),
+                        deletions: deletions.parse().ok(),
+                    })
+                })
+                .collect();
+            Some(CommitSummary {
+                hash,
+                time,
+                subject,
+                insertions: files.iter().filter_map(|f| f.insertions).sum(),
+                deletions: files.iter().filter_map(|f| f.deletions).sum(),
+                files,
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::process::Command;
+
+    use super::*;
+    use crate::config::GitConfig;
+
+    fn git(dir: &Path, args: &[&str]) {
+        let status = Command::new("git")
+            .args(args)
+            .current_dir(dir)
+            .status()
+            .expect("should run git");
+        assert!(status.success(), "git {args:?} failed");
+    }
+
+    #[test]
+    fn test_should_parse_commit_log_with_numstat() {
+        let log = "\x1eabc123\x1f1700000000\x1ffeat(login): phase 1 - Setup\n\n\
+                   10\t2\tsrc/main.rs\n-\t-\tassets/logo.png\n\
+                   \x1edef456\x1f1700000100\x1ffix(login): review fixes\n\n\
+                   1\t1\tsrc/main.rs\n";
+
+        let commits = parse_commit_log(log);
+
+        assert_eq!(commits.len(), 2);
+        assert_eq!(commits[0].hash, "abc123");
+        assert_eq!(commits[0].time, 1_700_000_000);
+        assert_eq!(commits[0].subject, "feat(login): phase 1 - Setup");
+        assert_eq!((commits[0].insertions, commits[0].deletions), (10, 2));
+        assert_eq!(
+            commits[0].files[1],
+            FileChange {
+                path: "assets/logo.png".to_owned(),
+                insertions: None,
+                deletions: None,
+            }
+        );
+        assert_eq!(commits[1].files.len(), 1);
+        assert!(parse_commit_log("").is_empty());
+    }
+
+    #[test]
+    fn test_should_sum_session_usage() {
+        let usage = |turns, cost| AgentUsage {
+            agent: "code".to_owned(),
+            phase: "Setup".to_owned(),
+            turns,
+            input_tokens: 100,
+            output_tokens: 10,
+            cost_usd: cost,
+        };
+
+        let totals = UsageTotals::from_sessions(&[usage(3, Some(0.5)), usage(2, None)]);
+
+        assert_eq!(totals.sessions, 2);
+        assert_eq!(totals.turns, 5);
+        assert_eq!((totals.input_tokens, totals.output_tokens), (200, 20));
+        assert_eq!(totals.cost_usd, Some(0.5));
+        assert_eq!(UsageTotals::from_sessions(&[]).cost_usd, None);
+    }
+
+    #[tokio::test]
+    async fn test_should_build_report_with_branch_commits() {
+        let repo = tempfile::TempDir::new().expect("should create temp dir");
+        let root = repo.path();
+        git(root, &["init", "-q", "-b", "main"]);
+        git(root, &["config", "user.email", "gba@example.com"]);
+        git(root, &["config", "user.name", "gba"]);
+        git(root, &["commit", "-q", "--allow-empty", "-m", "init"]);
+        git(root, &["checkout", "-q", "-b", "feat/login"]);
+        std::fs::write(root.join("a.rs"), "fn a() {}\nfn b() {}\n").expect("should write");
+        git(root, &["add", "."]);
+        git(
+            root,
+            &["commit", "-q", "-m", "feat(login): phase 1 - Setup"],
+        );
+
+        let gba_dir = root.join(".gba");
+        let feature_dir = gba_dir.join("features/login");
+        std::fs::create_dir_all(&feature_dir).expect("should create feature dir");
+        std::fs::write(
+            feature_dir.join("phases.yaml"),
+            "feature: Login\nphases:\n  - name: Setup\n    description: d\n    tasks: [t]\n\
+             verification:\n  criteria: [c]\n  testCommands: [cargo test]\n",
+        )
+        .expect("should write spec");
+        let config = GitConfig {
+            branch_pattern: "feat/{slug}".to_owned(),
+            base_branch: "main".to_owned(),
+            ..GitConfig::default()
+        };
+        let ops = GitOps::new(root.to_path_buf(), config);
+
+        let report = build_report(&gba_dir, &ops, "main", "login")
+            .await
+            .expect("should build report");
+
+        assert_eq!(report.branch, "feat/login");
+        assert_eq!(report.spec.feature, "Login");
+        assert_eq!(report.commits.len(), 1);
+        assert_eq!(report.commits[0].subject, "feat(login): phase 1 - Setup");
+        assert_eq!(report.commits[0].insertions, 2);
+        assert!(report.review_rounds.is_empty());
+        assert_eq!(report.totals, UsageTotals::default());
+
+        let missing = GitOps::new(root.to_path_buf(), GitConfig::default());
+        let report = build_report(&gba_dir, &missing, "main", "login")
+            .await
+            .expect("should build report without a branch");
+        assert!(report.commits.is_empty());
+    }
+}
@@ -0,0 +1,563 @@
+//! Code review helpers (internal).
+//!
+//! Parses the issues reported by review agents, merges the findings of
+//! several reviewers into one deduplicated, attributed list, and persists
+//! each review round to `.gba/features/<slug>/review.yaml`.
+
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+use tracing::{debug, instrument};
+
+use crate::error::CoreError;
+use crate::events::{Issue, Severity};
+
+/// Two issues on lines at most this far apart may describe the same problem.
+const LINE_TOLERANCE: u32 = 3;
+
+/// Minimum description similarity for issues on the same line.
+const SAME_LINE_SIMILARITY: f64 = 0.25;
+
+/// Minimum description similarity for issues on nearby or unknown lines.
+const SIMILARITY_THRESHOLD: f64 = 0.5;
+
+// ── Review Record ────────────────────────────────────────────
+
+/// Review findings of a run, persisted as `review.yaml`.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct ReviewRecord {
+    /// Review rounds in execution order.
+    pub rounds: Vec<ReviewRound>,
+}
+
+impl ReviewRecord {
+    /// All issues across rounds, in the order they were reported.
+    pub(crate) fn issues(&self) -> impl Iterator<Item = &Issue> {
+        self.rounds.iter().flat_map(|r| r.issues.iter())
+    }
+}
+
+/// One iteration of a review stage.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReviewRound {
+    /// Name of the review stage.
+    pub stage: String,
+    /// One-based iteration within the stage.
+    pub iteration: u32,
+    /// Reviewers that took part.
+    pub reviewers: Vec<String>,
+    /// Merged issues, each attributed to the reviewers that raised it.
+    pub issues: Vec<Issue>,
+    /// Reviewers whose session failed; their issues are missing from the
+    /// round.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub failed: Vec<ReviewerFailure>,
+}
+
+/// A reviewer whose session failed during a review round.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReviewerFailure {
+    /// Reviewer name.
+    pub reviewer: String,
+    /// Why the session failed.
+    pub error: String,
+}
+
+/// Write the review record to `.gba/features/<slug>/review.yaml`.
+///
+/// # Errors
+///
+/// Returns `CoreError::Io` if the file cannot be written.
+/// Returns `CoreError::Yaml` if the record cannot be serialized.
+#[instrument(skip(gba_dir, record))]
+pub(crate) fn save_review_record(
+    gba_dir: &Path,
+    slug: &str,
+    record: &ReviewRecord,
+) -> Result<(), CoreError> {
+    let feature_dir = gba_dir.join("features").join(slug);
+    fs::create_dir_all(&feature_dir)?;
+    let path = feature_dir.join("review.yaml");
+    fs::write(&path, serde_yaml::to_string(record)?)?;
+    debug!(path = %path.display(), rounds = record.rounds.len(), "saved review record");
+    Ok(())
+}
+
+/// Read the review record from `.gba/features/<slug>/review.yaml`.
+///
+/// Returns an empty record if the feature has not been reviewed.
+///
+/// # Errors
+///
+/// Returns `CoreError::Io` if the file cannot be read.
+/// Returns `CoreError::Yaml` if the file cannot be parsed.
+#[instrument(skip(gba_dir))]
+pub(crate) fn load_review_record(gba_dir: &Path, slug: &str) -> Result<ReviewRecord, CoreError> {
+    let path = gba_dir.join("features").join(slug).join("review.yaml");
+    if !path.exists() {
+        return Ok(ReviewRecord::default());
+    }
+    Ok(serde_yaml::from_str(&fs::read_to_string(&path)?)?)
+}
+
+// ── Merging ──────────────────────────────────────────────────
+
+/// Merge the issues raised by several reviewers.
+///
+/// Issues on the same file, on the same or nearby lines, with similar
+/// descriptions are treated as one. The merged issue keeps the first
+/// description, the highest severity, and lists every reviewer that raised
+/// it.
+pub(crate) fn merge_issues(reports: Vec<(String, Vec<Issue>)>) -> Vec<Issue> {
+    let mut merged: Vec<Issue> = Vec::new();
+
+    for (reviewer, issues) in reports {
+        for mut issue in issues {
+            if let Some(existing) = merged.iter_mut().find(|m| is_duplicate(m, &issue)) {
+                existing.severity = existing.severity.clone().min(issue.severity);
+                existing.line = existing.line.or(issue.line);
+                if !existing.reviewers.contains(&reviewer) {
+                    existing.reviewers.push(reviewer.clone());
+                }
+            } else {
+                issue.reviewers = vec![reviewer.clone()];
+                merged.push(issue);
+            }
+        }
+    }
+
+    merged
+}
+
+/// Whether two issues describe the same problem.
+fn is_duplicate(a: &Issue, b: &Issue) -> bool {
+    if normalize_path(&a.file) != normalize_path(&b.file) {
+        return false;
+    }
+    let threshold = match (a.line, b.line) {
+        (Some(x), Some(y)) if x == y => SAME_LINE_SIMILARITY,
+        (Some(x), Some(y)) if x.abs_diff(y) > LINE_TOLERANCE => return false,
+        _ => SIMILARITY_THRESHOLD,
+    };
+    description_similarity(&a.description, &b.description) >= threshold
+}
+
+/// Strip a leading `./` so `./src/a.rs` and `src/a.rs` compare equal.
+fn normalize_path(path: &Path) -> &Path {
+    path.strip_prefix("./").unwrap_or(path)
+}
+
+/// Jaccard similarity of the significant words of two descriptions.
+fn description_similarity(a: &str, b: &str) -> f64 {
+    let words = |s: &str| -> HashSet<String> {
+        s.split(|c: char| !c.is_alphanumeric())
+            .filter(|w| w.len() >= 3)
+            .map(str::to_lowercase)
+            .collect()
+    };
+    let (a, b) = (words(a), words(b));
+    let union = a.union(&b).count();
+    if union == 0 {
+        return 0.0;
+    }
+    a.intersection(&b).count() as f64 / union as f64
+}
+
+// ── Parsing ──────────────────────────────────────────────────
+
+/// Parse review issues from the review agent's text output.
+///
+/// Expects issues in the format (`line` is optional):
+/// ```text
+/// - severity: error
+///   file: src/main.rs
+///   line: 42
+///   description: Missing error handling
+/// ```
+///
+/// Also handles inline formats like:
+/// ```text
+/// - [error] src/main.rs: Missing error handling
+/// - [error] src/main.rs:42: Missing error handling
+/// ```
+pub(crate) fn parse_review_issues(output: &str) -> Vec<Issue> {
+    let mut issues = Vec::new();
+
+    // Try block format first
+    let block_issues = parse_block_format(output);
+    if !block_issues.is_empty() {
+        return block_issues;
+    }
+
+    // Try inline format: - [severity] file: description
+    for line in output.lines() {
+        let trimmed = line.trim();
+        if let Some(issue) = parse_inline_issue(trimmed) {
+            issues.push(issue);
+        }
+    }
+
+    issues
+}
+
+/// Parse issues in the block format (YAML-like).
+fn parse_block_format(output: &str) -> Vec<Issue> {
+    let mut issues = Vec::new();
+    let mut current_severity: Option<Severity> = None;
+    let mut current_file: Option<String> = None;
+    let mut current_line: Option<u32> = None;
+    let mut current_description: Option<String> = None;
+
+    for line in output.lines() {
+        let trimmed = line.trim();
+
+        // Check for severity field
+        if let Some(rest) = trimmed
+            .strip_prefix("severity:")
+            .or_else(|| trimmed.strip_prefix("- severity:").map(|s| s.trim_start()))
+        {
+            // Flush previous issue if any
+            if let (Some(sev), Some(file), Some(desc)) =
+                (&current_severity, &current_file, &current_description)
+            {
+                issues.push(Issue {
+                    severity: sev.clone(),
+                    file: PathBuf::from(file),
+                    line: current_line,
+                    description: desc.clone(),
+                    reviewers: Vec::new(),
+                });
+            }
+            current_severity = parse_severity(rest.trim());
+            current_file = None;
+            current_line = None;
+            current_description = None;
+            continue;
+        }
+
+        if let Some(rest) = trimmed.strip_prefix("file:") {
+            current_file = Some(rest.trim().to_owned());
+            continue;
+        }
+
+        if let Some(rest) = trimmed.strip_prefix("line:") {
+            current_line = rest.trim().parse().ok();
+            continue;
+        }
+
+        if let Some(rest) = trimmed.strip_prefix("description:") {
+            current_description = Some(rest.trim().to_owned());
+            continue;
+        }
+    }
+
+    // Flush the last issue
+    if let (Some(sev), Some(file), Some(desc)) =
+        (current_severity, current_file, current_description)
+    {
+        issues.push(Issue {
+            severity: sev,
+            file: PathBuf::from(file),
+            line: current_line,
+            description: desc,
+            reviewers: Vec::new(),
+        });
+    }
+
+    issues
+}
+
+/// Parse a single inline issue in the format `- [severity] file: description`
+/// or `- [severity] file:line: description`.
+fn parse_inline_issue(line: &str) -> Option<Issue> {
+    let content = line.strip_prefix('-')?.trim();
+
+    // Match [severity]
+    let content = content.strip_prefix('[')?;
+    let bracket_end = content.find(']')?;
+    let severity_str = &content[..bracket_end];
+    let rest = content[bracket_end + 1..].trim();
+
+    let severity = parse_severity(severity_str)?;
+
+    // Match file: description
+    let colon_pos = rest.find(':')?;
+    let file = rest[..colon_pos].trim();
+    let mut description = rest[colon_pos + 1..].trim();
+
+    // Optional line number right after the file
+    let mut line_number = None;
+    if let Some((number, desc)) = description.split_once(':')
+        && let Ok(n) = number.trim().parse::<u32>()
+    {
+        line_number = Some(n);
+        description = desc.trim();
+    }
+
+    if file.is_empty() || description.is_empty() {
+        return None;
+    }
+
+    Some(Issue {
+        severity,
+        file: PathBuf::from(file),
+        line: line_number,
+        description: description.to_owned(),
+        reviewers: Vec::new(),
+    })
+}
+
+/// Parse a severity string to a [`Severity`] enum variant.
+fn parse_severity(s: &str) -> Option<Severity> {
+    match s.to_lowercase().trim() {
+        "error" => Some(Severity::Error),
+        "warning" | "warn" => Some(Severity::Warning),
+        "suggestion" | "info" | "note" => Some(Severity::Suggestion),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn issue(severity: Severity, file: &str, line: Option<u32>, description: &str) -> Issue {
+        Issue {
+            severity,
+            file: PathBuf::from(file),
+            line,
+            description: description.to_owned(),
+            reviewers: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_should_parse_review_issues_block_format() {
+        let output = r"
+Here are the issues found:
+
+- severity: error
+  file: src/main.rs
+  description: Missing error handling for database connection
+
+- severity: warning
+  file: src/lib.rs
+  description: Consider using a more descriptive variable name
+
+- severity: suggestion
+  file: tests/integration.rs
+  description: Add more edge case tests
+";
+
+        let issues = parse_review_issues(output);
+
+        assert_eq!(issues.len(), 3);
+        assert_eq!(issues[0].severity, Severity::Error);
+        assert_eq!(issues[0].file, PathBuf::from("src/main.rs"));
+        assert!(issues[0].description.contains("Missing error handling"));
+
+        assert_eq!(issues[1].severity, Severity::Warning);
+        assert_eq!(issues[1].file, PathBuf::from("src/lib.rs"));
+
+        assert_eq!(issues[2].severity, Severity::Suggestion);
+        assert_eq!(issues[2].file, PathBuf::from("tests/integration.rs"));
+    }
+
+    #[test]
+    fn test_should_parse_review_issues_inline_format() {
+        let output = r"
+Review complete. Issues:
+- [error] src/main.rs: Missing error handling
+- [warning] src/config.rs: Unused import
+- [suggestion] src/lib.rs: Consider extracting this function
+";
+
+        let issues = parse_review_issues(output);
+
+        assert_eq!(issues.len(), 3);
+        assert_eq!(issues[0].severity, Severity::Error);
+        assert_eq!(issues[0].file, PathBuf::from("src/main.rs"));
+        assert_eq!(issues[0].description, "Missing error handling");
+
+        assert_eq!(issues[1].severity, Severity::Warning);
+        assert_eq!(issues[1].file, PathBuf::from("src/config.rs"));
+
+        assert_eq!(issues[2].severity, Severity::Suggestion);
+    }
+
+    #[test]
+    fn test_should_parse_no_issues() {
+        let output = r"
+Code review complete. No issues found. The implementation looks good
+and follows all the project conventions.
+";
+
+        let issues = parse_review_issues(output);
+        assert!(issues.is_empty());
+    }
+
+    #[test]
+    fn test_should_parse_empty_output() {
+        let issues = parse_review_issues("");
+        assert!(issues.is_empty());
+    }
+
+    #[test]
+    fn test_should_parse_severity_variants() {
+        assert_eq!(parse_severity("error"), Some(Severity::Error));
+        assert_eq!(parse_severity("Error"), Some(Severity::Error));
+        assert_eq!(parse_severity("ERROR"), Some(Severity::Error));
+        assert_eq!(parse_severity("warning"), Some(Severity::Warning));
+        assert_eq!(parse_severity("warn"), Some(Severity::Warning));
+        assert_eq!(parse_severity("suggestion"), Some(Severity::Suggestion));
+        assert_eq!(parse_severity("info"), Some(Severity::Suggestion));
+        assert_eq!(parse_severity("note"), Some(Severity::Suggestion));
+        assert_eq!(parse_severity("unknown"), None);
+    }
+
+    #[test]
+    fn test_should_parse_inline_issue_correctly() {
+        let line = "- [error] src/main.rs: Missing error handling";
+        let issue = parse_inline_issue(line.trim());
+        assert!(issue.is_some());
+        let issue = issue.expect("should parse");
+        assert_eq!(issue.severity, Severity::Error);
+        assert_eq!(issue.file, PathBuf::from("src/main.rs"));
+        assert_eq!(issue.description, "Missing error handling");
+    }
+
+    #[test]
+    fn test_should_reject_malformed_inline_issue() {
+        assert!(parse_inline_issue("not an issue").is_none());
+        assert!(parse_inline_issue("- [error]").is_none());
+        assert!(parse_inline_issue("- [error] :").is_none());
+        assert!(parse_inline_issue("- [unknown] file: desc").is_none());
+    }
+
+    #[test]
+    fn test_should_parse_issue_line_numbers() {
+        let block = "- severity: error\n  file: src/db.rs\n  line: 42\n  description: Query is built by string concatenation\n";
+        let issues = parse_review_issues(block);
+        assert_eq!(issues.len(), 1);
+        assert_eq!(issues[0].line, Some(42));
+
+        let inline =
+            parse_inline_issue("- [warning] src/db.rs:7: Clone in hot loop").expect("should parse");
+        assert_eq!(inline.file, PathBuf::from("src/db.rs"));
+        assert_eq!(inline.line, Some(7));
+        assert_eq!(inline.description, "Clone in hot loop");
+    }
+
+    #[test]
+    fn test_should_merge_duplicate_issues_across_reviewers() {
+        let reports = vec![
+            (
+                "security".to_owned(),
+                vec![
+                    issue(
+                        Severity::Warning,
+                        "src/db.rs",
+                        Some(42),
+                        "SQL query built with format! allows injection",
+                    ),
+                    issue(
+                        Severity::Error,
+                        "src/auth.rs",
+                        None,
+                        "Token compared with ==",
+                    ),
+                ],
+            ),
+            (
+                "performance".to_owned(),
+                vec![
+                    issue(
+                        Severity::Error,
+                        "./src/db.rs",
+                        Some(43),
+                        "SQL query built with format! on every call allows injection",
+                    ),
+                    issue(
+                        Severity::Suggestion,
+                        "src/db.rs",
+                        Some(90),
+                        "Reuse the prepared statement",
+                    ),
+                ],
+            ),
+        ];
+
+        let merged = merge_issues(reports);
+
+        assert_eq!(merged.len(), 3);
+        assert_eq!(merged[0].file, PathBuf::from("src/db.rs"));
+        assert_eq!(merged[0].severity, Severity::Error);
+        assert_eq!(merged[0].reviewers, vec!["security", "performance"]);
+        assert!(
+            merged[0]
+                .description
+                .starts_with("SQL query built with format!")
+        );
+        assert_eq!(merged[1].reviewers, vec!["security"]);
+        assert_eq!(merged[2].reviewers, vec!["performance"]);
+    }
+
+    #[test]
+    fn test_should_keep_distinct_issues_on_distant_lines() {
+        let a = issue(
+            Severity::Error,
+            "src/a.rs",
+            Some(10),
+            "Missing error handling",
+        );
+        let b = issue(
+            Severity::Error,
+            "src/a.rs",
+            Some(80),
+            "Missing error handling",
+        );
+        assert!(!is_duplicate(&a, &b));
+
+        let c = issue(
+            Severity::Error,
+            "src/a.rs",
+            None,
+            "Missing error handling here",
+        );
+        assert!(is_duplicate(&a, &c));
+    }
+
+    #[test]
+    fn test_should_save_and_load_review_record() {
+        let dir = tempfile::TempDir::new().expect("should create temp dir");
+        let mut merged = issue(Severity::Error, "src/a.rs", Some(1), "Bug");
+        merged.reviewers = vec!["security".to_owned()];
+        let record = ReviewRecord {
+            rounds: vec![ReviewRound {
+                stage: "review".to_owned(),
+                iteration: 1,
+                reviewers: vec!["security".to_owned()],
+                issues: vec![merged],
+                failed: Vec::new(),
+            }],
+        };
+
+        save_review_record(dir.path(), "feat", &record).expect("should save");
+
+        let parsed = load_review_record(dir.path(), "feat").expect("should load");
+        assert_eq!(parsed.rounds.len(), 1);
+        assert_eq!(parsed.rounds[0].issues[0].reviewers, vec!["security"]);
+        assert_eq!(parsed.issues().count(), 1);
+        assert!(
+            load_review_record(dir.path(), "other")
+                .expect("should load missing record")
+                .rounds
+                .is_empty()
+        );
+    }
+}
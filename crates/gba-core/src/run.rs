@@ -1,46 +1,63 @@
 //! Run workflow implementation.
 //!
-//! Implements the automated phase-by-phase execution pipeline for a feature.
-//! The workflow loads a feature spec (`phases.yaml`), ensures a git worktree
-//! exists, executes each phase via the coding agent, runs precommit hooks,
-//! performs code review and verification, and finally creates a pull request.
+//! Implements the automated execution pipeline for a feature. The workflow
+//! loads a feature spec (`phases.yaml`), ensures a git worktree exists, then
+//! runs the stages configured under `pipeline.stages`. The default pipeline
+//! executes each phase via the coding agent (running precommit hooks after
+//! each one), performs code review and verification, and finally creates a
+//! pull request.
 //!
 //! Progress is reported through [`RunEvent`] on a channel consumed by the CLI
 //! via [`RunStream`].
 //!
 //! # Edge cases
 //!
-//! - **Empty phases list**: if `phases.yaml` has no phases, execution skips
-//!   directly to the review/verification steps.
-//! - **Missing verification commands**: verification is skipped when no test
-//!   commands are defined.
+//! - **Empty phases list**: if `phases.yaml` has no phases, the `phases`
+//!   stage is skipped.
+//! - **Missing verification commands**: verification stages are skipped when
+//!   no test commands or criteria are defined.
 //! - **Missing design spec**: a warning is logged and an empty string is used
 //!   so the coding agent still receives valid context.
 //! - **Resume support**: completed phases are detected and skipped automatically.
+//! - **Concurrent runs**: a feature runs at most once at a time; the run holds
+//!   `.gba/features/<slug>/run.lock` until its background task ends.
 
 use std::path::{Path, PathBuf};
 use std::sync::Arc;
 
 use claude_agent_sdk_rs::{ContentBlock, Message};
+use futures::future::join_all;
 use serde_json::json;
 use tokio::sync::mpsc;
 use tracing::{debug, error, info, instrument, warn};
 
 use crate::agent::AgentRunner;
-use crate::config::{HooksConfig, ReviewConfig, VerificationConfig};
+use crate::archive::{SessionScope, TranscriptArchive};
+use crate::config::{
+    HooksConfig, ReviewConfig, ReviewerConfig, StageConfig, StageKind, VerificationConfig,
+};
 use crate::engine::Engine;
 use crate::error::CoreError;
-use crate::events::{Issue, RunEvent, RunStream, Severity};
+use crate::events::{Issue, PhaseSummary, RunEvent, RunStream};
 use crate::git::GitOps;
-use crate::hooks::HookRunner;
+use crate::hooks::{HookOutput, HookRunner};
+use crate::lock::PidLock;
+use crate::permission::{PermissionBroker, ToolApprover};
+use crate::review::{
+    ReviewRecord, ReviewRound, ReviewerFailure, merge_issues, parse_review_issues,
+    save_review_record,
+};
 use crate::spec::{
-    Execution, FeatureSpec, PhaseResult, ReviewResult, StepStatus, VerificationResult,
-    load_design_spec, load_feature_spec, save_feature_spec,
+    Execution, FeatureSpec, HookRun, PhaseResult, ReviewResult, StageResult, StepStatus,
+    VerificationResult, load_design_spec, load_feature_spec, save_feature_spec,
 };
 
 /// Channel buffer size for run events.
 const EVENT_CHANNEL_SIZE: usize = 64;
 
+/// File name of the run lock inside the feature directory.
+const RUN_LOCK_FILE: &str = "run.lock";
+
 /// Context passed to the background execution task.
 ///
 /// Contains all the owned/cloned components the background task needs,
@@ -65,6 +82,80 @@ struct RunContext {
     base_branch: String,
     /// Auto-commit setting.
     auto_commit: bool,
+    /// Pipeline stages to execute, in order.
+    stages: Vec<StageConfig>,
+    /// Asks the user to approve tool calls in `manual` permission mode.
+    permissions: Arc<PermissionBroker>,
+    /// Transcripts of the run's agent sessions.
+    transcripts: TranscriptArchive,
+}
+
+impl RunContext {
+    /// Template context builder for a stage of `spec`.
+    fn template_context<'a>(
+        &'a self,
+        stage_ctx: &StageContext<'a>,
+        spec: &'a FeatureSpec,
+    ) -> TemplateContext<'a> {
+        TemplateContext {
+            repo_path: &self.repo_path,
+            slug: stage_ctx.slug,
+            design_spec: stage_ctx.design_spec,
+            spec,
+            base_branch: &self.base_branch,
+        }
+    }
+
+    /// Run an agent with its own system template.
+    async fn run_agent(
+        &self,
+        agent_name: &str,
+        task_template: &str,
+        context: &serde_json::Value,
+        cwd: Option<&Path>,
+        scope: &SessionScope,
+    ) -> Result<Vec<Message>, CoreError> {
+        let system_template = format!("{agent_name}/system");
+        self.run_session(
+            agent_name,
+            &system_template,
+            task_template,
+            context,
+            cwd,
+            scope,
+        )
+        .await
+    }
+
+    /// Run an agent session in `scope`, routing tool approvals through the
+    /// run's permission broker and recording the session's transcript. The
+    /// scope's label names the phase or stage shown to the user when a tool
+    /// call needs approval.
+    async fn run_session(
+        &self,
+        agent_name: &str,
+        system_template: &str,
+        task_template: &str,
+        context: &serde_json::Value,
+        cwd: Option<&Path>,
+        scope: &SessionScope,
+    ) -> Result<Vec<Message>, CoreError> {
+        let transcript =
+            self.transcripts
+                .open_session(scope, agent_name, system_template, task_template);
+        let approver = ToolApprover::new(Arc::clone(&self.permissions), agent_name, scope.label())
+            .with_transcript(transcript);
+        self.agent_runner
+            .run_agent_with_system(
+                agent_name,
+                system_template,
+                task_template,
+                context,
+                cwd,
+                Some(&approver),
+            )
+            .await
+    }
 }
 
 /// Start the run execution workflow.
@@ -76,6 +167,7 @@ struct RunContext {
 ///
 /// Returns `CoreError::NotInitialized` if `.gba/` does not exist.
 /// Returns `CoreError::FeatureNotFound` if the feature spec does not exist.
+/// Returns `CoreError::Locked` if the feature is already running.
 #[instrument(skip(engine))]
 pub(crate) async fn run_execution(engine: &Engine, slug: &str) -> Result<RunStream, CoreError> {
     // Verify initialized
@@ -97,13 +189,19 @@ pub(crate) async fn run_execution(engine: &Engine, slug: &str) -> Result<RunStre
         Err(e) => return Err(e),
     };
 
+    // Held by the background task until it ends
+    let lock = PidLock::acquire(
+        &gba_dir.join("features").join(slug).join(RUN_LOCK_FILE),
+        &format!("feature {slug:?} is running"),
+    )?;
+
     // Ensure worktree exists
     let worktree_path = engine.git().ensure_worktree(slug).await?;
     info!(worktree = %worktree_path.display(), "worktree ready");
 
-    // Create event channels
+    // Create event and permission channels
     let (event_tx, event_rx) = mpsc::channel(EVENT_CHANNEL_SIZE);
-    let stream = RunStream::new(event_rx);
+    let (permission_tx, permission_rx) = mpsc::channel(EVENT_CHANNEL_SIZE);
 
     // Build the context for the background task
     let project_config = engine.project_config().clone();
@@ -117,39 +215,123 @@ pub(crate) async fn run_execution(engine: &Engine, slug: &str) -> Result<RunStre
         repo_path: engine.config().repo_path().clone(),
         base_branch: project_config.git.base_branch.clone(),
         auto_commit: project_config.git.auto_commit,
+        stages: project_config.pipeline.stages.clone(),
+        permissions: PermissionBroker::spawn(event_tx.clone(), permission_rx),
+        transcripts: TranscriptArchive::open(&gba_dir.join("features").join(slug))?,
     };
 
     let slug_owned = slug.to_owned();
 
     // Spawn background execution task
-    tokio::spawn(async move {
-        execute_phases(ctx, slug_owned, spec, design_spec, event_tx).await;
+    let task = tokio::spawn(async move {
+        let _lock = lock;
+        execute_pipeline(ctx, slug_owned, spec, design_spec, event_tx).await;
     });
 
-    Ok(stream)
+    Ok(RunStream::new(event_rx, permission_tx, task))
+}
+
+/// Why a pipeline stage stopped before finishing.
+#[derive(Debug)]
+enum StageError {
+    /// The event receiver is gone; stop quietly.
+    Cancelled,
+    /// The stage failed; report the error and stop the pipeline.
+    Failed(CoreError),
+}
+
+impl From<CoreError> for StageError {
+    fn from(e: CoreError) -> Self {
+        Self::Failed(e)
+    }
+}
+
+/// Result of a stage that ran to completion.
+#[derive(Debug)]
+struct StageOutcome {
+    /// Agent turns consumed by the stage.
+    turns: u32,
+    /// `Completed`, or `Failed` for stages whose failure does not stop the
+    /// pipeline (PR creation).
+    status: StepStatus,
+    /// Human-readable outcome for [`RunEvent::StageFinished`].
+    summary: String,
+}
+
+/// Results accumulated across pipeline stages.
+#[derive(Debug)]
+struct PipelineState {
+    /// Review totals across all review stages.
+    review: ReviewResult,
+    /// Attributed review findings, persisted as `review.yaml`.
+    review_record: ReviewRecord,
+    /// Result of the most recent verification stage.
+    verification: VerificationResult,
+    /// PR URL once a PR stage succeeded.
+    pr: Option<String>,
+    /// Per-stage outcomes in execution order.
+    stages: Vec<StageResult>,
+    /// Precommit hook runs in execution order.
+    hooks: Vec<HookRun>,
 }
 
-/// Execute all phases, review, verification, and PR creation in the background.
+impl PipelineState {
+    fn new() -> Self {
+        Self {
+            review: ReviewResult {
+                turns: 0,
+                issues_found: 0,
+                issues_fixed: 0,
+            },
+            review_record: ReviewRecord::default(),
+            verification: VerificationResult {
+                turns: 0,
+                passed: true,
+                evidence: None,
+            },
+            pr: None,
+            stages: Vec::new(),
+            hooks: Vec::new(),
+        }
+    }
+
+    /// Total agent turns across all recorded stages.
+    fn total_turns(&self) -> u32 {
+        self.stages
+            .iter()
+            .fold(0u32, |acc, s| acc.saturating_add(s.turns))
+    }
+}
+
+/// Execute the configured pipeline stages in the background.
 ///
-/// Sends [`RunEvent`]s on the channel as each step completes. If any step
+/// Sends [`RunEvent`]s on the channel as each step completes. If a stage
 /// fails, sends a [`RunEvent::Error`] and returns. The spec is saved after
 /// each phase so that a resume picks up where execution left off.
 #[instrument(skip_all, fields(slug = %slug, total_phases = spec.phases.len()))]
-async fn execute_phases(
+async fn execute_pipeline(
     ctx: RunContext,
     slug: String,
     mut spec: FeatureSpec,
     design_spec: String,
     event_tx: mpsc::Sender<RunEvent>,
 ) {
-    let total_phases = spec.phases.len();
-
-    // Send Started event
     if send_event(
         &event_tx,
         RunEvent::Started {
             feature: spec.feature.clone(),
-            total_phases,
+            phases: spec
+                .phases
+                .iter()
+                .map(|phase| PhaseSummary {
+                    name: phase.name.clone(),
+                    status: phase
+                        .result
+                        .as_ref()
+                        .map(|r| r.status.clone())
+                        .unwrap_or_default(),
+                })
+                .collect(),
         },
     )
     .await
@@ -159,15 +341,417 @@ async fn execute_phases(
     }
 
     let worktree_path = ctx.git.worktree_path(&slug);
-    let mut total_turns: u32 = 0;
+    let mut state = PipelineState::new();
+
+    for stage in &ctx.stages {
+        let name = stage.name().to_owned();
+
+        if let Some(reason) = skip_reason(&ctx, stage, &spec) {
+            debug!(stage = %name, reason, "skipping stage");
+            state.stages.push(StageResult {
+                name,
+                kind: stage.kind,
+                status: StepStatus::Skipped,
+                turns: 0,
+            });
+            continue;
+        }
 
-    // Handle empty phases list -- skip directly to review/verification
-    if total_phases == 0 {
-        info!(slug = %slug, "no phases defined, skipping to review/verification");
+        if send_event(
+            &event_tx,
+            RunEvent::StageStarted {
+                name: name.clone(),
+                kind: stage.kind,
+            },
+        )
+        .await
+        .is_err()
+        {
+            return;
+        }
+
+        let stage_ctx = StageContext {
+            stage,
+            slug: &slug,
+            design_spec: &design_spec,
+            worktree_path: &worktree_path,
+            event_tx: &event_tx,
+        };
+        let outcome = match run_stage(&ctx, &stage_ctx, &mut spec, &mut state).await {
+            Ok(outcome) => outcome,
+            Err(StageError::Cancelled) => return,
+            Err(StageError::Failed(e)) => {
+                let _ = send_event(&event_tx, RunEvent::Error(e)).await;
+                return;
+            }
+        };
+
+        state.stages.push(StageResult {
+            name: name.clone(),
+            kind: stage.kind,
+            status: outcome.status.clone(),
+            turns: outcome.turns,
+        });
+
+        if send_event(
+            &event_tx,
+            RunEvent::StageFinished {
+                name,
+                kind: stage.kind,
+                status: outcome.status,
+                summary: outcome.summary,
+            },
+        )
+        .await
+        .is_err()
+        {
+            return;
+        }
+    }
+
+    // ── Update Execution Summary ─────────────────────────────────
+    let total_turns = state.total_turns();
+    spec.execution = Some(Execution {
+        status: StepStatus::Completed,
+        total_turns,
+        review: state.review,
+        verification: state.verification,
+        pr: state.pr,
+        stages: state.stages,
+        denials: ctx.permissions.take_denials(),
+        hooks: state.hooks,
+        usage: ctx.permissions.take_usage(),
+    });
+
+    if let Err(e) = save_feature_spec(&ctx.gba_dir, &slug, &spec) {
+        let _ = send_event(&event_tx, RunEvent::Error(e)).await;
+        return;
+    }
+
+    let _ = send_event(&event_tx, RunEvent::Finished).await;
+    info!(slug = %slug, total_turns, "run execution finished");
+}
+
+/// Per-stage inputs shared by the stage runners.
+#[derive(Debug)]
+struct StageContext<'a> {
+    /// The stage being executed.
+    stage: &'a StageConfig,
+    /// Feature slug.
+    slug: &'a str,
+    /// Design specification content.
+    design_spec: &'a str,
+    /// Path to the worktree.
+    worktree_path: &'a Path,
+    /// Channel for progress events.
+    event_tx: &'a mpsc::Sender<RunEvent>,
+}
+
+/// Feature inputs of the template contexts passed to a run's agent sessions.
+///
+/// Shared by the pipeline and by prompt previews, so a preview renders a
+/// template with exactly the context a run would use.
+#[derive(Debug)]
+pub(crate) struct TemplateContext<'a> {
+    /// Path to the repository root.
+    pub(crate) repo_path: &'a Path,
+    /// Feature slug.
+    pub(crate) slug: &'a str,
+    /// Design specification content.
+    pub(crate) design_spec: &'a str,
+    /// The feature spec.
+    pub(crate) spec: &'a FeatureSpec,
+    /// Base branch name.
+    pub(crate) base_branch: &'a str,
+}
+
+impl TemplateContext<'_> {
+    /// Context of a coding phase's session: the code agent's system context
+    /// plus the phase (zero-based `index`) and the phases completed before
+    /// the run started.
+    ///
+    /// # Errors
+    ///
+    /// Returns `CoreError::Agent` if the phase cannot be serialized.
+    pub(crate) fn phase(
+        &self,
+        index: usize,
+        completed_phases: &[serde_json::Value],
+    ) -> Result<serde_json::Value, CoreError> {
+        let phase = serde_json::to_value(&self.spec.phases[index])
+            .map_err(|e| CoreError::Agent(format!("failed to serialize phase: {e}")))?;
+        Ok(json!({
+            "repo_path": self.repo_path.display().to_string(),
+            "feature_slug": self.slug,
+            "design_spec": self.design_spec,
+            "phase": phase,
+            "phase_index": index + 1,
+            "total_phases": self.spec.phases.len(),
+            "completed_phases": completed_phases,
+        }))
+    }
+
+    /// Context of the code agent's session fixing a failed precommit hook.
+    pub(crate) fn hook_fix(&self, hook: &HookOutput) -> serde_json::Value {
+        json!({
+            "repo_path": self.repo_path.display().to_string(),
+            "feature_slug": self.slug,
+            "design_spec": self.design_spec,
+            "hook_name": hook.name,
+            "hook_command": hook.command,
+            "hook_output": format!("{}\n{}", hook.stdout, hook.stderr),
+        })
+    }
+
+    /// Context of a custom agent stage's session.
+    pub(crate) fn agent_stage(&self, stage: &str, diff: &str) -> serde_json::Value {
+        let phases: Vec<&str> = self.spec.phases.iter().map(|p| p.name.as_str()).collect();
+        json!({
+            "repo_path": self.repo_path.display().to_string(),
+            "feature_slug": self.slug,
+            "design_spec": self.design_spec,
+            "feature_description": self.spec.feature,
+            "phases": phases,
+            "verification_criteria": self.spec.verification.criteria,
+            "base_branch": self.base_branch,
+            "diff": diff,
+            "stage": stage,
+        })
     }
 
-    // ── Phase Execution ──────────────────────────────────────────
-    let completed_phases = collect_completed_phases(&spec);
+    /// Context of the reviewer sessions of a review round.
+    pub(crate) fn review(&self, diff: &str) -> serde_json::Value {
+        json!({
+            "repo_path": self.repo_path.display().to_string(),
+            "feature_slug": self.slug,
+            "design_spec": self.design_spec,
+            "verification_criteria": self.spec.verification.criteria,
+            "diff": diff,
+        })
+    }
+
+    /// Context of the code agent's session fixing the issues of a review
+    /// round.
+    pub(crate) fn review_fix(&self, issues: &[Issue]) -> serde_json::Value {
+        json!({
+            "repo_path": self.repo_path.display().to_string(),
+            "feature_slug": self.slug,
+            "design_spec": self.design_spec,
+            "issues": issues.iter().map(issue_to_json).collect::<Vec<_>>(),
+        })
+    }
+
+    /// Context of a verification round's verify session.
+    pub(crate) fn verification(&self) -> serde_json::Value {
+        json!({
+            "repo_path": self.repo_path.display().to_string(),
+            "feature_slug": self.slug,
+            "design_spec": self.design_spec,
+            "criteria": self.spec.verification.criteria,
+            "test_commands": self.spec.verification.test_commands,
+        })
+    }
+
+    /// Context of the code agent's session fixing a failed verification,
+    /// given the verify agent's `output`.
+    pub(crate) fn verification_fix(&self, output: &str) -> serde_json::Value {
+        json!({
+            "repo_path": self.repo_path.display().to_string(),
+            "feature_slug": self.slug,
+            "design_spec": self.design_spec,
+            "failures": [],
+            "output": output,
+        })
+    }
+
+    /// Context of the PR session, summarizing the phases, review and
+    /// verification results.
+    pub(crate) fn pr(
+        &self,
+        branch: &str,
+        review: &ReviewResult,
+        review_record: &ReviewRecord,
+        verification: &VerificationResult,
+    ) -> serde_json::Value {
+        let phases: Vec<serde_json::Value> = self
+            .spec
+            .phases
+            .iter()
+            .map(|p| {
+                json!({
+                    "name": p.name,
+                    "result": p.result.as_ref().map(|r| json!({
+                        "turns": r.turns,
+                        "commit": r.commit.as_deref().unwrap_or("unknown"),
+                    })),
+                })
+            })
+            .collect();
+
+        json!({
+            "repo_path": self.repo_path.display().to_string(),
+            "feature_slug": self.slug,
+            "design_spec": self.design_spec,
+            "feature_description": self.spec.feature,
+            "branch": branch,
+            "base_branch": self.base_branch,
+            "phases": phases,
+            "review": {
+                "issues_found": review.issues_found,
+                "issues_fixed": review.issues_fixed,
+                "issues": review_record.issues().map(issue_to_json).collect::<Vec<_>>(),
+            },
+            "verification": {
+                "passed": verification.passed,
+            },
+        })
+    }
+}
+
+/// Explain why a stage should not run, or `None` if it should.
+fn skip_reason(ctx: &RunContext, stage: &StageConfig, spec: &FeatureSpec) -> Option<&'static str> {
+    match stage.kind {
+        StageKind::Phases if spec.phases.is_empty() => Some("no phases defined"),
+        StageKind::Review if !ctx.review_config.enabled => Some("review disabled"),
+        StageKind::Verification if !ctx.verification_config.enabled => {
+            Some("verification disabled")
+        }
+        StageKind::Verification
+            if spec.verification.test_commands.is_empty()
+                && spec.verification.criteria.is_empty() =>
+        {
+            Some("no verification commands or criteria defined")
+        }
+        _ => None,
+    }
+}
+
+/// Run a single pipeline stage and fold its result into `state`.
+async fn run_stage(
+    ctx: &RunContext,
+    stage_ctx: &StageContext<'_>,
+    spec: &mut FeatureSpec,
+    state: &mut PipelineState,
+) -> Result<StageOutcome, StageError> {
+    let completed = |turns, summary| StageOutcome {
+        turns,
+        status: StepStatus::Completed,
+        summary,
+    };
+
+    match stage_ctx.stage.kind {
+        StageKind::Phases => {
+            let (turns, executed) =
+                run_phases_stage(ctx, stage_ctx, spec, &mut state.hooks).await?;
+            Ok(completed(turns, format!("{executed} phase(s) executed")))
+        }
+        StageKind::Review => {
+            let first_round = state.review_record.rounds.len();
+            let result = run_review_cycle(ctx, stage_ctx, spec, &mut state.review_record).await?;
+            debug!(issues_found = result.issues_found, "review completed");
+            let issues = state.review_record.rounds[first_round..]
+                .iter()
+                .flat_map(|r| r.issues.iter().cloned())
+                .collect();
+            emit(stage_ctx.event_tx, RunEvent::ReviewCompleted { issues }).await?;
+
+            let mut summary = format!(
+                "{} issue(s) found, {} fixed",
+                result.issues_found, result.issues_fixed
+            );
+            let mut failed: Vec<&str> = state.review_record.rounds[first_round..]
+                .iter()
+                .flat_map(|r| r.failed.iter().map(|f| f.reviewer.as_str()))
+                .collect();
+            failed.sort_unstable();
+            failed.dedup();
+            if !failed.is_empty() {
+                summary.push_str(&format!("; reviewer(s) failed: {}", failed.join(", ")));
+            }
+            let turns = result.turns;
+            state.review.turns = state.review.turns.saturating_add(result.turns);
+            state.review.issues_found = state
+                .review
+                .issues_found
+                .saturating_add(result.issues_found);
+            state.review.issues_fixed = state
+                .review
+                .issues_fixed
+                .saturating_add(result.issues_fixed);
+            Ok(completed(turns, summary))
+        }
+        StageKind::Verification => {
+            let result = run_verification_cycle(ctx, stage_ctx, spec).await?;
+            let details = if result.passed {
+                "all criteria passed".to_owned()
+            } else {
+                "some criteria failed".to_owned()
+            };
+            emit(
+                stage_ctx.event_tx,
+                RunEvent::VerificationCompleted {
+                    passed: result.passed,
+                    details: details.clone(),
+                },
+            )
+            .await?;
+
+            let turns = result.turns;
+            state.verification = VerificationResult {
+                turns: state.verification.turns.saturating_add(result.turns),
+                passed: result.passed,
+                evidence: result.evidence,
+            };
+            Ok(completed(turns, details))
+        }
+        StageKind::Agent => {
+            let turns = run_agent_stage(ctx, stage_ctx, spec, &mut state.hooks).await?;
+            Ok(completed(turns, format!("{turns} turn(s)")))
+        }
+        StageKind::Pr => match create_pr(ctx, stage_ctx, spec, state).await {
+            Ok(url) => {
+                emit(stage_ctx.event_tx, RunEvent::PrCreated { url: url.clone() }).await?;
+                state.pr = Some(url.clone());
+                Ok(completed(0, url))
+            }
+            Err(e) => {
+                // A missing PR should not discard the work; record and continue
+                warn!(error = %e, "PR creation failed, continuing");
+                let summary = format!("PR creation failed: {e}");
+                emit(
+                    stage_ctx.event_tx,
+                    RunEvent::Error(CoreError::Agent(summary.clone())),
+                )
+                .await?;
+                Ok(StageOutcome {
+                    turns: 0,
+                    status: StepStatus::Failed,
+                    summary,
+                })
+            }
+        },
+    }
+}
+
+/// Execute every pending phase with the code agent.
+///
+/// Completed phases are skipped (resume support). After each phase, runs
+/// precommit hooks (appending each run to `hooks`), commits if `autoCommit`
+/// is enabled and persists the spec. Returns the turns consumed and the
+/// number of phases executed.
+async fn run_phases_stage(
+    ctx: &RunContext,
+    stage_ctx: &StageContext<'_>,
+    spec: &mut FeatureSpec,
+    hooks: &mut Vec<HookRun>,
+) -> Result<(u32, usize), StageError> {
+    let slug = stage_ctx.slug;
+    let worktree_path = stage_ctx.worktree_path;
+    let event_tx = stage_ctx.event_tx;
+    let total_phases = spec.phases.len();
+    let completed_phases = collect_completed_phases(spec);
+    let mut total_turns: u32 = 0;
+    let mut executed = 0;
 
     for index in 0..total_phases {
         // Skip already completed phases (resume support)
@@ -179,33 +763,23 @@ async fn execute_phases(
         }
 
         let phase_name = spec.phases[index].name.clone();
-
-        if send_event(
-            &event_tx,
+        emit(
+            event_tx,
             RunEvent::PhaseStarted {
                 index,
                 name: phase_name.clone(),
             },
         )
-        .await
-        .is_err()
-        {
-            return;
-        }
+        .await?;
 
         // Run coding agent for this phase
-        let phase_ctx = PhaseContext {
-            slug: &slug,
-            design_spec: &design_spec,
-            phase: &spec.phases[index],
-            index,
-            total_phases,
-            completed_phases: &completed_phases,
-            worktree_path: &worktree_path,
-        };
-        let agent_result = run_coding_phase(&ctx, &phase_ctx).await;
-
-        let turns = match agent_result {
+        let context = ctx
+            .template_context(stage_ctx, spec)
+            .phase(index, &completed_phases)?;
+        let scope = SessionScope::phase(stage_ctx.stage.name(), index, &phase_name);
+        let outcome =
+            run_coding_phase(ctx, stage_ctx, index, &completed_phases, &context, &scope).await;
+        let turns = match outcome {
             Ok(t) => t,
             Err(e) => {
                 // Save spec on failure so resume picks up here
@@ -214,42 +788,29 @@ async fn execute_phases(
                     turns: 0,
                     commit: None,
                 });
-                if let Err(save_err) = save_feature_spec(&ctx.gba_dir, &slug, &spec) {
+                if let Err(save_err) = save_feature_spec(&ctx.gba_dir, slug, spec) {
                     warn!(error = %save_err, "failed to save spec after phase failure");
                 }
-                let _ = send_event(&event_tx, RunEvent::Error(e)).await;
-                return;
+                return Err(e.into());
             }
         };
         total_turns = total_turns.saturating_add(turns);
+        executed += 1;
 
         // Run precommit hooks if configured
-        let hook_result = run_hooks_cycle(&ctx, &slug, &worktree_path, &event_tx).await;
-        if let Err(e) = hook_result {
-            let _ = send_event(&event_tx, RunEvent::Error(e)).await;
-            return;
-        }
+        run_hooks_cycle(
+            ctx,
+            &ctx.template_context(stage_ctx, spec),
+            &scope,
+            worktree_path,
+            event_tx,
+            hooks,
+        )
+        .await?;
 
         // Commit if auto_commit is enabled
-        let commit_hash = if ctx.auto_commit {
-            let commit_msg = format!("feat({}): phase {} - {}", slug, index + 1, phase_name);
-            match ctx.git.commit(&worktree_path, &commit_msg).await {
-                Ok(hash) => {
-                    info!(hash = %hash, phase = index + 1, "committed phase");
-                    Some(hash)
-                }
-                Err(CoreError::Git(msg)) if msg.contains("nothing to commit") => {
-                    debug!(phase = index + 1, "no changes to commit for phase");
-                    None
-                }
-                Err(e) => {
-                    let _ = send_event(&event_tx, RunEvent::Error(e)).await;
-                    return;
-                }
-            }
-        } else {
-            None
-        };
+        let commit_msg = format!("feat({}): phase {} - {}", slug, index + 1, phase_name);
+        let commit_hash = commit_changes(ctx, worktree_path, &commit_msg, &scope).await?;
 
         // Update phase result
         spec.phases[index].result = Some(PhaseResult {
@@ -259,157 +820,103 @@ async fn execute_phases(
         });
 
         // Persist spec after each phase
-        if let Err(e) = save_feature_spec(&ctx.gba_dir, &slug, &spec) {
-            let _ = send_event(&event_tx, RunEvent::Error(e)).await;
-            return;
-        }
+        save_feature_spec(&ctx.gba_dir, slug, spec)?;
 
-        if send_event(
-            &event_tx,
+        emit(
+            event_tx,
             RunEvent::PhaseCommitted {
                 index,
                 commit_hash: commit_hash.unwrap_or_else(|| "(no changes)".to_owned()),
             },
         )
-        .await
-        .is_err()
-        {
-            return;
-        }
+        .await?;
     }
 
-    // ── Code Review ──────────────────────────────────────────────
-    let review_result = if ctx.review_config.enabled {
-        if send_event(&event_tx, RunEvent::ReviewStarted)
-            .await
-            .is_err()
-        {
-            return;
-        }
-
-        match run_review_cycle(&ctx, &slug, &spec, &design_spec, &worktree_path).await {
-            Ok(result) => {
-                total_turns = total_turns.saturating_add(result.turns);
-                let issues_count = result.issues_found;
-                if send_event(
-                    &event_tx,
-                    RunEvent::ReviewCompleted {
-                        issues: Vec::new(), // summary only, details in spec
-                    },
-                )
-                .await
-                .is_err()
-                {
-                    return;
-                }
-                debug!(issues_found = issues_count, "review completed");
-                result
-            }
-            Err(e) => {
-                let _ = send_event(&event_tx, RunEvent::Error(e)).await;
-                return;
-            }
-        }
-    } else {
-        ReviewResult {
-            turns: 0,
-            issues_found: 0,
-            issues_fixed: 0,
-        }
-    };
-
-    // ── Verification ─────────────────────────────────────────────
-    // Skip verification when no test commands are defined
-    let skip_verification =
-        spec.verification.test_commands.is_empty() && spec.verification.criteria.is_empty();
-    if skip_verification {
-        debug!("no verification commands or criteria defined, skipping verification step");
-    }
+    Ok((total_turns, executed))
+}
 
-    let verification_result = if ctx.verification_config.enabled && !skip_verification {
-        if send_event(&event_tx, RunEvent::VerificationStarted)
-            .await
-            .is_err()
-        {
-            return;
-        }
+/// Run a custom agent stage in the worktree.
+///
+/// The agent receives the feature context and the current diff against the
+/// base branch. Its changes go through the precommit hooks and are
+/// committed like a phase.
+#[instrument(skip_all, fields(stage = stage_ctx.stage.name()))]
+async fn run_agent_stage(
+    ctx: &RunContext,
+    stage_ctx: &StageContext<'_>,
+    spec: &FeatureSpec,
+    hooks: &mut Vec<HookRun>,
+) -> Result<u32, StageError> {
+    let stage = stage_ctx.stage;
+    let diff = ctx
+        .git
+        .get_diff(stage_ctx.worktree_path, &ctx.base_branch)
+        .await
+        .unwrap_or_default();
+    let context = ctx
+        .template_context(stage_ctx, spec)
+        .agent_stage(stage.name(), &diff);
 
-        match run_verification_cycle(&ctx, &slug, &spec, &design_spec, &worktree_path).await {
-            Ok(result) => {
-                total_turns = total_turns.saturating_add(result.turns);
-                let passed = result.passed;
-                let details = if passed {
-                    "all criteria passed".to_owned()
-                } else {
-                    "some criteria failed".to_owned()
-                };
-                if send_event(
-                    &event_tx,
-                    RunEvent::VerificationCompleted { passed, details },
-                )
-                .await
-                .is_err()
-                {
-                    return;
-                }
-                result
-            }
-            Err(e) => {
-                let _ = send_event(&event_tx, RunEvent::Error(e)).await;
-                return;
-            }
-        }
-    } else {
-        VerificationResult {
-            turns: 0,
-            passed: true,
-        }
-    };
+    let template = stage.template().unwrap_or_default();
+    let scope = SessionScope::stage(stage.name());
+    let messages = ctx
+        .run_agent(
+            stage.agent(),
+            &template,
+            &context,
+            Some(stage_ctx.worktree_path),
+            &scope,
+        )
+        .await?;
+    let turns = extract_turn_count(&messages);
 
-    // ── PR Creation ──────────────────────────────────────────────
-    let pr_url = match create_pr(&ctx, &slug, &spec, &review_result, &verification_result).await {
-        Ok(url) => {
-            if send_event(&event_tx, RunEvent::PrCreated { url: url.clone() })
-                .await
-                .is_err()
-            {
-                return;
-            }
-            Some(url)
-        }
-        Err(e) => {
-            warn!(error = %e, "PR creation failed, continuing");
-            let _ = send_event(
-                &event_tx,
-                RunEvent::Error(CoreError::Agent(format!("PR creation failed: {e}"))),
-            )
-            .await;
-            None
-        }
-    };
+    run_hooks_cycle(
+        ctx,
+        &ctx.template_context(stage_ctx, spec),
+        &scope,
+        stage_ctx.worktree_path,
+        stage_ctx.event_tx,
+        hooks,
+    )
+    .await?;
+    let commit_msg = format!("chore({}): {} stage", stage_ctx.slug, stage.name());
+    commit_changes(ctx, stage_ctx.worktree_path, &commit_msg, &scope).await?;
 
-    // ── Update Execution Summary ─────────────────────────────────
-    spec.execution = Some(Execution {
-        status: StepStatus::Completed,
-        total_turns,
-        review: review_result,
-        verification: verification_result,
-        pr: pr_url,
-    });
+    Ok(turns)
+}
 
-    if let Err(e) = save_feature_spec(&ctx.gba_dir, &slug, &spec) {
-        let _ = send_event(&event_tx, RunEvent::Error(e)).await;
-        return;
+/// Commit all worktree changes if `autoCommit` is enabled, and link the
+/// transcripts of the sessions in `scope` to the commit.
+///
+/// Returns the commit hash, or `None` if auto-commit is off or there was
+/// nothing to commit.
+async fn commit_changes(
+    ctx: &RunContext,
+    worktree_path: &Path,
+    message: &str,
+    scope: &SessionScope,
+) -> Result<Option<String>, CoreError> {
+    if !ctx.auto_commit {
+        return Ok(None);
+    }
+    match ctx.git.commit(worktree_path, message).await {
+        Ok(hash) => {
+            info!(hash = %hash, message, "committed changes");
+            ctx.transcripts.link_commit(scope, &hash);
+            Ok(Some(hash))
+        }
+        Err(CoreError::Git(msg)) if msg.contains("nothing to commit") => {
+            debug!(message, "no changes to commit");
+            Ok(None)
+        }
+        Err(e) => Err(e),
     }
-
-    let _ = send_event(&event_tx, RunEvent::Finished).await;
-    info!(slug = %slug, total_turns, "run execution finished");
 }
 
 // ── Phase Helpers ────────────────────────────────────────────
 
 /// Collect information about completed phases for resume context.
-fn collect_completed_phases(spec: &FeatureSpec) -> Vec<serde_json::Value> {
+pub(crate) fn collect_completed_phases(spec: &FeatureSpec) -> Vec<serde_json::Value> {
     spec.phases
         .iter()
         .enumerate()
@@ -427,76 +934,50 @@ fn collect_completed_phases(spec: &FeatureSpec) -> Vec<serde_json::Value> {
         .collect()
 }
 
-/// Context for a single coding phase execution.
-#[derive(Debug)]
-struct PhaseContext<'a> {
-    /// Feature slug.
-    slug: &'a str,
-    /// Design specification content.
-    design_spec: &'a str,
-    /// The phase to execute.
-    phase: &'a crate::spec::Phase,
-    /// Zero-based phase index.
-    index: usize,
-    /// Total number of phases.
-    total_phases: usize,
-    /// Previously completed phases (for resume context).
-    completed_phases: &'a [serde_json::Value],
-    /// Path to the worktree.
-    worktree_path: &'a Path,
+/// Task template of a coding phase: the stage's template if configured,
+/// otherwise the resume template when earlier phases are completed, else the
+/// fresh task template.
+pub(crate) fn phase_template(
+    stage: &StageConfig,
+    completed_phases: &[serde_json::Value],
+) -> String {
+    stage.template().unwrap_or_else(|| {
+        if completed_phases.is_empty() {
+            "code/task".to_owned()
+        } else {
+            "code/resume".to_owned()
+        }
+    })
 }
 
 /// Run the coding agent for a single phase.
 ///
-/// If there are completed phases, uses the resume template; otherwise uses
-/// the fresh task template. Returns the number of turns consumed.
-#[instrument(skip_all, fields(index = phase_ctx.index, slug = phase_ctx.slug))]
+/// `context` is the phase's template context (see
+/// [`TemplateContext::phase`]). Returns the number of turns consumed.
+#[instrument(skip_all, fields(index = index, slug = stage_ctx.slug))]
 async fn run_coding_phase(
     ctx: &RunContext,
-    phase_ctx: &PhaseContext<'_>,
+    stage_ctx: &StageContext<'_>,
+    index: usize,
+    completed_phases: &[serde_json::Value],
+    context: &serde_json::Value,
+    scope: &SessionScope,
 ) -> Result<u32, CoreError> {
-    let phase_json = serde_json::to_value(phase_ctx.phase)
-        .map_err(|e| CoreError::Agent(format!("failed to serialize phase: {e}")))?;
-
-    let system_context = json!({
-        "repo_path": ctx.repo_path.display().to_string(),
-        "feature_slug": phase_ctx.slug,
-        "design_spec": phase_ctx.design_spec,
-    });
-
-    let task_template = if phase_ctx.completed_phases.is_empty() {
-        "code/task"
-    } else {
-        "code/resume"
-    };
-
-    let task_context = json!({
-        "phase": phase_json,
-        "phase_index": phase_ctx.index + 1,
-        "total_phases": phase_ctx.total_phases,
-        "completed_phases": phase_ctx.completed_phases,
-    });
-
-    // Merge system and task context for the agent
-    let mut full_context = system_context;
-    if let serde_json::Value::Object(task_map) = task_context
-        && let serde_json::Value::Object(ref mut full_map) = full_context
-    {
-        full_map.extend(task_map);
-    }
+    let stage = stage_ctx.stage;
+    let task_template = phase_template(stage, completed_phases);
 
     let messages = ctx
-        .agent_runner
         .run_agent(
-            "code",
-            task_template,
-            &full_context,
-            Some(phase_ctx.worktree_path),
+            stage.agent(),
+            &task_template,
+            context,
+            Some(stage_ctx.worktree_path),
+            scope,
         )
         .await?;
 
     let turns = extract_turn_count(&messages);
-    debug!(turns, phase = phase_ctx.index + 1, "coding phase completed");
+    debug!(turns, phase = index + 1, "coding phase completed");
     Ok(turns)
 }
 
@@ -506,12 +987,19 @@ async fn run_coding_phase(
 ///
 /// Iterates up to `max_retries` times. On each failure, sends the hook output
 /// to the coding agent with the `code/hook_fix` template, then re-runs hooks.
-#[instrument(skip(ctx, worktree_path, event_tx))]
+/// `scope` is the phase or stage whose changes are checked; fix sessions
+/// run in it. Every hook run is appended to `history`.
+#[instrument(
+    skip(ctx, template_ctx, scope, worktree_path, event_tx, history),
+    fields(phase = scope.label())
+)]
 async fn run_hooks_cycle(
     ctx: &RunContext,
-    slug: &str,
+    template_ctx: &TemplateContext<'_>,
+    scope: &SessionScope,
     worktree_path: &Path,
     event_tx: &mpsc::Sender<RunEvent>,
+    history: &mut Vec<HookRun>,
 ) -> Result<(), CoreError> {
     let runner = HookRunner::new(&ctx.hooks_config);
     if !runner.has_hooks() {
@@ -523,8 +1011,15 @@ async fn run_hooks_cycle(
     for attempt in 0..=max_retries {
         let results = runner.run_all(worktree_path).await?;
 
-        // Report each hook result
+        // Record and report each hook result
         for result in &results {
+            history.push(HookRun {
+                phase: scope.label().to_owned(),
+                hook: result.name.clone(),
+                command: result.command.clone(),
+                attempt: attempt + 1,
+                passed: result.passed,
+            });
             let _ = send_event(
                 event_tx,
                 RunEvent::HookResult {
@@ -567,19 +1062,16 @@ async fn run_hooks_cycle(
             }
 
             debug!(hook = %result.name, attempt, "running hook fix agent");
-            let hook_output = format!("{}\n{}", result.stdout, result.stderr);
-            let context = json!({
-                "repo_path": ctx.repo_path.display().to_string(),
-                "feature_slug": slug,
-                "design_spec": "",
-                "hook_name": result.name,
-                "hook_command": result.command,
-                "hook_output": hook_output,
-            });
+            let context = template_ctx.hook_fix(result);
 
-            ctx.agent_runner
-                .run_agent("code", "code/hook_fix", &context, Some(worktree_path))
-                .await?;
+            ctx.run_agent(
+                "code",
+                "code/hook_fix",
+                &context,
+                Some(worktree_path),
+                scope,
+            )
+            .await?;
         }
     }
 
@@ -590,23 +1082,33 @@ async fn run_hooks_cycle(
 
 /// Run the code review loop.
 ///
-/// Gets the diff, runs the review agent, parses issues, and if issues are
-/// found, runs the coding agent with fix instructions. Repeats up to
-/// `max_iterations`.
-#[instrument(skip(ctx, spec, design_spec, worktree_path))]
+/// Gets the diff, runs every configured reviewer concurrently on it, merges
+/// and deduplicates their issues, and if issues are found, runs the coding
+/// agent with the stage's fix template. Each round is appended to `record`
+/// and saved to `review.yaml`. Repeats up to `max_iterations`.
+#[instrument(skip_all, fields(stage = stage_ctx.stage.name()))]
 async fn run_review_cycle(
     ctx: &RunContext,
-    slug: &str,
+    stage_ctx: &StageContext<'_>,
     spec: &FeatureSpec,
-    design_spec: &str,
-    worktree_path: &Path,
+    record: &mut ReviewRecord,
 ) -> Result<ReviewResult, CoreError> {
+    let StageContext {
+        stage,
+        slug,
+        worktree_path,
+        ..
+    } = *stage_ctx;
+    let task_template = stage.template().unwrap_or_default();
     let max_iterations = ctx.review_config.max_iterations;
+    let reviewers = stage_reviewers(&ctx.review_config, stage);
     let mut total_turns: u32 = 0;
     let mut total_issues_found: u32 = 0;
     let mut total_issues_fixed: u32 = 0;
 
     for iteration in 0..max_iterations {
+        let scope = SessionScope::stage(stage.name()).with_iteration(iteration + 1);
+
         // Get diff against base branch
         let diff = ctx
             .git
@@ -619,26 +1121,43 @@ async fn run_review_cycle(
             break;
         }
 
-        // Run review agent (non-preset, pure text analysis)
-        let review_context = json!({
-            "repo_path": ctx.repo_path.display().to_string(),
-            "feature_slug": slug,
-            "design_spec": design_spec,
-            "verification_criteria": spec.verification.criteria,
-            "diff": diff,
+        // Run all reviewers on the same diff (non-preset, pure text analysis)
+        let review_context = ctx.template_context(stage_ctx, spec).review(&diff);
+
+        let sessions = reviewers.iter().map(|reviewer| {
+            let system_template = reviewer.system_template();
+            let review_context = &review_context;
+            let task_template = &task_template;
+            let scope = &scope;
+            async move {
+                ctx.run_session(
+                    reviewer.agent(),
+                    &system_template,
+                    task_template,
+                    review_context,
+                    None,
+                    scope,
+                )
+                .await
+            }
         });
-
-        let messages = ctx
-            .agent_runner
-            .run_agent("review", "review/task", &review_context, None)
-            .await?;
-
-        let turns = extract_turn_count(&messages);
-        total_turns = total_turns.saturating_add(turns);
-
-        // Extract text output from review agent
-        let review_output = extract_text_from_messages(&messages);
-        let issues = parse_review_issues(&review_output);
+        let outputs = split_review_outputs(&reviewers, join_all(sessions).await)?;
+        total_turns = total_turns.saturating_add(outputs.turns);
+        let reviewer_names = outputs
+            .reports
+            .iter()
+            .map(|(name, _)| name.clone())
+            .collect();
+        let issues = merge_issues(outputs.reports);
+
+        record.rounds.push(ReviewRound {
+            stage: stage.name().to_owned(),
+            iteration: iteration + 1,
+            reviewers: reviewer_names,
+            issues: issues.clone(),
+            failed: outputs.failed,
+        });
+        save_review_record(&ctx.gba_dir, slug, record)?;
 
         if issues.is_empty() {
             debug!(iteration, "review found no issues");
@@ -650,27 +1169,16 @@ async fn run_review_cycle(
         info!(iteration, issues = issue_count, "review found issues");
 
         // Run coding agent to fix issues
-        let issues_json: Vec<serde_json::Value> = issues
-            .iter()
-            .map(|issue| {
-                json!({
-                    "severity": format!("{:?}", issue.severity).to_lowercase(),
-                    "file": issue.file.display().to_string(),
-                    "description": issue.description,
-                })
-            })
-            .collect();
-
-        let fix_context = json!({
-            "repo_path": ctx.repo_path.display().to_string(),
-            "feature_slug": slug,
-            "design_spec": design_spec,
-            "issues": issues_json,
-        });
+        let fix_context = ctx.template_context(stage_ctx, spec).review_fix(&issues);
 
         let fix_messages = ctx
-            .agent_runner
-            .run_agent("code", "review/fix", &fix_context, Some(worktree_path))
+            .run_agent(
+                "code",
+                stage.fix_template(),
+                &fix_context,
+                Some(worktree_path),
+                &scope,
+            )
             .await?;
 
         let fix_turns = extract_turn_count(&fix_messages);
@@ -678,16 +1186,13 @@ async fn run_review_cycle(
         total_issues_fixed = total_issues_fixed.saturating_add(issue_count);
 
         // Commit review fixes
-        if ctx.auto_commit {
-            let commit_msg = format!("fix({}): review iteration {} fixes", slug, iteration + 1);
-            match ctx.git.commit(worktree_path, &commit_msg).await {
-                Ok(hash) => debug!(hash = %hash, "committed review fixes"),
-                Err(CoreError::Git(msg)) if msg.contains("nothing to commit") => {
-                    debug!("no review fix changes to commit");
-                }
-                Err(e) => return Err(e),
-            }
-        }
+        let commit_msg = format!(
+            "fix({}): {} iteration {} fixes",
+            slug,
+            stage.name(),
+            iteration + 1
+        );
+        commit_changes(ctx, worktree_path, &commit_msg, &scope).await?;
     }
 
     Ok(ReviewResult {
@@ -697,40 +1202,116 @@ async fn run_review_cycle(
     })
 }
 
+/// Reviewer outputs of one review round.
+#[derive(Debug, Default)]
+struct ReviewOutputs {
+    /// Issues of each reviewer whose session succeeded.
+    reports: Vec<(String, Vec<Issue>)>,
+    /// Reviewers whose session failed.
+    failed: Vec<ReviewerFailure>,
+    /// Turns consumed by the successful sessions.
+    turns: u32,
+}
+
+/// Split the session results of a review round into reports and failures,
+/// so one failing reviewer does not discard the others' findings.
+///
+/// # Errors
+///
+/// Returns the first reviewer's error if every reviewer failed.
+fn split_review_outputs(
+    reviewers: &[ReviewerConfig],
+    results: Vec<Result<Vec<Message>, CoreError>>,
+) -> Result<ReviewOutputs, CoreError> {
+    let mut outputs = ReviewOutputs::default();
+    let mut first_error = None;
+    for (reviewer, result) in reviewers.iter().zip(results) {
+        match result {
+            Ok(messages) => {
+                outputs.turns = outputs.turns.saturating_add(extract_turn_count(&messages));
+                let issues = parse_review_issues(&extract_text_from_messages(&messages));
+                debug!(reviewer = %reviewer.name, issues = issues.len(), "reviewer finished");
+                outputs.reports.push((reviewer.name.clone(), issues));
+            }
+            Err(e) => {
+                warn!(reviewer = %reviewer.name, error = %e, "reviewer failed");
+                outputs.failed.push(ReviewerFailure {
+                    reviewer: reviewer.name.clone(),
+                    error: e.to_string(),
+                });
+                first_error.get_or_insert(e);
+            }
+        }
+    }
+    match first_error {
+        Some(e) if outputs.reports.is_empty() => Err(e),
+        _ => Ok(outputs),
+    }
+}
+
+/// Reviewers for a review stage.
+///
+/// Uses `review.reviewers` when configured, otherwise a single reviewer
+/// named after the stage's agent.
+pub(crate) fn stage_reviewers(config: &ReviewConfig, stage: &StageConfig) -> Vec<ReviewerConfig> {
+    if !config.reviewers.is_empty() {
+        return config.reviewers.clone();
+    }
+    vec![ReviewerConfig {
+        name: stage.agent().to_owned(),
+        agent: Some(stage.agent().to_owned()),
+        system_template: None,
+    }]
+}
+
+/// Template context for a review issue.
+fn issue_to_json(issue: &Issue) -> serde_json::Value {
+    json!({
+        "severity": format!("{:?}", issue.severity).to_lowercase(),
+        "file": issue.file.display().to_string(),
+        "line": issue.line,
+        "description": issue.description,
+        "reviewers": issue.reviewers,
+    })
+}
+
 // ── Verification Helpers ─────────────────────────────────────
 
 /// Run the verification loop.
 ///
-/// Runs the verify agent with test commands, and if verification fails,
-/// runs the coding agent to fix issues. Repeats up to `max_iterations`.
-#[instrument(skip(ctx, spec, design_spec, worktree_path))]
+/// Runs the stage's verify agent with test commands, and if verification
+/// fails, runs the coding agent with the stage's fix template. Repeats up to
+/// `max_iterations`.
+#[instrument(skip_all, fields(stage = stage_ctx.stage.name()))]
 async fn run_verification_cycle(
     ctx: &RunContext,
-    slug: &str,
+    stage_ctx: &StageContext<'_>,
     spec: &FeatureSpec,
-    design_spec: &str,
-    worktree_path: &Path,
 ) -> Result<VerificationResult, CoreError> {
+    let StageContext {
+        stage,
+        slug,
+        worktree_path,
+        ..
+    } = *stage_ctx;
+    let task_template = stage.template().unwrap_or_default();
     let max_iterations = ctx.verification_config.max_iterations;
     let mut total_turns: u32 = 0;
+    let mut evidence = None;
 
     for iteration in 0..max_iterations {
+        let scope = SessionScope::stage(stage.name()).with_iteration(iteration + 1);
+
         // Run verify agent
-        let verify_context = json!({
-            "repo_path": ctx.repo_path.display().to_string(),
-            "feature_slug": slug,
-            "design_spec": design_spec,
-            "criteria": spec.verification.criteria,
-            "test_commands": spec.verification.test_commands,
-        });
+        let verify_context = ctx.template_context(stage_ctx, spec).verification();
 
         let messages = ctx
-            .agent_runner
             .run_agent(
-                "verify",
-                "verify/task",
+                stage.agent(),
+                &task_template,
                 &verify_context,
                 Some(worktree_path),
+                &scope,
             )
             .await?;
 
@@ -746,6 +1327,7 @@ async fn run_verification_cycle(
             return Ok(VerificationResult {
                 turns: total_turns,
                 passed: true,
+                evidence: Some(verify_output),
             });
         }
 
@@ -753,46 +1335,42 @@ async fn run_verification_cycle(
 
         // If this is the last iteration, return failure
         if iteration + 1 >= max_iterations {
+            evidence = Some(verify_output);
             break;
         }
 
         // Run coding agent to fix verification failures
-        let fix_context = json!({
-            "repo_path": ctx.repo_path.display().to_string(),
-            "feature_slug": slug,
-            "design_spec": design_spec,
-            "failures": [],
-            "output": verify_output,
-        });
+        let fix_context = ctx
+            .template_context(stage_ctx, spec)
+            .verification_fix(&verify_output);
 
         let fix_messages = ctx
-            .agent_runner
-            .run_agent("code", "verify/fix", &fix_context, Some(worktree_path))
+            .run_agent(
+                "code",
+                stage.fix_template(),
+                &fix_context,
+                Some(worktree_path),
+                &scope,
+            )
             .await?;
 
         let fix_turns = extract_turn_count(&fix_messages);
         total_turns = total_turns.saturating_add(fix_turns);
 
         // Commit verification fixes
-        if ctx.auto_commit {
-            let commit_msg = format!(
-                "fix({}): verification iteration {} fixes",
-                slug,
-                iteration + 1
-            );
-            match ctx.git.commit(worktree_path, &commit_msg).await {
-                Ok(hash) => debug!(hash = %hash, "committed verification fixes"),
-                Err(CoreError::Git(msg)) if msg.contains("nothing to commit") => {
-                    debug!("no verification fix changes to commit");
-                }
-                Err(e) => return Err(e),
-            }
-        }
+        let commit_msg = format!(
+            "fix({}): {} iteration {} fixes",
+            slug,
+            stage.name(),
+            iteration + 1
+        );
+        commit_changes(ctx, worktree_path, &commit_msg, &scope).await?;
     }
 
     Ok(VerificationResult {
         turns: total_turns,
         passed: false,
+        evidence,
     })
 }
 
@@ -800,54 +1378,33 @@ async fn run_verification_cycle(
 
 /// Create a pull request via the coding agent.
 ///
-/// Renders the `code/pr` template and runs the code agent, which uses
-/// the `gh` CLI to create the PR. Extracts the PR URL from the agent's
-/// output.
-#[instrument(skip(ctx, spec, review_result, verification_result))]
+/// Renders the stage's template (`code/pr` by default) and runs its agent,
+/// which uses the `gh` CLI to create the PR. Extracts the PR URL from the
+/// agent's output.
+#[instrument(skip_all, fields(slug = stage_ctx.slug))]
 async fn create_pr(
     ctx: &RunContext,
-    slug: &str,
+    stage_ctx: &StageContext<'_>,
     spec: &FeatureSpec,
-    review_result: &ReviewResult,
-    verification_result: &VerificationResult,
+    state: &PipelineState,
 ) -> Result<String, CoreError> {
-    let branch = ctx.git.branch_name(slug);
+    let slug = stage_ctx.slug;
     let worktree_path = ctx.git.worktree_path(slug);
-
-    let phases_json: Vec<serde_json::Value> = spec
-        .phases
-        .iter()
-        .map(|p| {
-            json!({
-                "name": p.name,
-                "result": p.result.as_ref().map(|r| json!({
-                    "turns": r.turns,
-                    "commit": r.commit.as_deref().unwrap_or("unknown"),
-                })),
-            })
-        })
-        .collect();
-
-    let pr_context = json!({
-        "repo_path": ctx.repo_path.display().to_string(),
-        "feature_slug": slug,
-        "design_spec": "",
-        "feature_description": spec.feature,
-        "branch": branch,
-        "base_branch": ctx.base_branch,
-        "phases": phases_json,
-        "review": {
-            "issues_found": review_result.issues_found,
-            "issues_fixed": review_result.issues_fixed,
-        },
-        "verification": {
-            "passed": verification_result.passed,
-        },
-    });
+    let pr_context = ctx.template_context(stage_ctx, spec).pr(
+        &ctx.git.branch_name(slug),
+        &state.review,
+        &state.review_record,
+        &state.verification,
+    );
 
     let messages = ctx
-        .agent_runner
-        .run_agent("code", "code/pr", &pr_context, Some(&worktree_path))
+        .run_agent(
+            stage_ctx.stage.agent(),
+            &stage_ctx.stage.template().unwrap_or_default(),
+            &pr_context,
+            Some(&worktree_path),
+            &SessionScope::stage(stage_ctx.stage.name()),
+        )
         .await?;
 
     // Extract PR URL from agent output
@@ -923,133 +1480,6 @@ fn check_verification_passed(messages: &[Message], output: &str) -> bool {
     !has_fail || has_pass
 }
 
-/// Parse review issues from the review agent's text output.
-///
-/// Expects issues in the format:
-/// ```text
-/// - severity: error
-///   file: src/main.rs
-///   description: Missing error handling
-/// ```
-///
-/// Also handles inline formats like:
-/// ```text
-/// - [error] src/main.rs: Missing error handling
-/// ```
-pub(crate) fn parse_review_issues(output: &str) -> Vec<Issue> {
-    let mut issues = Vec::new();
-
-    // Try block format first
-    let block_issues = parse_block_format(output);
-    if !block_issues.is_empty() {
-        return block_issues;
-    }
-
-    // Try inline format: - [severity] file: description
-    for line in output.lines() {
-        let trimmed = line.trim();
-        if let Some(issue) = parse_inline_issue(trimmed) {
-            issues.push(issue);
-        }
-    }
-
-    issues
-}
-
-/// Parse issues in the block format (YAML-like).
-fn parse_block_format(output: &str) -> Vec<Issue> {
-    let mut issues = Vec::new();
-    let mut current_severity: Option<Severity> = None;
-    let mut current_file: Option<String> = None;
-    let mut current_description: Option<String> = None;
-
-    for line in output.lines() {
-        let trimmed = line.trim();
-
-        // Check for severity field
-        if let Some(rest) = trimmed
-            .strip_prefix("severity:")
-            .or_else(|| trimmed.strip_prefix("- severity:").map(|s| s.trim_start()))
-        {
-            // Flush previous issue if any
-            if let (Some(sev), Some(file), Some(desc)) =
-                (&current_severity, &current_file, &current_description)
-            {
-                issues.push(Issue {
-                    severity: sev.clone(),
-                    file: PathBuf::from(file),
-                    description: desc.clone(),
-                });
-            }
-            current_severity = parse_severity(rest.trim());
-            current_file = None;
-            current_description = None;
-            continue;
-        }
-
-        if let Some(rest) = trimmed.strip_prefix("file:") {
-            current_file = Some(rest.trim().to_owned());
-            continue;
-        }
-
-        if let Some(rest) = trimmed.strip_prefix("description:") {
-            current_description = Some(rest.trim().to_owned());
-            continue;
-        }
-    }
-
-    // Flush the last issue
-    if let (Some(sev), Some(file), Some(desc)) =
-        (current_severity, current_file, current_description)
-    {
-        issues.push(Issue {
-            severity: sev,
-            file: PathBuf::from(file),
-            description: desc,
-        });
-    }
-
-    issues
-}
-
-/// Parse a single inline issue in the format: `- [severity] file: description`
-fn parse_inline_issue(line: &str) -> Option<Issue> {
-    let content = line.strip_prefix('-')?.trim();
-
-    // Match [severity]
-    let content = content.strip_prefix('[')?;
-    let bracket_end = content.find(']')?;
-    let severity_str = &content[..bracket_end];
-    let rest = content[bracket_end + 1..].trim();
-
-    let severity = parse_severity(severity_str)?;
-
-    // Match file: description
-    let colon_pos = rest.find(':')?;
-    let file = rest[..colon_pos].trim();
-    let description = rest[colon_pos + 1..].trim();
-
-    if file.is_empty() || description.is_empty() {
-        return None;
-    }
-
-    Some(Issue {
-        severity,
-        file: PathBuf::from(file),
-        description: description.to_owned(),
-    })
-}
-
-/// Parse a severity string to a [`Severity`] enum variant.
-fn parse_severity(s: &str) -> Option<Severity> {
-    match s.to_lowercase().trim() {
-        "error" => Some(Severity::Error),
-        "warning" | "warn" => Some(Severity::Warning),
-        "suggestion" | "info" | "note" => Some(Severity::Suggestion),
-        _ => None,
-    }
-}
-
 /// Extract a PR URL from agent text output.
 ///
 /// Looks for common GitHub PR URL patterns in the output text.
@@ -1072,6 +1502,14 @@ fn extract_pr_url(output: &str) -> Option<String> {
     None
 }
 
+/// Send an event from inside a stage, cancelling the stage if the
+/// receiver is gone.
+async fn emit(tx: &mpsc::Sender<RunEvent>, event: RunEvent) -> Result<(), StageError> {
+    send_event(tx, event)
+        .await
+        .map_err(|()| StageError::Cancelled)
+}
+
 /// Send an event on the channel, returning an error if the receiver is gone.
 async fn send_event(tx: &mpsc::Sender<RunEvent>, event: RunEvent) -> Result<(), ()> {
     tx.send(event).await.map_err(|_| {
@@ -1088,77 +1526,6 @@ mod tests {
     use crate::engine::Engine;
     use crate::spec::{FeatureSpec, Phase, PhaseResult, StepStatus, VerificationPlan};
 
-    #[test]
-    fn test_should_parse_review_issues_block_format() {
-        let output = r"
-Here are the issues found:
-
-- severity: error
-  file: src/main.rs
-  description: Missing error handling for database connection
-
-- severity: warning
-  file: src/lib.rs
-  description: Consider using a more descriptive variable name
-
-- severity: suggestion
-  file: tests/integration.rs
-  description: Add more edge case tests
-";
-
-        let issues = parse_review_issues(output);
-
-        assert_eq!(issues.len(), 3);
-        assert_eq!(issues[0].severity, Severity::Error);
-        assert_eq!(issues[0].file, PathBuf::from("src/main.rs"));
-        assert!(issues[0].description.contains("Missing error handling"));
-
-        assert_eq!(issues[1].severity, Severity::Warning);
-        assert_eq!(issues[1].file, PathBuf::from("src/lib.rs"));
-
-        assert_eq!(issues[2].severity, Severity::Suggestion);
-        assert_eq!(issues[2].file, PathBuf::from("tests/integration.rs"));
-    }
-
-    #[test]
-    fn test_should_parse_review_issues_inline_format() {
-        let output = r"
-Review complete. Issues:
-- [error] src/main.rs: Missing error handling
-- [warning] src/config.rs: Unused import
-- [suggestion] src/lib.rs: Consider extracting this function
-";
-
-        let issues = parse_review_issues(output);
-
-        assert_eq!(issues.len(), 3);
-        assert_eq!(issues[0].severity, Severity::Error);
-        assert_eq!(issues[0].file, PathBuf::from("src/main.rs"));
-        assert_eq!(issues[0].description, "Missing error handling");
-
-        assert_eq!(issues[1].severity, Severity::Warning);
-        assert_eq!(issues[1].file, PathBuf::from("src/config.rs"));
-
-        assert_eq!(issues[2].severity, Severity::Suggestion);
-    }
-
-    #[test]
-    fn test_should_parse_no_issues() {
-        let output = r"
-Code review complete. No issues found. The implementation looks good
-and follows all the project conventions.
-";
-
-        let issues = parse_review_issues(output);
-        assert!(issues.is_empty());
-    }
-
-    #[test]
-    fn test_should_parse_empty_output() {
-        let issues = parse_review_issues("");
-        assert!(issues.is_empty());
-    }
-
     #[test]
     fn test_should_identify_completed_phases() {
         let spec = FeatureSpec {
@@ -1226,6 +1593,115 @@ and follows all the project conventions.
         assert!(completed.is_empty());
     }
 
+    #[test]
+    fn test_should_render_design_spec_in_pr_system_prompt() {
+        let spec = FeatureSpec {
+            feature: "Login".to_owned(),
+            phases: vec![Phase {
+                name: "Phase 1".to_owned(),
+                description: "First".to_owned(),
+                tasks: vec!["Task".to_owned()],
+                result: Some(PhaseResult {
+                    status: StepStatus::Completed,
+                    turns: 3,
+                    commit: Some("abc123".to_owned()),
+                }),
+            }],
+            verification: VerificationPlan {
+                criteria: vec![],
+                test_commands: vec![],
+            },
+            execution: None,
+        };
+        let ctx = TemplateContext {
+            repo_path: Path::new("/repo"),
+            slug: "login",
+            design_spec: "Use OAuth.",
+            spec: &spec,
+            base_branch: "main",
+        };
+        let context = ctx.pr(
+            "gba/login",
+            &ReviewResult {
+                turns: 0,
+                issues_found: 0,
+                issues_fixed: 0,
+            },
+            &ReviewRecord::default(),
+            &VerificationResult {
+                turns: 0,
+                passed: true,
+                evidence: None,
+            },
+        );
+
+        let pm = gba_pm::PromptManager::new().expect("should create prompt manager");
+        let system = pm.render("code/system", &context).expect("should render");
+        assert!(system.contains("Use OAuth."));
+    }
+
+    #[tokio::test]
+    async fn test_should_skip_disabled_or_empty_stages() {
+        let project_config = crate::config::ProjectConfig::default();
+        let (event_tx, _event_rx) = mpsc::channel(1);
+        let (_permission_tx, permission_rx) = mpsc::channel(1);
+        let engine_config = EngineConfig::builder()
+            .repo_path(PathBuf::from("/tmp/test"))
+            .build();
+        let mut ctx = RunContext {
+            agent_runner: Arc::new(
+                AgentRunner::new(&engine_config, &project_config).expect("should create runner"),
+            ),
+            git: GitOps::new(PathBuf::from("/tmp/test"), project_config.git.clone()),
+            hooks_config: project_config.hooks.clone(),
+            review_config: project_config.review.clone(),
+            verification_config: project_config.verification.clone(),
+            gba_dir: PathBuf::from("/tmp/test/.gba"),
+            repo_path: PathBuf::from("/tmp/test"),
+            base_branch: "main".to_owned(),
+            auto_commit: true,
+            stages: project_config.pipeline.stages.clone(),
+            permissions: PermissionBroker::spawn(event_tx, permission_rx),
+            transcripts: TranscriptArchive::open(Path::new("/tmp/test/.gba/features/test"))
+                .expect("should open archive"),
+        };
+        let spec = FeatureSpec {
+            feature: "Test".to_owned(),
+            phases: vec![],
+            verification: VerificationPlan {
+                criteria: vec![],
+                test_commands: vec![],
+            },
+            execution: None,
+        };
+        let stage = StageConfig::new;
+
+        assert!(skip_reason(&ctx, &stage(StageKind::Phases), &spec).is_some());
+        assert!(skip_reason(&ctx, &stage(StageKind::Verification), &spec).is_some());
+        assert!(skip_reason(&ctx, &stage(StageKind::Review), &spec).is_none());
+        assert!(skip_reason(&ctx, &stage(StageKind::Agent), &spec).is_none());
+
+        ctx.review_config.enabled = false;
+        assert_eq!(
+            skip_reason(&ctx, &stage(StageKind::Review), &spec),
+            Some("review disabled")
+        );
+    }
+
+    #[test]
+    fn test_should_sum_stage_turns() {
+        let mut state = PipelineState::new();
+        for (name, turns) in [("phases", 10), ("review", 4), ("pr", 1)] {
+            state.stages.push(StageResult {
+                name: name.to_owned(),
+                kind: StageKind::Agent,
+                status: StepStatus::Completed,
+                turns,
+            });
+        }
+        assert_eq!(state.total_turns(), 15);
+    }
+
     #[tokio::test]
     async fn test_should_return_not_initialized() {
         let dir = tempfile::TempDir::new().expect("should create temp dir");
@@ -1265,17 +1741,34 @@ and follows all the project conventions.
         );
     }
 
-    #[test]
-    fn test_should_parse_severity_variants() {
-        assert_eq!(parse_severity("error"), Some(Severity::Error));
-        assert_eq!(parse_severity("Error"), Some(Severity::Error));
-        assert_eq!(parse_severity("ERROR"), Some(Severity::Error));
-        assert_eq!(parse_severity("warning"), Some(Severity::Warning));
-        assert_eq!(parse_severity("warn"), Some(Severity::Warning));
-        assert_eq!(parse_severity("suggestion"), Some(Severity::Suggestion));
-        assert_eq!(parse_severity("info"), Some(Severity::Suggestion));
-        assert_eq!(parse_severity("note"), Some(Severity::Suggestion));
-        assert_eq!(parse_severity("unknown"), None);
+    #[tokio::test]
+    async fn test_should_refuse_to_run_a_running_feature() {
+        let dir = tempfile::TempDir::new().expect("should create temp dir");
+        let feature_dir = dir.path().join(".gba").join("features").join("a");
+        std::fs::create_dir_all(&feature_dir).expect("should create feature dir");
+        std::fs::write(
+            feature_dir.join("phases.yaml"),
+            "feature: F\nphases:\n  - name: Setup\n    description: d\n    tasks: [t]\n\
+             verification:\n  criteria: [c]\n  testCommands: [cargo test]\n",
+        )
+        .expect("should write spec");
+        let config = EngineConfig::builder()
+            .repo_path(dir.path().to_path_buf())
+            .build();
+        let engine = Engine::new(config).await.expect("should create engine");
+
+        let lock_path = feature_dir.join(RUN_LOCK_FILE);
+        let lock = PidLock::acquire(&lock_path, "test").expect("should lock");
+        let result = engine.run("a").await;
+        assert!(
+            matches!(result, Err(CoreError::Locked(_))),
+            "expected Locked, got: {result:?}"
+        );
+
+        // Not a git repository: the worktree fails and the lock is released
+        drop(lock);
+        assert!(matches!(engine.run("a").await, Err(CoreError::Git(_))));
+        assert!(!lock_path.exists());
     }
 
     #[test]
@@ -1339,21 +1832,39 @@ Done.
     }
 
     #[test]
-    fn test_should_parse_inline_issue_correctly() {
-        let line = "- [error] src/main.rs: Missing error handling";
-        let issue = parse_inline_issue(line.trim());
-        assert!(issue.is_some());
-        let issue = issue.expect("should parse");
-        assert_eq!(issue.severity, Severity::Error);
-        assert_eq!(issue.file, PathBuf::from("src/main.rs"));
-        assert_eq!(issue.description, "Missing error handling");
-    }
+    fn test_should_keep_successful_reviewers_when_one_fails() {
+        let reviewer = |name: &str| ReviewerConfig {
+            name: name.to_owned(),
+            agent: None,
+            system_template: None,
+        };
+        let reviewers = [reviewer("security"), reviewer("performance")];
 
-    #[test]
-    fn test_should_reject_malformed_inline_issue() {
-        assert!(parse_inline_issue("not an issue").is_none());
-        assert!(parse_inline_issue("- [error]").is_none());
-        assert!(parse_inline_issue("- [error] :").is_none());
-        assert!(parse_inline_issue("- [unknown] file: desc").is_none());
+        let outputs = split_review_outputs(
+            &reviewers,
+            vec![
+                Ok(Vec::new()),
+                Err(CoreError::Agent("rate limited".to_owned())),
+            ],
+        )
+        .expect("should keep the successful reviewer");
+        assert_eq!(outputs.reports.len(), 1);
+        assert_eq!(outputs.reports[0].0, "security");
+        assert_eq!(
+            outputs.failed,
+            vec![ReviewerFailure {
+                reviewer: "performance".to_owned(),
+                error: "agent error: rate limited".to_owned(),
+            }]
+        );
+
+        let result = split_review_outputs(
+            &reviewers,
+            vec![
+                Err(CoreError::Agent("down".to_owned())),
+                Err(CoreError::Agent("rate limited".to_owned())),
+            ],
+        );
+        assert!(matches!(result, Err(CoreError::Agent(ref msg)) if msg == "down"));
     }
 }
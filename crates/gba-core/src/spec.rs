@@ -8,10 +8,13 @@
 use std::fs;
 use std::path::Path;
 
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use tracing::{debug, instrument};
 
+use crate::config::StageKind;
 use crate::error::CoreError;
+use crate::validate;
 
 // ── Feature Spec ─────────────────────────────────────────────
 
@@ -20,6 +23,10 @@ use crate::error::CoreError;
 /// Serialized as `phases.yaml`. Plan fields are written by `gba plan`;
 /// result fields are written by `gba run` as execution progresses.
 ///
+/// Unknown keys are rejected in the plan fields, which people write, but
+/// ignored in the result records, which gba writes, so specs written by a
+/// newer gba still load.
+///
 /// # Examples
 ///
 /// ```
@@ -43,8 +50,8 @@ use crate::error::CoreError;
 /// assert_eq!(spec.feature, "Add login page");
 /// assert_eq!(spec.phases.len(), 1);
 /// ```
-#[derive(Debug, Clone, Serialize, Deserialize)]
-#[serde(rename_all = "camelCase")]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
 pub struct FeatureSpec {
     /// Human-readable feature description.
     pub feature: String,
@@ -64,8 +71,8 @@ pub struct FeatureSpec {
 ///
 /// Each phase represents a logical unit of work (e.g., "Core data structures",
 /// "Business logic") that the coding agent implements in one session.
-#[derive(Debug, Clone, Serialize, Deserialize)]
-#[serde(rename_all = "camelCase")]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
 pub struct Phase {
     /// Phase name (e.g., "Phase 1: Core data structures").
     pub name: String,
@@ -82,7 +89,7 @@ pub struct Phase {
 }
 
 /// Execution result for a single phase.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct PhaseResult {
     /// Current status of this phase.
@@ -97,7 +104,7 @@ pub struct PhaseResult {
 }
 
 /// Status of a phase or the overall execution.
-#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq, JsonSchema)]
 #[serde(rename_all = "camelCase")]
 pub enum StepStatus {
     /// Not yet started.
@@ -109,11 +116,13 @@ pub enum StepStatus {
     Completed,
     /// Failed with an error.
     Failed,
+    /// Not run because it is disabled or has nothing to do.
+    Skipped,
 }
 
 /// Verification criteria and test commands from the feature spec.
-#[derive(Debug, Clone, Serialize, Deserialize)]
-#[serde(rename_all = "camelCase")]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
 pub struct VerificationPlan {
     /// Human-readable acceptance criteria.
     pub criteria: Vec<String>,
@@ -123,7 +132,7 @@ pub struct VerificationPlan {
 }
 
 /// Overall execution summary, written to `phases.yaml` by `gba run`.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct Execution {
     /// Overall execution status.
@@ -141,10 +150,107 @@ pub struct Execution {
     /// PR URL, set after the PR is created.
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub pr: Option<String>,
+
+    /// Outcome of each pipeline stage, in execution order.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub stages: Vec<StageResult>,
+
+    /// Tool calls denied by the tool policy, in the order they happened.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub denials: Vec<ToolDenial>,
+
+    /// Every precommit hook run, in the order they happened.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub hooks: Vec<HookRun>,
+
+    /// Turns, tokens and cost of each agent session, in the order they
+    /// finished.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub usage: Vec<AgentUsage>,
+}
+
+/// One run of a precommit hook during `gba run`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct HookRun {
+    /// Phase the hook checked, or the stage name outside the phases stage.
+    pub phase: String,
+
+    /// Hook name.
+    pub hook: String,
+
+    /// Shell command that was executed.
+    pub command: String,
+
+    /// One-based attempt within the phase's hook-fix cycle.
+    pub attempt: u32,
+
+    /// Whether the hook passed.
+    pub passed: bool,
+}
+
+/// Turns, tokens and cost of one agent session.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct AgentUsage {
+    /// Agent that ran the session.
+    pub agent: String,
+
+    /// Phase the agent worked on, or the stage name outside the phases
+    /// stage.
+    pub phase: String,
+
+    /// Agent API round-trips.
+    pub turns: u32,
+
+    /// Input tokens, including cache reads and writes.
+    pub input_tokens: u64,
+
+    /// Output tokens.
+    pub output_tokens: u64,
+
+    /// Cost in USD, if the SDK reported it.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cost_usd: Option<f64>,
+}
+
+/// A tool call denied by the tool policy during a run.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ToolDenial {
+    /// Agent that made the call.
+    pub agent: String,
+
+    /// Phase the agent worked on, or the stage name outside the phases
+    /// stage.
+    pub phase: String,
+
+    /// Tool name (e.g. `Bash`, `Write`).
+    pub tool: String,
+
+    /// Rule that denied the call.
+    pub reason: String,
+}
+
+/// Outcome of a single run pipeline stage.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct StageResult {
+    /// Stage name.
+    pub name: String,
+
+    /// Stage kind.
+    pub kind: StageKind,
+
+    /// Stage status.
+    pub status: StepStatus,
+
+    /// Number of agent turns consumed by the stage.
+    pub turns: u32,
 }
 
 /// Summary of the code review step.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct ReviewResult {
     /// Number of agent turns consumed during review.
@@ -158,7 +264,7 @@ pub struct ReviewResult {
 }
 
 /// Summary of the verification step.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct VerificationResult {
     /// Number of agent turns consumed during verification.
@@ -166,6 +272,10 @@ pub struct VerificationResult {
 
     /// Whether all verification criteria passed.
     pub passed: bool,
+
+    /// Final output of the verify agent, as evidence for the verdict.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub evidence: Option<String>,
 }
 
 // ── File operations ──────────────────────────────────────────
@@ -177,7 +287,8 @@ pub struct VerificationResult {
 /// # Errors
 ///
 /// Returns `CoreError::FeatureNotFound` if the feature directory does not exist.
-/// Returns `CoreError::InvalidSpec` if the YAML content cannot be parsed.
+/// Returns `CoreError::InvalidSpec` if the YAML content cannot be parsed or
+/// violates a validation rule (empty or duplicate phase names).
 /// Returns `CoreError::Io` if the file cannot be read.
 #[instrument(skip(gba_dir))]
 pub(crate) fn load_feature_spec(gba_dir: &Path, slug: &str) -> Result<FeatureSpec, CoreError> {
@@ -188,9 +299,63 @@ pub(crate) fn load_feature_spec(gba_dir: &Path, slug: &str) -> Result<FeatureSpe
     let content = fs::read_to_string(&phases_path)?;
     let spec: FeatureSpec = serde_yaml::from_str(&content)
         .map_err(|e| CoreError::InvalidSpec(format!("{}: {e}", phases_path.display())))?;
+    let diagnostics = validate::feature_spec_diagnostics(&phases_path, &content, &spec);
+    if !diagnostics.is_empty() {
+        return Err(CoreError::InvalidSpec(validate::join_diagnostics(
+            &diagnostics,
+        )));
+    }
     Ok(spec)
 }
 
+/// Merge a plan amended by the plan agent into the spec it started from.
+///
+/// Completed phases of `original` are kept first, in their original order,
+/// with their results and commits. The amended plan supplies every other
+/// phase, the feature description and the verification plan; amended phases
+/// named like a completed phase are dropped, and results of the remaining
+/// phases are cleared so they run again. When phases are left to run, the
+/// execution summary is marked pending.
+pub(crate) fn merge_amended_spec(original: &FeatureSpec, amended: FeatureSpec) -> FeatureSpec {
+    let completed: Vec<Phase> = original
+        .phases
+        .iter()
+        .filter(|p| {
+            p.result
+                .as_ref()
+                .is_some_and(|r| r.status == StepStatus::Completed)
+        })
+        .cloned()
+        .collect();
+
+    let pending: Vec<Phase> = amended
+        .phases
+        .into_iter()
+        .filter(|p| !completed.iter().any(|c| c.name.trim() == p.name.trim()))
+        .map(|p| Phase { result: None, ..p })
+        .collect();
+
+    let mut phases = completed;
+    phases.extend(pending);
+
+    let mut execution = original.execution.clone();
+    if phases.iter().any(|p| {
+        p.result
+            .as_ref()
+            .is_none_or(|r| r.status != StepStatus::Completed)
+    }) && let Some(execution) = execution.as_mut()
+    {
+        execution.status = StepStatus::Pending;
+    }
+
+    FeatureSpec {
+        feature: amended.feature,
+        phases,
+        verification: amended.verification,
+        execution,
+    }
+}
+
 /// Save a [`FeatureSpec`] to `phases.yaml` for the given feature slug.
 ///
 /// Writes to `.gba/features/<slug>/phases.yaml`, creating parent directories
@@ -327,8 +492,36 @@ mod tests {
                 verification: VerificationResult {
                     turns: 6,
                     passed: true,
+                    evidence: Some("All 12 tests passed".to_owned()),
                 },
                 pr: Some("https://github.com/org/repo/pull/42".to_owned()),
+                stages: vec![StageResult {
+                    name: "review".to_owned(),
+                    kind: StageKind::Review,
+                    status: StepStatus::Completed,
+                    turns: 8,
+                }],
+                denials: vec![ToolDenial {
+                    agent: "code".to_owned(),
+                    phase: "Setup".to_owned(),
+                    tool: "Bash".to_owned(),
+                    reason: "command matches denied pattern \"curl\"".to_owned(),
+                }],
+                hooks: vec![HookRun {
+                    phase: "Setup".to_owned(),
+                    hook: "clippy".to_owned(),
+                    command: "cargo clippy".to_owned(),
+                    attempt: 1,
+                    passed: true,
+                }],
+                usage: vec![AgentUsage {
+                    agent: "code".to_owned(),
+                    phase: "Setup".to_owned(),
+                    turns: 12,
+                    input_tokens: 4000,
+                    output_tokens: 800,
+                    cost_usd: Some(0.12),
+                }],
             }),
         };
 
@@ -352,6 +545,75 @@ mod tests {
             exec.pr.as_deref(),
             Some("https://github.com/org/repo/pull/42")
         );
+        assert_eq!(exec.stages.len(), 1);
+        assert_eq!(exec.stages[0].kind, StageKind::Review);
+        assert_eq!(exec.denials.len(), 1);
+        assert_eq!(exec.denials[0].tool, "Bash");
+        assert_eq!(
+            exec.verification.evidence.as_deref(),
+            Some("All 12 tests passed")
+        );
+        assert_eq!(exec.hooks[0].hook, "clippy");
+        assert_eq!(exec.usage[0].cost_usd, Some(0.12));
+    }
+
+    #[test]
+    fn test_should_merge_amended_spec_keeping_completed_phases() {
+        let phase = |name: &str, status: Option<StepStatus>| Phase {
+            name: name.to_owned(),
+            description: format!("{name} description"),
+            tasks: vec![format!("{name} task")],
+            result: status.map(|status| PhaseResult {
+                status,
+                turns: 3,
+                commit: Some(format!("{name}-commit")),
+            }),
+        };
+        let verification = VerificationPlan {
+            criteria: vec![],
+            test_commands: vec!["cargo test".to_owned()],
+        };
+        let original = FeatureSpec {
+            feature: "Login".to_owned(),
+            phases: vec![
+                phase("Setup", Some(StepStatus::Completed)),
+                phase("Api", Some(StepStatus::Failed)),
+                phase("Ui", None),
+            ],
+            verification: verification.clone(),
+            execution: None,
+        };
+        // The agent reordered and split pending phases and edited a
+        // completed one
+        let amended = FeatureSpec {
+            feature: "Login with OAuth".to_owned(),
+            phases: vec![
+                phase("Ui", None),
+                Phase {
+                    tasks: vec!["rewritten".to_owned()],
+                    result: None,
+                    ..phase("Setup", None)
+                },
+                phase("Api: tokens", Some(StepStatus::Failed)),
+                phase("Api: routes", None),
+            ],
+            verification,
+            execution: None,
+        };
+
+        let merged = merge_amended_spec(&original, amended);
+        let names: Vec<&str> = merged.phases.iter().map(|p| p.name.as_str()).collect();
+        assert_eq!(names, ["Setup", "Ui", "Api: tokens", "Api: routes"]);
+        assert_eq!(merged.feature, "Login with OAuth");
+        assert_eq!(merged.phases[0].tasks, ["Setup task"]);
+        assert_eq!(
+            merged.phases[0]
+                .result
+                .as_ref()
+                .and_then(|r| r.commit.as_deref()),
+            Some("Setup-commit")
+        );
+        assert!(merged.phases[1..].iter().all(|p| p.result.is_none()));
     }
 
     #[test]
@@ -387,6 +649,53 @@ mod tests {
         assert_eq!(loaded.phases.len(), 1);
     }
 
+    #[test]
+    fn test_should_ignore_unknown_fields_in_execution_records() {
+        let dir = tempfile::TempDir::new().expect("should create temp dir");
+        let feature_dir = dir.path().join("features").join("newer");
+        fs::create_dir_all(&feature_dir).expect("should create feature dir");
+        fs::write(
+            feature_dir.join("phases.yaml"),
+            r#"feature: Newer
+phases:
+  - name: Phase 1
+    description: d
+    tasks: [t]
+    result:
+      status: completed
+      turns: 3
+      startedAt: "2026-01-01T00:00:00Z"
+verification:
+  criteria: []
+  testCommands: []
+execution:
+  status: completed
+  totalTurns: 3
+  review: { turns: 0, issuesFound: 0, issuesFixed: 0, reviewers: 2 }
+  verification: { turns: 0, passed: true }
+  stages:
+    - { name: phases, kind: phases, status: completed, turns: 3, durationMs: 10 }
+  usage:
+    - { agent: code, phase: Phase 1, turns: 3, inputTokens: 1, outputTokens: 1, model: m }
+"#,
+        )
+        .expect("should write spec");
+
+        let spec = load_feature_spec(dir.path(), "newer").expect("should load");
+        assert_eq!(spec.phases[0].result.as_ref().map(|r| r.turns), Some(3));
+
+        // Plan fields written by people stay strict
+        fs::write(
+            feature_dir.join("phases.yaml"),
+            "feature: Newer\nphases:\n  - name: P\n    description: d\n    taks: [t]\nverification:\n  criteria: []\n  testCommands: []\n",
+        )
+        .expect("should write spec");
+        assert!(matches!(
+            load_feature_spec(dir.path(), "newer"),
+            Err(CoreError::InvalidSpec(_))
+        ));
+    }
+
     #[test]
     fn test_should_return_feature_not_found_for_missing_spec() {
         let dir = tempfile::TempDir::new().expect("should create temp dir");
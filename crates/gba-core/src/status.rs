@@ -0,0 +1,362 @@
+//! Feature status overview.
+//!
+//! Scans `.gba/features/*/phases.yaml` and summarizes where each feature
+//! stands: phase progress, execution, review and verification outcomes, the
+//! PR, the state of its worktree and when it was last worked on.
+
+use std::fs;
+use std::path::Path;
+use std::time::UNIX_EPOCH;
+
+use serde::Serialize;
+use tracing::{debug, instrument};
+
+use crate::error::CoreError;
+use crate::events::PhaseSummary;
+use crate::git::{self, GitOps};
+use crate::spec::{ReviewResult, StepStatus, VerificationResult, load_feature_spec};
+
+/// Status of one planned feature.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FeatureStatus {
+    /// Feature slug.
+    pub slug: String,
+
+    /// Feature description from `phases.yaml`.
+    pub feature: String,
+
+    /// Phases with their status.
+    pub phases: Vec<PhaseSummary>,
+
+    /// Overall execution status, or `None` if the feature was never run.
+    pub execution: Option<StepStatus>,
+
+    /// Code review summary of the last run.
+    pub review: Option<ReviewResult>,
+
+    /// Verification summary of the last run.
+    pub verification: Option<VerificationResult>,
+
+    /// PR URL, once a PR was created.
+    pub pr: Option<String>,
+
+    /// State of the feature's worktree.
+    pub worktree: WorktreeState,
+
+    /// Latest change to the feature's files or commit in its worktree, in
+    /// seconds since the Unix epoch.
+    pub last_activity: Option<u64>,
+
+    /// Why `phases.yaml` could not be loaded, in which case the fields above
+    /// are empty.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+impl FeatureStatus {
+    /// Number of completed phases.
+    pub fn completed_phases(&self) -> usize {
+        self.phases
+            .iter()
+            .filter(|p| p.status == StepStatus::Completed)
+            .count()
+    }
+}
+
+/// State of a feature's worktree in `.trees/<slug>`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum WorktreeState {
+    /// No worktree exists.
+    Missing,
+    /// The worktree has no uncommitted changes.
+    Clean,
+    /// The worktree has uncommitted changes.
+    Dirty,
+}
+
+impl WorktreeState {
+    /// Name shown to users.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Missing => "missing",
+            Self::Clean => "clean",
+            Self::Dirty => "dirty",
+        }
+    }
+}
+
+impl std::fmt::Display for WorktreeState {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Status of every feature with a `phases.yaml`, sorted by slug. Features
+/// whose spec cannot be loaded are listed with [`FeatureStatus::error`] set.
+///
+/// # Errors
+///
+/// Returns `CoreError::NotInitialized` if `.gba/` does not exist.
+/// Returns `CoreError::Io` if the features directory cannot be read.
+/// Returns `CoreError::Git` if a worktree's state cannot be read.
+#[instrument(skip(gba_dir, git))]
+pub(crate) async fn list_features(
+    gba_dir: &Path,
+    git: &GitOps,
+) -> Result<Vec<FeatureStatus>, CoreError> {
+    if !gba_dir.exists() {
+        return Err(CoreError::NotInitialized);
+    }
+    let features_dir = gba_dir.join("features");
+    if !features_dir.exists() {
+        return Ok(Vec::new());
+    }
+
+    let mut slugs: Vec<String> = fs::read_dir(&features_dir)?
+        .filter_map(Result::ok)
+        .filter(|entry| entry.path().join("phases.yaml").is_file())
+        .filter_map(|entry| entry.file_name().into_string().ok())
+        .collect();
+    slugs.sort_unstable();
+
+    let mut statuses = Vec::with_capacity(slugs.len());
+    for slug in slugs {
+        let status = match feature_status(gba_dir, git, &slug).await {
+            Err(CoreError::InvalidSpec(e)) => {
+                debug!(slug, error = %e, "listing feature with invalid spec");
+                FeatureStatus {
+                    worktree: worktree_state(git, &slug).await?,
+                    last_activity: last_activity(gba_dir, git, &slug).await?,
+                    error: Some(e),
+                    ..empty_status(&slug)
+                }
+            }
+            result => result?,
+        };
+        statuses.push(status);
+    }
+    Ok(statuses)
+}
+
+/// Status of one feature.
+///
+/// # Errors
+///
+/// Returns `CoreError::FeatureNotFound` if the feature has no `phases.yaml`.
+/// Returns `CoreError::InvalidSpec` if `phases.yaml` is invalid.
+/// Returns `CoreError::Git` if the worktree's state cannot be read.
+#[instrument(skip(gba_dir, git))]
+pub(crate) async fn feature_status(
+    gba_dir: &Path,
+    git: &GitOps,
+    slug: &str,
+) -> Result<FeatureStatus, CoreError> {
+    let spec = load_feature_spec(gba_dir, slug)?;
+    let phases = spec
+        .phases
+        .iter()
+        .map(|phase| PhaseSummary {
+            name: phase.name.clone(),
+            status: phase
+                .result
+                .as_ref()
+                .map(|r| r.status.clone())
+                .unwrap_or_default(),
+        })
+        .collect();
+    let execution = spec.execution;
+
+    Ok(FeatureStatus {
+        feature: spec.feature,
+        phases,
+        execution: execution.as_ref().map(|e| e.status.clone()),
+        review: execution.as_ref().map(|e| e.review.clone()),
+        verification: execution.as_ref().map(|e| e.verification.clone()),
+        pr: execution.and_then(|e| e.pr),
+        worktree: worktree_state(git, slug).await?,
+        last_activity: last_activity(gba_dir, git, slug).await?,
+        ..empty_status(slug)
+    })
+}
+
+fn empty_status(slug: &str) -> FeatureStatus {
+    FeatureStatus {
+        slug: slug.to_owned(),
+        feature: String::new(),
+        phases: Vec::new(),
+        execution: None,
+        review: None,
+        verification: None,
+        pr: None,
+        worktree: WorktreeState::Missing,
+        last_activity: None,
+        error: None,
+    }
+}
+
+async fn worktree_state(git: &GitOps, slug: &str) -> Result<WorktreeState, CoreError> {
+    let path = git.worktree_path(slug);
+    if !path.exists() {
+        return Ok(WorktreeState::Missing);
+    }
+    Ok(if git::is_dirty(&path).await? {
+        WorktreeState::Dirty
+    } else {
+        WorktreeState::Clean
+    })
+}
+
+/// Latest modification of the feature directory's files or commit in the
+/// feature's worktree.
+async fn last_activity(gba_dir: &Path, git: &GitOps, slug: &str) -> Result<Option<u64>, CoreError> {
+    let files = latest_mtime(&gba_dir.join("features").join(slug));
+    let worktree = git.worktree_path(slug);
+    let commit = if worktree.exists() {
+        git::last_commit_time(&worktree).await?
+    } else {
+        None
+    };
+    Ok(files.max(commit))
+}
+
+/// Latest modification time of the files under `dir`, in seconds since the
+/// Unix epoch.
+fn latest_mtime(dir: &Path) -> Option<u64> {
+    let entries = fs::read_dir(dir).ok()?;
+    entries
+        .filter_map(Result::ok)
+        .filter_map(|entry| {
+            let metadata = entry.metadata().ok()?;
+            if metadata.is_dir() {
+                return latest_mtime(&entry.path());
+            }
+            let modified = metadata.modified().ok()?;
+            Some(modified.duration_since(UNIX_EPOCH).ok()?.as_secs())
+        })
+        .max()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, SystemTime};
+
+    use super::*;
+    use crate::config::GitConfig;
+
+    const SPEC: &str = "\
+feature: Login page
+phases:
+  - name: Setup
+    description: Scaffold
+    tasks: [a]
+    result:
+      status: completed
+      turns: 3
+      commit: abc123
+  - name: Routes
+    description: Routes
+    tasks: [b]
+verification:
+  criteria: [works]
+  testCommands: [cargo test]
+execution:
+  status: failed
+  totalTurns: 3
+  review:
+    turns: 0
+    issuesFound: 2
+    issuesFixed: 1
+  verification:
+    turns: 0
+    passed: false
+  pr: https://github.com/o/r/pull/7
+";
+
+    fn write_feature(gba_dir: &Path, slug: &str, spec: &str) {
+        let dir = gba_dir.join("features").join(slug);
+        fs::create_dir_all(dir.join("specs")).expect("should create feature dir");
+        fs::write(dir.join("phases.yaml"), spec).expect("should write spec");
+    }
+
+    #[tokio::test]
+    async fn test_should_list_feature_statuses() {
+        let repo = tempfile::TempDir::new().expect("should create temp dir");
+        let gba_dir = repo.path().join(".gba");
+        let git = GitOps::new(repo.path().to_path_buf(), GitConfig::default());
+        write_feature(&gba_dir, "login", SPEC);
+        write_feature(&gba_dir, "broken", "feature: [");
+        // A directory without phases.yaml is not a feature
+        fs::create_dir_all(gba_dir.join("features/draft")).expect("should create dir");
+
+        let statuses = list_features(&gba_dir, &git)
+            .await
+            .expect("should list features");
+        let slugs: Vec<&str> = statuses.iter().map(|s| s.slug.as_str()).collect();
+        assert_eq!(slugs, ["broken", "login"]);
+
+        assert!(statuses[0].error.is_some());
+        let login = &statuses[1];
+        assert_eq!(login.feature, "Login page");
+        assert_eq!((login.completed_phases(), login.phases.len()), (1, 2));
+        assert_eq!(login.execution, Some(StepStatus::Failed));
+        assert_eq!(login.review.as_ref().map(|r| r.issues_found), Some(2));
+        assert_eq!(login.verification.as_ref().map(|v| v.passed), Some(false));
+        assert_eq!(login.pr.as_deref(), Some("https://github.com/o/r/pull/7"));
+        assert_eq!(login.worktree, WorktreeState::Missing);
+        assert!(login.last_activity.is_some());
+
+        let json = serde_json::to_value(login).expect("should serialize");
+        assert_eq!(json["worktree"], "missing");
+        assert_eq!(json["phases"][0]["status"], "completed");
+        assert!(json.get("error").is_none());
+    }
+
+    #[tokio::test]
+    async fn test_should_fail_for_missing_feature_or_uninitialized_repo() {
+        let repo = tempfile::TempDir::new().expect("should create temp dir");
+        let gba_dir = repo.path().join(".gba");
+        let git = GitOps::new(repo.path().to_path_buf(), GitConfig::default());
+
+        assert!(matches!(
+            list_features(&gba_dir, &git).await,
+            Err(CoreError::NotInitialized)
+        ));
+        fs::create_dir_all(&gba_dir).expect("should create .gba");
+        assert!(
+            list_features(&gba_dir, &git)
+                .await
+                .expect("should list")
+                .is_empty()
+        );
+        assert!(matches!(
+            feature_status(&gba_dir, &git, "nope").await,
+            Err(CoreError::FeatureNotFound(_))
+        ));
+    }
+
+    #[test]
+    fn test_should_find_latest_mtime_recursively() {
+        let dir = tempfile::TempDir::new().expect("should create temp dir");
+        fs::create_dir_all(dir.path().join("specs")).expect("should create dir");
+        let old = dir.path().join("phases.yaml");
+        let new = dir.path().join("specs/design.md");
+        fs::write(&old, "").expect("should write");
+        fs::write(&new, "").expect("should write");
+        let time = |secs| SystemTime::UNIX_EPOCH + Duration::from_secs(secs);
+        fs::File::options()
+            .write(true)
+            .open(&old)
+            .and_then(|f| f.set_modified(time(1_000)))
+            .expect("should set mtime");
+        fs::File::options()
+            .write(true)
+            .open(&new)
+            .and_then(|f| f.set_modified(time(2_000)))
+            .expect("should set mtime");
+
+        assert_eq!(latest_mtime(dir.path()), Some(2_000));
+        assert_eq!(latest_mtime(&dir.path().join("missing")), None);
+    }
+}
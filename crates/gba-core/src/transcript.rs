@@ -0,0 +1,279 @@
+//! Plan session transcripts (internal).
+//!
+//! Records everything said in a plan session -- prompts sent by the engine,
+//! user inputs, agent messages and tool calls -- to
+//! `.gba/features/<slug>/plan-transcript.jsonl`, one entry per line, along
+//! with the SDK session id needed to resume the conversation. A readable
+//! `plan-transcript.md` is exported next to it after every turn.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+use tracing::warn;
+
+use crate::error::CoreError;
+use crate::jsonl;
+
+/// File name of the JSON Lines transcript inside the feature directory.
+const TRANSCRIPT_FILE: &str = "plan-transcript.jsonl";
+
+/// File name of the Markdown export inside the feature directory.
+const MARKDOWN_FILE: &str = "plan-transcript.md";
+
+/// Maximum characters of tool input shown in the Markdown export.
+const MAX_TOOL_INPUT_CHARS: usize = 200;
+
+/// One entry of a plan transcript.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "camelCase")]
+pub(crate) enum TranscriptEntry {
+    /// The SDK session the following entries belong to.
+    #[serde(rename_all = "camelCase")]
+    Session {
+        /// SDK session id, used to resume the conversation.
+        session_id: String,
+    },
+    /// A prompt sent by the engine (task, repair or resume prompt).
+    Prompt {
+        /// Rendered prompt.
+        text: String,
+    },
+    /// Input typed by the user.
+    User {
+        /// User input.
+        text: String,
+    },
+    /// Text of one agent turn.
+    Agent {
+        /// Agent text.
+        text: String,
+    },
+    /// A tool call made by the agent.
+    ToolCall {
+        /// Tool name.
+        name: String,
+        /// Tool input.
+        input: serde_json::Value,
+    },
+}
+
+/// Append-only transcript of a plan session.
+#[derive(Debug)]
+pub(crate) struct Transcript {
+    /// Feature slug, used as the Markdown title.
+    slug: String,
+    /// Path of the JSON Lines transcript.
+    path: PathBuf,
+    /// Path of the Markdown export.
+    markdown_path: PathBuf,
+    /// Last recorded SDK session id.
+    session_id: Option<String>,
+}
+
+impl Transcript {
+    /// Open the transcript in `feature_dir`, continuing an existing one.
+    pub(crate) fn open(feature_dir: &Path, slug: &str) -> Self {
+        let path = feature_dir.join(TRANSCRIPT_FILE);
+        let session_id = load_transcript(&path)
+            .ok()
+            .and_then(|e| last_session_id(&e));
+        Self {
+            slug: slug.to_owned(),
+            path,
+            markdown_path: feature_dir.join(MARKDOWN_FILE),
+            session_id,
+        }
+    }
+
+    /// Append an entry.
+    pub(crate) fn record(&mut self, entry: TranscriptEntry) {
+        jsonl::append(&self.path, &entry);
+    }
+
+    /// Record the SDK session id if it differs from the last one.
+    pub(crate) fn record_session(&mut self, session_id: &str) {
+        if self.session_id.as_deref() == Some(session_id) {
+            return;
+        }
+        self.session_id = Some(session_id.to_owned());
+        self.record(TranscriptEntry::Session {
+            session_id: session_id.to_owned(),
+        });
+    }
+
+    /// Rewrite the Markdown export from the full transcript.
+    pub(crate) fn export_markdown(&self) {
+        let result = load_transcript(&self.path).and_then(|entries| {
+            Ok(fs::write(
+                &self.markdown_path,
+                to_markdown(&self.slug, &entries),
+            )?)
+        });
+        if let Err(e) = result {
+            warn!(path = %self.markdown_path.display(), error = %e, "failed to export plan transcript");
+        }
+    }
+}
+
+/// Read the transcript at `path` (see [`jsonl::load()`]).
+///
+/// # Errors
+///
+/// Returns `CoreError::Io` if the file cannot be read.
+pub(crate) fn load_transcript(path: &Path) -> Result<Vec<TranscriptEntry>, CoreError> {
+    jsonl::load(path)
+}
+
+/// The SDK session id of the feature's last plan session.
+///
+/// # Errors
+///
+/// Returns `CoreError::FeatureNotFound` if the feature has no transcript or
+/// the transcript records no session.
+/// Returns `CoreError::Io` if the transcript cannot be read.
+pub(crate) fn load_session_id(feature_dir: &Path, slug: &str) -> Result<String, CoreError> {
+    let path = feature_dir.join(TRANSCRIPT_FILE);
+    if !path.exists() {
+        return Err(CoreError::FeatureNotFound(format!(
+            "plan transcript not found for {slug}"
+        )));
+    }
+    last_session_id(&load_transcript(&path)?)
+        .ok_or_else(|| CoreError::FeatureNotFound(format!("no plan session recorded for {slug}")))
+}
+
+fn last_session_id(entries: &[TranscriptEntry]) -> Option<String> {
+    entries.iter().rev().find_map(|entry| match entry {
+        TranscriptEntry::Session { session_id } => Some(session_id.clone()),
+        _ => None,
+    })
+}
+
+/// Render a transcript as Markdown.
+fn to_markdown(slug: &str, entries: &[TranscriptEntry]) -> String {
+    let mut out = format!("# Plan: {slug}\n");
+    for entry in entries {
+        match entry {
+            TranscriptEntry::Session { session_id } => {
+                out.push_str(&format!("\n<!-- session: {session_id} -->\n"));
+            }
+            TranscriptEntry::Prompt { text } => {
+                out.push_str(&format!("\n## Prompt\n\n{}\n", text.trim()));
+            }
+            TranscriptEntry::User { text } => {
+                out.push_str(&format!("\n## User\n\n{}\n", text.trim()));
+            }
+            TranscriptEntry::Agent { text } => {
+                out.push_str(&format!("\n## Agent\n\n{}\n", text.trim()));
+            }
+            TranscriptEntry::ToolCall { name, input } => {
+                out.push_str(&format!("\n- Tool `{name}`: {}\n", summarize_input(input)));
+            }
+        }
+    }
+    out
+}
+
+/// Short, single-line description of a tool input.
+fn summarize_input(input: &serde_json::Value) -> String {
+    for field in ["file_path", "command", "pattern", "path"] {
+        if let Some(value) = input.get(field).and_then(|v| v.as_str()) {
+            return format!("`{value}`");
+        }
+    }
+    let json = input.to_string();
+    let shown: String = json.chars().take(MAX_TOOL_INPUT_CHARS).collect();
+    if shown.len() < json.len() {
+        format!("`{shown}...`")
+    } else {
+        format!("`{shown}`")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn test_should_append_entries_and_remember_session() {
+        let dir = tempfile::TempDir::new().expect("should create temp dir");
+        let mut transcript = Transcript::open(dir.path(), "login");
+        transcript.record(TranscriptEntry::Prompt {
+            text: "Plan login".to_owned(),
+        });
+        transcript.record_session("s-1");
+        transcript.record_session("s-1");
+        transcript.record(TranscriptEntry::Agent {
+            text: "Which provider?".to_owned(),
+        });
+
+        // Reopening continues the same file
+        let mut reopened = Transcript::open(dir.path(), "login");
+        reopened.record_session("s-1");
+        reopened.record(TranscriptEntry::User {
+            text: "GitHub".to_owned(),
+        });
+        reopened.record_session("s-2");
+
+        let entries =
+            load_transcript(&dir.path().join(TRANSCRIPT_FILE)).expect("should load transcript");
+        assert_eq!(entries.len(), 5, "got: {entries:?}");
+        assert_eq!(
+            load_session_id(dir.path(), "login").expect("should find session"),
+            "s-2"
+        );
+    }
+
+    #[test]
+    fn test_should_skip_truncated_lines() {
+        let dir = tempfile::TempDir::new().expect("should create temp dir");
+        let path = dir.path().join(TRANSCRIPT_FILE);
+        fs::write(
+            &path,
+            "{\"kind\":\"session\",\"sessionId\":\"s-1\"}\n{\"kind\":\"agent\",\"te",
+        )
+        .expect("should write transcript");
+
+        let entries = load_transcript(&path).expect("should load transcript");
+        assert_eq!(entries.len(), 1);
+        assert_eq!(
+            load_session_id(dir.path(), "login").expect("should find session"),
+            "s-1"
+        );
+    }
+
+    #[test]
+    fn test_should_fail_to_resume_without_transcript() {
+        let dir = tempfile::TempDir::new().expect("should create temp dir");
+        let result = load_session_id(dir.path(), "login");
+        assert!(matches!(result, Err(CoreError::FeatureNotFound(_))));
+    }
+
+    #[test]
+    fn test_should_export_markdown() {
+        let entries = vec![
+            TranscriptEntry::Session {
+                session_id: "s-1".to_owned(),
+            },
+            TranscriptEntry::Agent {
+                text: "What should login support?\n".to_owned(),
+            },
+            TranscriptEntry::User {
+                text: "GitHub OAuth".to_owned(),
+            },
+            TranscriptEntry::ToolCall {
+                name: "Write".to_owned(),
+                input: json!({"file_path": "/repo/.gba/features/login/phases.yaml", "content": "x"}),
+            },
+        ];
+
+        let markdown = to_markdown("login", &entries);
+        assert!(markdown.starts_with("# Plan: login\n"));
+        assert!(markdown.contains("## Agent\n\nWhat should login support?\n"));
+        assert!(markdown.contains("## User\n\nGitHub OAuth\n"));
+        assert!(markdown.contains("- Tool `Write`: `/repo/.gba/features/login/phases.yaml`"));
+    }
+}
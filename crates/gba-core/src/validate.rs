@@ -0,0 +1,963 @@
+//! Configuration and feature spec validation.
+//!
+//! Validation runs in two stages. First each file is deserialized on its own
+//! so that syntax errors and unknown keys are reported with the position
+//! `serde_yaml` provides. Then semantic rules that the type system cannot
+//! express (positive iteration limits, unique names, branch pattern
+//! placeholders) are checked and mapped back to the line of the offending
+//! key. Results are returned as [`Diagnostic`]s for `gba config validate` and
+//! `gba spec validate`, and the engine refuses to start on semantic errors.
+
+use std::collections::HashSet;
+use std::fmt;
+use std::path::Path;
+
+use schemars::schema_for;
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+
+use crate::agent::load_prompt_manager;
+use crate::config::{EngineConfig, ProjectConfig, StageKind};
+use crate::error::CoreError;
+use crate::layers::{ConfigOrigin, ConfigSources, ResolvedConfig, resolve};
+use crate::prompts::template_issues;
+use crate::spec::FeatureSpec;
+
+/// Placeholders supported in `git.branchPattern`.
+const BRANCH_PLACEHOLDERS: &[&str] = &["id", "slug"];
+
+/// A single validation problem with its source location.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Diagnostic {
+    /// Where the problem is: a file path, or a non-file origin such as
+    /// `env (GBA_MODEL)`.
+    pub source: String,
+    /// One-based line number, if known.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub line: Option<usize>,
+    /// One-based column number, if known.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub column: Option<usize>,
+    /// Human-readable description of the problem.
+    pub message: String,
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match (self.line, self.column) {
+            (Some(line), Some(col)) => write!(f, "{}:{line}:{col}: {}", self.source, self.message),
+            (Some(line), None) => write!(f, "{}:{line}: {}", self.source, self.message),
+            _ => write!(f, "{}: {}", self.source, self.message),
+        }
+    }
+}
+
+/// A segment of a path into a YAML document.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum PathSegment {
+    /// Mapping key.
+    Key(&'static str),
+    /// Sequence index.
+    Index(usize),
+}
+
+/// A semantic rule violation, located by a path into the document.
+#[derive(Debug, Clone)]
+pub(crate) struct Violation {
+    /// Path to the offending value.
+    pub path: Vec<PathSegment>,
+    /// Description of the problem.
+    pub message: String,
+}
+
+impl Violation {
+    fn new(path: Vec<PathSegment>, message: impl Into<String>) -> Self {
+        Self {
+            path,
+            message: message.into(),
+        }
+    }
+
+    /// Dotted key of the violation's path with sequence indices removed,
+    /// matching the keys used for config origins.
+    fn origin_key(&self) -> String {
+        self.path
+            .iter()
+            .filter_map(|s| match s {
+                PathSegment::Key(k) => Some(*k),
+                PathSegment::Index(_) => None,
+            })
+            .collect::<Vec<_>>()
+            .join(".")
+    }
+}
+
+// ── JSON Schemas ─────────────────────────────────────────────
+
+/// JSON Schema for `.gba/config.yaml` ([`ProjectConfig`]).
+///
+/// # Examples
+///
+/// ```
+/// let schema = gba_core::project_config_schema();
+/// assert_eq!(schema["additionalProperties"], false);
+/// ```
+pub fn project_config_schema() -> serde_json::Value {
+    serde_json::to_value(schema_for!(ProjectConfig)).unwrap_or_default()
+}
+
+/// JSON Schema for `phases.yaml` ([`FeatureSpec`]).
+pub fn feature_spec_schema() -> serde_json::Value {
+    serde_json::to_value(schema_for!(FeatureSpec)).unwrap_or_default()
+}
+
+// ── Project config ───────────────────────────────────────────
+
+/// Validate the layered project configuration of a repository.
+///
+/// Checks every config file layer for syntax errors and unknown keys, then
+/// applies semantic rules to the merged result. Each semantic problem is
+/// reported against the layer that supplied the offending value.
+///
+/// # Errors
+///
+/// Returns `CoreError::Io` if a config file exists but cannot be read.
+pub fn validate_project_config(engine_config: &EngineConfig) -> Result<Vec<Diagnostic>, CoreError> {
+    validate_project_config_with(engine_config, &ConfigSources::from_process())
+}
+
+/// Validate the project configuration using explicit sources.
+pub(crate) fn validate_project_config_with(
+    engine_config: &EngineConfig,
+    sources: &ConfigSources,
+) -> Result<Vec<Diagnostic>, CoreError> {
+    let files = [
+        sources.user_config.clone(),
+        Some(engine_config.config_path()),
+        Some(engine_config.local_config_path()),
+    ];
+
+    let mut diagnostics = Vec::new();
+    for path in files.into_iter().flatten() {
+        if !path.is_file() {
+            continue;
+        }
+        let content = std::fs::read_to_string(&path)?;
+        if let Err(diagnostic) = parse_document::<ProjectConfig>(&path, &content) {
+            diagnostics.push(diagnostic);
+        }
+    }
+    if !diagnostics.is_empty() {
+        return Ok(diagnostics);
+    }
+
+    let resolved = match resolve(engine_config, sources) {
+        Ok(resolved) => resolved,
+        Err(e) => {
+            return Ok(vec![Diagnostic {
+                source: engine_config.config_path().display().to_string(),
+                line: None,
+                column: None,
+                message: e.to_string(),
+            }]);
+        }
+    };
+
+    Ok(locate_config_violations(
+        &resolved,
+        check_project_config(resolved.config()),
+    ))
+}
+
+/// Validate a resolved config and convert semantic violations into a
+/// single `CoreError::Config`.
+pub(crate) fn ensure_valid_config(resolved: &ResolvedConfig) -> Result<(), CoreError> {
+    let violations = check_project_config(resolved.config());
+    if violations.is_empty() {
+        return Ok(());
+    }
+    let diagnostics = locate_config_violations(resolved, violations);
+    Err(CoreError::Config(join_diagnostics(&diagnostics)))
+}
+
+/// Apply semantic rules to a project configuration.
+pub(crate) fn check_project_config(config: &ProjectConfig) -> Vec<Violation> {
+    use PathSegment::{Index, Key};
+
+    let mut violations = Vec::new();
+
+    if config.review.max_iterations == 0 {
+        violations.push(Violation::new(
+            vec![Key("review"), Key("maxIterations")],
+            "maxIterations must be greater than 0",
+        ));
+    }
+    if config.verification.max_iterations == 0 {
+        violations.push(Violation::new(
+            vec![Key("verification"), Key("maxIterations")],
+            "maxIterations must be greater than 0",
+        ));
+    }
+
+    if config.git.base_branch.trim().is_empty() {
+        violations.push(Violation::new(
+            vec![Key("git"), Key("baseBranch")],
+            "baseBranch must not be empty",
+        ));
+    }
+    if let Err(message) = check_branch_pattern(&config.git.branch_pattern) {
+        violations.push(Violation::new(
+            vec![Key("git"), Key("branchPattern")],
+            message,
+        ));
+    }
+
+    let mut seen = HashSet::new();
+    for (i, hook) in config.hooks.pre_commit.iter().enumerate() {
+        let path = |field| vec![Key("hooks"), Key("preCommit"), Index(i), Key(field)];
+        if hook.name.trim().is_empty() {
+            violations.push(Violation::new(path("name"), "hook name must not be empty"));
+        } else if !seen.insert(hook.name.as_str()) {
+            violations.push(Violation::new(
+                path("name"),
+                format!("duplicate hook name {:?}", hook.name),
+            ));
+        }
+        if hook.command.trim().is_empty() {
+            violations.push(Violation::new(
+                path("command"),
+                format!("hook {:?} has an empty command", hook.name),
+            ));
+        }
+    }
+
+    let mut seen = HashSet::new();
+    for (i, reviewer) in config.review.reviewers.iter().enumerate() {
+        let path = vec![Key("review"), Key("reviewers"), Index(i), Key("name")];
+        if reviewer.name.trim().is_empty() {
+            violations.push(Violation::new(path, "reviewer name must not be empty"));
+        } else if !seen.insert(reviewer.name.as_str()) {
+            violations.push(Violation::new(
+                path,
+                format!("duplicate reviewer name {:?}", reviewer.name),
+            ));
+        }
+    }
+
+    let mut seen = HashSet::new();
+    for (i, stage) in config.pipeline.stages.iter().enumerate() {
+        let path = |field| vec![Key("pipeline"), Key("stages"), Index(i), Key(field)];
+        if stage.kind == StageKind::Agent && stage.agent.is_none() {
+            violations.push(Violation::new(
+                path("kind"),
+                "agent stages must name an agent",
+            ));
+        }
+        if !seen.insert(stage.name()) {
+            let field = if stage.name.is_some() { "name" } else { "kind" };
+            violations.push(Violation::new(
+                path(field),
+                format!(
+                    "duplicate stage name {:?} (set `name` to tell stages apart)",
+                    stage.name()
+                ),
+            ));
+        }
+    }
+
+    for (i, pattern) in config.tool_policy.deny_commands.iter().enumerate() {
+        if let Err(e) = regex::Regex::new(pattern) {
+            violations.push(Violation::new(
+                vec![Key("toolPolicy"), Key("denyCommands"), Index(i)],
+                format!("invalid command pattern {pattern:?}: {e}"),
+            ));
+        }
+    }
+
+    violations
+}
+
+/// Check that a branch pattern only uses known placeholders and includes at
+/// least one of them, so every feature gets its own branch.
+fn check_branch_pattern(pattern: &str) -> Result<(), String> {
+    if pattern.trim().is_empty() {
+        return Err("branchPattern must not be empty".to_owned());
+    }
+
+    let mut found = false;
+    let mut rest = pattern;
+    while let Some(start) = rest.find('{') {
+        let after = &rest[start + 1..];
+        let end = after
+            .find('}')
+            .ok_or_else(|| format!("unclosed placeholder in branchPattern {pattern:?}"))?;
+        let name = &after[..end];
+        if !BRANCH_PLACEHOLDERS.contains(&name) {
+            return Err(format!(
+                "unknown placeholder {{{name}}} in branchPattern (expected {{id}} or {{slug}})"
+            ));
+        }
+        found = true;
+        rest = &after[end + 1..];
+    }
+    if rest.contains('}') {
+        return Err(format!("unmatched '}}' in branchPattern {pattern:?}"));
+    }
+    if !found {
+        return Err("branchPattern must contain {id} or {slug}".to_owned());
+    }
+    Ok(())
+}
+
+/// Map config violations to diagnostics using the origin of each key.
+fn locate_config_violations(
+    resolved: &ResolvedConfig,
+    violations: Vec<Violation>,
+) -> Vec<Diagnostic> {
+    violations
+        .into_iter()
+        .map(|violation| {
+            let origin = origin_for(resolved, &violation.origin_key());
+            match origin {
+                ConfigOrigin::User(path)
+                | ConfigOrigin::Project(path)
+                | ConfigOrigin::Local(path) => {
+                    let line = std::fs::read_to_string(path)
+                        .ok()
+                        .and_then(|content| locate(&content, &violation.path));
+                    Diagnostic {
+                        source: path.display().to_string(),
+                        line,
+                        column: None,
+                        message: violation.message,
+                    }
+                }
+                other => Diagnostic {
+                    source: other.to_string(),
+                    line: None,
+                    column: None,
+                    message: violation.message,
+                },
+            }
+        })
+        .collect()
+}
+
+/// Find the origin of a dotted key, walking up to parent keys when the key
+/// itself lies inside a value set as a whole (such as a hook list).
+fn origin_for<'a>(resolved: &'a ResolvedConfig, key: &str) -> &'a ConfigOrigin {
+    let mut key = key;
+    loop {
+        let origin = resolved.origin(key);
+        if *origin != ConfigOrigin::Default {
+            return origin;
+        }
+        match key.rsplit_once('.') {
+            Some((parent, _)) => key = parent,
+            None => return origin,
+        }
+    }
+}
+
+// ── Feature spec ─────────────────────────────────────────────
+
+/// Validate a feature's `phases.yaml`.
+///
+/// # Errors
+///
+/// Returns `CoreError::FeatureNotFound` if the feature has no `phases.yaml`.
+/// Returns `CoreError::Io` if the file cannot be read.
+pub fn validate_feature_spec(
+    engine_config: &EngineConfig,
+    slug: &str,
+) -> Result<Vec<Diagnostic>, CoreError> {
+    let path = engine_config
+        .gba_dir()
+        .join("features")
+        .join(slug)
+        .join("phases.yaml");
+    if !path.exists() {
+        return Err(CoreError::FeatureNotFound(slug.to_owned()));
+    }
+    let content = std::fs::read_to_string(&path)?;
+    Ok(validate_feature_spec_source(&path, &content))
+}
+
+/// Validate the contents of a `phases.yaml` file.
+pub(crate) fn validate_feature_spec_source(path: &Path, content: &str) -> Vec<Diagnostic> {
+    match parse_document::<FeatureSpec>(path, content) {
+        Ok(spec) => feature_spec_diagnostics(path, content, &spec),
+        Err(diagnostic) => vec![diagnostic],
+    }
+}
+
+/// Check an already parsed `phases.yaml`, locating problems in its
+/// `content`.
+pub(crate) fn feature_spec_diagnostics(
+    path: &Path,
+    content: &str,
+    spec: &FeatureSpec,
+) -> Vec<Diagnostic> {
+    check_feature_spec(spec)
+        .into_iter()
+        .map(|violation| Diagnostic {
+            source: path.display().to_string(),
+            line: locate(content, &violation.path),
+            column: None,
+            message: violation.message,
+        })
+        .collect()
+}
+
+/// Validate the files a plan session must produce in `feature_dir`:
+/// `phases.yaml`, `specs/design.md` and `specs/verification.md`.
+///
+/// Missing or empty files are reported like any other problem, so the
+/// result can be handed back to the plan agent as is.
+pub(crate) fn validate_plan_output(feature_dir: &Path) -> Vec<Diagnostic> {
+    let missing = |path: &Path, message: &str| Diagnostic {
+        source: path.display().to_string(),
+        line: None,
+        column: None,
+        message: message.to_owned(),
+    };
+
+    let mut diagnostics = Vec::new();
+    let phases_path = feature_dir.join("phases.yaml");
+    match std::fs::read_to_string(&phases_path) {
+        Ok(content) => diagnostics.extend(validate_feature_spec_source(&phases_path, &content)),
+        Err(_) => diagnostics.push(missing(&phases_path, "file not found")),
+    }
+    for name in ["design.md", "verification.md"] {
+        let path = feature_dir.join("specs").join(name);
+        match std::fs::read_to_string(&path) {
+            Ok(content) if content.trim().is_empty() => {
+                diagnostics.push(missing(&path, "file is empty"));
+            }
+            Ok(_) => {}
+            Err(_) => diagnostics.push(missing(&path, "file not found")),
+        }
+    }
+    diagnostics
+}
+
+/// Apply semantic rules to a feature spec.
+pub(crate) fn check_feature_spec(spec: &FeatureSpec) -> Vec<Violation> {
+    use PathSegment::{Index, Key};
+
+    let mut violations = Vec::new();
+
+    if spec.feature.trim().is_empty() {
+        violations.push(Violation::new(
+            vec![Key("feature")],
+            "feature description must not be empty",
+        ));
+    }
+
+    let mut seen = HashSet::new();
+    for (i, phase) in spec.phases.iter().enumerate() {
+        let path = vec![Key("phases"), Index(i), Key("name")];
+        if phase.name.trim().is_empty() {
+            violations.push(Violation::new(
+                path,
+                format!("phase {} has an empty name", i + 1),
+            ));
+        } else if !seen.insert(phase.name.trim()) {
+            violations.push(Violation::new(
+                path,
+                format!("duplicate phase name {:?}", phase.name),
+            ));
+        }
+    }
+
+    for (i, command) in spec.verification.test_commands.iter().enumerate() {
+        if command.trim().is_empty() {
+            violations.push(Violation::new(
+                vec![Key("verification"), Key("testCommands"), Index(i)],
+                "test command must not be empty",
+            ));
+        }
+    }
+
+    violations
+}
+
+/// Render diagnostics as a single `; `-separated message.
+pub(crate) fn join_diagnostics(diagnostics: &[Diagnostic]) -> String {
+    diagnostics
+        .iter()
+        .map(ToString::to_string)
+        .collect::<Vec<_>>()
+        .join("; ")
+}
+
+// ── Prompt templates ─────────────────────────────────────────
+
+/// Validate the effective prompt templates of a repository.
+///
+/// Loads the built-in templates with the project's overrides and reports
+/// templates that use variables their contract does not declare, reference
+/// missing templates, or require variables the context they are rendered
+/// with does not provide. Problems are reported against the template file,
+/// or the template name for built-ins.
+///
+/// # Errors
+///
+/// Returns `CoreError::Config` if the project configuration is invalid.
+/// Returns `CoreError::Io` if a config file exists but cannot be read.
+pub fn validate_prompts(engine_config: &EngineConfig) -> Result<Vec<Diagnostic>, CoreError> {
+    validate_prompts_with(engine_config, &ConfigSources::from_process())
+}
+
+/// Validate the prompt templates using explicit config sources.
+pub(crate) fn validate_prompts_with(
+    engine_config: &EngineConfig,
+    sources: &ConfigSources,
+) -> Result<Vec<Diagnostic>, CoreError> {
+    let resolved = resolve(engine_config, sources)?;
+    ensure_valid_config(&resolved)?;
+    let project_config = resolved.into_config();
+
+    let pm = match load_prompt_manager(engine_config, &project_config) {
+        Ok(pm) => pm,
+        Err(CoreError::Prompt(e)) => {
+            return Ok(vec![Diagnostic {
+                source: "prompts".to_owned(),
+                line: None,
+                column: None,
+                message: e.to_string(),
+            }]);
+        }
+        Err(e) => return Err(e),
+    };
+
+    Ok(template_issues(&pm, &project_config)
+        .into_iter()
+        .map(|issue| {
+            let source = pm
+                .template(&issue.template)
+                .ok()
+                .and_then(|t| t.origin.path().map(|p| p.display().to_string()))
+                .unwrap_or_else(|| format!("{} (built-in)", issue.template));
+            Diagnostic {
+                source,
+                line: None,
+                column: None,
+                message: issue.message,
+            }
+        })
+        .collect())
+}
+
+// ── Helpers ──────────────────────────────────────────────────
+
+/// Deserialize a YAML document, converting errors into a located diagnostic.
+///
+/// Empty documents deserialize from an empty mapping so that files with only
+/// comments are accepted wherever every field has a default.
+fn parse_document<T: DeserializeOwned>(path: &Path, content: &str) -> Result<T, Diagnostic> {
+    let source = if content.trim().is_empty() {
+        "{}"
+    } else {
+        content
+    };
+    serde_yaml::from_str(source).map_err(|e| {
+        let location = e.location();
+        Diagnostic {
+            source: path.display().to_string(),
+            line: location.as_ref().map(|l| l.line()),
+            column: location.as_ref().map(|l| l.column()),
+            message: strip_location(&e.to_string()),
+        }
+    })
+}
+
+/// Remove the trailing " at line X column Y" that `serde_yaml` appends,
+/// since the diagnostic prints the location itself.
+fn strip_location(message: &str) -> String {
+    match message.find(" at line ") {
+        Some(idx) => message[..idx].to_owned(),
+        None => message.to_owned(),
+    }
+}
+
+/// Best-effort lookup of the one-based line of a value in block-style YAML.
+///
+/// Walks the document by indentation: mapping keys are matched as direct
+/// children of the current node and sequence indices count `- ` items. When
+/// a segment cannot be found, the line of the deepest matched ancestor is
+/// returned.
+pub(crate) fn locate(content: &str, path: &[PathSegment]) -> Option<usize> {
+    let lines: Vec<&str> = content.lines().collect();
+    let mut start = 0;
+    let mut end = lines.len();
+    let mut parent_indent: Option<usize> = None;
+    let mut found: Option<usize> = None;
+
+    for segment in path {
+        let candidates = (start..end).filter_map(|i| {
+            let line = lines[i];
+            let trimmed = line.trim_start();
+            if trimmed.is_empty() || trimmed.starts_with('#') {
+                return None;
+            }
+            Some((i, line.len() - trimmed.len(), trimmed))
+        });
+
+        match segment {
+            PathSegment::Key(key) => {
+                // Keys on a `- key:` line sit two columns right of the dash.
+                let keyed: Vec<(usize, usize, &str)> = candidates
+                    .map(|(i, indent, trimmed)| match trimmed.strip_prefix("- ") {
+                        Some(rest) => (i, indent + 2, rest.trim_start()),
+                        None => (i, indent, trimmed),
+                    })
+                    .filter(|(_, indent, _)| parent_indent.is_none_or(|p| *indent > p))
+                    .collect();
+                let Some(child_indent) = keyed.iter().map(|(_, indent, _)| *indent).min() else {
+                    return found;
+                };
+                let prefix = format!("{key}:");
+                let Some(&(i, indent, _)) = keyed
+                    .iter()
+                    .find(|(_, ind, text)| *ind == child_indent && text.starts_with(&prefix))
+                else {
+                    return found;
+                };
+                found = Some(i + 1);
+                start = i + 1;
+                end = block_end(&lines, start, end, indent);
+                parent_indent = Some(indent);
+            }
+            PathSegment::Index(index) => {
+                let items: Vec<(usize, usize)> = candidates
+                    .filter(|(_, indent, trimmed)| {
+                        (trimmed.starts_with("- ") || *trimmed == "-")
+                            && parent_indent.is_none_or(|p| *indent >= p)
+                    })
+                    .map(|(i, indent, _)| (i, indent))
+                    .collect();
+                let Some(item_indent) = items.iter().map(|(_, indent)| *indent).min() else {
+                    return found;
+                };
+                let Some(&(i, _)) = items
+                    .iter()
+                    .filter(|(_, indent)| *indent == item_indent)
+                    .nth(*index)
+                else {
+                    return found;
+                };
+                found = Some(i + 1);
+                start = i;
+                end = item_end(&lines, i + 1, end, item_indent);
+                parent_indent = Some(item_indent);
+            }
+        }
+    }
+
+    found
+}
+
+/// End (exclusive) of the block that follows a key at `indent`.
+///
+/// Sequence items at the same indent as the key still belong to it.
+fn block_end(lines: &[&str], start: usize, end: usize, indent: usize) -> usize {
+    (start..end)
+        .find(|&i| {
+            let trimmed = lines[i].trim_start();
+            if trimmed.is_empty() || trimmed.starts_with('#') {
+                return false;
+            }
+            let line_indent = lines[i].len() - trimmed.len();
+            line_indent < indent || (line_indent == indent && !trimmed.starts_with('-'))
+        })
+        .unwrap_or(end)
+}
+
+/// End (exclusive) of a sequence item whose dash is at `indent`.
+fn item_end(lines: &[&str], start: usize, end: usize, indent: usize) -> usize {
+    (start..end)
+        .find(|&i| {
+            let trimmed = lines[i].trim_start();
+            if trimmed.is_empty() || trimmed.starts_with('#') {
+                return false;
+            }
+            lines[i].len() - trimmed.len() <= indent
+        })
+        .unwrap_or(end)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use super::*;
+
+    fn engine_config_with(config: &str) -> (tempfile::TempDir, EngineConfig) {
+        let dir = tempfile::TempDir::new().expect("should create temp dir");
+        let engine_config = EngineConfig::builder()
+            .repo_path(dir.path().to_path_buf())
+            .build();
+        std::fs::create_dir_all(engine_config.gba_dir()).expect("should create .gba dir");
+        std::fs::write(engine_config.config_path(), config).expect("should write config");
+        (dir, engine_config)
+    }
+
+    #[test]
+    fn test_should_accept_default_config() {
+        let (_dir, engine_config) = engine_config_with("");
+        let diagnostics = validate_project_config_with(&engine_config, &ConfigSources::default())
+            .expect("should validate");
+        assert!(diagnostics.is_empty(), "unexpected: {diagnostics:?}");
+    }
+
+    #[test]
+    fn test_should_report_unknown_key_with_location() {
+        let (_dir, engine_config) =
+            engine_config_with("git:\n  autoCommit: true\n  baseBrnch: dev\n");
+        let diagnostics = validate_project_config_with(&engine_config, &ConfigSources::default())
+            .expect("should validate");
+
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].line, Some(3));
+        assert!(
+            diagnostics[0].message.contains("unknown field `baseBrnch`"),
+            "message: {}",
+            diagnostics[0].message
+        );
+    }
+
+    #[test]
+    fn test_should_report_semantic_errors_at_key_line() {
+        let (_dir, engine_config) = engine_config_with(
+            "review:\n  enabled: true\n  maxIterations: 0\ngit:\n  branchPattern: \"feat/{ticket}\"\nhooks:\n  preCommit:\n    - name: build\n      command: cargo build\n    - name: build\n      command: \"\"\n",
+        );
+        let diagnostics = validate_project_config_with(&engine_config, &ConfigSources::default())
+            .expect("should validate");
+
+        let lines: Vec<(Option<usize>, &str)> = diagnostics
+            .iter()
+            .map(|d| (d.line, d.message.as_str()))
+            .collect();
+        assert_eq!(diagnostics.len(), 4, "got: {lines:?}");
+        assert_eq!(diagnostics[0].line, Some(3));
+        assert_eq!(diagnostics[1].line, Some(5));
+        assert!(diagnostics[1].message.contains("{ticket}"));
+        assert_eq!(diagnostics[2].line, Some(10));
+        assert!(diagnostics[2].message.contains("duplicate hook name"));
+        assert_eq!(diagnostics[3].line, Some(11));
+    }
+
+    #[test]
+    fn test_should_validate_pipeline_stages() {
+        let (_dir, engine_config) = engine_config_with(
+            "pipeline:\n  stages:\n    - kind: phases\n    - kind: review\n    - kind: agent\n    - kind: review\n",
+        );
+        let diagnostics = validate_project_config_with(&engine_config, &ConfigSources::default())
+            .expect("should validate");
+
+        assert_eq!(diagnostics.len(), 2, "got: {diagnostics:?}");
+        assert_eq!(diagnostics[0].line, Some(5));
+        assert!(diagnostics[0].message.contains("must name an agent"));
+        assert_eq!(diagnostics[1].line, Some(6));
+        assert!(diagnostics[1].message.contains("duplicate stage name"));
+    }
+
+    #[test]
+    fn test_should_reject_duplicate_reviewer_names() {
+        let (_dir, engine_config) = engine_config_with(
+            "review:\n  reviewers:\n    - name: security\n    - name: performance\n    - name: security\n",
+        );
+        let diagnostics = validate_project_config_with(&engine_config, &ConfigSources::default())
+            .expect("should validate");
+
+        assert_eq!(diagnostics.len(), 1, "got: {diagnostics:?}");
+        assert_eq!(diagnostics[0].line, Some(5));
+        assert!(diagnostics[0].message.contains("duplicate reviewer name"));
+    }
+
+    #[test]
+    fn test_should_reject_invalid_deny_command_pattern() {
+        let (_dir, engine_config) = engine_config_with(
+            "toolPolicy:\n  denyCommands:\n    - \"git push .*--force\"\n    - \"curl (\"\n",
+        );
+        let diagnostics = validate_project_config_with(&engine_config, &ConfigSources::default())
+            .expect("should validate");
+
+        assert_eq!(diagnostics.len(), 1, "got: {diagnostics:?}");
+        assert_eq!(diagnostics[0].line, Some(4));
+        assert!(diagnostics[0].message.contains("invalid command pattern"));
+    }
+
+    #[test]
+    fn test_should_attribute_env_override_violation_to_env() {
+        let (_dir, engine_config) = engine_config_with("");
+        let sources = ConfigSources {
+            user_config: None,
+            env: vec![("GBA_REVIEW_MAX_ITERATIONS".to_owned(), "0".to_owned())],
+        };
+        let diagnostics =
+            validate_project_config_with(&engine_config, &sources).expect("should validate");
+
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].source, "env (GBA_REVIEW_MAX_ITERATIONS)");
+    }
+
+    #[test]
+    fn test_should_validate_plan_output() {
+        let dir = tempfile::TempDir::new().expect("should create temp dir");
+        let feature_dir = dir.path();
+        std::fs::create_dir_all(feature_dir.join("specs")).expect("should create specs dir");
+
+        let diagnostics = validate_plan_output(feature_dir);
+        assert_eq!(diagnostics.len(), 3, "got: {diagnostics:?}");
+        assert!(diagnostics.iter().all(|d| d.message == "file not found"));
+
+        std::fs::write(
+            feature_dir.join("phases.yaml"),
+            "feature: Login\nphases:\n- name: Setup\n  description: d\n  tasks: [a]\n- name: Setup\n  description: d\n  tasks: [b]\nverification:\n  criteria: []\n  testCommands: []\n",
+        )
+        .expect("should write phases");
+        std::fs::write(feature_dir.join("specs/design.md"), "# Design\n").expect("should write");
+        std::fs::write(feature_dir.join("specs/verification.md"), "  \n").expect("should write");
+
+        let diagnostics = validate_plan_output(feature_dir);
+        assert_eq!(diagnostics.len(), 2, "got: {diagnostics:?}");
+        assert_eq!(diagnostics[0].line, Some(6));
+        assert!(diagnostics[0].message.contains("duplicate phase name"));
+        assert!(diagnostics[1].source.ends_with("verification.md"));
+        assert_eq!(diagnostics[1].message, "file is empty");
+    }
+
+    #[test]
+    fn test_should_check_branch_pattern_placeholders() {
+        assert!(check_branch_pattern("feat/{id}-{slug}").is_ok());
+        assert!(check_branch_pattern("{slug}").is_ok());
+        assert!(check_branch_pattern("feat/static").is_err());
+        assert!(check_branch_pattern("feat/{slug").is_err());
+        assert!(check_branch_pattern("feat/slug}").is_err());
+        assert!(check_branch_pattern("feat/{name}").is_err());
+        assert!(check_branch_pattern("").is_err());
+    }
+
+    #[test]
+    fn test_should_validate_feature_spec_rules() {
+        let content = r#"feature: "Login"
+phases:
+- name: Setup
+  description: d
+  tasks: [a]
+- name: ""
+  description: d
+  tasks: [b]
+- name: Setup
+  description: d
+  tasks: [c]
+verification:
+  criteria: []
+  testCommands:
+  - cargo test
+  - " "
+"#;
+        let diagnostics = validate_feature_spec_source(Path::new("phases.yaml"), content);
+
+        assert_eq!(diagnostics.len(), 3, "got: {diagnostics:?}");
+        assert_eq!(diagnostics[0].line, Some(6));
+        assert_eq!(diagnostics[1].line, Some(9));
+        assert!(diagnostics[1].message.contains("duplicate phase name"));
+        assert_eq!(diagnostics[2].line, Some(16));
+        assert_eq!(
+            diagnostics[0].to_string(),
+            "phases.yaml:6: phase 2 has an empty name"
+        );
+    }
+
+    #[test]
+    fn test_should_report_spec_parse_error_with_location() {
+        let content = "feature: x\nphases: []\nverification:\n  criteria: []\n  testCommands: []\n  extra: 1\n";
+        let diagnostics = validate_feature_spec_source(Path::new("phases.yaml"), content);
+
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].line, Some(6));
+        assert!(diagnostics[0].message.contains("unknown field `extra`"));
+    }
+
+    #[test]
+    fn test_should_return_feature_not_found_when_validating_missing_spec() {
+        let (_dir, engine_config) = engine_config_with("");
+        let result = validate_feature_spec(&engine_config, "missing");
+        assert!(matches!(result, Err(CoreError::FeatureNotFound(_))));
+    }
+
+    #[test]
+    fn test_should_generate_schemas_rejecting_unknown_keys() {
+        let schema = project_config_schema();
+        assert_eq!(schema["additionalProperties"], false);
+        assert!(schema["properties"]["git"].is_object());
+
+        let schema = feature_spec_schema();
+        assert_eq!(schema["additionalProperties"], false);
+        assert!(schema["properties"]["phases"].is_object());
+    }
+
+    #[test]
+    fn test_should_locate_nested_keys() {
+        let content = "a:\n  b: 1\n  c:\n    - x: 1\n    - x: 2\n      y: 3\n";
+        use PathSegment::{Index, Key};
+        assert_eq!(locate(content, &[Key("a"), Key("c")]), Some(3));
+        assert_eq!(locate(content, &[Key("a"), Key("c"), Index(1)]), Some(5));
+        assert_eq!(
+            locate(content, &[Key("a"), Key("c"), Index(1), Key("y")]),
+            Some(6)
+        );
+        // Missing segment falls back to the deepest match
+        assert_eq!(locate(content, &[Key("a"), Key("zzz")]), Some(1));
+        assert_eq!(locate(content, &[Key("nope")]), None);
+    }
+
+    #[test]
+    fn test_should_report_prompt_mismatches_against_template_files() {
+        let (_dir, engine_config) = engine_config_with(
+            "pipeline:\n  stages:\n    - kind: phases\n    - kind: agent\n      agent: docs\n",
+        );
+        let docs = engine_config.gba_dir().join("agents").join("docs");
+        std::fs::create_dir_all(&docs).expect("should create agent dir");
+        std::fs::write(docs.join("config.yml"), "preset: true\n").expect("should write config");
+        std::fs::write(
+            docs.join("system.md.j2"),
+            "{#- contract\nrequired: [repo_path]\n-#}\nDocument {{ repo_path }}.",
+        )
+        .expect("should write system");
+        std::fs::write(
+            docs.join("task.md.j2"),
+            "{#- contract\nrequired: [changelog]\n-#}\nUpdate {{ changelog }} for {{ diff }}.",
+        )
+        .expect("should write task");
+
+        let diagnostics = validate_prompts_with(&engine_config, &ConfigSources::default())
+            .expect("should validate");
+        let messages: Vec<String> = diagnostics.iter().map(ToString::to_string).collect();
+        let task = docs.join("task.md.j2").display().to_string();
+        assert_eq!(
+            messages,
+            [
+                format!(
+                    "{task}: requires `changelog`, which the `docs` stage context does not provide"
+                ),
+                format!("{task}: uses variable `diff`, which its contract does not declare"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_should_format_diagnostic_without_location() {
+        let diagnostic = Diagnostic {
+            source: PathBuf::from("config.yaml").display().to_string(),
+            line: None,
+            column: None,
+            message: "bad".to_owned(),
+        };
+        assert_eq!(diagnostic.to_string(), "config.yaml: bad");
+    }
+}
@@ -0,0 +1,155 @@
+//! Template contract headers and template references.
+//!
+//! A template declares the variables it expects in a Jinja comment at the
+//! very top of its source:
+//!
+//! ```text
+//! {#- contract
+//! required: [repo_path, design_spec]
+//! optional: [feature_slug]
+//! -#}
+//! ```
+//!
+//! The header is an ordinary comment, so it renders as nothing and keeps the
+//! line numbers of render errors pointing at the file.
+
+use crate::error::PmError;
+use crate::template::TemplateContract;
+
+/// Keyword opening a contract header comment.
+const CONTRACT_KEYWORD: &str = "contract";
+
+/// Tags whose first string argument names another template.
+const REFERENCE_TAGS: &[&str] = &["include", "import", "from", "extends"];
+
+/// A reference from one template to another.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct Reference {
+    /// The tag making the reference (`include`, `import`, `from` or `extends`).
+    pub tag: &'static str,
+    /// Name of the referenced template.
+    pub name: String,
+}
+
+impl Reference {
+    /// Whether the referenced template renders with the referencing
+    /// template's context. Imported macros only see their arguments.
+    pub fn shares_context(&self) -> bool {
+        matches!(self.tag, "include" | "extends")
+    }
+}
+
+/// Parse the contract header of a template, if it has one.
+///
+/// # Errors
+///
+/// Returns `PmError::InvalidTemplate` if the header is not valid contract
+/// YAML.
+pub(crate) fn parse_contract(
+    name: &str,
+    source: &str,
+) -> Result<Option<TemplateContract>, PmError> {
+    let Some(rest) = source.strip_prefix("{#") else {
+        return Ok(None);
+    };
+    let rest = rest
+        .strip_prefix('-')
+        .unwrap_or(rest)
+        .trim_start_matches([' ', '\t']);
+    let Some(rest) = rest.strip_prefix(CONTRACT_KEYWORD) else {
+        return Ok(None);
+    };
+    if !rest.starts_with(['\n', '\r']) {
+        return Ok(None);
+    }
+    let Some(end) = rest.find("#}") else {
+        return Ok(None);
+    };
+    let body = rest[..end].trim_end();
+    let body = body.strip_suffix('-').unwrap_or(body);
+
+    serde_yaml::from_str::<Option<TemplateContract>>(body)
+        .map(Option::unwrap_or_default)
+        .map(Some)
+        .map_err(|e| PmError::InvalidTemplate(format!("{name}: invalid contract: {e}")))
+}
+
+/// Templates referenced by literal names in `{% include %}`, `{% import %}`,
+/// `{% from %}` and `{% extends %}` tags. Names computed at render time are
+/// not found.
+pub(crate) fn references(source: &str) -> Vec<Reference> {
+    let mut references = Vec::new();
+    let mut rest = source;
+    while let Some(start) = rest.find("{%") {
+        let after = &rest[start + 2..];
+        let end = after.find("%}").unwrap_or(after.len());
+        let tag = after[..end].trim_matches(['-', '+', ' ', '\t', '\r', '\n']);
+        rest = &after[end..];
+
+        let keyword = tag.split_whitespace().next().unwrap_or_default();
+        let Some(&tag_name) = REFERENCE_TAGS.iter().find(|&&t| t == keyword) else {
+            continue;
+        };
+        if let Some(name) = quoted(&tag[keyword.len()..]) {
+            references.push(Reference {
+                tag: tag_name,
+                name: name.to_owned(),
+            });
+        }
+    }
+    references
+}
+
+/// The leading string literal of a tag's arguments.
+fn quoted(args: &str) -> Option<&str> {
+    let args = args.trim_start();
+    let quote = args.chars().next().filter(|c| matches!(c, '"' | '\''))?;
+    let body = &args[1..];
+    body.find(quote).map(|end| &body[..end])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_should_parse_contract_header() {
+        let source = "{#- contract\nrequired: [repo_path]\noptional: [feature_slug]\n-#}\nbody";
+        let contract = parse_contract("code/system", source)
+            .expect("should parse")
+            .expect("should have contract");
+        assert!(contract.required.contains("repo_path"));
+        assert!(contract.optional.contains("feature_slug"));
+
+        assert_eq!(parse_contract("x", "{# a comment #}body").unwrap(), None);
+        assert_eq!(parse_contract("x", "body {#- contract\n-#}").unwrap(), None);
+        assert_eq!(
+            parse_contract("x", "{# contract\n#}").unwrap(),
+            Some(TemplateContract::default())
+        );
+        assert!(matches!(
+            parse_contract("x", "{#- contract\nrequire: [a]\n-#}"),
+            Err(PmError::InvalidTemplate(_))
+        ));
+    }
+
+    #[test]
+    fn test_should_find_template_references() {
+        let source = r#"{% extends "review/system" %}
+{%- include '_partials/repository' -%}
+{% from "_macros/lists" import bullets %}{% include name %}{% if x %}{% endif %}"#;
+        let references = references(source);
+        let names: Vec<(&str, &str)> = references
+            .iter()
+            .map(|r| (r.tag, r.name.as_str()))
+            .collect();
+        assert_eq!(
+            names,
+            [
+                ("extends", "review/system"),
+                ("include", "_partials/repository"),
+                ("from", "_macros/lists"),
+            ]
+        );
+    }
+}
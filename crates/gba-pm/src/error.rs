@@ -13,6 +13,10 @@ pub enum PmError {
     #[error("template not found: {0}")]
     TemplateNotFound(String),
 
+    /// A requested agent has no configuration.
+    #[error("agent not found: {0}")]
+    AgentNotFound(String),
+
     /// Rendering a template failed (e.g., syntax error or missing variable).
     #[error("render error: {0}")]
     RenderError(String),
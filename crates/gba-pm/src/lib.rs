@@ -4,10 +4,13 @@
 //! time from the built-in `agents/` directory and supports runtime overrides
 //! from a custom directory on disk.
 
+mod contract;
 mod error;
 mod manager;
 mod template;
 
 pub use error::PmError;
 pub use manager::PromptManager;
-pub use template::{AgentConfig, PromptTemplate};
+pub use template::{
+    AgentConfig, PermissionMode, PromptTemplate, TemplateContract, TemplateIssue, TemplateOrigin,
+};
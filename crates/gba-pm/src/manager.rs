@@ -1,20 +1,45 @@
 //! Prompt manager implementation.
 //!
-//! `PromptManager` loads built-in Jinja2 templates at compile time and supports
-//! loading custom overrides from a directory at runtime.
+//! `PromptManager` loads built-in Jinja2 templates and agent configs at
+//! compile time and supports loading custom overrides and user-defined agents
+//! from a directory at runtime.
 
+use std::collections::{BTreeMap, BTreeSet};
 use std::fs;
 use std::path::Path;
 
-use minijinja::Environment;
+use minijinja::{Environment, UndefinedBehavior};
 use tracing::debug;
 
+use crate::contract::{parse_contract, references};
 use crate::error::PmError;
-use crate::template::AgentConfig;
+use crate::template::{
+    AgentConfig, PromptTemplate, TemplateContract, TemplateIssue, TemplateOrigin,
+};
 
 /// Built-in templates embedded at compile time from the `agents/` directory.
 /// Each entry is `(name, source)` where name follows `{agent}/{template}` convention.
+/// Shared fragments live in the `_partials/` (for `{% include %}`) and
+/// `_macros/` (for `{% import %}`) namespaces.
 const BUILT_IN_TEMPLATES: &[(&str, &str)] = &[
+    // shared partials
+    (
+        "_partials/coding_standards",
+        include_str!("../../../agents/_partials/coding_standards.md.j2"),
+    ),
+    (
+        "_partials/repository",
+        include_str!("../../../agents/_partials/repository.md.j2"),
+    ),
+    // shared macros
+    (
+        "_macros/lists",
+        include_str!("../../../agents/_macros/lists.md.j2"),
+    ),
+    (
+        "_macros/review",
+        include_str!("../../../agents/_macros/review.md.j2"),
+    ),
     // init agent
     (
         "init/system",
@@ -27,6 +52,22 @@ const BUILT_IN_TEMPLATES: &[(&str, &str)] = &[
         include_str!("../../../agents/plan/system.md.j2"),
     ),
     ("plan/task", include_str!("../../../agents/plan/task.md.j2")),
+    (
+        "plan/amend",
+        include_str!("../../../agents/plan/amend.md.j2"),
+    ),
+    (
+        "plan/brief",
+        include_str!("../../../agents/plan/brief.md.j2"),
+    ),
+    (
+        "plan/repair",
+        include_str!("../../../agents/plan/repair.md.j2"),
+    ),
+    (
+        "plan/resume",
+        include_str!("../../../agents/plan/resume.md.j2"),
+    ),
     // code agent
     (
         "code/system",
@@ -55,6 +96,22 @@ const BUILT_IN_TEMPLATES: &[(&str, &str)] = &[
         "review/fix",
         include_str!("../../../agents/review/fix.md.j2"),
     ),
+    (
+        "review/personas/api_design",
+        include_str!("../../../agents/review/personas/api_design.md.j2"),
+    ),
+    (
+        "review/personas/performance",
+        include_str!("../../../agents/review/personas/performance.md.j2"),
+    ),
+    (
+        "review/personas/security",
+        include_str!("../../../agents/review/personas/security.md.j2"),
+    ),
+    (
+        "review/personas/spec_compliance",
+        include_str!("../../../agents/review/personas/spec_compliance.md.j2"),
+    ),
     // verify agent
     (
         "verify/system",
@@ -70,6 +127,9 @@ const BUILT_IN_TEMPLATES: &[(&str, &str)] = &[
     ),
 ];
 
+/// File name of an agent's configuration inside its directory.
+const AGENT_CONFIG_FILE: &str = "config.yml";
+
 /// Built-in agent configurations embedded at compile time.
 const BUILT_IN_CONFIGS: &[(&str, &str)] = &[
     ("init", include_str!("../../../agents/init/config.yml")),
@@ -81,9 +141,22 @@ const BUILT_IN_CONFIGS: &[(&str, &str)] = &[
 
 /// Manages prompt templates and renders them with context variables.
 ///
-/// Supports built-in templates (compiled into the binary via `include_str!`)
-/// and custom overrides loaded from disk. Custom templates with the same name
-/// as built-in templates will replace them.
+/// Supports built-in templates and agent configs (compiled into the binary
+/// via `include_str!`) and custom overrides loaded from disk. Custom templates
+/// and `config.yml` files with the same name as built-ins replace them; new
+/// names define new agents.
+///
+/// Templates share fragments through the `_partials/` and `_macros/`
+/// namespaces, e.g. `{% include "_partials/repository" %}` or
+/// `{% from "_macros/lists" import bullets %}`. Includes and imports are
+/// resolved at render time, so overriding a shared fragment changes it for
+/// every template that uses it.
+///
+/// Templates declare the variables they expect in a contract header (see
+/// [`TemplateContract`]). Rendering is strict: printing or iterating an
+/// undefined variable fails instead of producing empty text, while tests
+/// such as `{% if brief %}` treat it as false. An override without a header
+/// keeps the contract of the built-in it replaces.
 ///
 /// # Examples
 ///
@@ -97,41 +170,73 @@ const BUILT_IN_CONFIGS: &[(&str, &str)] = &[
 #[derive(Debug)]
 pub struct PromptManager {
     env: Environment<'static>,
+    agents: BTreeMap<String, AgentConfig>,
+    /// Source file of each template loaded from disk; absent for built-ins.
+    origins: BTreeMap<String, TemplateOrigin>,
+    /// Declared variables of each template with a contract header.
+    contracts: BTreeMap<String, TemplateContract>,
 }
 
 impl PromptManager {
-    /// Create a manager pre-loaded with built-in templates.
+    /// Create a manager pre-loaded with built-in templates and agents.
     ///
-    /// All templates from the `agents/` directory are embedded at compile time
-    /// and registered with the internal minijinja environment.
+    /// All templates and agent configs from the `agents/` directory are
+    /// embedded at compile time and registered with the manager.
     ///
     /// # Errors
     ///
     /// Returns `PmError::InvalidTemplate` if any built-in template has invalid
-    /// Jinja2 syntax.
+    /// Jinja2 syntax or an invalid contract header.
+    /// Returns `PmError::ConfigParse` if a built-in agent config is invalid.
     pub fn new() -> Result<Self, PmError> {
         let mut env = Environment::new();
+        env.set_undefined_behavior(UndefinedBehavior::SemiStrict);
+        let mut contracts = BTreeMap::new();
 
         for &(name, source) in BUILT_IN_TEMPLATES {
+            if let Some(contract) = parse_contract(name, source)? {
+                contracts.insert(name.to_owned(), contract);
+            }
             env.add_template_owned(name.to_owned(), source.to_owned())
                 .map_err(|e| PmError::InvalidTemplate(format!("{name}: {e}")))?;
             debug!(template = name, "loaded built-in template");
         }
 
-        Ok(Self { env })
+        let agents = BUILT_IN_CONFIGS
+            .iter()
+            .map(|&(name, _)| Ok((name.to_owned(), Self::load_agent_config(name)?)))
+            .collect::<Result<_, PmError>>()?;
+
+        Ok(Self {
+            env,
+            agents,
+            origins: BTreeMap::new(),
+            contracts,
+        })
     }
 
-    /// Load custom templates from a directory, overriding built-in templates
-    /// with the same name.
+    /// Load custom templates and agent configs from a directory, overriding
+    /// built-ins with the same name.
     ///
     /// Walks the directory recursively looking for `.md.j2` files. Template
     /// names are derived from relative paths with the extension stripped.
     /// For example, `dir/init/system.md.j2` becomes `init/system`.
     ///
+    /// Each immediate subdirectory containing a `config.yml` defines the
+    /// agent of the same name: `dir/review/config.yml` replaces the built-in
+    /// review config, and `dir/docs/config.yml` adds a `docs` agent whose
+    /// prompts are `docs/system` and the other templates next to it.
+    /// Directories starting with `_` (such as `_partials/` and `_macros/`)
+    /// hold shared templates only and never define agents.
+    ///
     /// # Errors
     ///
     /// Returns `PmError::Io` if the directory cannot be read. Returns
-    /// `PmError::InvalidTemplate` if a template file contains invalid Jinja2 syntax.
+    /// `PmError::InvalidTemplate` if a template file contains invalid Jinja2
+    /// syntax or an invalid contract header.
+    /// Returns `PmError::ConfigParse` if an agent `config.yml` cannot be parsed.
+    /// Returns `PmError::TemplateNotFound` if an agent `config.yml` has no
+    /// `<name>/system` template next to it or among the loaded templates.
     pub fn load_dir(&mut self, dir: &Path) -> Result<(), PmError> {
         if !dir.is_dir() {
             return Err(std::io::Error::new(
@@ -141,11 +246,41 @@ impl PromptManager {
             .into());
         }
 
-        load_templates_recursive(dir, dir, &mut self.env)?;
+        load_templates_recursive(dir, dir, self)?;
+        self.load_agent_configs(dir)?;
 
         Ok(())
     }
 
+    /// Register the `config.yml` of every agent directory directly under `dir`.
+    fn load_agent_configs(&mut self, dir: &Path) -> Result<(), PmError> {
+        for entry in fs::read_dir(dir)? {
+            let path = entry?.path();
+            let config_path = path.join(AGENT_CONFIG_FILE);
+            if !config_path.is_file() {
+                continue;
+            }
+            let Some(name) = path.file_name().and_then(|n| n.to_str()) else {
+                continue;
+            };
+            if name.starts_with('_') {
+                continue;
+            }
+
+            let config = Self::load_agent_config_from_file(&config_path)?;
+            let system = format!("{name}/system");
+            if self.env.get_template(&system).is_err() {
+                return Err(PmError::TemplateNotFound(format!(
+                    "{system} (required by {})",
+                    config_path.display()
+                )));
+            }
+            debug!(agent = name, path = %config_path.display(), "loaded agent config");
+            self.agents.insert(name.to_owned(), config);
+        }
+        Ok(())
+    }
+
     /// Render a named template with the given context.
     ///
     /// The context is a `serde_json::Value` that provides variables available
@@ -154,7 +289,9 @@ impl PromptManager {
     /// # Errors
     ///
     /// Returns `PmError::TemplateNotFound` if no template with the given name exists.
-    /// Returns `PmError::RenderError` if rendering fails (e.g., missing variables).
+    /// Returns `PmError::RenderError` if the context lacks a variable the
+    /// template's contract requires, or rendering fails (e.g., an undefined
+    /// variable is printed).
     ///
     /// # Examples
     ///
@@ -173,6 +310,14 @@ impl PromptManager {
             .get_template(name)
             .map_err(|_| PmError::TemplateNotFound(name.to_owned()))?;
 
+        let missing = self.missing_variables(name, ctx);
+        if !missing.is_empty() {
+            return Err(PmError::RenderError(format!(
+                "{name}: missing required variables: {}",
+                missing.join(", ")
+            )));
+        }
+
         tmpl.render(ctx)
             .map_err(|e| PmError::RenderError(format!("{name}: {e}")))
     }
@@ -187,14 +332,203 @@ impl PromptManager {
         names
     }
 
-    /// Load an agent configuration by agent name.
+    /// Get a template's effective source and where it was loaded from.
+    ///
+    /// # Errors
+    ///
+    /// Returns `PmError::TemplateNotFound` if no template with the given name exists.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use gba_pm::{PromptManager, TemplateOrigin};
+    ///
+    /// let pm = PromptManager::new().unwrap();
+    /// let template = pm.template("code/task").unwrap();
+    /// assert_eq!(template.origin, TemplateOrigin::BuiltIn);
+    /// ```
+    pub fn template(&self, name: &str) -> Result<PromptTemplate, PmError> {
+        let tmpl = self
+            .env
+            .get_template(name)
+            .map_err(|_| PmError::TemplateNotFound(name.to_owned()))?;
+
+        Ok(PromptTemplate {
+            name: name.to_owned(),
+            source: tmpl.source().to_owned(),
+            origin: self
+                .origins
+                .get(name)
+                .cloned()
+                .unwrap_or(TemplateOrigin::BuiltIn),
+            contract: self.contracts.get(name).cloned(),
+        })
+    }
+
+    /// The contract a template declares, or the built-in's contract for an
+    /// override without a header. `None` for templates without a contract.
+    pub fn contract(&self, name: &str) -> Option<&TemplateContract> {
+        self.contracts.get(name)
+    }
+
+    /// Required variables of a template's contract that `ctx` does not
+    /// provide. A variable set to `null` counts as provided. Templates
+    /// without a contract require nothing.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use gba_pm::PromptManager;
+    /// use serde_json::json;
+    ///
+    /// let pm = PromptManager::new().unwrap();
+    /// let missing = pm.missing_variables("init/task", &json!({"repo_path": "/repo"}));
+    /// assert_eq!(missing, ["repo_tree"]);
+    /// ```
+    pub fn missing_variables(&self, name: &str, ctx: &serde_json::Value) -> Vec<&str> {
+        self.contracts.get(name).map_or_else(Vec::new, |contract| {
+            contract
+                .required
+                .iter()
+                .filter(|variable| ctx.get(variable.as_str()).is_none())
+                .map(String::as_str)
+                .collect()
+        })
+    }
+
+    /// Check every template against its contract.
+    ///
+    /// Reports templates that reference (include, import or extend) a
+    /// template that does not exist, and templates with a contract that use
+    /// a variable the contract does not declare. Variables used by included
+    /// and extended templates count as used by the template itself.
+    /// Imported macros only see their arguments and are not followed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use gba_pm::PromptManager;
+    ///
+    /// let pm = PromptManager::new().unwrap();
+    /// assert!(pm.check().is_empty());
+    /// ```
+    pub fn check(&self) -> Vec<TemplateIssue> {
+        let mut issues = Vec::new();
+
+        for name in self.list_templates() {
+            let Ok(tmpl) = self.env.get_template(name) else {
+                continue;
+            };
+            for reference in references(tmpl.source()) {
+                if self.env.get_template(&reference.name).is_err() {
+                    issues.push(TemplateIssue {
+                        template: name.to_owned(),
+                        message: format!(
+                            "{} of missing template {:?}",
+                            reference.tag, reference.name
+                        ),
+                    });
+                }
+            }
+
+            let Some(contract) = self.contracts.get(name) else {
+                continue;
+            };
+            for variable in self.used_variables(name) {
+                if !contract.declares(&variable) {
+                    issues.push(TemplateIssue {
+                        template: name.to_owned(),
+                        message: format!(
+                            "uses variable `{variable}`, which its contract does not declare"
+                        ),
+                    });
+                }
+            }
+        }
+
+        issues
+    }
+
+    /// Variables a template reads from the render context, including those
+    /// read by the templates it includes or extends. Environment globals
+    /// such as `range` are left out. Empty for unknown templates.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use gba_pm::PromptManager;
+    ///
+    /// let pm = PromptManager::new().unwrap();
+    /// let used = pm.used_variables("init/system");
+    /// assert!(used.contains("repo_path"));
+    /// assert!(used.contains("feature_slug"));
+    /// ```
+    pub fn used_variables(&self, name: &str) -> BTreeSet<String> {
+        let globals: BTreeSet<&str> = self.env.globals().map(|(name, _)| name).collect();
+        let mut used = BTreeSet::new();
+        self.collect_variables(name, &mut BTreeSet::new(), &mut used);
+        used.retain(|variable| !globals.contains(variable.as_str()));
+        used
+    }
+
+    /// Collect the variables `name` and the templates it includes or extends
+    /// read from the render context.
+    fn collect_variables(
+        &self,
+        name: &str,
+        visited: &mut BTreeSet<String>,
+        used: &mut BTreeSet<String>,
+    ) {
+        if !visited.insert(name.to_owned()) {
+            return;
+        }
+        let Ok(tmpl) = self.env.get_template(name) else {
+            return;
+        };
+        used.extend(tmpl.undeclared_variables(false));
+        for reference in references(tmpl.source()) {
+            if reference.shares_context() {
+                self.collect_variables(&reference.name, visited, used);
+            }
+        }
+    }
+
+    /// List all available agent names, built-in and custom, sorted.
     ///
-    /// Looks up the built-in `config.yml` for the given agent. Returns
-    /// the parsed `AgentConfig`.
+    /// # Examples
+    ///
+    /// ```
+    /// use gba_pm::PromptManager;
+    ///
+    /// let pm = PromptManager::new().unwrap();
+    /// assert_eq!(pm.list_agents(), ["code", "init", "plan", "review", "verify"]);
+    /// ```
+    pub fn list_agents(&self) -> Vec<&str> {
+        self.agents.keys().map(String::as_str).collect()
+    }
+
+    /// Get the effective configuration of an agent.
+    ///
+    /// Returns the config loaded from disk if a custom directory defined the
+    /// agent, otherwise the built-in config.
+    ///
+    /// # Errors
+    ///
+    /// Returns `PmError::AgentNotFound` if no agent with that name exists.
+    pub fn agent_config(&self, name: &str) -> Result<&AgentConfig, PmError> {
+        self.agents
+            .get(name)
+            .ok_or_else(|| PmError::AgentNotFound(name.to_owned()))
+    }
+
+    /// Load a built-in agent configuration by agent name.
+    ///
+    /// Looks up the built-in `config.yml` for the given agent, ignoring any
+    /// custom directories. Returns the parsed `AgentConfig`.
     ///
     /// # Errors
     ///
-    /// Returns `PmError::TemplateNotFound` if no built-in config exists for the agent.
+    /// Returns `PmError::AgentNotFound` if no built-in config exists for the agent.
     /// Returns `PmError::ConfigParse` if the YAML content cannot be parsed.
     ///
     /// # Examples
@@ -210,7 +544,7 @@ impl PromptManager {
             .iter()
             .find(|&&(n, _)| n == name)
             .map(|&(_, src)| src)
-            .ok_or_else(|| PmError::TemplateNotFound(format!("agent config: {name}")))?;
+            .ok_or_else(|| PmError::AgentNotFound(name.to_owned()))?;
 
         serde_yaml::from_str(yaml_source).map_err(|e| PmError::ConfigParse(format!("{name}: {e}")))
     }
@@ -228,11 +562,12 @@ impl PromptManager {
     }
 }
 
-/// Recursively walk a directory and load all `.md.j2` files as templates.
+/// Recursively walk a directory and load all `.md.j2` files as templates,
+/// recording the file each one came from and its contract.
 fn load_templates_recursive(
     base: &Path,
     current: &Path,
-    env: &mut Environment<'static>,
+    pm: &mut PromptManager,
 ) -> Result<(), PmError> {
     let entries = fs::read_dir(current)?;
 
@@ -241,7 +576,7 @@ fn load_templates_recursive(
         let path = entry.path();
 
         if path.is_dir() {
-            load_templates_recursive(base, &path, env)?;
+            load_templates_recursive(base, &path, pm)?;
         } else if let Some(ext) = path.extension() {
             // We look for files ending in `.j2` whose stem ends in `.md`
             // i.e., files matching `*.md.j2`.
@@ -254,11 +589,30 @@ fn load_templates_recursive(
                 if file_name.ends_with(".md.j2") {
                     let name = template_name_from_path(base, &path)?;
                     let source = fs::read_to_string(&path)?;
+                    let contract = parse_contract(&name, &source)?;
 
-                    env.add_template_owned(name.clone(), source)
+                    pm.env
+                        .add_template_owned(name.clone(), source)
                         .map_err(|e| PmError::InvalidTemplate(format!("{name}: {e}")))?;
 
                     debug!(template = %name, path = %path.display(), "loaded custom template");
+                    let built_in = BUILT_IN_TEMPLATES.iter().any(|&(n, _)| n == name);
+                    match contract {
+                        Some(contract) => {
+                            pm.contracts.insert(name.clone(), contract);
+                        }
+                        // Overrides without a header keep the built-in contract
+                        None if built_in => {}
+                        None => {
+                            pm.contracts.remove(&name);
+                        }
+                    }
+                    let origin = if built_in {
+                        TemplateOrigin::Override(path)
+                    } else {
+                        TemplateOrigin::Custom(path)
+                    };
+                    pm.origins.insert(name, origin);
                 }
             }
         }
@@ -324,6 +678,10 @@ mod tests {
 
         // Verify all expected built-in templates are present
         let expected = vec![
+            "_macros/lists",
+            "_macros/review",
+            "_partials/coding_standards",
+            "_partials/repository",
             "code/hook_fix",
             "code/pr",
             "code/resume",
@@ -331,9 +689,17 @@ mod tests {
             "code/task",
             "init/system",
             "init/task",
+            "plan/amend",
+            "plan/brief",
+            "plan/repair",
+            "plan/resume",
             "plan/system",
             "plan/task",
             "review/fix",
+            "review/personas/api_design",
+            "review/personas/performance",
+            "review/personas/security",
+            "review/personas/spec_compliance",
             "review/system",
             "review/task",
             "verify/fix",
@@ -362,6 +728,44 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_should_render_plan_prompts_for_brief() {
+        let pm = PromptManager::new().unwrap();
+
+        let interactive = json!({"repo_path": "/repo", "feature_slug": "login", "brief": null});
+        let system = pm.render("plan/system", &interactive).unwrap();
+        assert!(system.contains("Ask clarifying questions"));
+
+        let ctx = json!({
+            "repo_path": "/repo",
+            "feature_slug": "login",
+            "brief": "Add OAuth login with GitHub.",
+        });
+        let system = pm.render("plan/system", &ctx).unwrap();
+        assert!(!system.contains("Ask clarifying questions"));
+        assert!(system.contains("## Assumptions"));
+        let task = pm.render("plan/brief", &ctx).unwrap();
+        assert!(task.contains("Add OAuth login with GitHub."));
+    }
+
+    #[test]
+    fn test_should_render_plan_amend_prompt_with_phase_status() {
+        let pm = PromptManager::new().unwrap();
+        let ctx = json!({
+            "feature_slug": "login",
+            "design": null,
+            "phases": [
+                {"name": "Setup", "description": "d", "tasks": ["a"], "status": "completed", "commit": "abc123"},
+                {"name": "Api", "description": "d", "tasks": ["b"], "status": "pending", "commit": null},
+            ],
+        });
+
+        let rendered = pm.render("plan/amend", &ctx).unwrap();
+        assert!(rendered.contains("### Setup (completed, commit `abc123`)"));
+        assert!(rendered.contains("### Api (pending)"));
+        assert!(rendered.contains("No `specs/design.md` found"));
+    }
+
     #[test]
     fn test_should_return_error_for_missing_template() {
         let pm = PromptManager::new().unwrap();
@@ -403,6 +807,10 @@ mod tests {
         let rendered = pm.render("custom_agent/custom_task", &json!({"name": "World"}));
         assert!(rendered.is_ok());
         assert_eq!(rendered.unwrap(), "Hello World!");
+        assert_eq!(
+            pm.template("custom_agent/custom_task").unwrap().origin,
+            TemplateOrigin::Custom(agent_dir.join("custom_task.md.j2"))
+        );
     }
 
     #[test]
@@ -429,6 +837,20 @@ mod tests {
             rendered, "Custom init system prompt for /my/repo",
             "custom template should override built-in"
         );
+
+        let template = pm.template("init/system").unwrap();
+        assert_eq!(
+            template.origin,
+            TemplateOrigin::Override(init_dir.join("system.md.j2"))
+        );
+        assert_eq!(
+            template.source,
+            "Custom init system prompt for {{ repo_path }}"
+        );
+        assert_eq!(
+            pm.template("init/task").unwrap().origin,
+            TemplateOrigin::BuiltIn
+        );
     }
 
     #[test]
@@ -450,7 +872,7 @@ mod tests {
         fs::create_dir_all(&agent_dir).unwrap();
 
         // Create a .md.j2 file (should be loaded)
-        fs::write(agent_dir.join("template.md.j2"), "Valid template").unwrap();
+        fs::write(agent_dir.join("system.md.j2"), "Valid template").unwrap();
 
         // Create non-.md.j2 files (should be ignored)
         fs::write(agent_dir.join("readme.md"), "Not a template").unwrap();
@@ -462,7 +884,7 @@ mod tests {
 
         let templates = pm.list_templates();
         assert!(
-            templates.contains(&"agent/template"),
+            templates.contains(&"agent/system"),
             "should load .md.j2 files"
         );
         // The built-in count + 1 custom template
@@ -540,8 +962,8 @@ mod tests {
         let config = PromptManager::load_agent_config("unknown_agent");
         assert!(config.is_err(), "should fail for unknown agent");
         assert!(
-            matches!(config.unwrap_err(), PmError::TemplateNotFound(_)),
-            "error should be TemplateNotFound"
+            matches!(config.unwrap_err(), PmError::AgentNotFound(_)),
+            "error should be AgentNotFound"
         );
     }
 
@@ -569,23 +991,319 @@ mod tests {
     }
 
     #[test]
-    fn test_should_render_template_with_empty_context() {
+    fn test_should_render_review_persona_extending_review_system() {
         let pm = PromptManager::new().unwrap();
+        let rendered = pm
+            .render(
+                "review/personas/security",
+                &json!({"repo_path": "/r", "feature_slug": "f"}),
+            )
+            .unwrap();
 
-        // The code/pr template uses variables, but we should get a render
-        // (minijinja renders undefined variables as empty by default)
-        let ctx = json!({});
+        assert!(rendered.starts_with("You are a security reviewer."));
+        assert!(rendered.contains("Focus on security"));
+        assert!(!rendered.contains("Focus on substantive issues"));
+        // Shared sections come from review/system
+        assert!(rendered.contains("- Feature: `f`"));
+        assert!(rendered.contains("## Output Format"));
+    }
 
-        // The init/system template uses {{ repo_path }}, rendering with
-        // empty context should either succeed (with empty values) or fail
-        // depending on minijinja's undefined behavior. Let's test a template
-        // that does not use conditional logic on required vars.
-        let result = pm.render("init/system", &ctx);
-        // minijinja with default settings treats undefined as empty string
+    #[test]
+    fn test_should_render_review_fix_with_attribution() {
+        let pm = PromptManager::new().unwrap();
+        let rendered = pm
+            .render(
+                "review/fix",
+                &json!({"issues": [
+                    {"severity": "error", "file": "src/db.rs", "line": 42, "description": "SQL injection", "reviewers": ["security", "spec_compliance"]},
+                    {"severity": "warning", "file": "src/lib.rs", "description": "Clone in loop"},
+                ]}),
+            )
+            .unwrap();
+
+        assert!(rendered.contains(
+            "- **[error]** `src/db.rs:42`: SQL injection (raised by security, spec_compliance)"
+        ));
+        assert!(rendered.contains("- **[warning]** `src/lib.rs`: Clone in loop\n"));
+    }
+
+    #[test]
+    fn test_should_list_built_in_agents() {
+        let pm = PromptManager::new().unwrap();
+        assert_eq!(
+            pm.list_agents(),
+            vec!["code", "init", "plan", "review", "verify"]
+        );
+        assert!(!pm.agent_config("review").unwrap().preset);
+    }
+
+    #[test]
+    fn test_should_override_built_in_agent_config_from_directory() {
+        let dir = TempDir::new().unwrap();
+        let review_dir = dir.path().join("review");
+        fs::create_dir_all(&review_dir).unwrap();
+        fs::write(
+            review_dir.join("config.yml"),
+            "preset: true\nmodel: claude-haiku-4-5\n",
+        )
+        .unwrap();
+
+        let mut pm = PromptManager::new().unwrap();
+        pm.load_dir(dir.path()).unwrap();
+
+        let config = pm.agent_config("review").unwrap();
+        assert!(config.preset);
+        assert_eq!(config.model.as_deref(), Some("claude-haiku-4-5"));
+        // The built-in accessor is unaffected
+        assert!(!PromptManager::load_agent_config("review").unwrap().preset);
+    }
+
+    #[test]
+    fn test_should_define_new_agent_from_directory() {
+        let dir = TempDir::new().unwrap();
+        let agent_dir = dir.path().join("security-review");
+        fs::create_dir_all(&agent_dir).unwrap();
+        fs::write(agent_dir.join("config.yml"), "preset: false\n").unwrap();
+        fs::write(agent_dir.join("system.md.j2"), "Audit {{ repo_path }}").unwrap();
+        // Template-only directories do not define agents
+        fs::create_dir_all(dir.path().join("_shared")).unwrap();
+        fs::write(dir.path().join("_shared").join("note.md.j2"), "n").unwrap();
+
+        let mut pm = PromptManager::new().unwrap();
+        pm.load_dir(dir.path()).unwrap();
+
+        assert_eq!(
+            pm.list_agents(),
+            vec![
+                "code",
+                "init",
+                "plan",
+                "review",
+                "security-review",
+                "verify"
+            ]
+        );
+        assert!(!pm.agent_config("security-review").unwrap().preset);
+        let rendered = pm
+            .render("security-review/system", &json!({"repo_path": "/r"}))
+            .unwrap();
+        assert_eq!(rendered, "Audit /r");
+    }
+
+    #[test]
+    fn test_should_render_shared_partials_in_agent_prompts() {
+        let pm = PromptManager::new().unwrap();
+        let ctx = json!({"repo_path": "/r", "feature_slug": "f", "design_spec": "D"});
+
+        let init = pm
+            .render("init/system", &json!({"repo_path": "/r"}))
+            .unwrap();
+        assert!(init.contains("## Repository\n\n- Path: `/r`\n\n## Rules"));
+        let code = pm.render("code/system", &ctx).unwrap();
+        assert!(code.contains("- Feature: `f`\n- Working in git worktree: `.trees/f`\n"));
+        assert!(code.contains("- Handle errors properly."));
+        let security = pm.render("review/personas/security", &ctx).unwrap();
+        assert!(security.contains("- Path: `/r`\n- Feature: `f`\n\n## Rules"));
+    }
+
+    #[test]
+    fn test_should_override_shared_partials_for_all_agents() {
+        let dir = TempDir::new().unwrap();
+        let partials = dir.path().join("_partials");
+        fs::create_dir_all(&partials).unwrap();
+        fs::write(
+            partials.join("repository.md.j2"),
+            "## Repository\n\nMonorepo at `{{ repo_path }}`.",
+        )
+        .unwrap();
+
+        let mut pm = PromptManager::new().unwrap();
+        pm.load_dir(dir.path()).unwrap();
+
+        let ctx = json!({"repo_path": "/r", "feature_slug": "f", "design_spec": ""});
+        for name in ["init/system", "plan/system", "code/system", "verify/system"] {
+            let rendered = pm.render(name, &ctx).unwrap();
+            assert!(rendered.contains("Monorepo at `/r`."), "{name}");
+            assert!(!rendered.contains("- Path:"), "{name}");
+        }
+    }
+
+    #[test]
+    fn test_should_import_custom_macros_from_directory() {
+        let dir = TempDir::new().unwrap();
+        let macros = dir.path().join("_macros");
+        fs::create_dir_all(&macros).unwrap();
+        fs::write(
+            macros.join("commits.md.j2"),
+            "{% macro rule(scope) %}- Prefix commit subjects with `{{ scope }}:`.{% endmacro %}",
+        )
+        .unwrap();
+        // Shared namespaces never define agents
+        fs::write(macros.join("config.yml"), "preset: false\n").unwrap();
+        let agent_dir = dir.path().join("docs");
+        fs::create_dir_all(&agent_dir).unwrap();
+        fs::write(agent_dir.join("config.yml"), "preset: true\n").unwrap();
+        fs::write(
+            agent_dir.join("system.md.j2"),
+            "{% import \"_macros/commits\" as commits %}{{ commits.rule(\"docs\") }}\n{% from \"_macros/lists\" import bullets %}{{ bullets([\"a\", \"b\"]) }}",
+        )
+        .unwrap();
+
+        let mut pm = PromptManager::new().unwrap();
+        pm.load_dir(dir.path()).unwrap();
+
+        assert!(!pm.list_agents().contains(&"_macros"));
+        let rendered = pm.render("docs/system", &json!({})).unwrap();
+        assert_eq!(
+            rendered,
+            "- Prefix commit subjects with `docs:`.\n- a\n- b\n"
+        );
+    }
+
+    #[test]
+    fn test_should_reject_invalid_agent_config_in_directory() {
+        let dir = TempDir::new().unwrap();
+        let agent_dir = dir.path().join("docs");
+        fs::create_dir_all(&agent_dir).unwrap();
+        fs::write(agent_dir.join("config.yml"), "tools: [Read]\n").unwrap();
+
+        let mut pm = PromptManager::new().unwrap();
+        let result = pm.load_dir(dir.path());
+        assert!(matches!(result, Err(PmError::ConfigParse(_))));
+    }
+
+    #[test]
+    fn test_should_reject_agent_config_without_system_template() {
+        let dir = TempDir::new().unwrap();
+        let agent_dir = dir.path().join("docs");
+        fs::create_dir_all(&agent_dir).unwrap();
+        fs::write(agent_dir.join("config.yml"), "preset: true\n").unwrap();
+        fs::write(agent_dir.join("task.md.j2"), "Write docs").unwrap();
+
+        let mut pm = PromptManager::new().unwrap();
+        let result = pm.load_dir(dir.path());
         assert!(
-            result.is_ok(),
-            "render with empty context should succeed (undefined renders as empty)"
+            matches!(result, Err(PmError::TemplateNotFound(ref msg)) if msg.starts_with("docs/system")),
+            "got: {result:?}"
+        );
+
+        // Overriding a built-in agent's config keeps its built-in system template
+        let dir = TempDir::new().unwrap();
+        fs::create_dir_all(dir.path().join("review")).unwrap();
+        fs::write(
+            dir.path().join("review").join("config.yml"),
+            "preset: true\n",
+        )
+        .unwrap();
+        pm.load_dir(dir.path()).unwrap();
+        assert!(pm.agent_config("review").unwrap().preset);
+    }
+
+    #[test]
+    fn test_should_return_error_for_unknown_agent() {
+        let pm = PromptManager::new().unwrap();
+        assert!(matches!(
+            pm.agent_config("unknown_agent"),
+            Err(PmError::AgentNotFound(_))
+        ));
+    }
+
+    #[test]
+    fn test_should_reject_context_missing_required_variables() {
+        let pm = PromptManager::new().unwrap();
+
+        let result = pm.render("init/system", &json!({}));
+        assert!(
+            matches!(&result, Err(PmError::RenderError(msg)) if msg.contains("repo_path")),
+            "{result:?}"
+        );
+        // Optional variables may be left out, required ones may be null
+        let rendered = pm
+            .render(
+                "plan/system",
+                &json!({"repo_path": "/r", "feature_slug": null}),
+            )
+            .unwrap();
+        assert!(rendered.contains("Ask clarifying questions"));
+    }
+
+    #[test]
+    fn test_should_fail_on_undefined_variable_without_contract() {
+        let dir = TempDir::new().unwrap();
+        fs::create_dir_all(dir.path().join("docs")).unwrap();
+        fs::write(
+            dir.path().join("docs/task.md.j2"),
+            "Document {{ feature_slgu }}.{% if extra %} {{ extra }}{% endif %}",
+        )
+        .unwrap();
+
+        let mut pm = PromptManager::new().unwrap();
+        pm.load_dir(dir.path()).unwrap();
+
+        let result = pm.render("docs/task", &json!({"feature_slug": "login"}));
+        assert!(matches!(result, Err(PmError::RenderError(_))), "{result:?}");
+        let rendered = pm.render("docs/task", &json!({"feature_slgu": "login"}));
+        assert_eq!(rendered.unwrap(), "Document login.");
+    }
+
+    #[test]
+    fn test_should_keep_built_in_contract_for_override_without_header() {
+        let dir = TempDir::new().unwrap();
+        fs::create_dir_all(dir.path().join("code")).unwrap();
+        fs::write(
+            dir.path().join("code/task.md.j2"),
+            "Implement {{ phase.name }} of {{ feature_slgu }}.",
+        )
+        .unwrap();
+        fs::create_dir_all(dir.path().join("review")).unwrap();
+        fs::write(
+            dir.path().join("review/task.md.j2"),
+            "{#- contract\nrequired: [diff]\noptional: [feature_slug]\n-#}\n\
+             Review {{ diff }}{% include \"_partials/missing\" %}",
+        )
+        .unwrap();
+
+        let mut pm = PromptManager::new().unwrap();
+        pm.load_dir(dir.path()).unwrap();
+
+        let contract = pm.template("code/task").unwrap().contract.unwrap();
+        assert!(contract.required.contains("phase_index"));
+        assert_eq!(
+            pm.template("review/task")
+                .unwrap()
+                .contract
+                .unwrap()
+                .required,
+            BTreeSet::from(["diff".to_owned()])
         );
+        let issues: Vec<String> = pm.check().iter().map(ToString::to_string).collect();
+        assert_eq!(
+            issues,
+            [
+                "code/task: uses variable `feature_slgu`, which its contract does not declare",
+                "review/task: include of missing template \"_partials/missing\"",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_should_check_variables_of_included_partials() {
+        let dir = TempDir::new().unwrap();
+        fs::create_dir_all(dir.path().join("_partials")).unwrap();
+        fs::write(
+            dir.path().join("_partials/repository.md.j2"),
+            "Repository `{{ repo_path }}` on `{{ branch }}`",
+        )
+        .unwrap();
+
+        let mut pm = PromptManager::new().unwrap();
+        pm.load_dir(dir.path()).unwrap();
+
+        let templates: Vec<String> = pm.check().into_iter().map(|i| i.template).collect();
+        assert!(templates.contains(&"code/system".to_owned()));
+        assert!(templates.contains(&"review/personas/security".to_owned()));
+        // The override has no header, so it keeps the built-in contract too
+        assert!(templates.contains(&"_partials/repository".to_owned()));
     }
 
     #[test]
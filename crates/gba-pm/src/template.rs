@@ -1,8 +1,15 @@
 //! Template and agent configuration types used by the prompt manager.
 //!
-//! Defines [`PromptTemplate`] for representing template sources and
-//! [`AgentConfig`] for agent-level settings parsed from `config.yml` files.
+//! Defines [`PromptTemplate`] for representing template sources, where they
+//! came from ([`TemplateOrigin`]) and the variables they expect
+//! ([`TemplateContract`]), and [`AgentConfig`] for agent-level settings
+//! parsed from `config.yml` files.
 
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
 /// Metadata about a prompt template, including its name and source content.
@@ -13,12 +20,84 @@ pub struct PromptTemplate {
 
     /// Raw Jinja2 template source.
     pub source: String,
+
+    /// Where the effective source was loaded from.
+    pub origin: TemplateOrigin,
+
+    /// Variables the template expects, or `None` if it declares no contract.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub contract: Option<TemplateContract>,
+}
+
+/// Where a template's effective source comes from.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", content = "path", rename_all = "camelCase")]
+pub enum TemplateOrigin {
+    /// Compiled into the binary from the `agents/` directory.
+    BuiltIn,
+    /// A file replacing the built-in template of the same name.
+    Override(PathBuf),
+    /// A file defining a template that has no built-in counterpart.
+    Custom(PathBuf),
+}
+
+impl TemplateOrigin {
+    /// The file the template was loaded from, or `None` for built-ins.
+    pub fn path(&self) -> Option<&Path> {
+        match self {
+            Self::BuiltIn => None,
+            Self::Override(path) | Self::Custom(path) => Some(path),
+        }
+    }
+}
+
+/// Variables a template expects in its render context.
+///
+/// Declared in a `{#- contract ... -#}` comment at the top of the template.
+/// Rendering fails when a required variable is missing, and
+/// [`PromptManager::check`](crate::PromptManager::check) reports variables a
+/// template uses without declaring them.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct TemplateContract {
+    /// Variables every render context must provide.
+    #[serde(default)]
+    pub required: BTreeSet<String>,
+
+    /// Variables the template only tests (e.g. `{% if brief %}`), which a
+    /// context may leave out.
+    #[serde(default)]
+    pub optional: BTreeSet<String>,
+}
+
+impl TemplateContract {
+    /// Whether the contract declares `variable` as required or optional.
+    pub fn declares(&self, variable: &str) -> bool {
+        self.required.contains(variable) || self.optional.contains(variable)
+    }
+}
+
+/// A template that does not match its contract.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct TemplateIssue {
+    /// Name of the offending template.
+    pub template: String,
+
+    /// Human-readable description of the mismatch.
+    pub message: String,
+}
+
+impl fmt::Display for TemplateIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.template, self.message)
+    }
 }
 
 /// Configuration for an agent, loaded from `config.yml` in an agent directory.
 ///
 /// Controls whether the agent uses the Claude Code preset (built-in tools)
-/// or runs as a plain text-analysis agent.
+/// or runs as a plain text-analysis agent, and carries per-agent session
+/// settings. Unset optional fields fall back to the engine-wide defaults.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 #[non_exhaustive]
@@ -34,4 +113,41 @@ pub struct AgentConfig {
     /// Disallow specific tools. Empty means nothing is disallowed.
     #[serde(default)]
     pub disallowed_tools: Vec<String>,
+
+    /// Claude model for this agent. `None` uses the engine-wide model.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub model: Option<String>,
+
+    /// Maximum number of conversation turns. `None` means no limit.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_turns: Option<u32>,
+
+    /// Additional environment variables for the agent process.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub env: BTreeMap<String, String>,
+
+    /// Additional directories the agent may read. Relative paths are
+    /// resolved against the repository root.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub add_dirs: Vec<PathBuf>,
+
+    /// Permission mode for tool use. `None` uses the engine-wide mode.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub permission_mode: Option<PermissionMode>,
+}
+
+/// Permission mode for agent tool invocations.
+///
+/// Controls whether the agent runs tools automatically or requires
+/// user approval for each invocation.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub enum PermissionMode {
+    /// Agent runs tools without asking (default).
+    #[default]
+    Auto,
+    /// Agent asks the user before each tool invocation.
+    Manual,
+    /// Agent cannot use tools (prompt-only mode).
+    None,
 }